@@ -9,33 +9,61 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::collections::HashMap;
 use crate::bytecode::{BlockVec,Instruction};
-use crate::util::{Digraph,Concretizable,SubsliceOffset};
+use crate::util::{Digraph,Concretizable,EdgeSet,SubsliceOffset};
 use super::{EvmState,EvmStack};
-use super::{aw256,ConcreteStack,ConcreteState,trace,ConcreteMemory,UnknownStorage};
+use super::{aw256,ConcreteStack,ConcreteState,trace_with_valid_hints,valid_hints,ConcreteMemory,UnknownStorage};
 
 use Instruction::*;
 
-type DefaultState = ConcreteState<ConcreteStack<aw256>,ConcreteMemory<aw256>,UnknownStorage<aw256>>;
+/// The default state used for analyses which trace execution.  This
+/// tracks stack and memory concretely, but not storage.
+pub type DefaultState = ConcreteState<ConcreteStack<aw256>,ConcreteMemory<aw256>,UnknownStorage<aw256>>;
 
 /// A block graph is a directed graph over the basic blocks of a
-/// bytecode sequence.
+/// bytecode sequence.  Block identifiers are assigned in ascending
+/// order of their starting byte offset (see `BlockVec::start_pc()`),
+/// and do not depend on the order in which edges are discovered.
+/// Thus, the same bytecode always yields the same identifiers.
 pub type BlockGraph<'a> = Digraph<BlockVec<'a>>;
 
 impl<'a> BlockGraph<'a> {
+    /// Construct a block graph from a given sequence of basic blocks.
+    /// Since the underlying analysis maintains a _set_ of states at
+    /// each instruction, a jump whose target is one of several
+    /// constants (e.g. depending on the path taken to reach it) is
+    /// connected to each of them.
+    /// Any `jump` or `jumpi` whose target cannot be resolved (e.g.
+    /// because it depends on calldata) contributes no jump edge
+    /// (see `unresolved_jumps()`).  This returns `Err` if the
+    /// underlying analysis does not complete within the given limit.
     pub fn from_blocks(blocks: BlockVec<'a>, limit: usize) -> Result<Self,Self> {
+        Self::from_blocks_with_hints(blocks,limit,&HashMap::new())
+    }
+
+    /// Construct a block graph as for `from_blocks()`, using a map of
+    /// _jump hints_ to resolve otherwise-unresolved jumps.  This maps
+    /// the byte offset of a `jump` or `jumpi` to the byte offsets of
+    /// its known targets.  Hinted targets give rise to edges, and
+    /// analysis continues from them so that downstream blocks are
+    /// also discovered.  Any hinted target which is not a valid jump
+    /// destination (i.e. a `JUMPDEST`) is dropped.
+    pub fn from_blocks_with_hints(blocks: BlockVec<'a>, limit: usize, hints: &HashMap<usize,Vec<usize>>) -> Result<Self,Self> {
 	let insns = blocks.insns();
+        let hints = valid_hints(insns,hints);
         // Construct block graph
         let mut graph = BlockGraph::new(blocks.len()+1,blocks);
         // Compute analysis results
         let init = DefaultState::new();
         // Run the abstract trace
 	let mut err = false;
-        let trace : Vec<Vec<DefaultState>> = match trace(insns,init,limit) {
+        let trace : Vec<Vec<DefaultState>> = match trace_with_valid_hints(insns,init,limit,&hints) {
 	    Ok(states) => states,
 	    Err(states) => { err = true; states} 
 	};
         // Connect edges!
+        let mut pc = 0;
         for b in 0..graph.len() {
             let blk = graph.get(b);
             let start = insns.subslice_offset(blk);
@@ -43,42 +71,43 @@ impl<'a> BlockGraph<'a> {
             //
             for i in start..end {
                 let insn = &insns[i];
-                match insn {
+                let next = pc + insn.length();
+                // Determine the branch targets (which are byte
+                // offsets) of any dynamic branch.
+                let targets : Vec<usize> = match insn {
                     JUMP|JUMPI => {
-                        for st in &trace[i] {
-                            let target : usize = st.stack().peek(0).constant().to();
-                            // Convert the branch target (which is a
-                            // byte offset) into the corresponding
-                            // block offset.
-                            let bid = graph.nodes().lookup_pc(target);
-                            // Connect edge
-                            graph.connect(b,bid);
+                        let mut ts = Vec::new();
+                        let mut unresolved = false;
+                        for st in trace[i].iter() {
+                            match st.stack().peek(0).try_constant() {
+                                Some(t) => ts.push(t.to()),
+                                None => unresolved = true
+                            }
                         }
-                        if insn == &JUMP {
-                            // Jump instruction doesn't fall through.
-                            // Observe its safe to break here, since
-                            // we know this instruction terminate the
-                            // enclosinc basic block.
-                            break;
+                        if unresolved {
+                            // Target not known, hence rely on hints (if any).
+                            log::debug!("pc {pc:#06x} --> unresolved");
+                            ts.extend(hints.get(&pc).into_iter().flatten());
                         }
+                        ts
                     }
-                    INVALID|RETURN|REVERT|SELFDESTRUCT|STOP|DATA(_) => {
-                        // Instructions which don't fall through.
-                        // Observe its safe to break here, since we
-                        // know these instructions terminate the
-                        // enclosinc basic block.
-                        break;
-                    }
-                    _ => {
-                        // Instructions which do not branch, but do
-                        // fall through to the following instruction.
+                    _ => Vec::new()
+                };
+                // Connect any successor which lies outside this
+                // block.  Observe that only the last instruction of
+                // a block can fall through into another block.
+                for succ in insn.successors(pc,&targets) {
+                    if succ != next || (i+1) == end {
+                        // Convert the successor (which is a byte
+                        // offset) into the corresponding block
+                        // offset.
+                        let bid = graph.nodes().lookup_pc(succ);
+                        log::debug!("pc {pc:#06x} --> block {bid}");
+                        // Connect edge
+                        graph.connect(b,bid);
                     }
                 }
-                // If we get here, then we have an instruction which
-                // falls through to the next.  If this is the last
-                // instruction in the block, then add an edge
-                // accordingly in the graph.
-                if (i+1) == end { graph.connect(b,b+1); }
+                pc = next;
             }
         }
         // Done
@@ -90,6 +119,166 @@ impl<'a> BlockGraph<'a> {
     }
 }
 
+impl<'a> BlockGraph<'a> {
+    /// Determine the set of blocks ending in a `jump` or `jumpi`
+    /// whose target could not be resolved (e.g. because it depends
+    /// on calldata).  Such blocks may be missing outgoing edges in
+    /// this graph.  This returns `None` if the underlying analysis
+    /// does not complete within the given limit.
+    pub fn unresolved_jumps(&self, limit: usize) -> Option<EdgeSet> {
+        self.unresolved_jumps_with_hints(limit,&HashMap::new())
+    }
+
+    /// Determine the set of blocks ending in an unresolved `jump` or
+    /// `jumpi` as for `unresolved_jumps()`, where the graph was
+    /// constructed using a given map of jump hints (see
+    /// `from_blocks_with_hints()`).  A jump is not reported if it has
+    /// at least one valid hinted target.
+    pub fn unresolved_jumps_with_hints(&self, limit: usize, hints: &HashMap<usize,Vec<usize>>) -> Option<EdgeSet> {
+        let mut blocks = EdgeSet::new();
+        for pc in find_unresolved_jumps_with_hints(self.nodes().insns(),limit,hints)? {
+            blocks.insert(self.nodes().lookup_pc(pc));
+        }
+        Some(blocks)
+    }
+
+    /// Determine the set of blocks reachable from the entry block
+    /// (i.e. block `0`), including the entry block itself.  Any block
+    /// not in this set is dead.
+    pub fn reachable(&self) -> EdgeSet {
+        let mut reached = EdgeSet::new();
+        if self.is_empty() { return reached; }
+        let mut worklist = vec![0];
+        reached.insert(0);
+        while let Some(b) = worklist.pop() {
+            for &c in self.outgoing(b) {
+                // NOTE: edges may target the exit of the graph.
+                if c < self.len() && reached.insert(c) {
+                    worklist.push(c);
+                }
+            }
+        }
+        reached
+    }
+}
+
+impl<'a> BlockGraph<'a> {
+    /// Render this graph in Graphviz DOT format.  Each block is
+    /// labelled with its starting byte offset and number of
+    /// instructions.  Fall-through edges (i.e. where control
+    /// continues into the following block) are dashed, whilst jump
+    /// edges are solid.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph {\n    node [shape=box];\n");
+        for b in 0..self.len() {
+            let pc = self.nodes().start_pc(b);
+            out.push_str(&format!("    b{b} [label=\"{pc:#06x} ({})\"];\n",self.get(b).len()));
+        }
+        for (b,c) in self.out_iter() {
+            // NOTE: edges may target the exit of the graph.
+            if c >= self.len() { continue; }
+            let fallthru = c == b+1 && self.get(b).last().is_some_and(|insn| insn.fallthru());
+            let style = if fallthru { "dashed" } else { "solid" };
+            out.push_str(&format!("    b{b} -> b{c} [style={style}];\n"));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Identify the byte offsets of all reachable `jump` or `jumpi`
+/// instructions whose target cannot be resolved to a constant (e.g.
+/// because it is computed from calldata).  Such jumps give rise to
+/// imprecision in the control-flow graph.  This returns `None` if
+/// the underlying analysis does not complete within the given
+/// limit.
+pub fn find_unresolved_jumps(insns: &[Instruction], limit: usize) -> Option<Vec<usize>> {
+    find_unresolved_jumps_with_hints(insns,limit,&HashMap::new())
+}
+
+/// Identify the byte offsets of all reachable `jump` or `jumpi`
+/// instructions whose target cannot be resolved, as for
+/// `find_unresolved_jumps()`, using a map of jump hints to continue
+/// past unresolved jumps.  Any jump with at least one valid hinted
+/// target is considered resolved.
+pub fn find_unresolved_jumps_with_hints(insns: &[Instruction], limit: usize, hints: &HashMap<usize,Vec<usize>>) -> Option<Vec<usize>> {
+    let hints = valid_hints(insns,hints);
+    let trace : Vec<Vec<DefaultState>> = trace_with_valid_hints(insns,DefaultState::new(),limit,&hints).ok()?;
+    let mut unresolved = Vec::new();
+    let mut pc = 0;
+    for (i,insn) in insns.iter().enumerate() {
+        let hinted = hints.get(&pc).is_some_and(|ts| !ts.is_empty());
+        if matches!(insn,JUMP|JUMPI) && !hinted && trace[i].iter().any(|st| st.stack().peek(0).try_constant().is_none()) {
+            unresolved.push(pc);
+        }
+        pc += insn.length();
+    }
+    Some(unresolved)
+}
+
+/// Identifies which of two merged block graphs discovered a given
+/// edge.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,PartialOrd,Ord)]
+pub enum EdgeOrigin {
+    /// Edge was found only by the first graph.
+    First,
+    /// Edge was found only by the second graph.
+    Second,
+    /// Edge was found by both graphs.
+    Both
+}
+
+/// The result of merging two block graphs constructed over the same
+/// basic blocks (e.g. from a precise and an over-approximate
+/// analysis).  This contains the union of their edges, along with
+/// the origin of each edge.
+pub struct MergedGraph<'a> {
+    /// Graph containing the union of all edges.
+    pub graph: BlockGraph<'a>,
+    /// Origin of each edge in the merged graph, in sorted order.
+    pub origins: Vec<(usize,usize,EdgeOrigin)>
+}
+
+impl<'a> MergedGraph<'a> {
+    /// Determine the origin of a given edge, or `None` if no such
+    /// edge exists in the merged graph.
+    pub fn origin(&self, from: usize, to: usize) -> Option<EdgeOrigin> {
+        self.origins.iter().find(|(f,t,_)| *f == from && *t == to).map(|(_,_,o)| *o)
+    }
+}
+
+impl<'a> BlockGraph<'a> {
+    /// Merge this block graph with another constructed over the
+    /// same basic blocks, producing the union of their edges.  This
+    /// returns `None` if the two graphs do not have identical block
+    /// boundaries, since edges could not then be meaningfully
+    /// combined.
+    pub fn merge(&self, other: &BlockGraph<'a>) -> Option<MergedGraph<'a>> {
+        if self.nodes() != other.nodes() { return None; }
+        let blocks = self.nodes().clone();
+        let mut graph = BlockGraph::new(blocks.len()+1,blocks);
+        let mut origins = Vec::new();
+        // Include edges from the first graph
+        for (from,to) in self.out_iter() {
+            graph.connect(from,to);
+            let origin = if other.outgoing(from).contains(to) {
+                EdgeOrigin::Both
+            } else {
+                EdgeOrigin::First
+            };
+            origins.push((from,to,origin));
+        }
+        // Include edges only in the second graph
+        for (from,to) in other.out_iter() {
+            if graph.connect(from,to) {
+                origins.push((from,to,EdgeOrigin::Second));
+            }
+        }
+        origins.sort();
+        Some(MergedGraph{graph,origins})
+    }
+}
+
 impl<'a> From<&'a [Instruction]> for BlockGraph<'a>
 {
     /// Construct a graph of the basic blocks for a given instruction
@@ -0,0 +1,58 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::util::{cyclomatic_complexity,cyclomatic_complexity_from,Digraph};
+use super::BlockGraph;
+
+/// Compute the _cyclomatic complexity_ of a given block graph (e.g.
+/// for an entire contract).  Since a contract can terminate at many
+/// points, every block which can terminate execution is first
+/// connected to a single (virtual) exit node.  Without this, a block
+/// containing a conditional branch followed by (say) a `REVERT`
+/// would not be counted as a decision point.
+pub fn block_complexity(graph: &BlockGraph) -> usize {
+    let ids = node_ids(graph);
+    cyclomatic_complexity(&with_exit(graph,&ids))
+}
+
+/// Compute the _cyclomatic complexity_ of those blocks reachable from
+/// a given entry block (e.g. the entry of a public function).  As for
+/// `block_complexity`, all terminating blocks are first connected to
+/// a single exit node.
+pub fn function_complexity(graph: &BlockGraph, entry: usize) -> usize {
+    let ids = node_ids(graph);
+    cyclomatic_complexity_from(&with_exit(graph,&ids),entry)
+}
+
+fn node_ids(graph: &BlockGraph) -> Vec<usize> {
+    (0..=graph.len()).collect()
+}
+
+/// Construct a copy of a block graph which includes an explicit exit
+/// node, to which every block that can terminate execution is
+/// connected.
+fn with_exit<'a>(graph: &BlockGraph, ids: &'a [usize]) -> Digraph<&'a [usize]> {
+    let n = graph.len();
+    let mut g = Digraph::new(n+1,ids);
+    //
+    for i in 0..n {
+        for j in graph.outgoing(i).iter().filter(|j| **j < n) {
+            g.connect(i,*j);
+        }
+        // Check whether this block can terminate
+        if let Some(last) = graph.get(i).last() {
+            if !last.fallthru() && !last.can_branch() {
+                g.connect(i,n);
+            }
+        }
+    }
+    g
+}
@@ -0,0 +1,77 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::bytecode::Instruction;
+use crate::util::{w256,Concretizable};
+use super::{execute,find_dependencies,DefaultState,EvmStack,EvmState,Outcome,trace};
+
+use Instruction::*;
+
+/// For a given bytecode sequence, identify those instructions which
+/// complete the computation of a constant.  This is intended for
+/// display purposes, since constants are often assembled from several
+/// instructions.  For example:
+///
+/// ```txt
+///    push 0x1
+///    push 0xa0
+///    shl
+///    push 0x1
+///    or      // = 0x10000000000000000000000000000000000000001
+/// ```
+///
+/// Here, only the final `or` is annotated since it completes the
+/// sequence (i.e. the _maximal munch_).  An arithmetic (or bitwise)
+/// instruction produces a constant when it always produces the same
+/// value along every path reaching it.  This returns an entry for each
+/// instruction, or `None` if the analysis does not complete within the
+/// given limit.
+pub fn find_constants(insns: &[Instruction], limit: usize) -> Option<Vec<Option<w256>>> {
+    let states : Vec<Vec<DefaultState>> = trace(insns,DefaultState::new(),limit).ok()?;
+    // Determine the constant (if any) produced by each instruction
+    let constants : Vec<Option<w256>> = insns.iter().zip(&states).map(|(insn,sts)| {
+        if is_arithmetic(insn) { constant(insn,sts) } else { None }
+    }).collect();
+    // Annotate only the last instruction of each sequence.  That is,
+    // those whose result is not consumed by another constant.
+    let deps = find_dependencies(insns,limit).ok()?;
+    let mut annotations = constants.clone();
+    for (i,c) in constants.iter().enumerate() {
+        if c.is_none() { continue; }
+        for f in 0..deps.frames(i) {
+            for &j in deps.get_frame(i,f) { annotations[j] = None; }
+        }
+    }
+    Some(annotations)
+}
+
+/// Determine the constant produced by executing an instruction in
+/// every given state (if there is one).
+fn constant(insn: &Instruction, states: &[DefaultState]) -> Option<w256> {
+    let mut result = None;
+    for st in states {
+        let w = match execute(insn,st.clone()) {
+            Outcome::Continue(nst) => nst.stack().peek(0).try_constant()?,
+            _ => { return None; }
+        };
+        if result.is_some_and(|r| r != w) { return None; }
+        result = Some(w);
+    }
+    result
+}
+
+/// Determine whether an instruction computes a single value purely
+/// from its operands.
+fn is_arithmetic(insn: &Instruction) -> bool {
+    matches!(insn,
+             ADD|MUL|SUB|DIV|SDIV|MOD|SMOD|ADDMOD|MULMOD|EXP|SIGNEXTEND|
+             LT|GT|SLT|SGT|EQ|ISZERO|AND|OR|XOR|NOT|BYTE|SHL|SHR|SAR)
+}
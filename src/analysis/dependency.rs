@@ -166,6 +166,7 @@ impl<T:EvmStack> DependencyStack<T> {
 
 impl<T:EvmStack> EvmStack for DependencyStack<T> {
     type Word = T::Word;
+    const MAX_DEPTH: usize = T::MAX_DEPTH;
     
     fn size(&self) -> usize {
         assert!(self.is_valid()); 
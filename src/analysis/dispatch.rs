@@ -0,0 +1,130 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::bytecode::Instruction;
+use crate::bytecode::Instruction::*;
+
+/// A small table of well-known function signatures, taken from the
+/// [4byte directory](https://www.4byte.directory).  This is not
+/// intended to be exhaustive, but covers the most common functions
+/// (e.g. those of ERC20 / ERC721 tokens).
+const KNOWN_SIGNATURES : &[(u32,&str)] = &[
+    (0x01ffc9a7,"supportsInterface(bytes4)"),
+    (0x06fdde03,"name()"),
+    (0x081812fc,"getApproved(uint256)"),
+    (0x095ea7b3,"approve(address,uint256)"),
+    (0x18160ddd,"totalSupply()"),
+    (0x23b872dd,"transferFrom(address,address,uint256)"),
+    (0x2e1a7d4d,"withdraw(uint256)"),
+    (0x313ce567,"decimals()"),
+    (0x3f4ba83a,"unpause()"),
+    (0x40c10f19,"mint(address,uint256)"),
+    (0x42842e0e,"safeTransferFrom(address,address,uint256)"),
+    (0x42966c68,"burn(uint256)"),
+    (0x5c975abb,"paused()"),
+    (0x60fe47b1,"set(uint256)"),
+    (0x6352211e,"ownerOf(uint256)"),
+    (0x6d4ce63c,"get()"),
+    (0x70a08231,"balanceOf(address)"),
+    (0x715018a6,"renounceOwnership()"),
+    (0x8456cb59,"pause()"),
+    (0x8da5cb5b,"owner()"),
+    (0x95d89b41,"symbol()"),
+    (0xa22cb465,"setApprovalForAll(address,bool)"),
+    (0xa9059cbb,"transfer(address,uint256)"),
+    (0xc87b56dd,"tokenURI(uint256)"),
+    (0xd0e30db0,"deposit()"),
+    (0xdd62ed3e,"allowance(address,address)"),
+    (0xe985e9c5,"isApprovedForAll(address,address)"),
+    (0xf2fde38b,"transferOwnership(address)")
+];
+
+/// Represents an entry in the function dispatch table of a contract.
+/// That is, a function selector along with the (byte) offset of the
+/// code implementing that function.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct DispatchEntry {
+    /// The four byte function selector.
+    pub selector: u32,
+    /// The byte offset where the function begins.
+    pub pc: usize
+}
+
+impl DispatchEntry {
+    /// Lookup the signature of this function in the table of
+    /// well-known signatures (if it is present).
+    pub fn signature(&self) -> Option<&'static str> {
+        find_signature(self.selector)
+    }
+}
+
+/// Lookup the signature for a given function selector in the table
+/// of well-known signatures.
+pub fn find_signature(selector: u32) -> Option<&'static str> {
+    KNOWN_SIGNATURES.binary_search_by_key(&selector, |(s,_)| *s).ok().map(|i| KNOWN_SIGNATURES[i].1)
+}
+
+/// Recover the function dispatch table from a given sequence of
+/// instructions.  This looks for the sequence typically generated by
+/// a compiler (e.g. `solc`) for dispatching on the selector, namely:
+///
+/// ```txt
+///    dup1
+///    push4 selector
+///    eq
+///    push target
+///    jumpi
+/// ```
+///
+/// Here, the `dup1` is optional and may instead follow the `push4`
+/// (i.e. as `dup2`).  Entries are returned in the order they are
+/// encountered.
+pub fn find_dispatch_table(insns: &[Instruction]) -> Vec<DispatchEntry> {
+    let mut entries = Vec::new();
+    let mut pc = 0;
+    //
+    for (i,insn) in insns.iter().enumerate() {
+        match insn {
+            PUSH(bytes) if bytes.len() == 4 => {
+                if let Some(target) = match_dispatch(&insns[i+1..]) {
+                    let selector = u32::from_be_bytes(bytes[..].try_into().unwrap());
+                    entries.push(DispatchEntry{selector,pc: target});
+                }
+            }
+            _ => {}
+        }
+        pc += insn.length();
+    }
+    // Sanity check targets are within bounds
+    entries.retain(|e| e.pc < pc);
+    //
+    entries
+}
+
+/// Attempt to match the remainder of a dispatch sequence (i.e. the
+/// instructions following the `push4`), returning the target of the
+/// branch if successful.
+fn match_dispatch(insns: &[Instruction]) -> Option<usize> {
+    // Skip optional duplication of the selector
+    let insns = match insns.first() {
+        Some(DUP(_)) => &insns[1..],
+        _ => insns
+    };
+    //
+    match insns {
+        [EQ,PUSH(bytes),JUMPI,..] if bytes.len() <= 8 => {
+            let mut target = 0usize;
+            for b in bytes { target = (target << 8) | (*b as usize); }
+            Some(target)
+        }
+        _ => None
+    }
+}
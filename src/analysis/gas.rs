@@ -0,0 +1,66 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::bytecode::opcode::*;
+
+/// Gas charged per word of memory (`G_memory` in the yellow paper).
+pub const G_MEMORY : u64 = 3;
+
+/// Determine the total gas cost of a memory of a given size (in
+/// bytes).  Following the yellow paper, this is `3a + a^2/512` where
+/// `a` is the number of words.  Observe that this is quadratic, and
+/// hence dominated by the linear component for small memories.
+pub fn memory_cost(size: u64) -> u64 {
+    let words = size.div_ceil(32);
+    // NOTE: saturating arithmetic prevents overflow for (absurdly)
+    // large memories.
+    G_MEMORY.saturating_mul(words).saturating_add(words.saturating_mul(words) / 512)
+}
+
+/// Determine the gas charged for expanding memory from one size to
+/// another (in bytes).  This is zero when memory is not expanded.
+pub fn memory_expansion_cost(from: u64, to: u64) -> u64 {
+    memory_cost(to).saturating_sub(memory_cost(from))
+}
+
+/// Determine the _static_ (i.e. base) gas cost of a given opcode.
+/// This excludes any dynamic costs (e.g. for memory expansion, or
+/// for accessing cold accounts and storage slots), which are charged
+/// in addition to this.  Accesses are assumed to be _warm_.  Unknown
+/// opcodes have no static cost.
+pub fn static_gas(opcode: u8) -> u64 {
+    match opcode {
+        STOP|RETURN|REVERT|INVALID => 0,
+        JUMPDEST => 1,
+        ADDRESS|ORIGIN|CALLER|CALLVALUE|CALLDATASIZE|CODESIZE|GASPRICE => 2,
+        RETURNDATASIZE|COINBASE|TIMESTAMP|NUMBER|DIFFICULTY|GASLIMIT|CHAINID => 2,
+        BASEFEE|BLOBBASEFEE => 2,
+        POP|PC|MSIZE|GAS|PUSH0 => 2,
+        RJUMP => 2,
+        RJUMPI|RJUMPV => 4,
+        RETF => 3,
+        CALLF|JUMPF => 5,
+        ADD|SUB|LT|GT|SLT|SGT|EQ|ISZERO|AND|OR|XOR|NOT|BYTE|SHL|SHR|SAR|BLOBHASH => 3,
+        CALLDATALOAD|CALLDATACOPY|CODECOPY|RETURNDATACOPY|MLOAD|MSTORE|MSTORE8|MCOPY => 3,
+        PUSH1..=PUSH32|DUP1..=DUP16|SWAP1..=SWAP16 => 3,
+        MUL|DIV|SDIV|MOD|SMOD|SIGNEXTEND|SELFBALANCE => 5,
+        ADDMOD|MULMOD|JUMP => 8,
+        EXP|JUMPI => 10,
+        BLOCKHASH => 20,
+        KECCAK256 => 30,
+        BALANCE|EXTCODESIZE|EXTCODECOPY|EXTCODEHASH|SLOAD|SSTORE|TLOAD|TSTORE => 100,
+        CALL|CALLCODE|DELEGATECALL|STATICCALL => 100,
+        LOG0..=LOG4 => 375 * (1 + (opcode - LOG0) as u64),
+        SELFDESTRUCT => 5000,
+        CREATE|CREATE2 => 32000,
+        _ => 0
+    }
+}
@@ -0,0 +1,119 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::bytecode::{Disassemble,Instruction};
+use crate::util::{w256,Concretizable};
+use super::{aw256,execute,DefaultState};
+use super::{EvmStack,EvmState,Outcome};
+use super::trace::determine_byte_offsets;
+
+/// Represents an immutable value baked into the runtime code of a
+/// contract by its constructor.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct Immutable {
+    /// Byte offset within the runtime code where the value is
+    /// located (i.e. the start of the corresponding `push` operand).
+    pub offset: usize,
+    /// The value itself.
+    pub value: w256
+}
+
+/// Recover the immutable values written into the runtime code by the
+/// constructor of a contract, given its _creation bytecode_.  This
+/// works by executing the constructor concretely to determine the
+/// runtime code it returns, and comparing this against the runtime
+/// _template_ copied from the creation bytecode.  Every `push`
+/// operand in the template which was patched by the constructor is
+/// reported as an immutable.  Since the constructor may branch on
+/// unknown values (e.g. `CALLVALUE`), all paths are explored until
+/// one returns.  This fails if no path returns known code within the
+/// given number of steps.
+pub fn find_immutables(creation: &[u8], limit: usize) -> Option<Vec<Immutable>> {
+    let (runtime,template) = execute_constructor(creation,limit)?;
+    // Disassemble template to identify patchable operands
+    let mut immutables = Vec::new();
+    let mut pc = 0;
+    //
+    for insn in template.disassemble() {
+        let len = insn.length();
+        if let Instruction::PUSH(bytes) = &insn {
+            let range = (pc+1)..(pc+1+bytes.len());
+            // Check whether operand was patched
+            if range.end <= runtime.len() && runtime[range.clone()] != template[range.clone()] {
+                let value = w256::from_be_slice(&runtime[range]);
+                immutables.push(Immutable{offset: pc+1, value});
+            }
+        }
+        pc += len;
+    }
+    Some(immutables)
+}
+
+/// Execute the constructor in a given creation bytecode concretely,
+/// returning the runtime code it produces along with the template
+/// from which it was copied.
+fn execute_constructor(creation: &[u8], limit: usize) -> Option<(Vec<u8>,Vec<u8>)> {
+    let insns = creation.disassemble();
+    let offsets = determine_byte_offsets(&insns);
+    // Records (dest,offset,size) for each code copy encountered.
+    let mut copies = Vec::new();
+    let mut worklist = vec![DefaultState::new().with_code(creation)];
+    let mut count = 0;
+    //
+    while let Some(st) = worklist.pop() {
+        if count >= limit { return None; }
+        // Execution falling off the end is a STOP
+        let Some(&ipc) = offsets.get(st.pc()) else { continue; };
+        let insn = &insns[ipc];
+        count += 1;
+        //
+        if insn == &Instruction::CODECOPY && st.stack().has_operands(3) {
+            let stack = st.stack();
+            copies.push((constant(stack.peek(0)),constant(stack.peek(1)),constant(stack.peek(2))));
+        }
+        //
+        match execute(insn,st) {
+            // NOTE: a STOP also returns, but produces no runtime code.
+            Outcome::Return{offset,data: Some(runtime),..} if insn == &Instruction::RETURN && offset.is_constant() => {
+                let offset : usize = offset.constant().try_into().ok()?;
+                let size = runtime.len();
+                // Identify template which was copied here
+                let template = copies.iter().find_map(|c| match c {
+                    (Some(d),Some(o),Some(s)) if *d == offset && *s >= size => {
+                        let bytes = creation.get(*o..o+size)?;
+                        Some(bytes.to_vec())
+                    }
+                    _ => None
+                })?;
+                return Some((runtime,template));
+            }
+            Outcome::Continue(nst) => worklist.push(nst),
+            Outcome::Unknown(Some(nst)) => worklist.push(nst),
+            Outcome::Branch(sts) => worklist.extend(sts),
+            Outcome::Split(nst,bst) => {
+                worklist.push(bst);
+                worklist.push(nst);
+            }
+            _ => {}
+        }
+    }
+    // No path returned
+    None
+}
+
+/// Extract a (small) constant value, if one is known.
+fn constant(word: &aw256) -> Option<usize> {
+    if word.is_constant() && word.constant() <= w256::from(u32::MAX) {
+        Some(word.constant().to())
+    } else {
+        None
+    }
+}
@@ -0,0 +1,177 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::bytecode::{BlockVec,Builder,Instruction};
+use crate::util;
+use super::{Dependencies,find_dependencies};
+use super::trace::determine_byte_offsets;
+
+use Instruction::*;
+
+/// For a given bytecode sequence, identify small internal functions
+/// and inline them at their call sites.  This is intended as an aid
+/// to decompilation, since it removes the indirection through the
+/// internal function.  An internal function here is a single basic
+/// block of the form `jumpdest ... jump` which returns via a
+/// dynamically determined address (i.e. one pushed by the caller).
+/// A call site is then any `push f; jump` which branches to the
+/// function.  For example:
+///
+/// ```text
+///    push ret
+///    push 0x1
+///    push f
+///    jump
+/// ret:
+///    jumpdest
+///    stop
+/// f:
+///    jumpdest
+///    swap1
+///    jump
+/// ```
+///
+/// Here, `f` is inlined at its only call site (and then removed)
+/// giving:
+///
+/// ```text
+///    push ret
+///    push 0x1
+///    swap1
+///    jump
+/// ret:
+///    jumpdest
+///    stop
+/// ```
+///
+/// Observe that the function's return (i.e. its final `jump`) is
+/// retained, and this simply branches to the instruction following
+/// the original call.  A function is only inlined when it has at
+/// most `max_size` instructions, and is called from at most
+/// `max_sites` sites (and is not referenced in any other way).
+/// Since inlining changes the byte offsets of instructions, all
+/// pushes of jump targets are relocated.  However, other uses of code
+/// offsets (e.g. by `CODECOPY`) are not.  This returns `None` if the
+/// underlying analysis fails, or if the jump targets of the bytecode
+/// sequence cannot all be resolved to specific `push` instructions.
+pub fn inline_functions(insns: &[Instruction], max_size: usize, max_sites: usize, limit: usize) -> Option<Vec<Instruction>> {
+    let deps = find_dependencies(insns,limit).ok()?;
+    // Identify pushes of jump targets
+    let labels = find_labels(insns,&deps)?;
+    // Identify functions to inline
+    let blocks = BlockVec::new(insns);
+    let mut inlines : Vec<Option<(usize,usize)>> = vec![None;insns.len()];
+    let mut removed = vec![false;insns.len()];
+    let mut start = 0;
+    let mut count = 0;
+    //
+    for b in 0..blocks.len() {
+        let blk = blocks.get(b);
+        let end = start + blk.len();
+        if is_function(blk, &labels[start..end], max_size) && deps.frames(end-1) > 0 {
+            // Identify all references to this function
+            let refs : Vec<usize> = (0..insns.len()).filter(|i| labels[*i] == Some(start)).collect();
+            // Check every reference is a call site
+            let sites = refs.iter().all(|i| insns.get(i+1) == Some(&JUMP));
+            //
+            if sites && !refs.is_empty() && refs.len() <= max_sites {
+                for i in refs { inlines[i] = Some((start,end)); }
+                // Remove the original (unless control can fall into it)
+                if start == 0 || !insns[start-1].fallthru() {
+                    removed[start..end].fill(true);
+                }
+                count += 1;
+            }
+        }
+        start = end;
+    }
+    // Check whether anything to do
+    if count == 0 { return Some(insns.to_vec()); }
+    // Determine instructions of the rebuilt sequence
+    let mut selection = Vec::new();
+    let mut i = 0;
+    //
+    while i < insns.len() {
+        if removed[i] {
+            i += 1;
+        } else if let Some((s,e)) = inlines[i] {
+            // Inline function body (minus its jumpdest), skipping
+            // the original jump.
+            selection.extend(s+1..e);
+            i += 2;
+        } else {
+            selection.push(i);
+            i += 1;
+        }
+    }
+    // Rebuild the instruction sequence, relocating jump targets.
+    Some(Builder::relocate(insns,&labels,selection))
+}
+
+/// Determine whether a given block is a candidate function for
+/// inlining.  That is, it begins with a `jumpdest`, ends with a
+/// `jump` and contains no other branches or jump targets.
+fn is_function(blk: &[Instruction], labels: &[Option<usize>], max_size: usize) -> bool {
+    let n = blk.len();
+    n >= 2 && n <= max_size
+        && blk[0] == JUMPDEST
+        && blk[n-1] == JUMP
+        && blk[..n-1].iter().all(|insn| insn.fallthru() && !insn.can_branch())
+        && labels.iter().all(|l| l.is_none())
+}
+
+/// Identify those `push` instructions which provide the target of
+/// some jump.  For each such instruction, this gives the
+/// (instruction) offset of its target.  Targets are traced back
+/// through `dup` instructions, but otherwise must have been pushed
+/// directly.
+pub(crate) fn find_labels(insns: &[Instruction], deps: &Dependencies) -> Option<Vec<Option<usize>>> {
+    let offsets = determine_byte_offsets(insns);
+    let mut labels = vec![None;insns.len()];
+    let mut visited = vec![false;insns.len()];
+    //
+    for (i,insn) in insns.iter().enumerate() {
+        if matches!(insn,JUMP|JUMPI) {
+            for f in 0..deps.frames(i) {
+                // Target is always the topmost operand
+                let target = *deps.get_frame(i,f).last()?;
+                visited.fill(false);
+                for p in find_sources(target,insns,deps,&mut visited)? {
+                    let pc = match &insns[p] {
+                        PUSH(bytes) if bytes.len() <= 16 => util::from_be_bytes(bytes) as usize,
+                        _ => { return None; }
+                    };
+                    labels[p] = Some(*offsets.get(pc)?);
+                }
+            }
+        }
+    }
+    Some(labels)
+}
+
+/// Identify the `push` instruction(s) from which a given stack item
+/// originates, by tracing back through any `dup` instructions.
+fn find_sources(i: usize, insns: &[Instruction], deps: &Dependencies, visited: &mut [bool]) -> Option<Vec<usize>> {
+    match insns[i] {
+        PUSH(_) => Some(vec![i]),
+        DUP(_) if !visited[i] => {
+            visited[i] = true;
+            let mut sources = Vec::new();
+            for f in 0..deps.frames(i) {
+                let src = *deps.get_frame(i,f).first()?;
+                sources.extend(find_sources(src,insns,deps,visited)?);
+            }
+            Some(sources)
+        }
+        DUP(_) => Some(Vec::new()),
+        _ => None
+    }
+}
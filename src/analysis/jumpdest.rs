@@ -0,0 +1,111 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::bytecode::Instruction;
+use super::{cw256,ConcreteStack,ConcreteState,EvmStack,EvmState,trace,UnknownMemory,UnknownStorage};
+
+/// Describes a reachable `JUMPDEST` instruction, along with the
+/// height(s) of the stack upon entry to it.  Since a given `JUMPDEST`
+/// can be reached along different paths, there may be more than one
+/// such height.
+#[derive(Clone,Debug,PartialEq)]
+pub struct JumpDest {
+    /// Byte offset of the `JUMPDEST` instruction.
+    pub pc: usize,
+    /// The distinct stack heights on entry (in ascending order).
+    pub heights: Vec<usize>
+}
+
+impl JumpDest {
+    /// Get the incoming stack height of this `JUMPDEST`, provided it
+    /// is the same along all paths.
+    pub fn height(&self) -> Option<usize> {
+        match self.heights.as_slice() {
+            [h] => Some(*h),
+            _ => None
+        }
+    }
+}
+
+/// For a given bytecode sequence, determine the incoming stack
+/// height(s) for every reachable `JUMPDEST` instruction.  For
+/// example, consider this sequence:
+///
+/// ```txt
+///    push 0x1
+///    push lab
+///    jump
+/// lab:
+///    jumpdest
+/// ```
+///
+/// Here, the `jumpdest` has an incoming stack
+/// height of `1`.  If the analysis does not complete within the given
+/// limit, then `None` is returned.
+pub fn find_jumpdest_heights(insns: &[Instruction], limit: usize) -> Option<Vec<JumpDest>> {
+    // Configure analysis
+    type Stack = ConcreteStack<cw256>;
+    type Memory = UnknownMemory<cw256>;
+    type Storage = UnknownStorage<cw256>;
+    type State = ConcreteState<Stack,Memory,Storage>;
+    // Run the abstract trace
+    let states : Vec<Vec<State>> = trace(insns,State::new(),limit).ok()?;
+    //
+    let mut jumpdests = Vec::new();
+    let mut pc = 0;
+    //
+    for (insn,sts) in insns.iter().zip(&states) {
+        if insn == &Instruction::JUMPDEST && !sts.is_empty() {
+            jumpdests.push(JumpDest{pc,heights:stack_heights(sts)});
+        }
+        pc += insn.length();
+    }
+    // Done
+    Some(jumpdests)
+}
+
+/// Determine the distinct stack heights (in ascending order) for a
+/// given set of states.
+pub(crate) fn stack_heights<'a,T:EvmState+'a>(states: impl IntoIterator<Item=&'a T>) -> Vec<usize> {
+    let mut heights : Vec<usize> = states.into_iter().map(|st| st.stack().size()).collect();
+    heights.sort_unstable();
+    heights.dedup();
+    heights
+}
+
+/// Describes a reachable `JUMPDEST` instruction which is entered with
+/// differing stack heights along different paths.  For
+/// compiler-generated code, this typically indicates malformed
+/// bytecode (or an imprecision in the analysis).
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct StackMismatch {
+    /// Byte offset of the `JUMPDEST` instruction.
+    pub pc: usize,
+    /// The smallest stack height on entry.
+    pub min: usize,
+    /// The largest stack height on entry.
+    pub max: usize
+}
+
+/// For a given bytecode sequence, identify every reachable `JUMPDEST`
+/// whose incoming stack heights differ (see `find_jumpdest_heights()`
+/// for more).  If the analysis does not complete within the given
+/// limit, then `None` is returned.
+pub fn find_stack_mismatches(insns: &[Instruction], limit: usize) -> Option<Vec<StackMismatch>> {
+    let jumpdests = find_jumpdest_heights(insns,limit)?;
+    let mismatches = jumpdests.iter().filter_map(|jd| {
+        match jd.heights.as_slice() {
+            [min,..,max] => Some(StackMismatch{pc:jd.pc,min:*min,max:*max}),
+            _ => None
+        }
+    }).collect();
+    Some(mismatches)
+}
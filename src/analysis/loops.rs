@@ -0,0 +1,61 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::bytecode::{BlockVec,Instruction};
+use crate::util::{transitive_closure,Concretizable};
+use super::{BlockGraph,DefaultState,EvmStack,EvmState,trace};
+
+use Instruction::*;
+
+/// For a given bytecode sequence, identify all blocks which are part
+/// of a guaranteed _infinite loop_, returning their block indices (in
+/// ascending order).  A block is considered to be in an infinite loop
+/// when it lies on a cycle from which no path can escape.  For
+/// example, consider this sequence:
+///
+/// ```txt
+/// lab:
+///    jumpdest
+///    push lab
+///    jump
+/// ```
+///
+/// Here, the block at `lab` unconditionally jumps back to itself and,
+/// hence, will execute until all gas is exhausted.  Such loops are
+/// either bugs, or deliberate "lock" patterns.  Observe that a block
+/// containing a branch to an unknown target is conservatively assumed
+/// to escape.  If the analysis does not complete within the given
+/// limit, then `None` is returned.
+pub fn find_infinite_loops(insns: &[Instruction], limit: usize) -> Option<Vec<usize>> {
+    let states : Vec<Vec<DefaultState>> = trace(insns,DefaultState::new(),limit).ok()?;
+    // Construct the control-flow graph
+    let graph = BlockGraph::from_blocks(BlockVec::new(insns),limit).ok()?;
+    let reach = transitive_closure(&graph);
+    // Determine which blocks might escape, either because they have
+    // no successors, because they can reach an instruction which
+    // terminates execution (observe blocks are not split after a
+    // `JUMPI`), or because they branch to an unknown target.
+    let mut escapes : Vec<bool> = (0..graph.len()).map(|b| graph.outgoing(b).is_empty()).collect();
+    for (i,insn) in insns.iter().enumerate() {
+        let terminates = !insn.fallthru() && !insn.can_branch();
+        let unknown = matches!(insn,JUMP|JUMPI) && states[i].iter().any(|st| st.stack().peek(0).try_constant().is_none());
+        if !states[i].is_empty() && (terminates || unknown) {
+            escapes[graph.nodes().lookup_insn(i)] = true;
+        }
+    }
+    // A block is in an infinite loop if it can reach itself, and no
+    // block it can reach can escape.
+    let blocks = (0..graph.len()).filter(|&b| {
+        reach[b].contains(b) && !escapes[b] && reach[b].iter().all(|&c| c < graph.len() && !escapes[c])
+    }).collect();
+    // Done
+    Some(blocks)
+}
@@ -35,6 +35,21 @@ pub trait EvmMemory : fmt::Debug {
     /// Write a given value at a given address in memory, expanding
     /// memory as necessary.
     fn write8(&mut self, address: Self::Word, item: Self::Word);
+
+    /// Expand memory (if necessary) to cover an access of `len` bytes
+    /// at a given address, without reading or writing anything.  An
+    /// access of zero bytes does not expand memory.
+    fn expand(&mut self, address: Self::Word, len: u64);
+
+    /// Read the word at a given address in memory _without_ expanding
+    /// memory.  This is useful when memory has already been expanded
+    /// to cover the region being read.
+    fn peek(&self, address: Self::Word) -> Self::Word;
+
+    /// Get the size of memory (in bytes), as returned by `MSIZE`.
+    /// Memory expands in words whenever it is accessed, hence this
+    /// is always a multiple of `32`.
+    fn size(&self) -> Self::Word;
 }
 
 // ===================================================================
@@ -67,6 +82,18 @@ impl<T:EvmWord+Top> EvmMemory for UnknownMemory<T> {
     fn write8(&mut self, _address: Self::Word, _item: Self::Word) {
         // no op (for now)
     }
+
+    fn expand(&mut self, _address: Self::Word, _len: u64) {
+        // no op
+    }
+
+    fn peek(&self, _address: Self::Word) -> Self::Word {
+        T::TOP
+    }
+
+    fn size(&self) -> Self::Word {
+        T::TOP
+    }
 }
 
 impl<T:EvmWord+Top> Default for UnknownMemory<T> {
@@ -108,14 +135,32 @@ pub struct ConcreteMemory<T:EvmWord+Top> {
     // we're making an implicit assumption here that addressable
     // memory never exceeds 64bits.  That seems pretty reasonable for
     // the forseeable future.
-    words: BTreeMap<u64,T>
+    words: BTreeMap<u64,T>,
+    // Size of memory in bytes (which is always word aligned), or
+    // `None` if this is unknown (e.g. after accessing an unknown
+    // address).
+    size: Option<u64>
 }
 
 impl<T:EvmWord+Top> ConcreteMemory<T> {
     pub fn new() -> Self {
         let words = BTreeMap::new();
         // Memory is initially all zero
-        Self{top: false, words}
+        Self{top: false, words, size: Some(0)}
+    }
+
+    /// Expand memory (if necessary) to include an access of `len`
+    /// bytes at a given address.  Memory is expanded in words, hence
+    /// a single byte access at the last byte of memory expands it by
+    /// a whole word, whilst an access of zero bytes does not expand
+    /// it at all.
+    fn internal_expand(&mut self, addr: u64, len: u64) {
+        if let (Some(size),true) = (self.size,len != 0) {
+            // Determine end of access (rounded up to a word)
+            let end = addr.saturating_add(len).saturating_add(31) & !31;
+            //
+            self.size = Some(size.max(end));
+        }
     }
 
     fn internal_read(&self, addr: u64) -> T {
@@ -212,8 +257,9 @@ impl<T:EvmWord+Top> ConcreteMemory<T> {
             // Done
             self.words.insert(waddr,T::from(v));
         } else {
-            // In this case, writing a constant byte to an unknow word
-            // leaves an unknown word.
+            // In this case, writing a constant byte to a non-constant
+            // word leaves an unknown word.
+            self.words.insert(waddr,T::TOP);
         }
     }
 }
@@ -226,9 +272,13 @@ impl<T:EvmWord+Top> EvmMemory for ConcreteMemory<T> {
             // Note the conversion here should never fail since its
             // impossible for addressible memory to exceed 64bits.
             let addr : u64 = address.constant().to();
+            // Expand memory to cover read
+            self.internal_expand(addr,32);
             // Read word
             self.internal_read(addr)
         } else {
+            // Read address unknown, hence memory size unknown.
+            self.size = None;
             // Read address unknown, hence unknown value returned.
             T::TOP
         }
@@ -240,10 +290,12 @@ impl<T:EvmWord+Top> EvmMemory for ConcreteMemory<T> {
             // Note the conversion here should never fail since its
             // impossible for addressible memory to exceed 64bits.
             let addr : u64 = address.constant().to();
+            self.internal_expand(addr,32);
             self.internal_write(addr,item);
         } else {
             self.top = true;
             self.words.clear();
+            self.size = None;
         }
     }
 
@@ -251,12 +303,41 @@ impl<T:EvmWord+Top> EvmMemory for ConcreteMemory<T> {
         if address.is_constant() {
             // Note the conversion here should never fail since its
             // impossible for addressible memory to exceed 64bits.
-            let addr : u64 = address.constant().to();            
+            let addr : u64 = address.constant().to();
+            self.internal_expand(addr,1);
             self.internal_write8(addr,item);
         } else {
             // Unknown write.  Everything is lost.
             self.top = true;
             self.words.clear();
+            self.size = None;
+        }
+    }
+
+    fn expand(&mut self, address: Self::Word, len: u64) {
+        if address.is_constant() {
+            // Note the conversion here should never fail since its
+            // impossible for addressible memory to exceed 64bits.
+            let addr : u64 = address.constant().to();
+            self.internal_expand(addr,len);
+        } else if len != 0 {
+            // Expansion unknown, hence memory size unknown.
+            self.size = None;
+        }
+    }
+
+    fn peek(&self, address: Self::Word) -> Self::Word {
+        if address.is_constant() {
+            self.internal_read(address.constant().to())
+        } else {
+            T::TOP
+        }
+    }
+
+    fn size(&self) -> Self::Word {
+        match self.size {
+            Some(n) => T::from(w256::from(n)),
+            None => T::TOP
         }
     }
 }
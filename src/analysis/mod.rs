@@ -10,26 +10,55 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 mod cfg;
+mod complexity;
+mod constants;
 mod dependency;
+mod dispatch;
+mod gas;
 mod havoc;
+mod immutables;
+mod inline;
+mod jumpdest;
+mod loops;
 mod memory;
+mod purity;
 mod reachability;
+mod revert;
+mod redundancy;
+mod reentrancy;
 mod semantics;
+mod signature;
 mod state;
 mod state_set;
 mod stack;
 mod storage;
 mod trace;
 mod word;
+mod world;
 
 pub use cfg::*;
+pub use complexity::*;
+pub use constants::*;
 pub use dependency::*;
+pub use dispatch::*;
+pub use gas::*;
 pub use havoc::*;
+pub use immutables::*;
+pub use inline::*;
+pub use jumpdest::*;
+pub use loops::*;
 pub use memory::*;
+pub use purity::*;
 pub use reachability::*;
+pub use revert::*;
+pub use redundancy::*;
+pub use reentrancy::*;
+pub use semantics::*;
+pub use signature::*;
 pub use state::*;
 pub use state_set::*;
 pub use stack::*;
 pub use storage::*;
 pub use trace::*;
 pub use word::*;
+pub use world::*;
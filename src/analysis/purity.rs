@@ -0,0 +1,47 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::bytecode::{Disassemble,Instruction};
+use crate::bytecode::Instruction::*;
+
+/// Determine whether a given contract is _pure_.  That is, it
+/// contains no instruction which can modify (or depend upon) the
+/// state of the world beyond its input.  For example, any contract
+/// which reads or writes storage, makes a call, creates a contract,
+/// emits a log or inspects its execution environment (e.g. the
+/// caller or the block timestamp) is not considered pure.  Observe that this is a purely
+/// syntactic check over the decoded instructions, and hence is
+/// conservative (i.e. unreachable instructions are also considered).
+pub fn is_pure(bytes: &[u8]) -> bool {
+    let insns = bytes.disassemble();
+    !insns.iter().any(is_stateful)
+}
+
+/// Determine whether a given instruction can modify (or depend upon)
+/// the world state, including the transaction and block being
+/// executed.  Call data is considered input, and hence not stateful.
+pub fn is_stateful(insn: &Instruction) -> bool {
+    matches!(insn,
+             // Storage
+             SLOAD|SSTORE|TLOAD|TSTORE|
+             // Environment
+             ADDRESS|ORIGIN|CALLER|CALLVALUE|GASPRICE|GAS|
+             // Block
+             BLOCKHASH|COINBASE|TIMESTAMP|NUMBER|DIFFICULTY|GASLIMIT|CHAINID|BASEFEE|BLOBHASH|BLOBBASEFEE|
+             // Accounts
+             BALANCE|SELFBALANCE|EXTCODESIZE|EXTCODECOPY|EXTCODEHASH|
+             // Calls
+             CALL|CALLCODE|DELEGATECALL|STATICCALL|
+             // Creation / Destruction
+             CREATE|CREATE2|SELFDESTRUCT|
+             // Logging
+             LOG(_))
+}
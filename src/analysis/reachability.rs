@@ -9,8 +9,14 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
-use crate::bytecode::Instruction;
-use super::{cw256,ConcreteStack,ConcreteState,trace,UnknownMemory,UnknownStorage};
+use std::collections::HashSet;
+use crate::bytecode::{BlockVec,Builder,Instruction};
+use crate::util::{Concretizable,SubsliceOffset};
+use super::{find_dependencies,find_labels};
+use super::{cw256,BlockGraph,ConcreteStack,ConcreteState,DefaultState,EvmStack,EvmState,trace,UnknownMemory,UnknownStorage};
+use super::trace::determine_insn_offsets;
+
+use Instruction::*;
 
 /// For a given bytecode sequence, identify all _reachable_
 /// instructions.  An instruction is reachable if there exists a path
@@ -30,15 +36,25 @@ use super::{cw256,ConcreteStack,ConcreteState,trace,UnknownMemory,UnknownStorage
 /// instruction here is _unreachable_.  That is because there is no
 /// path through the control-flow graph which can lead to it.
 pub fn find_reachable(insns: &[Instruction], limit: usize) -> Result<Vec<bool>,()> {
-    // Configure analysis
-    type Stack = ConcreteStack<cw256>;
-    type Memory = UnknownMemory<cw256>;
-    type Storage = UnknownStorage<cw256>;
-    type State = ConcreteState<Stack,Memory,Storage>;    
-    // Construct initial state of EVM
-    let init = State::new();
+    find_reachable_from(insns,ReachState::new(),limit)
+}
+
+/// For a given bytecode sequence, identify all _reachable_
+/// instructions as for `find_reachable()`, but with knowledge of the
+/// underlying bytes.  This means jumps are only permitted to valid
+/// jump destinations (i.e. excluding `0x5b` bytes within the operand
+/// of a `PUSH`).
+#[allow(clippy::result_unit_err)]
+pub fn find_reachable_in(insns: &[Instruction], bytes: &[u8], limit: usize) -> Result<Vec<bool>,()> {
+    find_reachable_from(insns,ReachState::new().with_code(bytes),limit)
+}
+
+/// State used for reachability analysis.
+type ReachState = ConcreteState<ConcreteStack<cw256>,UnknownMemory<cw256>,UnknownStorage<cw256>>;
+
+fn find_reachable_from(insns: &[Instruction], init: ReachState, limit: usize) -> Result<Vec<bool>,()> {
     // Run the abstract trace
-    let states : Vec<Vec<State>> = trace(insns,init,limit).map_err(|e| ())?;
+    let states : Vec<Vec<ReachState>> = trace(insns,init,limit).map_err(|_| ())?;
     // Convert output into boolean reachability info
     let mut flags = Vec::new();
     //
@@ -52,3 +68,115 @@ pub fn find_reachable(insns: &[Instruction], limit: usize) -> Result<Vec<bool>,(
     // Done
     Ok(flags)
 }
+
+/// For a given bytecode sequence, identify the byte offsets of all
+/// instructions reachable from the instruction at a given byte
+/// offset (including itself).  That is, all instructions which could
+/// execute after that point.  For example, consider this sequence:
+///
+/// ```txt
+///    calldatasize
+///    push lab
+///    jumpi
+///    push 0x0
+///    pop
+/// lab:
+///    jumpdest
+///    stop
+/// ```
+///
+/// Here, only the `jumpdest` and `stop` are reachable from the
+/// `push 0x0`.  Reachability is determined from the control-flow
+/// graph.  Branches whose target cannot be resolved are ignored
+/// unless `over_approximate` is set, in which case every `jump` or
+/// `jumpi` is additionally assumed to reach every `jumpdest`.  If the
+/// given offset does not correspond to an instruction, or the
+/// analysis does not complete within the given limit, then `None` is
+/// returned.
+pub fn reachable_opcodes_from(insns: &[Instruction], start_pc: usize, over_approximate: bool, limit: usize) -> Option<HashSet<usize>> {
+    let states : Vec<Vec<DefaultState>> = trace(insns,DefaultState::new(),limit).ok()?;
+    let mut graph = BlockGraph::from_blocks(BlockVec::new(insns),limit).ok()?;
+    let n = graph.len();
+    let jumpdests : Vec<usize> = (0..n).filter(|&b| graph.get(b).first() == Some(&JUMPDEST)).collect();
+    if over_approximate {
+        for b in 0..n {
+            if matches!(graph.get(b).last(),Some(JUMP|JUMPI)) {
+                for &c in &jumpdests { graph.connect(b,c); }
+            }
+        }
+    }
+    // Determine byte offset of each instruction
+    let pcs = determine_insn_offsets(insns);
+    let start = pcs.iter().position(|pc| *pc == start_pc)?;
+    let start_blk = graph.nodes().lookup_insn(start);
+    let end = insns.subslice_offset(graph.get(start_blk)) + graph.get(start_blk).len();
+    // Include the remainder of the starting block.  Observe that
+    // its successors are determined only from these instructions,
+    // since any preceding branch (e.g. a `jumpi`) is not reachable.
+    let mut reached : HashSet<usize> = pcs[start..end].iter().copied().collect();
+    let mut worklist = Vec::new();
+    for i in start..end {
+        let insn = &insns[i];
+        let next = pcs[i] + insn.length();
+        let targets : Vec<usize> = match insn {
+            JUMP|JUMPI => {
+                if over_approximate { worklist.extend(&jumpdests); }
+                states[i].iter().filter_map(|st| st.stack().peek(0).try_constant()).map(|w| w.to()).collect()
+            }
+            _ => Vec::new()
+        };
+        for succ in insn.successors(pcs[i],&targets) {
+            if succ != next || (i+1) == end {
+                worklist.push(graph.nodes().lookup_pc(succ));
+            }
+        }
+    }
+    // Explore all successor blocks
+    let mut visited = vec![false; n];
+    while let Some(b) = worklist.pop() {
+        // NOTE: edges may target the exit of the graph.
+        if b < n && !visited[b] {
+            visited[b] = true;
+            let first = insns.subslice_offset(graph.get(b));
+            reached.extend(&pcs[first..first + graph.get(b).len()]);
+            worklist.extend(graph.outgoing(b));
+        }
+    }
+    Some(reached)
+}
+
+/// For a given bytecode sequence, remove all _dead_ blocks.  That is,
+/// basic blocks which are not reachable from the entry block in the
+/// control-flow graph.  For example, consider this sequence:
+///
+/// ```txt
+///    push lab
+///    jump
+///    push 0x0
+///    push 0x0
+///    return
+/// lab:
+///    jumpdest
+///    stop
+/// ```
+///
+/// Here, the block `push 0x0; push 0x0; return` is dead and will be
+/// removed.  Since this changes the byte offsets of instructions
+/// which follow, all pushes of jump targets are relocated.  However,
+/// other uses of code offsets (e.g. by `CODECOPY`) are not.  This
+/// returns `None` if the underlying analysis fails, or if the jump
+/// targets of the bytecode sequence cannot all be resolved to
+/// specific `push` instructions.
+pub fn strip_dead_code(insns: &[Instruction], limit: usize) -> Option<Vec<Instruction>> {
+    let graph = BlockGraph::from_blocks(BlockVec::new(insns),limit).ok()?;
+    let reachable = graph.reachable();
+    let deps = find_dependencies(insns,limit).ok()?;
+    // Identify pushes of jump targets
+    let labels = find_labels(insns,&deps)?;
+    // Rebuild the instruction sequence, relocating jump targets.
+    let selection = reachable.iter().flat_map(|b| {
+        let start = insns.subslice_offset(graph.get(*b));
+        start..start + graph.get(*b).len()
+    });
+    Some(Builder::relocate(insns,&labels,selection))
+}
@@ -0,0 +1,133 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::bytecode::{BlockVec,Instruction};
+use crate::util::{dominators,transitive_closure,w256,Concretizable,SubsliceOffset};
+use super::{BlockGraph,DefaultState,EvmStack,EvmState,trace};
+
+use Instruction::*;
+
+/// For a given bytecode sequence, identify all _redundant_ `SLOAD`
+/// instructions, returning their byte offsets.  An `SLOAD` is
+/// considered redundant when it reads a (known) storage slot which
+/// was already read (or written) by some earlier instruction, such
+/// that: (1) the earlier instruction dominates the `SLOAD`; and (2)
+/// there is no intervening instruction which might modify that slot.
+/// In such case, the value loaded could have been cached (e.g. on
+/// the stack) instead.  For example, consider this sequence:
+///
+/// ```txt
+///    push 0x0
+///    sload
+///    push 0x0
+///    sload
+/// ```
+///
+/// Here, the second `sload` is redundant since slot `0x0` cannot have
+/// changed since it was last read.  Observe that any storage write
+/// to an unknown slot, or any external call (which could reenter the
+/// contract), is conservatively assumed to modify every slot.
+pub fn find_redundant_sloads(insns: &[Instruction], limit: usize) -> Option<Vec<usize>> {
+    // Determine the storage slot accessed by each instruction (where
+    // known).
+    let states : Vec<Vec<DefaultState>> = trace(insns,DefaultState::new(),limit).ok()?;
+    let slots : Vec<Option<w256>> = insns.iter().zip(&states).map(|(insn,sts)| {
+        match insn {
+            SLOAD|SSTORE => determine_slot(sts),
+            _ => None
+        }
+    }).collect();
+    // Construct the control-flow graph
+    let graph = BlockGraph::from_blocks(BlockVec::new(insns),limit).ok()?;
+    let dom = dominators(&graph);
+    let reach = transitive_closure(&graph);
+    // Determine block boundaries (as instruction offsets)
+    let starts : Vec<usize> = (0..graph.len()).map(|b| insns.subslice_offset(graph.get(b))).collect();
+    let block_of = |i:usize| graph.nodes().lookup_insn(i);
+    //
+    let mut pcs = Vec::new();
+    let mut pc = 0;
+    //
+    for (i,insn) in insns.iter().enumerate() {
+        if let (SLOAD,Some(slot)) = (insn,slots[i]) {
+            let b = block_of(i);
+            // Look for an earlier access of the same slot.
+            let redundant = (0..insns.len()).any(|j| {
+                let a = block_of(j);
+                // Check instruction j accesses the same slot
+                if j == i || !matches!(insns[j],SLOAD|SSTORE) || slots[j] != Some(slot) {
+                    return false;
+                }
+                // Check instruction j dominates instruction i
+                if (a == b && j > i) || !dom[b].contains(a) {
+                    return false;
+                }
+                // Since blocks may contain conditional branches, also
+                // check block b cannot be reached from a before j.
+                if a != b && insns[starts[a]..j].iter().any(|x| matches!(x,JUMPI)) {
+                    return false;
+                }
+                // Determine those instructions which could execute
+                // between j and i.  When j can be reached again from
+                // i then, conservatively, this includes everything
+                // in both blocks.
+                let looping = reach[b].contains(a);
+                let mut between : Vec<usize> = if a == b && !looping {
+                    (j+1..i).collect()
+                } else if looping {
+                    (starts[a]..starts[a]+graph.get(a).len()).chain(starts[b]..starts[b]+graph.get(b).len()).collect()
+                } else {
+                    (j+1..starts[a]+graph.get(a).len()).chain(starts[b]..i).collect()
+                };
+                // Include all blocks lying on a path from a to b.
+                for x in 0..graph.len() {
+                    if x != a && x != b && reach[a].contains(x) && reach[x].contains(b) {
+                        between.extend(starts[x]..starts[x]+graph.get(x).len());
+                    }
+                }
+                // Check nothing in between could modify the slot.
+                !between.iter().any(|&k| may_modify(&insns[k],slots[k],slot))
+            });
+            if redundant { pcs.push(pc); }
+        }
+        pc += insn.length();
+    }
+    // Done
+    Some(pcs)
+}
+
+/// Determine the storage slot accessed by an instruction, given its
+/// incoming states.  This is only known when it is the same constant
+/// across all states.
+fn determine_slot(states: &[DefaultState]) -> Option<w256> {
+    let mut slot = None;
+    for st in states {
+        let w = st.stack().peek(0);
+        if !w.is_constant() { return None; }
+        match slot {
+            None => { slot = Some(w.constant()); }
+            Some(s) if s != w.constant() => { return None; }
+            _ => {}
+        }
+    }
+    slot
+}
+
+/// Determine whether a given instruction could modify a given storage
+/// slot.
+fn may_modify(insn: &Instruction, insn_slot: Option<w256>, slot: w256) -> bool {
+    match insn {
+        SSTORE => insn_slot.is_none_or(|s| s == slot),
+        // External calls may reenter the contract
+        CALL|CALLCODE|DELEGATECALL|CREATE|CREATE2 => true,
+        _ => false
+    }
+}
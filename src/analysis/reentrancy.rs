@@ -0,0 +1,108 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::bytecode::{BlockVec,Instruction};
+use crate::util::{transitive_closure,w256,Concretizable,EdgeSet};
+use super::{BlockGraph,DefaultState,EvmStack,EvmState,trace};
+use super::trace::determine_insn_offsets;
+
+use Instruction::*;
+
+/// Identifies a potential reentrancy vulnerability, where storage
+/// is written after an external call.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,PartialOrd,Ord)]
+pub struct Reentrancy {
+    /// Byte offset of the (value-bearing) `call` instruction.
+    pub call: usize,
+    /// Byte offset of the subsequent `sstore` instruction.
+    pub sstore: usize
+}
+
+/// For a given bytecode sequence, identify patterns which are
+/// susceptible to reentrancy.  That is, violations of the
+/// _checks-effects-interactions_ pattern where a storage location is
+/// read before an external call which may transfer value, and then
+/// written afterwards.  For example, consider this sequence:
+///
+/// ```txt
+///    push 0x0
+///    sload
+///    ...
+///    call
+///    ...
+///    push 0x0
+///    sstore
+/// ```
+///
+/// Here, a reentrant call could observe the original value of
+/// storage location `0x0`, since it is only updated after the call.
+/// This is a heuristic analysis.  A call is considered value-bearing
+/// unless its value is known to be zero, and storage locations which
+/// are unknown (e.g. mapping entries) are assumed to coincide.  The
+/// ordering of instructions is determined from the control-flow
+/// graph.  If the analysis does not complete within the given limit,
+/// then `None` is returned.
+pub fn find_reentrancy(insns: &[Instruction], limit: usize) -> Option<Vec<Reentrancy>> {
+    let states : Vec<Vec<DefaultState>> = trace(insns,DefaultState::new(),limit).ok()?;
+    // Construct the control-flow graph
+    let graph = BlockGraph::from_blocks(BlockVec::new(insns),limit).ok()?;
+    let reach = transitive_closure(&graph);
+    // Determine byte offset of each instruction
+    let pcs = determine_insn_offsets(insns);
+    // Identify storage accesses and calls
+    let mut loads = Vec::new();
+    let mut stores = Vec::new();
+    let mut calls = Vec::new();
+    //
+    for (i,insn) in insns.iter().enumerate() {
+        let sts = &states[i];
+        match insn {
+            SLOAD if !sts.is_empty() => loads.push((i,slots(sts))),
+            SSTORE if !sts.is_empty() => stores.push((i,slots(sts))),
+            CALL if sts.iter().any(|st| st.stack().peek(2).try_constant() != Some(w256::ZERO)) => calls.push(i),
+            _ => {}
+        }
+    }
+    // Look for matching patterns
+    let mut warnings = Vec::new();
+    for &c in &calls {
+        for (s,s_slots) in &stores {
+            if !precedes(c,*s,&graph,&reach) { continue; }
+            let read = loads.iter().any(|(l,l_slots)| precedes(*l,c,&graph,&reach) && may_alias(l_slots,s_slots));
+            if read {
+                warnings.push(Reentrancy{call: pcs[c], sstore: pcs[*s]});
+            }
+        }
+    }
+    warnings.sort();
+    warnings.dedup();
+    Some(warnings)
+}
+
+/// Determine the storage slots accessed by an instruction, where
+/// `None` indicates an unknown slot.
+fn slots(states: &[DefaultState]) -> Vec<Option<w256>> {
+    states.iter().map(|st| st.stack().peek(0).try_constant()).collect()
+}
+
+/// Determine whether two sets of storage slots may coincide.
+fn may_alias(lhs: &[Option<w256>], rhs: &[Option<w256>]) -> bool {
+    lhs.iter().any(|l| rhs.iter().any(|r| l.is_none() || r.is_none() || l == r))
+}
+
+/// Determine whether instruction `i` can be executed before
+/// instruction `j`.  That is, whether there is a path from `i` to `j`
+/// through the control-flow graph.
+fn precedes(i: usize, j: usize, graph: &BlockGraph, reach: &[EdgeSet]) -> bool {
+    let bi = graph.nodes().lookup_insn(i);
+    let bj = graph.nodes().lookup_insn(j);
+    (bi == bj && i < j) || reach[bi].contains(bj)
+}
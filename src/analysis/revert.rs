@@ -0,0 +1,91 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::fmt;
+use crate::util::{w256,ToHexString};
+use super::{EvmState,Outcome};
+
+/// Function selector for a standard `Error(string)` revert, as
+/// generated by e.g. `require(cond,"message")`.
+pub const ERROR_SELECTOR : [u8;4] = [0x08,0xc3,0x79,0xa0];
+
+/// Function selector for a standard `Panic(uint256)` revert, as
+/// generated by e.g. arithmetic overflow.
+pub const PANIC_SELECTOR : [u8;4] = [0x4e,0x48,0x7b,0x71];
+
+/// A structured representation of the data returned by a `REVERT`.
+/// This distinguishes the standard ABI-encoded errors generated by
+/// Solidity from custom errors (which are left undecoded).
+#[derive(Clone,Debug,PartialEq)]
+pub enum RevertReason {
+    /// A standard `Error(string)` revert with its message.
+    Error(String),
+    /// A standard `Panic(uint256)` revert with its panic code
+    /// (e.g. `0x11` for arithmetic overflow).
+    Panic(w256),
+    /// Any other revert data (e.g. a custom error).
+    Custom(Vec<u8>)
+}
+
+impl RevertReason {
+    /// Decode the given revert data.  Data which is not a well-formed
+    /// `Error(string)` or `Panic(uint256)` is treated as a custom
+    /// error.
+    pub fn decode(data: &[u8]) -> Self {
+        if let Some(msg) = decode_error(data) {
+            RevertReason::Error(msg)
+        } else if data.len() == 36 && data[..4] == PANIC_SELECTOR {
+            RevertReason::Panic(w256::from_be_slice(&data[4..]))
+        } else {
+            RevertReason::Custom(data.to_vec())
+        }
+    }
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RevertReason::Error(msg) => write!(f,"Error({msg:?})"),
+            RevertReason::Panic(code) => write!(f,"Panic({code:#x})"),
+            RevertReason::Custom(data) => write!(f,"{}",data.to_hex_string())
+        }
+    }
+}
+
+impl<T:EvmState> Outcome<T> {
+    /// Determine the reason for a revert, provided this outcome is a
+    /// revert whose data is known.
+    pub fn revert_reason(&self) -> Option<RevertReason> {
+        match self {
+            Outcome::Revert{data: Some(data),..} => Some(RevertReason::decode(data)),
+            _ => None
+        }
+    }
+}
+
+/// Attempt to decode an ABI-encoded `Error(string)`.  This consists
+/// of the selector, followed by the offset of the string (relative to
+/// the end of the selector), its length and then its contents.
+fn decode_error(data: &[u8]) -> Option<String> {
+    if data.len() < 4 || data[..4] != ERROR_SELECTOR { return None; }
+    let args = &data[4..];
+    let offset = read_usize(args,0)?;
+    let len = read_usize(args,offset)?;
+    let start = offset.checked_add(32)?;
+    let bytes = args.get(start..start.checked_add(len)?)?;
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+/// Read a word at a given offset which must fit within a `usize`.
+fn read_usize(bytes: &[u8], offset: usize) -> Option<usize> {
+    let word = bytes.get(offset..offset.checked_add(32)?)?;
+    w256::from_be_slice(word).try_into().ok()
+}
@@ -10,22 +10,46 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use crate::util::{Concretizable,w256,Top};
-use crate::bytecode::{Instruction};
+use crate::bytecode::{from_rel_offset,Instruction};
 use crate::bytecode::Instruction::*;
 use crate::util::{W256_ZERO};
-use super::{EvmState,EvmStack,EvmMemory,EvmStorage,EvmWord};
+use super::{memory_expansion_cost,static_gas,EvmState,EvmStack,EvmMemory,EvmStorage,EvmWord,WorldState};
+
+/// Upper bound on the number of bytes moved into or out of memory by
+/// a single instruction which is modelled precisely.  Beyond this,
+/// the bytes involved are simply considered unknown.
+const MAX_COPY_SIZE : w256 = w256::from_limbs([0x10000,0,0,0]);
+
+/// Upper bound on the number of bytes hashed by a single `KECCAK256`
+/// instruction which is modelled precisely.  Beyond this, the hash is
+/// simply considered unknown.
+const MAX_HASH_SIZE : w256 = w256::from_limbs([0x400,0,0,0]);
 
 /// Represents the possible outcomes from executing a given
 /// instruction in a given state.
 pub enum Outcome<T:EvmState> {
-    /// Signal contract return.
-    Return, // add more info about return state
+    /// Signal contract return.  This identifies the region of memory
+    /// holding the return data and, where it is known, the data
+    /// itself.
+    Return{offset: T::Word, size: T::Word, data: Option<Vec<u8>>},
+    /// Signal contract revert.  As for a return, this identifies the
+    /// region of memory holding the revert data and, where it is
+    /// known, the data itself.
+    Revert{offset: T::Word, size: T::Word, data: Option<Vec<u8>>},
     /// Indicates that a single ongoing execution state has been
     /// produced (i.e. no errors or branching has occurred).
     Continue(T),
     /// Indicates that the given state splits into two states
     /// (e.g. because of a branch).
     Split(T,T),
+    /// Indicates that the given state splits into any number of
+    /// states (e.g. because of a jump table whose index is unknown).
+    Branch(Vec<T>),
+    /// Indicates a branch to an unknown target (e.g. a jump whose
+    /// target is computed from input data).  Since the target cannot
+    /// be determined, only the fall-through state (if any) can
+    /// continue.
+    Unknown(Option<T>),
     /// Indicates an exception was raised.
     Exception(EvmException)
 }
@@ -53,7 +77,30 @@ use EvmException::*;
 
 /// Execute an instruction from the given EVM state producing one (or
 /// more) output states.
-pub fn execute<T:EvmState+Clone>(insn: &Instruction, state: T) -> Outcome<T>
+pub fn execute<T:EvmState+Clone>(insn: &Instruction, mut state: T) -> Outcome<T>
+where T::Word : Top {
+    // Charge static gas cost (if gas is being tracked)
+    if let Some(gas) = state.gas() {
+        let cost = static_gas_cost(insn);
+        if gas < cost { return Outcome::Exception(InsufficientGas); }
+        state.set_gas(gas - cost);
+    }
+    // Determine size of memory before execution (if gas is being
+    // tracked), so that any expansion can be charged for afterwards.
+    let msize = match state.gas() {
+        Some(_) => state.memory().size().try_constant(),
+        None => None
+    };
+    //
+    let outcome = execute_insn(insn, state);
+    //
+    match msize {
+        Some(size) => charge_memory_expansion(size.to(), outcome),
+        None => outcome
+    }
+}
+
+fn execute_insn<T:EvmState+Clone>(insn: &Instruction, state: T) -> Outcome<T>
 where T::Word : Top {
 
     let zero = T::Word::from(W256_ZERO); 
@@ -62,41 +109,41 @@ where T::Word : Top {
         // ===========================================================
         // 0s: Stop and Arithmetic Operations
         // ===========================================================
-        STOP => Outcome::Return,
+        STOP => execute_stop(state),
         ADD => execute_binary(state,|l,r| l.add(r)),
         MUL => execute_binary(state, |l,r| l.mul(r)),
         SUB => execute_binary(state, |l,r| l.sub(r)),
         DIV => execute_binary(state,  |l,r| if r == zero { zero.clone() } else { l.div(r) }),
-        SDIV => execute_binary(state,  |_,_| T::Word::TOP),
+        SDIV => execute_binary(state,  |l,r| l.sdiv(r)),
         MOD => execute_binary(state,  |l,r| if r == zero { zero.clone() } else { l.rem(r) }),
-        SMOD => execute_binary(state,  |_,_| T::Word::TOP),
+        SMOD => execute_binary(state,  |l,r| l.srem(r)),
         ADDMOD => execute_ternary(state,  |l,r,m| if m == zero { zero.clone() } else { l.add(r).rem(m) }),
         MULMOD => execute_ternary(state, |l,r,m| if m == zero { zero.clone() } else { l.mul(r).rem(m) }),
-        EXP => execute_binary(state,  |_,_| T::Word::TOP),
-        SIGNEXTEND => execute_binary(state,  |_,_| T::Word::TOP),
+        EXP => execute_binary(state,  |l,r| l.exp(r)),
+        SIGNEXTEND => execute_binary(state,  |l,r| l.signextend(r)),
 
         // ===========================================================
         // 10s: Comparison & Bitwise Logic Operations
         // ===========================================================
         LT => execute_binary(state, |l,r| l.less_than(r)),
         GT => execute_binary(state, |l,r| r.less_than(l)),
-        SLT => execute_binary(state, |_,_| T::Word::TOP),
-        SGT => execute_binary(state, |_,_| T::Word::TOP),
+        SLT => execute_binary(state, |l,r| l.signed_less_than(r)),
+        SGT => execute_binary(state, |l,r| r.signed_less_than(l)),
         EQ => execute_binary(state, |l,r| r.equal(l)),
         ISZERO => execute_unary(state, |l| l.is_zero()),
         AND => execute_binary(state, |l,r| l.and(r)),
         OR => execute_binary(state, |l,r| l.or(r)),
         XOR => execute_binary(state, |l,r| l.xor(r)),
         NOT => execute_unary(state, |w| w.not()),
-        BYTE => execute_binary(state, |_,_| T::Word::TOP),
-        SHL => execute_binary(state, |_,_| T::Word::TOP),
-        SHR => execute_binary(state, |_,_| T::Word::TOP),
-        SAR => execute_binary(state, |_,_| T::Word::TOP),
+        BYTE => execute_binary(state, |n,w| w.byte(n)),
+        SHL => execute_binary(state, |n,w| w.shl(n)),
+        SHR => execute_binary(state, |n,w| w.shr(n)),
+        SAR => execute_binary(state, |n,w| w.sar(n)),
 
         // ===========================================================
         // 20s: Keccak256
         // ===========================================================
-        KECCAK256 => execute_binary(state, |_,_| T::Word::TOP),
+        KECCAK256 => execute_keccak256(state),
 
         // ===========================================================
         // 30s: Environment Information
@@ -106,17 +153,35 @@ where T::Word : Top {
         ORIGIN => execute_producer(state, &[T::Word::TOP]),
         CALLER => execute_producer(state, &[T::Word::TOP]),
         CALLVALUE => execute_producer(state, &[T::Word::TOP]),
-        CALLDATALOAD => execute_unary(state, |_| T::Word::TOP),
-        CALLDATASIZE => execute_producer(state, &[T::Word::TOP]),
-        CALLDATACOPY => execute_consumer(state, 3),
-        CODESIZE => execute_producer(state, &[T::Word::TOP]),
-        CODECOPY => execute_consumer(state, 3),
+        CALLDATALOAD => execute_calldataload(state),
+        CALLDATASIZE => {
+            let size = match state.calldata() {
+                Some(data) => T::Word::from(w256::from(data.len())),
+                None => T::Word::TOP
+            };
+            execute_producer(state, &[size])
+        }
+        CALLDATACOPY => execute_copy(state, T::calldata),
+        CODESIZE => {
+            let size = match state.code() {
+                Some(code) => T::Word::from(w256::from(code.len())),
+                None => T::Word::TOP
+            };
+            execute_producer(state, &[size])
+        }
+        CODECOPY => execute_copy(state, T::code),
         GASPRICE => execute_producer(state, &[T::Word::TOP]),
-        EXTCODESIZE => execute_unary(state, |_| T::Word::TOP),
+        EXTCODESIZE => execute_extcode(state, WorldState::code_size),
         EXTCODECOPY => execute_consumer(state, 4),
-        RETURNDATASIZE => execute_producer(state, &[T::Word::TOP]),
-        RETURNDATACOPY => execute_consumer(state, 3),
-        EXTCODEHASH => execute_unary(state, |_| T::Word::TOP),
+        RETURNDATASIZE => {
+            let size = match state.returndata_size() {
+                Some(n) => T::Word::from(w256::from(n)),
+                None => T::Word::TOP
+            };
+            execute_producer(state, &[size])
+        }
+        RETURNDATACOPY => execute_returndatacopy(state),
+        EXTCODEHASH => execute_extcode(state, WorldState::code_hash),
 
         // ===========================================================
         // 40s: Block Information
@@ -129,6 +194,9 @@ where T::Word : Top {
         GASLIMIT => execute_producer(state, &[T::Word::TOP]),
         CHAINID => execute_producer(state, &[T::Word::TOP]),
         SELFBALANCE => execute_producer(state, &[T::Word::TOP]),
+        BASEFEE => execute_producer(state, &[T::Word::TOP]),
+        BLOBHASH => execute_unary(state, |_| T::Word::TOP),
+        BLOBBASEFEE => execute_producer(state, &[T::Word::TOP]),
 
         // ===========================================================
         // 50s: Stack, Memory Storage and Flow Operations
@@ -140,13 +208,30 @@ where T::Word : Top {
         SLOAD => execute_sload(state),
         SSTORE => execute_sstore(state),
         PC => execute_producer(state, &[T::Word::TOP]),
-        MSIZE => execute_producer(state, &[T::Word::TOP]),
-        GAS => execute_producer(state, &[T::Word::TOP]),
+        MSIZE => {
+            let size = state.memory().size();
+            execute_producer(state, &[size])
+        }
+        GAS => {
+            let gas = match state.gas() {
+                Some(gas) => T::Word::from(w256::from(gas)),
+                None => T::Word::TOP
+            };
+            execute_producer(state, &[gas])
+        }
         JUMPDEST => execute_nop(state),
         JUMP => execute_jump(state),
         JUMPI => execute_jumpi(state),
-	TLOAD => execute_unary(state, |_| T::Word::TOP),
-	TSTORE => execute_consumer(state, 2),
+        RJUMP(offset) => execute_rjump(state, *offset),
+        RJUMPI(offset) => execute_rjumpi(state, *offset),
+        RJUMPV(offsets) => execute_rjumpv(state, offsets),
+        CALLF(index) => execute_callf(state, *index),
+        // Return to an unknown caller
+        RETF => Outcome::Unknown(None),
+        JUMPF(index) => execute_jumpf(state, *index),
+	TLOAD => execute_tload(state),
+	TSTORE => execute_tstore(state),
+	MCOPY => execute_mcopy(state),
 
         PUSH0 => execute_push(state,&[]),        
         // ===========================================================
@@ -172,24 +257,22 @@ where T::Word : Top {
         // ===========================================================
         // f0s: System Operations
         // ===========================================================
-        CREATE => execute_consumer_producer(state, 3, &[T::Word::TOP]),
-        CALL => execute_consumer_producer(state, 7, &[T::Word::TOP]),
-        CALLCODE => execute_consumer_producer(state, 7, &[T::Word::TOP]),
-        RETURN => execute_consumer_outcome(state, 2, Outcome::Return),
-        DELEGATECALL => execute_consumer_producer(state, 6, &[T::Word::TOP]),
-        CREATE2 => execute_consumer_producer(state, 4, &[T::Word::TOP]),
-        STATICCALL => execute_consumer_producer(state, 6, &[T::Word::TOP]),
-        REVERT => execute_consumer_outcome(state, 2, Outcome::Exception(Revert)),
+        CREATE => execute_create(state, 3),
+        CALL => execute_call(state, 7),
+        CALLCODE => execute_call(state, 7),
+        RETURN => execute_return(state, false),
+        DELEGATECALL => execute_call(state, 6),
+        CREATE2 => execute_create(state, 4),
+        STATICCALL => execute_call(state, 6),
+        REVERT => execute_return(state, true),
         INVALID => Outcome::Exception(InvalidOpcode),
-        SELFDESTRUCT => execute_consumer_outcome(state, 1, Outcome::Return),
+        SELFDESTRUCT => execute_selfdestruct(state),
         // ===========================================================
         // XXs: Virtual Instructions
         // ===========================================================        
         HAVOC(n) => execute_havoc(state, *n),
-        //
-        _ => {
-            Outcome::Exception(InvalidOpcode)
-        }
+        // Bytes which do not correspond to any instruction.
+        DATA(_) => Outcome::Exception(InvalidOpcode),
     }
 }
 
@@ -266,16 +349,6 @@ where F:Fn(T::Word,T::Word,T::Word)->T::Word {
 // Producers / Consumers
 // ===================================================================
 
-fn execute_consumer_outcome<T:EvmState>(mut state: T, n: usize, outcome: Outcome<T>) -> Outcome<T> {
-    let stack = state.stack_mut();
-    //
-    if !stack.has_operands(n) {
-        Outcome::Exception(StackUnderflow)
-    } else {
-        outcome
-    }
-}
-
 fn execute_consumer_producer<T:EvmState>(mut state: T, n: usize, items: &[T::Word]) -> Outcome<T> {
     let stack = state.stack_mut();
     //
@@ -373,6 +446,292 @@ let stack = state.stack_mut();
     }
 }
 
+/// Copy bytes from some source (e.g. the code being executed) into
+/// memory.  When the source is unknown, the bytes written are
+/// unknown.  Copying nothing leaves memory untouched, whilst copying
+/// beyond the addressable range of memory exhausts gas.
+fn execute_copy<T:EvmState,F>(mut state: T, source: F) -> Outcome<T>
+where T::Word: Top, F: Fn(&T) -> Option<&[u8]> {
+    let stack = state.stack_mut();
+    //
+    if !stack.has_operands(3) {
+        Outcome::Exception(StackUnderflow)
+    } else {
+        // Pop destination, offset and size
+        let dest = stack.pop();
+        let offset = stack.pop();
+        let size = stack.pop();
+        //
+        if size.is_constant() && size.constant() == w256::ZERO {
+            // Nothing is copied (and memory is not expanded).
+        } else if dest.is_constant() && size.is_constant() && size.constant() <= MAX_COPY_SIZE {
+            if beyond_memory(dest.constant(),size.constant()) {
+                return Outcome::Exception(InsufficientGas);
+            }
+            let dest : u64 = dest.constant().to();
+            let size : u64 = size.constant().to();
+            // Determine bytes being copied (if known)
+            let bytes = match (source(&state),offset.is_constant()) {
+                (Some(bytes),true) => {
+                    let offset = offset.constant();
+                    // Bytes beyond the end of the source are zero
+                    (0..size).map(|i| {
+                        T::Word::from(w256::from(byte_at(bytes,offset.saturating_add(w256::from(i)))))
+                    }).collect()
+                }
+                (_,_) => vec![T::Word::TOP; size as usize]
+            };
+            // Write them into memory
+            let memory = state.memory_mut();
+            for (i,b) in bytes.into_iter().enumerate() {
+                memory.write8(T::Word::from(w256::from(dest + (i as u64))),b);
+            }
+        } else {
+            // Unknown write.  Everything is lost.
+            state.memory_mut().write(T::Word::TOP,T::Word::TOP);
+        }
+        // Move to next instruction
+        state.skip(1);
+        //
+        Outcome::Continue(state)
+    }
+}
+
+/// Check whether a region of memory with a given start and size
+/// extends beyond a 64-bit address.  Since expanding memory this far
+/// requires more gas than can ever be available, any access to such
+/// a region exhausts gas.
+fn beyond_memory(start: w256, size: w256) -> bool {
+    start.checked_add(size).is_none_or(|end| end > w256::from(u64::MAX))
+}
+
+/// Execute an `MCOPY` instruction, which copies a region of memory
+/// to another (potentially overlapping) region.  The source region is
+/// read in its entirety before being written, as for `memmove`.  As
+/// for other copies, copying nothing leaves memory untouched, whilst
+/// copying beyond the addressable range of memory exhausts gas.
+fn execute_mcopy<T:EvmState>(mut state: T) -> Outcome<T>
+where T::Word: Top {
+    let stack = state.stack_mut();
+    //
+    if !stack.has_operands(3) {
+        Outcome::Exception(StackUnderflow)
+    } else {
+        // Pop destination, source and size
+        let dest = stack.pop();
+        let src = stack.pop();
+        let size = stack.pop();
+        //
+        if size.is_constant() && size.constant() == w256::ZERO {
+            // Nothing is copied (and memory is not expanded).
+        } else if dest.is_constant() && src.is_constant() && size.is_constant() && size.constant() <= MAX_COPY_SIZE {
+            if beyond_memory(dest.constant(),size.constant()) || beyond_memory(src.constant(),size.constant()) {
+                return Outcome::Exception(InsufficientGas);
+            }
+            let size : u64 = size.constant().to();
+            let memory = state.memory_mut();
+            // Expand memory to cover both regions exactly once
+            memory.expand(dest.clone(),size);
+            memory.expand(src.clone(),size);
+            let dest : u64 = dest.constant().to();
+            let src = src.constant();
+            // Read source bytes (i.e. the most significant byte of
+            // the word starting at each address).
+            let bytes : Vec<T::Word> = (0..size).map(|i| {
+                let addr = T::Word::from(src.saturating_add(w256::from(i)));
+                memory.peek(addr).byte(T::Word::from(w256::ZERO))
+            }).collect();
+            // Write them into memory
+            for (i,b) in bytes.into_iter().enumerate() {
+                memory.write8(T::Word::from(w256::from(dest + (i as u64))),b);
+            }
+        } else {
+            // Unknown write.  Everything is lost.
+            state.memory_mut().write(T::Word::TOP,T::Word::TOP);
+        }
+        // Move to next instruction
+        state.skip(1);
+        //
+        Outcome::Continue(state)
+    }
+}
+
+fn execute_keccak256<T:EvmState>(mut state: T) -> Outcome<T>
+where T::Word: Top {
+    let stack = state.stack_mut();
+    //
+    if !stack.has_operands(2) {
+        Outcome::Exception(StackUnderflow)
+    } else {
+        // Pop offset and size
+        let offset = stack.pop();
+        let size = stack.pop();
+        // Hashing beyond the addressable range of memory exhausts gas
+        if offset.is_constant() && size.is_constant() && size.constant() != w256::ZERO
+            && beyond_memory(offset.constant(),size.constant()) {
+            return Outcome::Exception(InsufficientGas);
+        }
+        //
+        let hash = if offset.is_constant() && size.is_constant() && size.constant() <= MAX_HASH_SIZE {
+            let memory = state.memory_mut();
+            // Expand memory to cover exactly the region being hashed
+            memory.expand(offset.clone(),size.constant().to());
+            let offset = offset.constant();
+            let size : usize = size.constant().to();
+            // Read the words covering the region being hashed
+            let words = (0..size.div_ceil(32)).map(|i| {
+                memory.peek(T::Word::from(offset + w256::from(i * 32)))
+            }).collect();
+            T::Word::keccak256(words,size)
+        } else {
+            // Unknown region.  Memory size is lost.
+            state.memory_mut().read(T::Word::TOP);
+            T::Word::TOP
+        };
+        state.stack_mut().push(hash);
+        // Move to next instruction
+        state.skip(1);
+        //
+        Outcome::Continue(state)
+    }
+}
+
+fn execute_stop<T:EvmState>(_state: T) -> Outcome<T> {
+    let zero = T::Word::from(W256_ZERO);
+    Outcome::Return{offset: zero.clone(), size: zero, data: Some(Vec::new())}
+}
+
+/// Execute a call which consumes `n` operands and produces an
+/// unknown success flag.  Since the callee is not executed, the
+/// return data it produces is unknown.  Hence, the region of memory
+/// into which this is written (i.e. as determined by the last two
+/// operands) is unknown and, if that region is itself unknown, then
+/// memory is lost entirely.
+fn execute_call<T:EvmState>(mut state: T, n: usize) -> Outcome<T>
+where T::Word: Top {
+    let stack = state.stack();
+    //
+    if !stack.has_operands(n) {
+        return Outcome::Exception(StackUnderflow);
+    }
+    let offset = stack.peek(n-2).clone();
+    let size = stack.peek(n-1).clone();
+    //
+    if size.is_constant() && size.constant() == w256::ZERO {
+        // Nothing is written (and memory is not expanded).
+    } else if offset.is_constant() && size.is_constant() && size.constant() <= MAX_COPY_SIZE {
+        if beyond_memory(offset.constant(),size.constant()) {
+            return Outcome::Exception(InsufficientGas);
+        }
+        let offset = offset.constant();
+        let size : u64 = size.constant().to();
+        let memory = state.memory_mut();
+        for i in 0..size {
+            memory.write8(T::Word::from(offset + w256::from(i)),T::Word::TOP);
+        }
+    } else {
+        // Unknown write.  Everything is lost.
+        state.memory_mut().write(T::Word::TOP,T::Word::TOP);
+    }
+    state.set_returndata_size(None);
+    execute_consumer_producer(state, n, &[T::Word::TOP])
+}
+
+/// Execute a create which consumes `n` operands and produces an
+/// unknown address.  Since the initcode is not executed, the return
+/// data it produces is unknown.
+fn execute_create<T:EvmState>(mut state: T, n: usize) -> Outcome<T>
+where T::Word: Top {
+    if state.stack().has_operands(n) {
+        state.set_returndata_size(None);
+    }
+    execute_consumer_producer(state, n, &[T::Word::TOP])
+}
+
+/// Execute a `RETURNDATACOPY`, checking that the region being copied
+/// lies within the return data buffer (where this is known).  Since
+/// the contents of the buffer are unknown, the bytes written are
+/// unknown.
+fn execute_returndatacopy<T:EvmState>(state: T) -> Outcome<T>
+where T::Word: Top {
+    let stack = state.stack();
+    //
+    if !stack.has_operands(3) {
+        return Outcome::Exception(StackUnderflow);
+    }
+    let offset = stack.peek(1).try_constant();
+    let size = stack.peek(2).try_constant();
+    //
+    if let (Some(len),Some(offset),Some(size)) = (state.returndata_size(),offset,size) {
+        if offset.checked_add(size).is_none_or(|end| end > w256::from(len)) {
+            return Outcome::Exception(ReturnDataOverflow);
+        }
+    }
+    execute_copy(state, |_| None)
+}
+
+fn execute_selfdestruct<T:EvmState>(state: T) -> Outcome<T> {
+    if !state.stack().has_operands(1) {
+        Outcome::Exception(StackUnderflow)
+    } else {
+        execute_stop(state)
+    }
+}
+
+fn execute_return<T:EvmState>(mut state: T, revert: bool) -> Outcome<T> {
+    let stack = state.stack_mut();
+    //
+    if !stack.has_operands(2) {
+        Outcome::Exception(StackUnderflow)
+    } else {
+        // Pop offset and size
+        let offset = stack.pop();
+        let size = stack.pop();
+        // Reading beyond the addressable range of memory exhausts gas
+        if offset.is_constant() && size.is_constant() && size.constant() != w256::ZERO
+            && beyond_memory(offset.constant(),size.constant()) {
+            return Outcome::Exception(InsufficientGas);
+        }
+        // Read data from memory (if possible)
+        let data = if offset.is_constant() && size.is_constant() && size.constant() <= MAX_COPY_SIZE {
+            read_bytes(state.memory_mut(),offset.constant(),size.constant().to())
+        } else {
+            None
+        };
+        //
+        if revert {
+            Outcome::Revert{offset,size,data}
+        } else {
+            Outcome::Return{offset,size,data}
+        }
+    }
+}
+
+fn execute_calldataload<T:EvmState>(mut state: T) -> Outcome<T>
+where T::Word: Top {
+    let stack = state.stack_mut();
+    //
+    if !stack.has_operands(1) {
+        Outcome::Exception(StackUnderflow)
+    } else {
+        let offset = stack.pop();
+        //
+        let word = match state.calldata() {
+            Some(data) if offset.is_constant() => {
+                let offset = offset.constant();
+                // Bytes beyond the end of calldata are zero
+                let bytes : Vec<u8> = (0..32).map(|i| byte_at(data,offset.saturating_add(w256::from(i)))).collect();
+                T::Word::from(w256::from_be_slice(&bytes))
+            }
+            Some(_) => T::Word::TOP,
+            None => T::Word::calldataload(offset)
+        };
+        state.stack_mut().push(word);
+        state.skip(1);
+        Outcome::Continue(state)
+    }
+}
+
 fn execute_sload<T:EvmState>(mut state: T) -> Outcome<T> {
     let stack = state.stack_mut();
     //
@@ -410,6 +769,75 @@ fn execute_sstore<T:EvmState>(mut state: T) -> Outcome<T> {
     }
 }
 
+fn execute_tload<T:EvmState>(mut state: T) -> Outcome<T> {
+    let stack = state.stack_mut();
+    //
+    if !stack.has_operands(1) {
+        Outcome::Exception(StackUnderflow)
+    } else {
+        // Determine address to load from
+        let address = stack.pop();
+        // Read word from transient storage
+        let word = state.transient_mut().get(address);
+        // Push value at address
+        state.stack_mut().push(word);
+        // Move to next instruction
+        state.skip(1);
+        //
+        Outcome::Continue(state)
+    }
+}
+
+fn execute_tstore<T:EvmState>(mut state: T) -> Outcome<T> {
+    let stack = state.stack_mut();
+    //
+    if !stack.has_operands(2) {
+        Outcome::Exception(StackUnderflow)
+    } else {
+        // Pop address and value to store
+        let address = stack.pop();
+        let word = stack.pop();
+        // Write word into transient storage
+        state.transient_mut().put(address, word);
+        // Move to next instruction
+        state.skip(1);
+        //
+        Outcome::Continue(state)
+    }
+}
+
+// ===================================================================
+// World State
+// ===================================================================
+
+/// Execute an instruction which queries some account in the world
+/// state (e.g. `EXTCODESIZE`).  The query is resolved only when the
+/// address is known and a model of the world state is available.
+fn execute_extcode<T:EvmState,F>(mut state: T, query: F) -> Outcome<T>
+where F:Fn(&WorldState,w256)->Option<w256>, T::Word: Top {
+    let stack = state.stack_mut();
+    //
+    if !stack.has_operands(1) {
+        Outcome::Exception(StackUnderflow)
+    } else {
+        // Pop account address
+        let address = stack.pop();
+        // Attempt to resolve query
+        let word = match state.world() {
+            Some(world) if address.is_constant() => {
+                query(world,address.constant()).map(T::Word::from)
+            }
+            _ => None
+        };
+        // Push result (or unknown)
+        state.stack_mut().push(word.unwrap_or(T::Word::TOP));
+        // Move to next instruction
+        state.skip(1);
+        //
+        Outcome::Continue(state)
+    }
+}
+
 // ===================================================================
 // Jump
 // ===================================================================
@@ -421,14 +849,155 @@ fn execute_jump<T:EvmState>(mut state: T) -> Outcome<T> {
         Outcome::Exception(StackUnderflow)
     } else {
         // Pop jump address
-        let address = stack.pop();
+        let address = match stack.pop().try_constant() {
+            Some(address) => address,
+            None => { return Outcome::Unknown(None); }
+        };
+        // Check address is a valid jump destination
+        if !is_valid_jumpdest(&state,address) {
+            return Outcome::Exception(InvalidJumpDest);
+        }
         // Jump to the concrete address
-        state.goto(address.constant().to());
+        state.goto(address.to());
         // Done
         Outcome::Continue(state)
     }
 }
 
+/// Check whether a given address is a valid jump destination.  If the
+/// code being executed is not known, then this is assumed to hold.
+fn is_valid_jumpdest<T:EvmState>(state: &T, address: w256) -> bool {
+    match state.jumpdests() {
+        Some(bitmap) => {
+            let pc : Option<usize> = address.try_into().ok();
+            pc.is_some_and(|pc| pc < bitmap.len() && bitmap[pc])
+        }
+        None => true
+    }
+}
+
+/// Execute a static (i.e. relative) jump, where the target is given
+/// as an offset relative to the following instruction.  Since the
+/// target was validated when the enclosing EOF container was
+/// constructed, there is no check for a `JUMPDEST`.
+fn execute_rjump<T:EvmState>(mut state: T, offset: i16) -> Outcome<T> {
+    let target = from_rel_offset(state.pc() + 3, offset);
+    state.goto(target);
+    Outcome::Continue(state)
+}
+
+fn execute_rjumpi<T:EvmState+Clone>(mut state: T, offset: i16) -> Outcome<T> {
+    let target = from_rel_offset(state.pc() + 3, offset);
+    let stack = state.stack_mut();
+    //
+    if !stack.has_operands(1) {
+        Outcome::Exception(StackUnderflow)
+    } else {
+        let value = stack.pop();
+        //
+        if value.is_constant() && value.constant() == w256::ZERO {
+            // Move to next instruction
+            state.skip(3);
+            Outcome::Continue(state)
+        } else if value.is_constant() {
+            // Jump to target (since any nonzero value is true)
+            state.goto(target);
+            Outcome::Continue(state)
+        } else {
+            let mut branch = state.clone();
+            state.skip(3);
+            branch.goto(target);
+            Outcome::Split(state,branch)
+        }
+    }
+}
+
+/// Execute a jump table.  An index beyond the end of the table falls
+/// through to the next instruction, whilst an unknown index may reach
+/// either the next instruction or any entry in the table.
+fn execute_rjumpv<T:EvmState+Clone>(mut state: T, offsets: &[i16]) -> Outcome<T> {
+    let length = 2 + (2 * offsets.len());
+    let next = state.pc() + length;
+    let targets : Vec<usize> = offsets.iter().map(|o| from_rel_offset(next,*o)).collect();
+    let stack = state.stack_mut();
+    //
+    if !stack.has_operands(1) {
+        return Outcome::Exception(StackUnderflow);
+    }
+    let index = stack.pop();
+    //
+    if index.is_constant() {
+        let index = index.constant();
+        match targets.iter().enumerate().find(|(i,_)| w256::from(*i) == index) {
+            Some((_,target)) => state.goto(*target),
+            None => state.skip(length)
+        }
+        Outcome::Continue(state)
+    } else {
+        let mut states = Vec::new();
+        for target in targets {
+            let mut branch = state.clone();
+            branch.goto(target);
+            states.push(branch);
+        }
+        state.skip(length);
+        states.push(state);
+        Outcome::Branch(states)
+    }
+}
+
+// ===================================================================
+// Functions
+// ===================================================================
+
+/// Execute a function call.  The callee itself is not executed here.
+/// Rather, its inputs are replaced by its outputs (as determined by
+/// its type).  When the types are not known, the stack effect cannot
+/// be determined and, hence, neither can the following state.
+fn execute_callf<T:EvmState>(mut state: T, index: u16) -> Outcome<T>
+where T::Word : Top {
+    let ty = match state.types() {
+        Some(types) => types.get(index as usize).copied(),
+        None => { return Outcome::Unknown(None); }
+    };
+    let ty = match ty {
+        Some(ty) => ty,
+        None => { return Outcome::Exception(InvalidOpcode); }
+    };
+    let (inputs,outputs) = (ty.inputs as usize, ty.outputs as usize);
+    let stack = state.stack_mut();
+    //
+    if !stack.has_operands(inputs) {
+        Outcome::Exception(StackUnderflow)
+    } else if !stack.has_capacity(outputs.saturating_sub(inputs)) {
+        Outcome::Exception(StackOverflow)
+    } else {
+        for _i in 0..inputs { stack.pop(); }
+        for _i in 0..outputs { stack.push(T::Word::TOP); }
+        state.skip(3);
+        Outcome::Continue(state)
+    }
+}
+
+/// Execute a tail call.  Since control does not return, execution
+/// continues in an unknown caller once the callee completes.
+/// Nevertheless, the callee's inputs must be present.
+fn execute_jumpf<T:EvmState>(state: T, index: u16) -> Outcome<T> {
+    let inputs = match state.types() {
+        Some(types) => match types.get(index as usize) {
+            Some(ty) => ty.inputs as usize,
+            None => { return Outcome::Exception(InvalidOpcode); }
+        }
+        None => 0
+    };
+    //
+    if !state.stack().has_operands(inputs) {
+        Outcome::Exception(StackUnderflow)
+    } else {
+        Outcome::Unknown(None)
+    }
+}
+
 fn execute_jumpi<T:EvmState+Clone>(mut state: T) -> Outcome<T> {
     let stack = state.stack_mut();
     //
@@ -436,24 +1005,45 @@ fn execute_jumpi<T:EvmState+Clone>(mut state: T) -> Outcome<T> {
         Outcome::Exception(StackUnderflow)
     } else {
         // Pop jump address & value
-        let address = stack.pop();
+        let address = stack.pop().try_constant();
         let value = stack.pop();
         // Check for concrete execution
-        if value == T::Word::from(w256::from(0)) {
+        if value.is_constant() && value.constant() == w256::ZERO {
             // Move to next instruction
             state.skip(1);
+            return Outcome::Continue(state);
+        }
+        // Check for unknown jump target
+        let address = match address {
+            Some(address) => address,
+            None if value.is_constant() => {
+                return Outcome::Unknown(None);
+            }
+            None => {
+                state.skip(1);
+                return Outcome::Unknown(Some(state));
+            }
+        };
+        let valid = is_valid_jumpdest(&state,address);
+        //
+        if value.is_constant() && !valid {
+            Outcome::Exception(InvalidJumpDest)
+        } else if value.is_constant() {
+            // Jump to address (since any nonzero value is true)
+            state.goto(address.to());
+            Outcome::Continue(state)
+        } else if !valid {
+            // Branch would raise an exception, hence only the
+            // fall-through can continue.
+            state.skip(1);
             Outcome::Continue(state)
-        } else if value == T::Word::from(w256::from(1)) {
-            // Jump to address            
-            state.goto(address.constant().to());
-            Outcome::Continue(state)            
         } else {
             // Jump to the concrete address
             let mut branch = state.clone();
             // Current state moves to next instruction
             state.skip(1);
             // Branch state jumps to address
-            branch.goto(address.constant().to());
+            branch.goto(address.to());
             // Done
             Outcome::Split(state,branch)
         }
@@ -542,3 +1132,74 @@ fn execute_havoc<T:EvmState>(mut state: T, k: usize) -> Outcome<T> {
     //    
     Outcome::Continue(state)
 }
+
+// ===================================================================
+// Helpers
+// ===================================================================
+
+/// Charge for any expansion of memory which occurred during execution
+/// of an instruction, given the size of memory beforehand.
+fn charge_memory_expansion<T:EvmState>(size: u64, outcome: Outcome<T>) -> Outcome<T> {
+    let charge = |mut st: T| -> Option<T> {
+        if let (Some(gas),Some(after)) = (st.gas(),st.memory().size().try_constant()) {
+            let cost = memory_expansion_cost(size,after.to());
+            if gas < cost { return None; }
+            st.set_gas(gas - cost);
+        }
+        Some(st)
+    };
+    //
+    match outcome {
+        Outcome::Continue(st) => match charge(st) {
+            Some(st) => Outcome::Continue(st),
+            None => Outcome::Exception(InsufficientGas)
+        },
+        Outcome::Split(l,r) => match (charge(l),charge(r)) {
+            (Some(l),Some(r)) => Outcome::Split(l,r),
+            _ => Outcome::Exception(InsufficientGas)
+        },
+        Outcome::Branch(sts) => match sts.into_iter().map(charge).collect() {
+            Some(sts) => Outcome::Branch(sts),
+            None => Outcome::Exception(InsufficientGas)
+        },
+        Outcome::Unknown(Some(st)) => match charge(st) {
+            Some(st) => Outcome::Unknown(Some(st)),
+            None => Outcome::Exception(InsufficientGas)
+        },
+        _ => outcome
+    }
+}
+
+/// Determine the static gas cost of a given instruction.  Observe
+/// that pseudo-instructions (e.g. `HAVOC`) have no cost.
+fn static_gas_cost(insn: &Instruction) -> u64 {
+    match insn {
+        DATA(_)|HAVOC(_) => 0,
+        _ => static_gas(insn.opcode())
+    }
+}
+
+/// Read a sequence of bytes from memory, provided they are all known.
+fn read_bytes<M:EvmMemory>(memory: &mut M, offset: w256, size: usize) -> Option<Vec<u8>>
+where M::Word: EvmWord {
+    let mut bytes = Vec::with_capacity(size);
+    //
+    for i in (0..size).step_by(32) {
+        let word = memory.read(M::Word::from(offset + w256::from(i)));
+        if !word.is_constant() { return None; }
+        let word : [u8;32] = word.constant().to_be_bytes();
+        let n = std::cmp::min(32, size - i);
+        bytes.extend_from_slice(&word[..n]);
+    }
+    Some(bytes)
+}
+
+/// Read a byte at a given offset within a byte sequence, where bytes
+/// beyond the end are zero.
+fn byte_at(bytes: &[u8], offset: w256) -> u8 {
+    if offset < w256::from(bytes.len()) {
+        bytes[offset.to::<usize>()]
+    } else {
+        0
+    }
+}
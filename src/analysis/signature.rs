@@ -0,0 +1,167 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::fmt;
+use crate::bytecode::{BlockVec,Instruction};
+use crate::util::SubsliceOffset;
+use super::jumpdest::stack_heights;
+use super::{execute,trace,trace_with_config,DefaultState,TraceConfig,EvmException,EvmStack,EvmState,Outcome};
+
+/// Describes the _stack signature_ of a basic block.  That is, the
+/// height(s) of the stack upon entry to the block, and the height(s)
+/// of the stack upon exit from the block.  Since a given block can be
+/// reached along different paths, there may be more than one height
+/// on entry (or exit).  Likewise, a block which terminates execution
+/// (e.g. with `STOP` or `RETURN`) has no exit heights.
+#[derive(Clone,Debug,PartialEq)]
+pub struct BlockSignature {
+    /// Byte offset of the first instruction in the block.
+    pub pc: usize,
+    /// The distinct stack heights on entry (in ascending order).
+    pub entry: Vec<usize>,
+    /// The distinct stack heights on exit (in ascending order).
+    pub exit: Vec<usize>
+}
+
+impl BlockSignature {
+    /// Check whether this block is reachable (i.e. has at least one
+    /// entry height).
+    pub fn is_reachable(&self) -> bool {
+        !self.entry.is_empty()
+    }
+}
+
+impl fmt::Display for BlockSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{:#06x} {:?} {:?}",self.pc,self.entry,self.exit)
+    }
+}
+
+/// For a given bytecode sequence, determine the stack signature of
+/// every basic block (in order of appearance).  For example,
+/// consider this sequence:
+///
+/// ```txt
+///    push 0x1
+///    push lab
+///    jump
+/// lab:
+///    jumpdest
+///    pop
+///    stop
+/// ```
+///
+/// Here, the first block has entry height `0` and exit height `1`,
+/// whilst the second has entry height `1` and no exit height (since
+/// it terminates).  If the analysis does not complete within the
+/// given limit, then `None` is returned.
+pub fn find_block_signatures(insns: &[Instruction], limit: usize) -> Option<Vec<BlockSignature>> {
+    // Run the abstract trace
+    let states : Vec<Vec<DefaultState>> = trace(insns,DefaultState::new(),limit).ok()?;
+    let blocks = BlockVec::new(insns);
+    //
+    let mut signatures = Vec::new();
+    let mut pc = 0;
+    //
+    for b in 0..blocks.len() {
+        let blk = blocks.get(b);
+        let start = insns.subslice_offset(blk);
+        let end = start + blk.len();
+        // Determine entry heights
+        let entry = stack_heights(&states[start]);
+        // Determine exit heights.  Observe that control can leave a
+        // block either from its last instruction, or via a branch
+        // taken from a `JUMPI` in the middle of the block.
+        let mut exits = Vec::new();
+        let mut next = pc;
+        for i in start..end {
+            let insn = &insns[i];
+            next += insn.length();
+            if insn != &Instruction::JUMPI && (i+1) != end { continue; }
+            for st in &states[i] {
+                match execute(insn,st.clone()) {
+                    Outcome::Continue(st)|Outcome::Unknown(Some(st)) => exits.push(st),
+                    Outcome::Split(l,r) => { exits.push(l); exits.push(r); }
+                    Outcome::Branch(sts) => exits.extend(sts),
+                    _ => {}
+                }
+            }
+            // Drop fall-through states which remain in this block.
+            if (i+1) != end { exits.retain(|st| st.pc() != next); }
+        }
+        let exit = stack_heights(&exits);
+        //
+        signatures.push(BlockSignature{pc,entry,exit});
+        pc = next;
+    }
+    // Done
+    Some(signatures)
+}
+
+/// For a given bytecode sequence, determine the maximum height of the
+/// stack upon entry to any reachable instruction (e.g. as needed for
+/// the `max_stack_height` of an EOF type section).  If the analysis
+/// does not complete within the given limit, then `None` is
+/// returned.
+pub fn find_max_stack_height(insns: &[Instruction], limit: usize) -> Option<usize> {
+    find_max_stack_height_with(insns,DefaultState::new(),&TraceConfig::new().with_limit(limit))
+}
+
+/// Determine the maximum stack height as for
+/// `find_max_stack_height()`, but starting from a given initial state
+/// (e.g. on entry to an EOF code section) and tracing according to a
+/// given configuration.
+pub fn find_max_stack_height_with(insns: &[Instruction], init: DefaultState, config: &TraceConfig) -> Option<usize> {
+    // Run the abstract trace
+    let states : Vec<Vec<DefaultState>> = trace_with_config(insns,init,config).ok()?;
+    //
+    Some(max_stack_height(&states))
+}
+
+/// Determine the maximum height of the stack across the states
+/// computed by a trace.
+pub(crate) fn max_stack_height(states: &[Vec<DefaultState>]) -> usize {
+    states.iter().flatten().map(|st| st.stack().size()).max().unwrap_or(0)
+}
+
+/// For a given bytecode sequence, identify the byte offsets of all
+/// reachable instructions which can be executed with too few operands
+/// on the stack (i.e. which can cause a stack underflow).  If the
+/// analysis does not complete within the given limit, then `None` is
+/// returned.
+pub fn find_stack_underflows(insns: &[Instruction], limit: usize) -> Option<Vec<usize>> {
+    find_stack_underflows_with(insns,DefaultState::new(),&TraceConfig::new().with_limit(limit))
+}
+
+/// Identify stack underflows as for `find_stack_underflows()`, but
+/// starting from a given initial state (e.g. on entry to an EOF code
+/// section) and tracing according to a given configuration.
+pub fn find_stack_underflows_with(insns: &[Instruction], init: DefaultState, config: &TraceConfig) -> Option<Vec<usize>> {
+    // Run the abstract trace
+    let states : Vec<Vec<DefaultState>> = trace_with_config(insns,init,config).ok()?;
+    //
+    Some(stack_underflows(insns,&states))
+}
+
+/// Identify the byte offsets of all instructions which can underflow
+/// the stack, given the states computed by a trace.
+pub(crate) fn stack_underflows(insns: &[Instruction], states: &[Vec<DefaultState>]) -> Vec<usize> {
+    let mut underflows = Vec::new();
+    let mut pc = 0;
+    for (insn,sts) in insns.iter().zip(states) {
+        let underflow = sts.iter().any(|st| {
+            matches!(execute(insn,st.clone()),Outcome::Exception(EvmException::StackUnderflow))
+        });
+        if underflow { underflows.push(pc); }
+        pc += insn.length();
+    }
+    underflows
+}
@@ -12,6 +12,10 @@
 use std::fmt;
 use super::{EvmWord};
 
+/// The maximum number of items which can be held on the operand stack
+/// (as defined in the yellow paper).
+pub const MAX_STACK_DEPTH: usize = 1024;
+
 /// Abstraction of the operand stack within an EVM.  This provides the
 /// minimal set of operations required to implement the semantics of a
 /// given bytecode instruction.  For example, pushing / popping items
@@ -23,9 +27,13 @@ pub trait EvmStack : fmt::Debug {
     /// values.
     type Word : EvmWord;
 
+    /// Maximum number of items which can be held on this stack.
+    /// Pushing beyond this limit raises a stack overflow.
+    const MAX_DEPTH: usize = MAX_STACK_DEPTH;
+
     /// Check capacity for `n` additional items on the stack.
     fn has_capacity(&self, n: usize) -> bool {
-        (1024 - self.size()) >= n
+        Self::MAX_DEPTH.saturating_sub(self.size()) >= n
     }
     
     /// Check at least `n` operands on the stack.
@@ -10,7 +10,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use std::fmt;
-use super::{EvmWord,EvmMemory,EvmStack,EvmStorage};
+use std::rc::Rc;
+use crate::bytecode::{jumpdest_bitmap,SectionType};
+use crate::util::Top;
+use super::{EvmWord,EvmMemory,EvmStack,EvmStorage,WorldState};
 
 // ===================================================================
 // State
@@ -40,9 +43,14 @@ pub trait EvmState : fmt::Debug {
     /// Defines the memory implementation used in this EVM.
     type Memory : EvmMemory<Word=Self::Word>;
 
-    /// Defines the memory implementation used in this EVM.
+    /// Defines the (persistent) storage implementation used in this
+    /// EVM.
     type Storage : EvmStorage<Word=Self::Word>;
 
+    /// Defines the transient storage implementation used in this EVM
+    /// (see EIP-1153).
+    type TransientStorage : EvmStorage<Word=Self::Word>;
+
     /// Get the program counter.  Every `EvmState` has a statically
     /// known `pc`.
     fn pc(&self) -> usize;
@@ -71,6 +79,61 @@ pub trait EvmState : fmt::Debug {
     /// this state.
     fn storage_mut(&mut self) -> &mut Self::Storage;
 
+    /// Get read access to the transient storage contained within
+    /// this state.  Unlike persistent storage, this is reset at the
+    /// end of every transaction.
+    fn transient(&self) -> &Self::TransientStorage;
+
+    /// Get write access to the transient storage contained within
+    /// this state.
+    fn transient_mut(&mut self) -> &mut Self::TransientStorage;
+
+    /// Get read access to the model of the world state in which this
+    /// state is executing (if one is provided).  When no model is
+    /// provided, nothing is known about other accounts.
+    fn world(&self) -> Option<&WorldState>;
+
+    /// Get the code being executed by this state (if it is known).
+    /// When the code is unknown, instructions which depend upon it
+    /// (e.g. `CODECOPY`) produce unknown values.
+    fn code(&self) -> Option<&[u8]>;
+
+    /// Get the input data (i.e. calldata) for this state (if it is
+    /// known).  When the calldata is unknown, instructions which
+    /// depend upon it (e.g. `CALLDATALOAD`) produce unknown values.
+    fn calldata(&self) -> Option<&[u8]>;
+
+    /// Get the valid jump destinations for the code being executed
+    /// by this state (if it is known).  This is a bitmap over the
+    /// byte offsets of the code.  When the code is unknown, any jump
+    /// destination is assumed to be valid.
+    fn jumpdests(&self) -> Option<&[bool]>;
+
+    /// Get the types of the code sections in the enclosing EOF
+    /// container (if they are known).  These determine the stack
+    /// effect of a function call (e.g. `CALLF`).
+    fn types(&self) -> Option<&[SectionType]>;
+
+    /// Get the size of the return data buffer (if it is known).  This
+    /// is initially empty, and is subsequently determined by the
+    /// most recent call (or create).
+    fn returndata_size(&self) -> Option<usize>;
+
+    /// Set the size of the return data buffer, where `None`
+    /// indicates the size is unknown.
+    fn set_returndata_size(&mut self, size: Option<usize>);
+
+    /// Get the amount of gas remaining (if it is being tracked).  When
+    /// gas is not tracked, execution never runs out of gas.
+    fn gas(&self) -> Option<u64>;
+
+    /// Set the amount of gas remaining.
+    fn set_gas(&mut self, gas: u64);
+
+    /// Stop tracking gas for this state, such that execution can no
+    /// longer run out of gas.
+    fn clear_gas(&mut self);
+
     /// Move _program counter_ over `n` bytes in the next instruction.
     fn skip(&mut self, n: usize);
 
@@ -84,41 +147,129 @@ pub trait EvmState : fmt::Debug {
 // ===================================================================
 
 /// An `EvmState` composed from three distinct (and potentially
-/// abstract) components: _stack_, _memory_ and _storage_.
+/// abstract) components: _stack_, _memory_ and _storage_.  Transient
+/// storage is managed separately from persistent storage and, by
+/// default, uses the same representation.
 #[derive(Clone,Debug,Eq,Ord,PartialEq,PartialOrd)]
-pub struct ConcreteState<S,M,T>
+pub struct ConcreteState<S,M,T,R=T>
 where S:EvmStack,
       M:EvmMemory<Word=S::Word>,
-      T:EvmStorage<Word=S::Word>    
+      T:EvmStorage<Word=S::Word>,
+      R:EvmStorage<Word=S::Word>
 {
     pc: usize,
     stack: S,
     memory: M,
-    storage: T
+    storage: T,
+    transient: R,
+    world: Option<Rc<WorldState>>,
+    code: Option<Rc<[u8]>>,
+    calldata: Option<Rc<[u8]>>,
+    jumpdests: Option<Rc<[bool]>>,
+    types: Option<Rc<[SectionType]>>,
+    returndata_size: Option<usize>,
+    gas: Option<u64>
 }
 
-impl<S,M,T> ConcreteState<S,M,T>
+impl<S,M,T,R> ConcreteState<S,M,T,R>
 where S:EvmStack+Default,
       M:EvmMemory<Word=S::Word>+Default,
-      T:EvmStorage<Word=S::Word>+Default   
+      T:EvmStorage<Word=S::Word>+Default,
+      R:EvmStorage<Word=S::Word>+Default
 {
     pub fn new() -> Self {
         let stack = S::default();
         let memory = M::default();
         let storage = T::default();
-        Self{pc:0,stack,memory,storage}
+        let transient = R::default();
+        Self{pc:0,stack,memory,storage,transient,world:None,code:None,calldata:None,jumpdests:None,types:None,returndata_size:Some(0),gas:None}
+    }
+}
+
+impl<S,M,T,R> ConcreteState<S,M,T,R>
+where S:EvmStack,
+      M:EvmMemory<Word=S::Word>,
+      T:EvmStorage<Word=S::Word>,
+      R:EvmStorage<Word=S::Word>
+{
+    /// Execute this state within a given model of the world state.
+    /// This allows, for example, the code of other accounts to be
+    /// resolved.
+    pub fn with_world(mut self, world: WorldState) -> Self {
+        self.world = Some(Rc::new(world));
+        self
+    }
+
+    /// Execute this state with a given initial transient storage.
+    /// This allows, for example, transient storage to be assumed
+    /// empty at the start of a transaction.
+    pub fn with_transient(mut self, transient: R) -> Self {
+        self.transient = transient;
+        self
+    }
+
+    /// Execute this state with knowledge of the code being executed.
+    /// This allows, for example, `CODECOPY` to be resolved and jump
+    /// destinations to be validated.
+    pub fn with_code(mut self, code: &[u8]) -> Self {
+        self.code = Some(Rc::from(code));
+        self.jumpdests = Some(Rc::from(jumpdest_bitmap(code)));
+        self
+    }
+
+    /// Execute this state with a given input (i.e. calldata).  This
+    /// allows, for example, `CALLDATALOAD` to be resolved.
+    pub fn with_calldata(mut self, calldata: &[u8]) -> Self {
+        self.calldata = Some(Rc::from(calldata));
+        self
+    }
+
+    /// Execute this state with knowledge of the code section types
+    /// of the enclosing EOF container.  This allows, for example, the
+    /// stack effect of `CALLF` to be determined.
+    pub fn with_types(mut self, types: &[SectionType]) -> Self {
+        self.types = Some(Rc::from(types));
+        self
+    }
+
+    /// Execute this state with a given gas budget.  Each instruction
+    /// executed is then charged against this budget.
+    pub fn with_gas(mut self, gas: u64) -> Self {
+        self.gas = Some(gas);
+        self
     }
 }
 
-impl<S,M,T> EvmState for ConcreteState<S,M,T>
+impl<S,M,T,R> ConcreteState<S,M,T,R>
 where S:EvmStack,
+      S::Word:Top,
       M:EvmMemory<Word=S::Word>,
-      T:EvmStorage<Word=S::Word>
+      T:EvmStorage<Word=S::Word>,
+      R:EvmStorage<Word=S::Word>
+{
+    /// Execute this state from the start of a given code section of
+    /// an EOF container, where `types` are the types of all code
+    /// sections in the container.  Thus, the stack holds the inputs
+    /// of the section (whose values are unknown), and the stack
+    /// effect of `CALLF` can be determined.
+    pub fn with_section(mut self, types: &[SectionType], section: usize) -> Self {
+        let inputs = types.get(section).map_or(0,|ty| ty.inputs);
+        for _i in 0..inputs { self.stack.push(S::Word::TOP); }
+        self.with_types(types)
+    }
+}
+
+impl<S,M,T,R> EvmState for ConcreteState<S,M,T,R>
+where S:EvmStack,
+      M:EvmMemory<Word=S::Word>,
+      T:EvmStorage<Word=S::Word>,
+      R:EvmStorage<Word=S::Word>
 {
     type Word = S::Word;
     type Stack = S;
     type Memory = M;
     type Storage = T;
+    type TransientStorage = R;
 
     fn pc(&self) -> usize {
         self.pc
@@ -148,6 +299,54 @@ where S:EvmStack,
         &mut self.storage
     }
 
+    fn transient(&self) -> &Self::TransientStorage {
+        &self.transient
+    }
+
+    fn transient_mut(&mut self) -> &mut Self::TransientStorage {
+        &mut self.transient
+    }
+
+    fn world(&self) -> Option<&WorldState> {
+        self.world.as_deref()
+    }
+
+    fn code(&self) -> Option<&[u8]> {
+        self.code.as_deref()
+    }
+
+    fn calldata(&self) -> Option<&[u8]> {
+        self.calldata.as_deref()
+    }
+
+    fn jumpdests(&self) -> Option<&[bool]> {
+        self.jumpdests.as_deref()
+    }
+
+    fn types(&self) -> Option<&[SectionType]> {
+        self.types.as_deref()
+    }
+
+    fn returndata_size(&self) -> Option<usize> {
+        self.returndata_size
+    }
+
+    fn set_returndata_size(&mut self, size: Option<usize>) {
+        self.returndata_size = size;
+    }
+
+    fn gas(&self) -> Option<u64> {
+        self.gas
+    }
+
+    fn set_gas(&mut self, gas: u64) {
+        self.gas = Some(gas);
+    }
+
+    fn clear_gas(&mut self) {
+        self.gas = None;
+    }
+
     fn skip(&mut self, n: usize) {
         self.pc += n;
         self.stack.goto(self.pc);            
@@ -161,10 +360,11 @@ where S:EvmStack,
     }
 }
 
-impl<S,M,T> fmt::Display for ConcreteState<S,M,T>
+impl<S,M,T,R> fmt::Display for ConcreteState<S,M,T,R>
 where S:EvmStack+Default+fmt::Display,
       M:EvmMemory<Word=S::Word>+Default+fmt::Display,
-      T:EvmStorage<Word=S::Word>+Default   
+      T:EvmStorage<Word=S::Word>+Default,
+      R:EvmStorage<Word=S::Word>
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f,"|{}|{}|",self.stack,self.memory)?;        
@@ -33,15 +33,16 @@ pub trait EvmStateSet : JoinInto<Self::State> {
 
 impl<T:Clone+Ord+EvmState+PartialEq> JoinInto<T> for Vec<T> {
     fn join_into(&mut self, other: &T) -> bool {
-        let n = self.len();
-        // Simplest possible join operator (for now)        
-        self.push(other.clone());
-        // Sort vec
-        self.sort_unstable();
-        // Deduplicate matching entries
-        self.dedup();
-        // Check whether anything actually changed.
-        n != self.len()
+        // Simplest possible join operator (for now).  States are
+        // maintained in sorted order without duplicates, such that
+        // each set has a canonical form.
+        match self.binary_search(other) {
+            Ok(_) => false,
+            Err(i) => {
+                self.insert(i,other.clone());
+                true
+            }
+        }
     }
 }
 
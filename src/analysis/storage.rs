@@ -11,7 +11,8 @@
 // limitations under the License.
 use std::fmt;
 use std::marker::PhantomData;
-use crate::util::Top;
+use std::collections::BTreeMap;
+use crate::util::{w256,Top};
 use super::{EvmWord};
 
 /// Abstraction of peristent storage within an EVM.  This provides the
@@ -82,3 +83,95 @@ impl<T:EvmWord+Top> fmt::Debug for UnknownStorage<T>
         Ok(())
     }
 }
+
+// ===================================================================
+// Concrete Storage
+// ===================================================================
+
+/// An implementation of `EvmStorage` which manages only "concrete"
+/// addresses (i.e. it doesn't perform any symbolic analysis).
+/// Locations which have not been written are unknown by default.
+/// Even transient storage, which is cleared at the end of every
+/// transaction, may have been written by an earlier call within the
+/// same transaction.  Where storage is known to be empty (e.g. at
+/// the start of a transaction), use `zeroed()` instead.
+#[derive(Clone,Eq,Ord,PartialEq,PartialOrd)]
+pub struct ConcreteStorage<T:EvmWord+Top> {
+    // Indicates whether or not locations stored outside of the words
+    // map have the known value zero (`top=false`), or an unknown
+    // value (`top=true`).
+    top: bool,
+    // Words stored at known addresses.
+    words: BTreeMap<w256,T>
+}
+
+impl<T:EvmWord+Top> ConcreteStorage<T> {
+    pub fn new() -> Self {
+        // Storage is initially unknown
+        Self{top: true, words: BTreeMap::new()}
+    }
+
+    /// Construct storage where every location is known to be zero.
+    pub fn zeroed() -> Self {
+        Self{top: false, words: BTreeMap::new()}
+    }
+}
+
+impl<T:EvmWord+Top> EvmStorage for ConcreteStorage<T> {
+    type Word = T;
+
+    fn get(&mut self, address: Self::Word) -> Self::Word {
+        match address.try_constant() {
+            Some(addr) => {
+                match self.words.get(&addr) {
+                    Some(v) => v.clone(),
+                    None if self.top => T::TOP,
+                    None => T::from(w256::ZERO)
+                }
+            }
+            None => T::TOP
+        }
+    }
+
+    fn put(&mut self, address: Self::Word, item: Self::Word) {
+        match address.try_constant() {
+            Some(addr) => {
+                self.words.insert(addr,item);
+            }
+            None => {
+                // Writing to an unknown address could overwrite any
+                // location.
+                self.top = true;
+                self.words.clear();
+            }
+        }
+    }
+}
+
+impl<T:EvmWord+Top> Default for ConcreteStorage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T:EvmWord+Top> fmt::Display for ConcreteStorage<T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{:?}",self)?;
+        Ok(())
+    }
+}
+
+impl<T:EvmWord+Top> fmt::Debug for ConcreteStorage<T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        if self.top { write!(f,"?:")?; }
+        for (k,v) in &self.words {
+            if !first { write!(f,",")?; }
+            first = false;
+            write!(f,"{:#0x}:={:?}", k, v)?;
+        }
+        Ok(())
+    }
+}
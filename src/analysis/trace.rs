@@ -9,19 +9,74 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::collections::HashMap;
 use std::fmt::Debug;
 use crate::util::{Bottom,Top};
 use crate::bytecode::Instruction;
-use super::{EvmState,EvmStateSet};
+use super::{EvmMemory,EvmStack,EvmState,EvmStateSet,EvmStorage};
 use super::semantics::{execute,Outcome};
 
+/// Trace execution of a given instruction sequence from an initial
+/// state, returning the set of all states reaching each instruction.
+/// Here, `T` is the set of states collected at each instruction and
+/// determines the abstract domain used (i.e. via its underlying
+/// state type).  The resulting vector is indexed by _instruction_
+/// (rather than byte offset), where unreachable instructions have an
+/// empty set.  At most `limit` instructions are executed, and if this
+/// is reached then the (incomplete) states are returned as an error.
+/// See `Trace` for a more convenient wrapper.
 pub fn trace<T>(insns: &[Instruction], init: T::State, limit: usize) -> Result<Vec<T>,Vec<T>>
 where T:EvmStateSet+Bottom+PartialEq+Debug,
       T::State: Clone, <T::State as EvmState>::Word: Top 
 {
+    trace_with_config(insns,init,&TraceConfig::new().with_limit(limit))
+}
+
+/// Trace execution as for `trace()`, but according to a given
+/// configuration.  This allows, for example, widening to be applied
+/// to ensure termination in the presence of loops.
+pub fn trace_with_config<T>(insns: &[Instruction], init: T::State, config: &TraceConfig) -> Result<Vec<T>,Vec<T>>
+where T:EvmStateSet+Bottom+PartialEq+Debug,
+      T::State: Clone, <T::State as EvmState>::Word: Top 
+{
+    trace_all(insns,init,config,&HashMap::new())
+}
+
+/// Trace execution as for `trace()`, but using a map of _jump hints_
+/// to continue past any `jump` or `jumpi` whose target cannot be
+/// resolved.  Specifically, `hints` maps the byte offset of a jump
+/// instruction to the byte offsets of its known targets (e.g. as
+/// supplied by the user, or from an external source).  Hints are
+/// only consulted when the target is unknown.  Any hinted target
+/// which is not a valid jump destination is dropped (see
+/// `valid_hints()`).
+pub fn trace_with_hints<T>(insns: &[Instruction], init: T::State, limit: usize, hints: &HashMap<usize,Vec<usize>>) -> Result<Vec<T>,Vec<T>>
+where T:EvmStateSet+Bottom+PartialEq+Debug,
+      T::State: Clone, <T::State as EvmState>::Word: Top 
+{
+    trace_with_valid_hints(insns,init,limit,&valid_hints(insns,hints))
+}
+
+/// Trace execution as for `trace_with_hints()`, where the given hints
+/// have already been filtered by `valid_hints()`.
+pub(crate) fn trace_with_valid_hints<T>(insns: &[Instruction], init: T::State, limit: usize, hints: &HashMap<usize,Vec<usize>>) -> Result<Vec<T>,Vec<T>>
+where T:EvmStateSet+Bottom+PartialEq+Debug,
+      T::State: Clone, <T::State as EvmState>::Word: Top 
+{
+    trace_all(insns,init,&TraceConfig::new().with_limit(limit),hints)
+}
+
+fn trace_all<T>(insns: &[Instruction], init: T::State, config: &TraceConfig, hints: &HashMap<usize,Vec<usize>>) -> Result<Vec<T>,Vec<T>>
+where T:EvmStateSet+Bottom+PartialEq+Debug,
+      T::State: Clone, <T::State as EvmState>::Word: Top 
+{
+    let limit = config.limit;
     // initialise state data
     let mut states = Vec::new();
     for _ in insns { states.push(T::BOTTOM); }
+    // Number of times each instruction has been visited from the
+    // worklist (i.e. as the start of a block).
+    let mut visits = vec![0usize;insns.len()];
     // calculate byte offsets
     let offsets = determine_byte_offsets(insns);
     // Initialise worklist
@@ -41,16 +96,29 @@ where T:EvmStateSet+Bottom+PartialEq+Debug,
         // Determine instruction position
         let mut pc = st.pc();
         let mut ipc = offsets[pc];
+        // Widen state if block visited too often
+        visits[ipc] += 1;
+        if config.widen_after.is_some_and(|n| visits[ipc] > n) {
+            widen(&mut st);
+        }
         //
-        while ipc < states.len() && states[ipc].join_into(&st) {
+        while ipc < states.len() && count != limit && states[ipc].join_into(&st) {
             let insn = &insns[ipc];
+            // Retain state for any hinted jump
+            let hinted = hints.get(&pc).map(|ts| (ts,st.clone()));
             // Update pc value (for next instruction)
             pc += insn.length();
             // Debug info
-            // println!("[{ipc}:{}] {:?}",insns[ipc],states[ipc]);
+            log::trace!("[{ipc}:{}] {:?}",insns[ipc],states[ipc]);
             //
-            match execute(insn,st) {
-                Outcome::Return|Outcome::Exception(_) => {
+            let outcome = execute(insn,st);
+            // Apply hints for an unknown branch target
+            if let (Outcome::Unknown(_),Some((targets,hst))) = (&outcome,hinted) {
+                worklist.extend(apply_hints(insn,hst,targets));
+            }
+            //
+            match outcome {
+                Outcome::Return{..}|Outcome::Revert{..}|Outcome::Exception(_) => {
                     // For now, we don't do anything specicial with
                     // accumulated returns.  However, at some point,
                     // it probably makes sense.
@@ -71,7 +139,22 @@ where T:EvmStateSet+Bottom+PartialEq+Debug,
                     st = nst;
                     // Add branch
                     worklist.push(bst);
-                }                
+                }
+                Outcome::Branch(sts) => {
+                    // Execution splits into many branches
+                    worklist.extend(sts);
+                    break;
+                }
+                Outcome::Unknown(None) => {
+                    // Branch to an unknown target, hence there is
+                    // nothing more we can do along this path.
+                    break;
+                }
+                Outcome::Unknown(Some(nst)) => {
+                    // Branch to an unknown target, but execution
+                    // continues along the fall-through.
+                    st = nst;
+                }
             }
             ipc += 1;
 	    count+=1;		    
@@ -85,7 +168,219 @@ where T:EvmStateSet+Bottom+PartialEq+Debug,
     Ok(states)
 }
 
-fn determine_byte_offsets(insns: &[Instruction]) -> Vec<usize> {
+// ===================================================================
+// Trace
+// ===================================================================
+
+/// The set of states reaching each instruction in a given sequence,
+/// as determined by `trace()`.  Unlike the raw result of `trace()`,
+/// this is indexed by byte offset (i.e. `pc`).  For example:
+///
+/// ```
+/// use evmil::analysis::{aw256,ConcreteMemory,ConcreteStack,ConcreteState,EvmStateSet,Trace,UnknownStorage};
+/// use evmil::bytecode::Instruction::*;
+///
+/// type State = ConcreteState<ConcreteStack<aw256>,ConcreteMemory<aw256>,UnknownStorage<aw256>>;
+///
+/// // push1 0x04; jump; invalid; jumpdest; stop
+/// let insns = [PUSH(vec![0x04]),JUMP,INVALID,JUMPDEST,STOP];
+/// let trace : Trace<Vec<State>> = Trace::new(&insns,State::new(),1000).unwrap();
+/// assert!(trace.is_reachable(0x04));
+/// assert!(!trace.is_reachable(0x03));
+/// assert_eq!(trace.states_at(0x05).count(),1);
+/// assert_eq!(trace.iter().filter(|(_,sts)| sts.size() > 0).count(),4);
+/// ```
+#[derive(Clone,Debug,PartialEq)]
+pub struct Trace<T> {
+    /// Byte offset of each instruction.
+    pcs: Vec<usize>,
+    /// Set of states reaching each instruction.
+    states: Vec<T>
+}
+
+impl<T:EvmStateSet> Trace<T> {
+    /// Trace execution of a given instruction sequence from an
+    /// initial state.  As for `trace()`, at most `limit` instructions
+    /// are executed and, if this is reached, the (incomplete) trace
+    /// is returned as an error.
+    pub fn new(insns: &[Instruction], init: T::State, limit: usize) -> Result<Self,Self>
+    where T:Bottom+PartialEq+Debug,
+          T::State: Clone, <T::State as EvmState>::Word: Top
+    {
+        match trace(insns,init,limit) {
+            Ok(states) => Ok(Self::from_states(insns,states)),
+            Err(states) => Err(Self::from_states(insns,states))
+        }
+    }
+
+    /// Trace execution as for `new()`, but according to a given
+    /// configuration.
+    pub fn with_config(insns: &[Instruction], init: T::State, config: &TraceConfig) -> Result<Self,Self>
+    where T:Bottom+PartialEq+Debug,
+          T::State: Clone, <T::State as EvmState>::Word: Top
+    {
+        match trace_with_config(insns,init,config) {
+            Ok(states) => Ok(Self::from_states(insns,states)),
+            Err(states) => Err(Self::from_states(insns,states))
+        }
+    }
+
+    /// Construct a trace from the raw result of `trace()` over a
+    /// given instruction sequence.
+    pub fn from_states(insns: &[Instruction], states: Vec<T>) -> Self {
+        assert_eq!(insns.len(),states.len());
+        Self{pcs: determine_insn_offsets(insns),states}
+    }
+
+    /// Iterate the states reaching the instruction at a given byte
+    /// offset.  This is empty if no instruction starts at that
+    /// offset, or it is unreachable.
+    pub fn states_at(&self, pc: usize) -> std::slice::Iter<'_,T::State> {
+        match self.pcs.binary_search(&pc) {
+            Ok(i) => self.states[i].iter(),
+            Err(_) => [].iter()
+        }
+    }
+
+    /// Determine whether the instruction at a given byte offset is
+    /// reachable.
+    pub fn is_reachable(&self, pc: usize) -> bool {
+        match self.pcs.binary_search(&pc) {
+            Ok(i) => self.states[i].size() > 0,
+            Err(_) => false
+        }
+    }
+
+    /// Iterate each instruction's byte offset, along with the set of
+    /// states reaching it.
+    pub fn iter(&self) -> impl Iterator<Item=(usize,&T)> {
+        self.pcs.iter().copied().zip(self.states.iter())
+    }
+
+    /// Extract the raw set of states reaching each instruction.
+    pub fn into_states(self) -> Vec<T> {
+        self.states
+    }
+}
+
+// ===================================================================
+// Trace Config
+// ===================================================================
+
+/// Configuration options for tracing execution (e.g. as used by
+/// `trace_with_config()`).  For example:
+///
+/// ```
+/// use evmil::analysis::TraceConfig;
+///
+/// let config = TraceConfig::new().with_limit(10_000).with_widening(3);
+/// assert_eq!(config.limit,10_000);
+/// assert_eq!(config.widen_after,Some(3));
+/// ```
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct TraceConfig {
+    /// Maximum number of instructions to execute before giving up.
+    pub limit: usize,
+    /// Number of times a block can be visited before any state
+    /// reaching it is widened.  If `None`, no widening is applied.
+    pub widen_after: Option<usize>
+}
+
+impl TraceConfig {
+    pub fn new() -> Self {
+        Self{limit: usize::MAX, widen_after: None}
+    }
+
+    /// Set the maximum number of instructions to execute.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Widen any state reaching a block after it has been visited
+    /// `n` times.  This ensures termination for loops whose bounds
+    /// are unknown since, once widened, every stack item, memory
+    /// location and storage location is `TOP`, whilst the size of
+    /// the return data buffer is unknown and gas is no longer
+    /// tracked (i.e. states reaching the block eventually
+    /// stabilise).
+    pub fn with_widening(mut self, n: usize) -> Self {
+        self.widen_after = Some(n);
+        self
+    }
+}
+
+impl Default for TraceConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Widen a given state by setting every item on the stack, and
+/// every location in memory and storage, to `TOP`.  Furthermore, the
+/// size of the return data buffer becomes unknown and gas is no
+/// longer tracked.  Observe that writing to an unknown address
+/// havocs memory (or storage) entirely.
+fn widen<S:EvmState>(st: &mut S)
+where S::Word: Top {
+    let stack = st.stack_mut();
+    for i in 0..stack.size() {
+        stack.set(i,S::Word::TOP);
+    }
+    st.memory_mut().write(S::Word::TOP,S::Word::TOP);
+    st.storage_mut().put(S::Word::TOP,S::Word::TOP);
+    st.transient_mut().put(S::Word::TOP,S::Word::TOP);
+    st.set_returndata_size(None);
+    st.clear_gas();
+}
+
+/// Construct the states arising from a hinted `jump` or `jumpi`.
+/// That is, the operands of the branch are popped and execution
+/// moves to each of the hinted targets.
+fn apply_hints<S:EvmState+Clone>(insn: &Instruction, mut st: S, targets: &[usize]) -> Vec<S> {
+    let n = match insn {
+        Instruction::JUMP => 1,
+        Instruction::JUMPI => 2,
+        _ => { return Vec::new(); }
+    };
+    for _ in 0..n { st.stack_mut().pop(); }
+    targets.iter().map(|&t| {
+        let mut nst = st.clone();
+        nst.goto(t);
+        nst
+    }).collect()
+}
+
+/// Filter a given map of jump hints, retaining only those targets
+/// which are valid jump destinations (i.e. the byte offset of a
+/// `JUMPDEST` instruction within the given sequence).  Any other
+/// target is dropped, and reported in the log.
+pub(crate) fn valid_hints(insns: &[Instruction], hints: &HashMap<usize,Vec<usize>>) -> HashMap<usize,Vec<usize>> {
+    let offsets = determine_insn_offsets(insns);
+    let is_jumpdest = |t: &usize| offsets.binary_search(t).is_ok_and(|i| insns[i] == Instruction::JUMPDEST);
+    hints.iter().map(|(&pc,ts)| {
+        let (valid,invalid) : (Vec<usize>,Vec<usize>) = ts.iter().copied().partition(is_jumpdest);
+        for t in invalid {
+            log::warn!("ignoring invalid jump hint {pc:#06x} --> {t:#06x}");
+        }
+        (pc,valid)
+    }).collect()
+}
+
+/// Determine the byte offset of each instruction in a given sequence.
+pub(crate) fn determine_insn_offsets(insns: &[Instruction]) -> Vec<usize> {
+    let mut pcs = Vec::with_capacity(insns.len());
+    let mut pc = 0;
+    for insn in insns {
+        pcs.push(pc);
+        pc += insn.length();
+    }
+    pcs
+}
+
+/// Map each byte offset in a given sequence to the index of the
+/// instruction which covers it.
+pub(crate) fn determine_byte_offsets(insns: &[Instruction]) -> Vec<usize> {
     let mut offsets = Vec::new();
 
     for (i,insn) in insns.iter().enumerate() {
@@ -10,7 +10,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use std::fmt;
-use crate::util::{Concretizable,w256,Top};
+use std::rc::Rc;
+use crate::util::{try_keccak256,Concretizable,i256,Interval,w256,Top,W256_THIRTYTWO};
 
 /// Represents the fundamental unit of computation within the EVM,
 /// namely a word.  This is intentially left abstract, so that it
@@ -22,6 +23,9 @@ pub trait EvmWord : Sized + Clone + fmt::Debug +
 {
     // Comparators
     fn less_than(self,rhs:Self)->Self;
+    /// Compare this word against another, treating both as signed
+    /// (two's complement) words.
+    fn signed_less_than(self,rhs:Self)->Self;
     fn equal(self,rhs:Self)->Self;
     fn is_zero(self)->Self;
     // Arithmetic
@@ -30,11 +34,34 @@ pub trait EvmWord : Sized + Clone + fmt::Debug +
     fn mul(self,rhs:Self)->Self;
     fn div(self,rhs:Self)->Self;
     fn rem(self,rhs:Self)->Self;
+    fn sdiv(self,rhs:Self)->Self;
+    fn srem(self,rhs:Self)->Self;
+    fn exp(self,rhs:Self)->Self;
+    fn signextend(self,rhs:Self)->Self;
     // Bitwise
     fn and(self,rhs:Self)->Self;
     fn or(self,rhs:Self)->Self;
     fn xor(self,rhs:Self)->Self;
     fn not(self)->Self;
+    /// Extract the `nth` byte of this word, where `0` is the most
+    /// significant byte.
+    fn byte(self,n:Self)->Self;
+    /// Shift this word left by `n` bits.
+    fn shl(self,n:Self)->Self;
+    /// Shift this word right by `n` bits.
+    fn shr(self,n:Self)->Self;
+    /// Shift this word right by `n` bits, preserving the sign bit.
+    fn sar(self,n:Self)->Self;
+    // Hashing
+    /// Compute the keccak256 hash of `len` bytes of memory, as given
+    /// by the sequence of words covering them (where any bytes in the
+    /// last word beyond `len` are ignored).
+    fn keccak256(words: Vec<Self>, len: usize)->Self;
+    // Environment
+    /// Describe the word loaded from (unknown) calldata at a given
+    /// offset.  Since calldata is fixed for the duration of a call,
+    /// loads from the same offset give the same word.
+    fn calldataload(offset: Self)->Self;
     // Misc
     fn havoc(self)->Self;    
 }
@@ -62,19 +89,8 @@ impl fmt::Debug for aw256 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             aw256::Word(w) => {
-                let mut first = true;
-                write!(f,"0x")?;
-                // Following is necessary because ruint::Uint doesn't
-                // appear to play nicely with formatting hexadecimal.                
-                for l in w.as_limbs().iter().rev() {
-                    if *l != 0 || !first {
-                        write!(f,"{l:02x}")?;
-                        first = false;
-                    }
-                }
-                if first {
-                    write!(f,"00")?;
-                } 
+                // Minimal width, except that at least one byte is shown.
+                write!(f,"{w:#04x}")?;
             }
             aw256::Unknown => {
                 write!(f,"??")?;
@@ -124,6 +140,15 @@ impl EvmWord for aw256 {
             (_,_) => aw256::Unknown
         }
     }
+    fn signed_less_than(self,rhs:Self)->Self {
+        match (self,rhs) {
+            (aw256::Word(l),aw256::Word(r)) => {
+                if i256::from(l) < i256::from(r) { aw256::Word(w256::from(1)) }
+                else { aw256::Word(w256::from(0)) }
+            }
+            (_,_) => aw256::Unknown
+        }
+    }
     fn equal(self,rhs:Self)->Self {
         match (self,rhs) {
             (aw256::Word(l),aw256::Word(r)) => {
@@ -176,6 +201,35 @@ impl EvmWord for aw256 {
             (_,_) => aw256::Unknown
         }
     }
+    fn sdiv(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (aw256::Word(l),aw256::Word(r)) => {
+                aw256::Word((i256::from(l) / i256::from(r)).into())
+            }
+            (_,_) => aw256::Unknown
+        }
+    }
+    fn srem(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (aw256::Word(l),aw256::Word(r)) => {
+                aw256::Word((i256::from(l) % i256::from(r)).into())
+            }
+            (_,_) => aw256::Unknown
+        }
+    }
+    fn exp(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            // Exponentiation by squaring (modulo 2^256)
+            (aw256::Word(l),aw256::Word(r)) => aw256::Word(l.wrapping_pow(r)),
+            (_,_) => aw256::Unknown
+        }
+    }
+    fn signextend(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (aw256::Word(l),aw256::Word(r)) => aw256::Word(i256::sign_extend(r,l).into()),
+            (_,_) => aw256::Unknown
+        }
+    }
     // bitwise
     fn and(self, rhs: Self) -> Self {
         match (self, rhs) {
@@ -201,11 +255,60 @@ impl EvmWord for aw256 {
             _ => aw256::Unknown
         }
     }
+    fn byte(self, n: Self) -> Self {
+        match (self, n) {
+            (aw256::Word(l),aw256::Word(n)) if n < W256_THIRTYTWO => {
+                let shift = 8 * (31 - n.to::<usize>());
+                aw256::Word((l >> shift) & w256::from(0xff))
+            }
+            (aw256::Word(_),aw256::Word(_)) => aw256::Word(w256::ZERO),
+            (_,_) => aw256::Unknown
+        }
+    }
+    fn shl(self, n: Self) -> Self {
+        match (self, n) {
+            (aw256::Word(l),aw256::Word(n)) => aw256::Word(l << shift_amount(n)),
+            (_,_) => aw256::Unknown
+        }
+    }
+    fn shr(self, n: Self) -> Self {
+        match (self, n) {
+            (aw256::Word(l),aw256::Word(n)) => aw256::Word(l >> shift_amount(n)),
+            (_,_) => aw256::Unknown
+        }
+    }
+    fn sar(self, n: Self) -> Self {
+        match (self, n) {
+            (aw256::Word(l),aw256::Word(n)) => aw256::Word((i256::from(l) >> shift_amount(n)).into()),
+            (_,_) => aw256::Unknown
+        }
+    }
+    fn keccak256(words: Vec<Self>, len: usize) -> Self {
+        let mut bytes = Vec::new();
+        for w in words {
+            match w {
+                aw256::Word(w) => bytes.extend(w.to_be_bytes::<32>()),
+                aw256::Unknown => { return aw256::Unknown; }
+            }
+        }
+        bytes.truncate(len);
+        match try_keccak256(&bytes) {
+            Some(w) => aw256::Word(w),
+            None => aw256::Unknown
+        }
+    }
+    fn calldataload(_offset: Self) -> Self { aw256::Unknown }
     fn havoc(self) -> Self {
         aw256::Unknown
     }
 }
 
+/// Convert a shift amount into a `usize`.  Since shifting by `256`
+/// or more always gives the same result, the amount is capped.
+fn shift_amount(n: w256) -> usize {
+    if n < w256::from(256) { n.to() } else { 256 }
+}
+
 // ===================================================================
 // Constant Word
 // ===================================================================
@@ -232,19 +335,8 @@ impl fmt::Debug for cw256 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             cw256::Word(w) => {
-                let mut first = true;
-                write!(f,"0x")?;
-                // Following is necessary because ruint::Uint doesn't
-                // appear to play nicely with formatting hexadecimal.                
-                for l in w.as_limbs().iter().rev() {
-                    if *l != 0 || !first {
-                        write!(f,"{l:02x}")?;
-                        first = false;
-                    }
-                }
-                if first {
-                    write!(f,"00")?;
-                } 
+                // Minimal width, except that at least one byte is shown.
+                write!(f,"{w:#04x}")?;
             }
             cw256::Unknown => {
                 write!(f,"??")?;
@@ -284,6 +376,7 @@ impl Concretizable for cw256 {
 
 impl EvmWord for cw256 {
     fn less_than(self,_rhs:Self)->Self { cw256::Unknown }
+    fn signed_less_than(self,_rhs:Self)->Self { cw256::Unknown }
     fn equal(self,_rhs:Self)->Self { cw256::Unknown }
     fn is_zero(self) -> Self { cw256::Unknown }
     fn add(self, _rhs: Self) -> Self { cw256::Unknown }
@@ -291,9 +384,317 @@ impl EvmWord for cw256 {
     fn mul(self, _rhs: Self) -> Self { cw256::Unknown }
     fn div(self, _rhs: Self) -> Self { cw256::Unknown }
     fn rem(self, _rhs: Self) -> Self { cw256::Unknown }
+    fn sdiv(self, _rhs: Self) -> Self { cw256::Unknown }
+    fn srem(self, _rhs: Self) -> Self { cw256::Unknown }
+    fn exp(self, _rhs: Self) -> Self { cw256::Unknown }
+    fn signextend(self, _rhs: Self) -> Self { cw256::Unknown }
     fn and(self, _rhs: Self) -> Self { cw256::Unknown }
     fn or(self, _rhs: Self) -> Self  { cw256::Unknown }
     fn xor(self, _rhs: Self) -> Self { cw256::Unknown }
     fn not(self) -> Self { cw256::Unknown }
+    fn byte(self, _n: Self) -> Self { cw256::Unknown }
+    fn shl(self, _n: Self) -> Self { cw256::Unknown }
+    fn shr(self, _n: Self) -> Self { cw256::Unknown }
+    fn sar(self, _n: Self) -> Self { cw256::Unknown }
+    fn keccak256(_words: Vec<Self>, _len: usize) -> Self { cw256::Unknown }
+    fn calldataload(_offset: Self) -> Self { cw256::Unknown }
     fn havoc(self) -> Self { cw256::Unknown }    
 }
+
+// ===================================================================
+// Interval Word
+// ===================================================================
+
+/// An abstract word represented as an interval of possible values
+/// (e.g. `5..15`).  This is more precise than `aw256` in situations
+/// where a value is unknown but bounded (e.g. a masked value or loop
+/// counter).  Operations which cannot be bounded yield the maximum
+/// interval.
+#[allow(non_camel_case_types)]
+pub type iw256 = Interval<w256>;
+
+impl iw256 {
+    /// Convert this interval into the simpler `aw256` domain.
+    fn to_aw256(self) -> aw256 {
+        if self.is_constant() { aw256::Word(self.start) } else { aw256::Unknown }
+    }
+
+    /// Convert an `aw256` into this domain.
+    fn from_aw256(w: aw256) -> Self {
+        match w {
+            aw256::Word(w) => Interval::from(w),
+            aw256::Unknown => Self::TOP
+        }
+    }
+
+    /// Apply a binary operator from the `aw256` domain, which gives a
+    /// precise result only when both operands are constant.
+    fn lift(self, rhs: Self, op: fn(aw256,aw256)->aw256) -> Self {
+        Self::from_aw256(op(self.to_aw256(),rhs.to_aw256()))
+    }
+}
+
+/// The interval representing an unknown boolean (i.e. `0..1`).
+const BOOLEAN : iw256 = Interval{start: w256::ZERO, end: w256::from_limbs([1,0,0,0])};
+
+/// Convert a boolean into a word.
+fn iw256_bool(b: bool) -> iw256 {
+    Interval::from(if b { w256::from(1) } else { w256::ZERO })
+}
+
+impl EvmWord for iw256 {
+    fn less_than(self,rhs:Self)->Self {
+        if self.end < rhs.start { iw256_bool(true) }
+        else if self.start >= rhs.end { iw256_bool(false) }
+        else { BOOLEAN }
+    }
+    fn signed_less_than(self,rhs:Self)->Self {
+        let max = w256::MAX >> 1;
+        if self.end <= max && rhs.end <= max {
+            // Both non-negative, hence same as unsigned.
+            self.less_than(rhs)
+        } else {
+            let r = self.lift(rhs,aw256::signed_less_than);
+            if r.is_constant() { r } else { BOOLEAN }
+        }
+    }
+    fn equal(self,rhs:Self)->Self {
+        if self.is_constant() && rhs.is_constant() { iw256_bool(self.start == rhs.start) }
+        else if self.end < rhs.start || rhs.end < self.start { iw256_bool(false) }
+        else { BOOLEAN }
+    }
+    fn is_zero(self) -> Self {
+        self.equal(Interval::from(w256::ZERO))
+    }
+    // Arithmetic
+    fn add(self, rhs: Self) -> Self {
+        match (self.start.checked_add(rhs.start),self.end.checked_add(rhs.end)) {
+            (Some(start),Some(end)) => Interval::new(start,end),
+            (_,_) => self.lift(rhs,aw256::add)
+        }
+    }
+    fn sub(self, rhs: Self) -> Self {
+        match (self.start.checked_sub(rhs.end),self.end.checked_sub(rhs.start)) {
+            (Some(start),Some(end)) => Interval::new(start,end),
+            (_,_) => self.lift(rhs,aw256::sub)
+        }
+    }
+    fn mul(self, rhs: Self) -> Self {
+        match (self.start.checked_mul(rhs.start),self.end.checked_mul(rhs.end)) {
+            (Some(start),Some(end)) => Interval::new(start,end),
+            (_,_) => self.lift(rhs,aw256::mul)
+        }
+    }
+    fn div(self, rhs: Self) -> Self {
+        if rhs.start != w256::ZERO {
+            Interval::new(self.start / rhs.end, self.end / rhs.start)
+        } else {
+            self.lift(rhs,aw256::div)
+        }
+    }
+    fn rem(self, rhs: Self) -> Self {
+        if self.is_constant() && rhs.is_constant() {
+            self.lift(rhs,aw256::rem)
+        } else if rhs.start != w256::ZERO && self.end < rhs.start {
+            self
+        } else if rhs.start != w256::ZERO {
+            Interval::new(w256::ZERO, rhs.end - w256::from(1))
+        } else {
+            Self::TOP
+        }
+    }
+    fn sdiv(self, rhs: Self) -> Self { self.lift(rhs,aw256::sdiv) }
+    fn srem(self, rhs: Self) -> Self { self.lift(rhs,aw256::srem) }
+    fn exp(self, rhs: Self) -> Self { self.lift(rhs,aw256::exp) }
+    fn signextend(self, rhs: Self) -> Self { self.lift(rhs,aw256::signextend) }
+    // bitwise
+    fn and(self, rhs: Self) -> Self {
+        if self.is_constant() && rhs.is_constant() {
+            self.lift(rhs,aw256::and)
+        } else {
+            // Result cannot exceed either operand
+            Interval::new(w256::ZERO, self.end.min(rhs.end))
+        }
+    }
+    fn or(self, rhs: Self) -> Self { self.lift(rhs,aw256::or) }
+    fn xor(self, rhs: Self) -> Self { self.lift(rhs,aw256::xor) }
+    fn not(self) -> Self { Interval::new(!self.end, !self.start) }
+    fn byte(self, n: Self) -> Self {
+        let r = self.lift(n,aw256::byte);
+        if r.is_constant() { r } else { Interval::new(w256::ZERO, w256::from(0xff)) }
+    }
+    fn shl(self, n: Self) -> Self { self.lift(n,aw256::shl) }
+    fn shr(self, n: Self) -> Self {
+        if n.is_constant() {
+            let k = shift_amount(n.start);
+            Interval::new(self.start >> k, self.end >> k)
+        } else {
+            Interval::new(w256::ZERO, self.end)
+        }
+    }
+    fn sar(self, n: Self) -> Self { self.lift(n,aw256::sar) }
+    fn keccak256(words: Vec<Self>, len: usize) -> Self {
+        let words = words.into_iter().map(|w| w.to_aw256()).collect();
+        Self::from_aw256(aw256::keccak256(words,len))
+    }
+    fn calldataload(_offset: Self) -> Self { Self::TOP }
+    fn havoc(self) -> Self {
+        Self::TOP
+    }
+}
+
+// ===================================================================
+// Symbolic Word
+// ===================================================================
+
+/// An abstract word which extends `aw256` by treating `KECCAK256` as
+/// an _uninterpreted function_.  That is, a hash is described by the
+/// contents of the memory region being hashed.  Thus, two hashes of
+/// provably equal regions (e.g. the same mapping key and slot) are
+/// themselves equal.  Likewise, a load from unknown calldata is
+/// described by its offset, such that symbolic keys (e.g. function
+/// arguments) can be hashed.  Any region containing an unknown value
+/// instead hashes to an unknown value, whilst regions which are
+/// entirely concrete are hashed as for `aw256`.
+#[derive(Clone,Eq,Ord,PartialOrd,PartialEq)]
+#[allow(non_camel_case_types)]
+pub enum sw256 {
+    Word(w256),
+    /// The hash of `len` bytes described by a sequence of words.
+    Keccak(Rc<[sw256]>,usize),
+    /// The word loaded from calldata at a given offset.
+    CallData(Rc<sw256>),
+    Unknown
+}
+
+impl sw256 {
+    /// Lift an operation over `aw256` into an operation over this
+    /// domain.  Hashes are treated as unknown values.
+    fn lift<F>(self, rhs: Self, op: F) -> Self
+    where F:Fn(aw256,aw256)->aw256 {
+        op(self.into(),rhs.into()).into()
+    }
+}
+
+impl From<sw256> for aw256 {
+    fn from(word: sw256) -> aw256 {
+        match word {
+            sw256::Word(w) => aw256::Word(w),
+            _ => aw256::Unknown
+        }
+    }
+}
+
+impl From<aw256> for sw256 {
+    fn from(word: aw256) -> sw256 {
+        match word {
+            aw256::Word(w) => sw256::Word(w),
+            aw256::Unknown => sw256::Unknown
+        }
+    }
+}
+
+impl fmt::Display for sw256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{:?}",self)
+    }
+}
+
+impl fmt::Debug for sw256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            sw256::Word(w) => write!(f,"{:?}",aw256::Word(*w)),
+            sw256::Keccak(words,len) => {
+                write!(f,"keccak(")?;
+                for (i,w) in words.iter().enumerate() {
+                    if i != 0 { write!(f,",")?; }
+                    write!(f,"{w:?}")?;
+                }
+                write!(f,";{len})")
+            }
+            sw256::CallData(offset) => write!(f,"calldata({offset:?})"),
+            sw256::Unknown => write!(f,"??")
+        }
+    }
+}
+
+impl From<w256> for sw256 {
+    fn from(word: w256) -> sw256 { sw256::Word(word) }
+}
+
+impl Top for sw256 {
+    const TOP : sw256 = sw256::Unknown;
+}
+
+impl Concretizable for sw256 {
+    type Item = w256;
+
+    fn is_constant(&self) -> bool {
+        matches!(self,sw256::Word(_))
+    }
+
+    fn constant(&self) -> w256 {
+        match self {
+            sw256::Word(w) => *w,
+            _ => {
+                panic!();
+            }
+        }
+    }
+}
+
+impl EvmWord for sw256 {
+    fn less_than(self,rhs:Self)->Self { self.lift(rhs,aw256::less_than) }
+    fn signed_less_than(self,rhs:Self)->Self { self.lift(rhs,aw256::signed_less_than) }
+    fn equal(self,rhs:Self)->Self {
+        match (self,rhs) {
+            // Identical symbolic words (e.g. hashes of identical
+            // regions) are equal
+            (l,r) if l == r && l != sw256::Unknown => sw256::Word(w256::from(1)),
+            (l,r) => l.lift(r,aw256::equal)
+        }
+    }
+    fn is_zero(self) -> Self { aw256::from(self).is_zero().into() }
+    fn add(self, rhs: Self) -> Self { self.lift(rhs,aw256::add) }
+    fn sub(self, rhs: Self) -> Self { self.lift(rhs,aw256::sub) }
+    fn mul(self, rhs: Self) -> Self { self.lift(rhs,aw256::mul) }
+    fn div(self, rhs: Self) -> Self { self.lift(rhs,aw256::div) }
+    fn rem(self, rhs: Self) -> Self { self.lift(rhs,aw256::rem) }
+    fn sdiv(self, rhs: Self) -> Self { self.lift(rhs,aw256::sdiv) }
+    fn srem(self, rhs: Self) -> Self { self.lift(rhs,aw256::srem) }
+    fn exp(self, rhs: Self) -> Self { self.lift(rhs,aw256::exp) }
+    fn signextend(self, rhs: Self) -> Self { self.lift(rhs,aw256::signextend) }
+    fn and(self, rhs: Self) -> Self { self.lift(rhs,aw256::and) }
+    fn or(self, rhs: Self) -> Self  { self.lift(rhs,aw256::or) }
+    fn xor(self, rhs: Self) -> Self { self.lift(rhs,aw256::xor) }
+    fn not(self) -> Self { aw256::from(self).not().into() }
+    fn byte(self, n: Self) -> Self { self.lift(n,aw256::byte) }
+    fn shl(self, n: Self) -> Self { self.lift(n,aw256::shl) }
+    fn shr(self, n: Self) -> Self { self.lift(n,aw256::shr) }
+    fn sar(self, n: Self) -> Self { self.lift(n,aw256::sar) }
+    fn keccak256(mut words: Vec<Self>, len: usize) -> Self {
+        if words.iter().any(|w| w == &sw256::Unknown) {
+            return sw256::Unknown;
+        } else if words.iter().all(|w| w.is_constant()) {
+            // Concrete region, hence hash directly (if possible).
+            let cwords = words.iter().map(|w| aw256::Word(w.constant())).collect();
+            if let aw256::Word(w) = aw256::keccak256(cwords,len) {
+                return sw256::Word(w);
+            }
+        }
+        // Clear any bytes in the last word beyond the region, since
+        // these are not part of it.
+        let n = len % 32;
+        if let (Some(sw256::Word(w)),true) = (words.last_mut(),n != 0) {
+            *w &= !(w256::MAX >> (n * 8));
+        }
+        sw256::Keccak(Rc::from(words),len)
+    }
+    fn calldataload(offset: Self) -> Self {
+        match offset {
+            sw256::Unknown => sw256::Unknown,
+            _ => sw256::CallData(Rc::new(offset))
+        }
+    }
+    fn havoc(self) -> Self { sw256::Unknown }
+}
@@ -0,0 +1,97 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::BTreeMap;
+use crate::util::{try_keccak256,w256,KECCAK_EMPTY};
+
+/// Mask used to truncate a word to a (`160` bit) account address.
+const ADDRESS_MASK : w256 = w256::from_limbs([u64::MAX,u64::MAX,0xFFFF_FFFF,0]);
+
+// ===================================================================
+// Account
+// ===================================================================
+
+/// Describes the state of an account within the world state.
+#[derive(Clone,Debug,Eq,Ord,PartialEq,PartialOrd)]
+pub struct Account {
+    /// Balance of this account (in wei).
+    pub balance: w256,
+    /// Nonce of this account.
+    pub nonce: u64,
+    /// Code associated with this account (if any).
+    pub code: Vec<u8>
+}
+
+impl Account {
+    pub fn new(balance: w256, nonce: u64, code: Vec<u8>) -> Self {
+        Self{balance,nonce,code}
+    }
+
+    /// Determine whether this account is _empty_ as defined in
+    /// EIP-161.  That is, it has no code, a zero nonce and a zero
+    /// balance.
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty() && self.nonce == 0 && self.balance == w256::ZERO
+    }
+}
+
+// ===================================================================
+// World State
+// ===================================================================
+
+/// A (partial) model of the world state in which a contract is
+/// executing.  This allows information about other accounts to be
+/// resolved during execution (e.g. for `EXTCODESIZE`).  Any account
+/// not included in the model is assumed not to exist.
+#[derive(Clone,Debug,Default,Eq,Ord,PartialEq,PartialOrd)]
+pub struct WorldState {
+    accounts: BTreeMap<w256,Account>
+}
+
+impl WorldState {
+    pub fn new() -> Self {
+        Self{accounts: BTreeMap::new()}
+    }
+
+    /// Add (or replace) the account at a given address.
+    pub fn insert(&mut self, address: w256, account: Account) {
+        self.accounts.insert(address & ADDRESS_MASK, account);
+    }
+
+    /// Get the account at a given address (if it exists).  Observe
+    /// that only the lower `160` bits of the address are considered.
+    pub fn get(&self, address: w256) -> Option<&Account> {
+        self.accounts.get(&(address & ADDRESS_MASK))
+    }
+
+    /// Determine the size of the code associated with a given
+    /// address, as returned by `EXTCODESIZE`.  This is zero for an
+    /// account which does not exist.
+    pub fn code_size(&self, address: w256) -> Option<w256> {
+        let size = self.get(address).map_or(0, |acc| acc.code.len());
+        Some(w256::from(size))
+    }
+
+    /// Determine the hash of the code associated with a given
+    /// address, as returned by `EXTCODEHASH`.  Following EIP-1052,
+    /// this is zero for an account which does not exist (or is
+    /// empty), and the hash of the empty sequence for an existing
+    /// account without code.  This returns `None` if the hash cannot
+    /// be computed (i.e. keccak support is disabled).
+    pub fn code_hash(&self, address: w256) -> Option<w256> {
+        match self.get(address) {
+            None => Some(w256::ZERO),
+            Some(acc) if acc.is_empty() => Some(w256::ZERO),
+            Some(acc) if acc.code.is_empty() => Some(KECCAK_EMPTY),
+            Some(acc) => try_keccak256(&acc.code)
+        }
+    }
+}
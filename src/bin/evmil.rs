@@ -17,12 +17,15 @@ use log::LevelFilter;
 use log4rs::append::console::ConsoleAppender;
 use log4rs::config::{Appender, Config, Root};
 use log4rs::encode::pattern::PatternEncoder;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 //
-use evmil::analysis::{aw256,ConcreteStack,ConcreteState,ConcreteMemory,UnknownStorage};
-use evmil::analysis::{find_dependencies,insert_havocs,trace};
+use evmil::analysis::{aw256,iw256,EvmState,ConcreteStack,ConcreteState,ConcreteMemory,UnknownStorage};
+use evmil::analysis::{block_complexity,find_constants,function_complexity,BlockGraph,find_block_signatures,find_dependencies,find_dispatch_table,find_jumpdest_heights,find_stack_mismatches,find_unresolved_jumps,insert_havocs,trace};
 use evmil::bytecode::{Assembly,Instruction,StructuredSection};
-use evmil::il::{Compiler,Parser};
-use evmil::util::{FromHexString, ToHexString};
+use evmil::fork::Fork;
+use evmil::il::{fold_constants,Compiler,Parser};
+use evmil::util::{FromHexString, ToHexString, Top};
 
 fn main() -> Result<(), Box<dyn Error>> {
     // Parse command-line arguments
@@ -35,6 +38,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             Command::new("compile")
                 .about("Compile EvmIL code to EVM bytecode")
                 .arg(Arg::new("eof").long("eof"))
+                .arg(Arg::new("fold").long("fold"))
                 .arg(Arg::new("file").required(true))
                 .visible_alias("c")
         )
@@ -44,8 +48,19 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .arg(Arg::new("code").short('c').long("code"))
                 .arg(Arg::new("eof").long("eof"))
                 .arg(Arg::new("debug").short('d').long("debug"))
+                .arg(Arg::new("domain").long("domain").takes_value(true))
                 .arg(Arg::new("havoc").long("havoc"))                
                 .arg(Arg::new("deps").long("deps"))
+                .arg(Arg::new("dispatch").long("dispatch"))
+                .arg(Arg::new("heights").long("heights"))
+                .arg(Arg::new("constants").long("constants"))
+                .arg(Arg::new("signatures").long("signatures"))
+                .arg(Arg::new("complexity").long("complexity"))
+                .arg(Arg::new("json").long("json"))
+                .arg(Arg::new("require-resolved").long("require-resolved"))
+                .arg(Arg::new("strict").long("strict"))
+                .arg(Arg::new("rpc").long("rpc").takes_value(true))
+                .arg(Arg::new("fork").long("fork").takes_value(true))
                 .arg(Arg::new("target").required(true))
                 .visible_alias("d")
         )
@@ -53,8 +68,10 @@ fn main() -> Result<(), Box<dyn Error>> {
             Command::new("assemble")
                 .about("Assemble EVM bytecode into a raw hex string")
                 .arg(Arg::new("eof").long("eof"))
+                .arg(Arg::new("compact").long("compact"))
                 .arg(Arg::new("target").required(true))
                 .visible_alias("a")
+                .alias("asm")
         )
         .subcommand(
             Command::new("infer")
@@ -62,6 +79,8 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .arg(Arg::new("debug").short('d').long("debug"))
                 .arg(Arg::new("havoc").long("havoc"))                
                 .arg(Arg::new("deps").long("deps"))
+                .arg(Arg::new("constants").long("constants"))
+                .arg(Arg::new("fork").long("fork").takes_value(true))
                 .arg(Arg::new("target").required(true))
                 .visible_alias("i")
         )
@@ -93,12 +112,15 @@ fn assemble(args: &ArgMatches) -> Result<bool, Box<dyn Error>> {
     // Read from asm file
     let context = fs::read_to_string(target)?;
     // Construct assembly from input file
-    let assembly = Assembly::from_str(&context)?;
+    let assembly = if args.contains_id("compact") {
+        Assembly::from_str_compact(&context)?
+    } else {
+        Assembly::from_str(&context)?
+    };
     // Check whether EOF or legacy code generation
     let bytes : Vec<u8> = if args.contains_id("eof") {
         // EVM Object Format
-        todo!()
-        //assembly.to_eof_bytes()
+        assembly.try_to_eof_bytes()?
     } else {
         // Legacy
         assembly.to_legacy_bytes()
@@ -116,7 +138,11 @@ fn compile(args: &ArgMatches) -> Result<bool, Box<dyn Error>> {
     // Read the test file
     let input = fs::read_to_string(filename)?;
     // Parse test file
-    let terms = Parser::new(&input).parse()?;
+    let mut terms = Parser::new(&input).parse()?;
+    // Apply constant folding (if requested)
+    if args.contains_id("fold") {
+        terms = terms.iter().map(fold_constants).collect();
+    }
     // Translate statements into bytecode instructions
     let mut compiler = Compiler::new();
     // Translate statements one-by-one
@@ -129,8 +155,7 @@ fn compile(args: &ArgMatches) -> Result<bool, Box<dyn Error>> {
     // Translate container into bytes
     let bytes : Vec<u8> = if args.contains_id("eof") {
         // EVM Object Format
-        todo!()
-        //assembly.to_eof_bytes()
+        assembly.try_to_eof_bytes()?
     } else {
         // Legacy
         assembly.to_legacy_bytes()
@@ -147,31 +172,94 @@ fn disassemble(args: &ArgMatches) -> Result<bool, Box<dyn Error>> {
     let mut hex = String::new();
     // Determine disassembly target
     let target = args.get_one::<String>("target").unwrap();
-    // Decide whether bytecode provided directly, or via a file.
-    if args.contains_id("code") {
-        // Provided directly
-        hex.push_str(target);
+    // Decide whether bytecode provided directly, via a file or via a
+    // node.
+    let bytes = if let Some(url) = args.get_one::<String>("rpc") {
+        // Target is the address of a deployed contract
+        fetch_code(url,target)?
     } else {
-        // Read hex from file
-        let context = fs::read_to_string(target)?;
-        // Read all lines of file
-        for l in context.lines() { hex.push_str(l); }
-    }
-    // Parse hex string into bytes
-    let bytes = hex.from_hex_string().unwrap();
+        if args.contains_id("code") {
+            // Provided directly
+            hex.push_str(target);
+        } else {
+            // Read hex from file
+            let context = fs::read_to_string(target)?;
+            // Read all lines of file
+            for l in context.lines() { hex.push_str(l); }
+        }
+        // Parse hex string into bytes
+        hex.from_hex_string().unwrap()
+    };
     // Construct bytecode representation
     let asm = if args.contains_id("eof") {
-        todo!()
-        //Assembly::from_eof_bytes(&bytes)?
+        Assembly::from_eof_bytes(&bytes)?
     } else {
         Assembly::from_legacy_bytes(&bytes)
     };
+    // Check all jumps are resolved (if requested)
+    if args.contains_id("require-resolved") {
+        check_resolved(&asm)?;
+    }
+    if args.contains_id("strict") {
+        check_stack_heights(&asm)?;
+    }
     //
-    disassemble_assembly(args,asm);
+    if args.contains_id("dispatch") {
+        disassemble_dispatch(&asm);
+    } else if args.contains_id("signatures") {
+        disassemble_signatures(&asm);
+    } else if args.contains_id("complexity") {
+        disassemble_complexity(&asm);
+    } else if args.contains_id("json") {
+        disassemble_json(&asm)?;
+    } else {
+        disassemble_assembly(args,asm)?;
+    }
     //
     Ok(true)
 }
 
+/// Check that the target of every reachable jump can be resolved,
+/// failing with an error identifying those which cannot.
+fn check_resolved(asm: &Assembly) -> Result<(), Box<dyn Error>> {
+    for section in asm {
+        if let StructuredSection::Code(insns) = section {
+            let pcs = find_unresolved_jumps(insns,usize::MAX).ok_or("analysis failed")?;
+            if !pcs.is_empty() {
+                let pcs : Vec<String> = pcs.iter().map(|pc| format!("{pc:#06x}")).collect();
+                return Err(format!("unresolved jump(s) at {}",pcs.join(", ")).into());
+            }
+        }
+    }
+    Ok(())
+}
+
+// Check that every reachable jumpdest is entered with the same stack
+// height along all paths, reporting an error otherwise.
+fn check_stack_heights(asm: &Assembly) -> Result<(), Box<dyn Error>> {
+    for section in asm {
+        if let StructuredSection::Code(insns) = section {
+            let mismatches = find_stack_mismatches(insns,usize::MAX).ok_or("analysis failed")?;
+            if !mismatches.is_empty() {
+                let ms : Vec<String> = mismatches.iter().map(|m| format!("{:#06x} ({} vs {})",m.pc,m.min,m.max)).collect();
+                return Err(format!("stack height mismatch(es) at {}",ms.join(", ")).into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fetch the code of a deployed contract from a JSON-RPC node.
+#[cfg(feature = "rpc")]
+fn fetch_code(url: &str, address: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    Ok(evmil::rpc::fetch_code(url,address)?)
+}
+
+#[cfg(not(feature = "rpc"))]
+fn fetch_code(_url: &str, _address: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    Err("rpc support not enabled (rebuild with --features rpc)".into())
+}
+
 fn infer(args: &ArgMatches) -> Result<bool, Box<dyn Error>> {
     let target = args.get_one::<String>("target").unwrap();
     // Read from asm file
@@ -179,31 +267,58 @@ fn infer(args: &ArgMatches) -> Result<bool, Box<dyn Error>> {
     // Construct assembly from input file
     let asm = Assembly::from_str(&context)?;
     //
-    disassemble_assembly(args,asm);
+    disassemble_assembly(args,asm)?;
     //
     Ok(true)
 }
 
-fn disassemble_assembly(args: &ArgMatches, mut asm: Assembly) {
+fn disassemble_assembly(args: &ArgMatches, mut asm: Assembly) -> Result<(), Box<dyn Error>> {
     // Check whether to insert havocs (or not)
     let havoc = args.contains_id("havoc");
     // Check whether debug information enabled (or not)    
     let debug = args.contains_id("debug");
     let deps = args.contains_id("deps");
+    let heights = args.contains_id("heights");
+    let constants = args.contains_id("constants");
+    // Determine abstract domain used for debug information
+    let interval = match args.get_one::<String>("domain").map(|s| s.as_str()) {
+        None|Some("constant") => false,
+        Some("interval") => true,
+        Some(name) => { return Err(format!("unknown domain \"{name}\"").into()); }
+    };
+    // Determine target fork (if given)
+    let fork = match args.get_one::<String>("fork") {
+        Some(name) => Some(Fork::lookup(name).ok_or(format!("unknown fork \"{name}\""))?),
+        None => None
+    };
     // Apply havoc inference (if requested)
     if havoc { asm = infer_havoc_insns(asm); }    
     //
+    // Determine section types (if known)
+    let types = asm.types();
+    let mut index = 0;
     for section in &asm {
         match section {
             StructuredSection::Code(insns) => {
                 println!(".code");
-                if debug {
-                    disassemble_debug_code(insns);
+                if debug && interval {
+                    let init = IntervalState::new();
+                    let init = match types { Some(ts) => init.with_section(ts,index), None => init };
+                    disassemble_debug_code(insns,init,fork);
+                } else if debug {
+                    let init = DebugState::new();
+                    let init = match types { Some(ts) => init.with_section(ts,index), None => init };
+                    disassemble_debug_code(insns,init,fork);
                 } else if deps {
-                    disassemble_dep_code(insns);                    
+                    disassemble_dep_code(insns,fork);                    
+                } else if heights {
+                    disassemble_height_code(insns,fork);
+                } else if constants {
+                    disassemble_constant_code(insns,fork);
                 } else {
-                    disassemble_code(insns);
+                    disassemble_code(insns,fork);
                 }
+                index += 1;
             }
             StructuredSection::Data(bytes) => {
                 println!(".data");
@@ -211,25 +326,75 @@ fn disassemble_assembly(args: &ArgMatches, mut asm: Assembly) {
             }
         }
     }
+    Ok(())
+}
+
+// Print the function dispatch table recovered from each code section
+// (if any).
+fn disassemble_dispatch(asm: &Assembly) {
+    for section in asm {
+        if let StructuredSection::Code(insns) = section {
+            let table = find_dispatch_table(insns);
+            //
+            if table.is_empty() {
+                println!("No dispatch table found");
+            }
+            for entry in table {
+                let sig = entry.signature().unwrap_or("???");
+                println!("{:#010x} _{:#06x} {sig}",entry.selector,entry.pc);
+            }
+        }
+    }
+}
+
+// Print the entry / exit stack heights of each basic block within
+// each code section.  Note that this can fail if the underlying static
+// analysis fails.
+fn disassemble_signatures(asm: &Assembly) {
+    for section in asm {
+        if let StructuredSection::Code(insns) = section {
+            let signatures = find_block_signatures(insns, usize::MAX).unwrap();
+            println!("pc entry exit");
+            for sig in signatures {
+                println!("{sig}");
+            }
+        }
+    }
+}
+
+// Print the cyclomatic complexity of each code section, along with
+// that of each function identified in its dispatch table (if any).
+fn disassemble_complexity(asm: &Assembly) {
+    for section in asm {
+        if let StructuredSection::Code(insns) = section {
+            let graph = BlockGraph::from(insns.as_slice());
+            println!("contract {}",block_complexity(&graph));
+            for entry in find_dispatch_table(insns) {
+                let blk = graph.nodes().lookup_pc(entry.pc);
+                let sig = entry.signature().unwrap_or("???");
+                println!("{:#010x} _{:#06x} {sig} {}",entry.selector,entry.pc,function_complexity(&graph,blk));
+            }
+        }
+    }
 }
 
 // Disassemble a code section _without_ debug information.  The reason
 // for separating out the two functions is that generating debug
 // information may fail.
-fn disassemble_code(insns: &[Instruction]) {
+fn disassemble_code(insns: &[Instruction], fork: Option<&Fork>) {
     let mut pc = 0;
     for insn in insns {
         if insn == &Instruction::JUMPDEST {
             println!("_{pc:#06x}:");
         }
-        println!("\t{insn}");
+        print_insn(insn,fork);
         pc += insn.length();
     } 
 }
 
 // Disassemble a code section _with_ dependency information.  Note
 // that this can fail if the underlying static analysis fails.
-fn disassemble_dep_code(insns: &[Instruction]) {
+fn disassemble_dep_code(insns: &[Instruction], fork: Option<&Fork>) {
     let mut pc = 0;
     //
     let deps = find_dependencies(insns, usize::MAX).map_err(|_| ()).unwrap();
@@ -246,18 +411,157 @@ fn disassemble_dep_code(insns: &[Instruction]) {
             }
         }
         println!();
-        println!("\t{insn}");
+        print_insn(insn,fork);
         pc += insn.length();
     } 
 }
 
+// Disassemble a code section annotating each reachable `JUMPDEST`
+// with its incoming stack height(s).  Note that this can fail if the
+// underlying static analysis fails.
+fn disassemble_height_code(insns: &[Instruction], fork: Option<&Fork>) {
+    let jumpdests = find_jumpdest_heights(insns, usize::MAX).unwrap();
+    let mut pc = 0;
+    for insn in insns {
+        if insn == &Instruction::JUMPDEST {
+            match jumpdests.iter().find(|jd| jd.pc == pc) {
+                Some(jd) => println!("_{pc:#06x}: ;; height={:?}",jd.heights),
+                None => println!("_{pc:#06x}: ;; unreachable")
+            }
+        }
+        print_insn(insn,fork);
+        pc += insn.length();
+    }
+}
+
+// ============================================================================
+// JSON
+// ============================================================================
+
+/// A single entry in the JSON listing of a disassembled contract.
+/// This is either an instruction (in which case `opcode` and
+/// `mnemonic` are present) or a data section.
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct JsonEntry {
+    pc: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    opcode: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mnemonic: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    operand: Option<String>,
+    /// Incoming stack height(s) for a `JUMPDEST` (empty if
+    /// unreachable).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    heights: Option<Vec<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<String>
+}
+
+#[cfg(feature = "serde")]
+impl JsonEntry {
+    fn insn(pc: usize, insn: &Instruction, heights: Option<Vec<usize>>) -> Self {
+        let text = insn.to_string();
+        let (mnemonic,operand) = match text.split_once(' ') {
+            Some((m,o)) => (m.to_string(),Some(o.to_string())),
+            None => (text,None)
+        };
+        let opcode = match insn {
+            Instruction::DATA(_) => None,
+            _ => Some(insn.opcode())
+        };
+        Self{pc,opcode,mnemonic:Some(mnemonic),operand,heights,data:None}
+    }
+
+    fn data(pc: usize, bytes: &[u8]) -> Self {
+        Self{pc,opcode:None,mnemonic:None,operand:None,heights:None,data:Some(bytes.to_hex_string())}
+    }
+}
+
+// Print a listing of all sections as a JSON array, annotating each
+// `JUMPDEST` with its incoming stack height(s).  Height annotations
+// are omitted if the underlying static analysis fails.
+#[cfg(feature = "serde")]
+fn disassemble_json(asm: &Assembly) -> Result<(), Box<dyn Error>> {
+    let mut entries = Vec::new();
+    let mut pc = 0;
+    for section in asm {
+        match section {
+            StructuredSection::Code(insns) => {
+                let jumpdests = find_jumpdest_heights(insns, usize::MAX);
+                let start = pc;
+                for insn in insns {
+                    let heights = match (insn,&jumpdests) {
+                        (Instruction::JUMPDEST,Some(jds)) => {
+                            // NOTE: jumpdest pcs are relative to the section
+                            let jd = jds.iter().find(|jd| jd.pc + start == pc);
+                            Some(jd.map(|jd| jd.heights.clone()).unwrap_or_default())
+                        }
+                        _ => None
+                    };
+                    entries.push(JsonEntry::insn(pc,insn,heights));
+                    pc += insn.length();
+                }
+            }
+            StructuredSection::Data(bytes) => {
+                entries.push(JsonEntry::data(pc,bytes));
+                pc += bytes.len();
+            }
+        }
+    }
+    println!("{}",serde_json::to_string_pretty(&entries)?);
+    Ok(())
+}
+
+#[cfg(not(feature = "serde"))]
+fn disassemble_json(_asm: &Assembly) -> Result<(), Box<dyn Error>> {
+    Err("json support not enabled (rebuild with --features serde)".into())
+}
+
+// Print a single instruction, taking into account the target fork
+// (if given).
+fn print_insn(insn: &Instruction, fork: Option<&Fork>) {
+    println!("\t{}",render_insn(insn,fork));
+}
+
+// Render a single instruction, taking into account the target fork
+// (if given).
+fn render_insn(insn: &Instruction, fork: Option<&Fork>) -> String {
+    match fork {
+        Some(fork) => insn.display(fork).to_string(),
+        None => insn.to_string()
+    }
+}
+
+// Disassemble a code section annotating each instruction which
+// completes the computation of a constant with its value.  Note that
+// this can fail if the underlying static analysis fails.
+fn disassemble_constant_code(insns: &[Instruction], fork: Option<&Fork>) {
+    let constants = find_constants(insns, usize::MAX).unwrap();
+    let mut pc = 0;
+    for (insn,constant) in insns.iter().zip(constants) {
+        if insn == &Instruction::JUMPDEST {
+            println!("_{pc:#06x}:");
+        }
+        match constant {
+            Some(w) => println!("\t{} // = {w:#x}",render_insn(insn,fork)),
+            None => print_insn(insn,fork)
+        }
+        pc += insn.length();
+    }
+}
+
 type DebugState = ConcreteState<ConcreteStack<aw256>,ConcreteMemory<aw256>,UnknownStorage<aw256>>;
+type IntervalState = ConcreteState<ConcreteStack<iw256>,ConcreteMemory<iw256>,UnknownStorage<iw256>>;
 
-// Disassemble a code section _with_ debug information.  Note that
+// Disassemble a code section _with_ debug information, using a given
+// initial state (which determines the abstract domain).  Note that
 // this can fail if the underlying static analysis fails.
-fn disassemble_debug_code(insns: &[Instruction]) {
+fn disassemble_debug_code<S>(insns: &[Instruction], init: S, fork: Option<&Fork>)
+where S: EvmState+Clone+Ord+std::fmt::Display, S::Word: Top {
     // Run the static analysis
-    let states : Vec<Vec<DebugState>> = trace(insns,DebugState::new(),usize::MAX).map_err(|_| ()).unwrap();
+    let states : Vec<Vec<S>> = trace(insns,init,usize::MAX).map_err(|_| ()).unwrap();
     // Print out info
     let mut pc = 0;
     for (i,insn) in insns.iter().enumerate() {
@@ -267,24 +571,19 @@ fn disassemble_debug_code(insns: &[Instruction]) {
         for st in &states[i] {
             println!("\t;; {}",st);
         }
-        println!("\t{insn}");
+        print_insn(insn,fork);
         pc += insn.length();
     } 
 }
 
 fn infer_havoc_insns(mut asm: Assembly) -> Assembly {
     // This could probably be more efficient :)
-    let sections = asm.iter_mut().map(|section| {
-        match section {
-            StructuredSection::Code(ref mut insns) => {    
-                let ninsns = insert_havocs(insns.clone(), usize::MAX).unwrap();
-	        StructuredSection::Code(ninsns)
-            }
-            _ => section.clone()
+    for section in asm.iter_mut() {
+        if let StructuredSection::Code(insns) = section {
+            *insns = insert_havocs(insns.clone(), usize::MAX).unwrap();
         }
-    }).collect();
-    // 
-    Assembly::new(sections)
+    }
+    asm
 }
 
 /// Initialise logging using a suitable pattern.
@@ -10,9 +10,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use std::slice::{Iter,IterMut};
+use crate::analysis::{determine_insn_offsets,max_stack_height,stack_underflows,trace_with_config,DefaultState};
 use super::{Instruction};
+use super::instruction::from_rel_offset;
 use super::{eof,legacy};
-pub use super::eof::DecodingError;
+pub use super::eof::{DecodingError,EncodingError,SectionType};
 use super::ParseError;
 
 // ============================================================================
@@ -27,8 +29,13 @@ use super::ParseError;
 /// the _data section_ should also come last.  However, for legacy
 /// contracts, they can be interleaved.
 #[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Assembly {
-    sections: Vec<StructuredSection>
+    sections: Vec<StructuredSection>,
+    /// The types of the code sections (if known), as declared in the
+    /// type section of an EOF container.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    types: Option<Vec<SectionType>>
 }
 
 impl Assembly {
@@ -62,12 +69,29 @@ impl Assembly {
     
     pub fn empty() -> Self {
         Self {
-            sections: Vec::new()
+            sections: Vec::new(),
+            types: None
         }
     }
 
     pub fn new(sections: Vec<StructuredSection>) -> Self {
-        Self { sections }
+        Self { sections, types: None }
+    }
+
+    /// Declare the types of the code sections in this assembly (in
+    /// order).  These are written into the type section when encoding
+    /// an EOF container, and are used when analysing each code
+    /// section (e.g. to determine the stack effect of `CALLF`).
+    pub fn with_types(mut self, types: Vec<SectionType>) -> Self {
+        self.types = Some(types);
+        self
+    }
+
+    /// Return the declared types of the code sections in this
+    /// assembly (if known).  For example, these are known for an
+    /// assembly decoded from an EOF container.
+    pub fn types(&self) -> Option<&[SectionType]> {
+        self.types.as_deref()
     }
 
     /// Return the number of sections in the code.
@@ -80,6 +104,24 @@ impl Assembly {
         self.sections.is_empty()
     }
 
+    /// Return the instructions of all code sections in this
+    /// assembly, in order.  Data sections are ignored.
+    pub fn instructions(&self) -> impl Iterator<Item=&Instruction> {
+        self.sections.iter().flat_map(|s| match s {
+            StructuredSection::Code(insns) => insns.as_slice(),
+            StructuredSection::Data(_) => &[]
+        })
+    }
+
+    /// Convert this assembly into the instructions of all its code
+    /// sections, in order.  Data sections are discarded.
+    pub fn into_instructions(self) -> Vec<Instruction> {
+        self.sections.into_iter().flat_map(|s| match s {
+            StructuredSection::Code(insns) => insns,
+            StructuredSection::Data(_) => Vec::new()
+        }).collect()
+    }
+
     pub fn iter(&self) -> Iter<'_,StructuredSection> {
         self.sections.iter()
     }
@@ -99,16 +141,102 @@ impl Assembly {
     pub fn from_str(input: &str) -> Result<Assembly,ParseError> {
         let parser = super::parser::Parser::new(input);
         parser.parse()
-    }    
+    }
+
+    /// Parse some assembly language into an `Assembly`, such that
+    /// pushes of labels use the smallest width which fits (rather
+    /// than always two bytes).
+    pub fn from_str_compact(input: &str) -> Result<Assembly,ParseError> {
+        let parser = super::parser::Parser::new(input).compact(true);
+        parser.parse()
+    }
 }
 
 impl Assembly {
+    /// Check that every instruction in every code section can be
+    /// encoded as valid bytecode (e.g. that no `PUSH` has more than
+    /// 32 bytes).  This is intended as a pre-flight check before
+    /// encoding, since encoding an invalid instruction will panic.
+    /// On failure, each invalid instruction is reported along with
+    /// its byte offset.
+    pub fn validate(&self) -> Result<(),Vec<(usize,ValidationError)>> {
+        let errors = self.validate_insns(false);
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Check that this assembly can be encoded as a valid EOF
+    /// container.  In addition to the checks of `validate()`, this
+    /// requires there is at most one data section, and that it comes
+    /// last.  Furthermore, each code section is statically checked:
+    /// relative jumps must land within the section, functions called
+    /// must exist, every instruction must be reachable, and no
+    /// reachable instruction may underflow the stack.  Where section
+    /// types are declared, each section is analysed with its inputs
+    /// on the stack, and its declared maximum stack height must match
+    /// that inferred.
+    pub fn validate_eof(&self) -> Result<(),Vec<(usize,ValidationError)>> {
+        let mut errors = self.validate_insns(true);
+        let sections = self.sections.iter().filter(|s| matches!(s,StructuredSection::Code(_))).count();
+        let mut pc = 0;
+        let mut section = 0;
+        let mut data = false;
+        for s in &self.sections {
+            match s {
+                StructuredSection::Code(insns) => {
+                    if data { errors.push((pc,ValidationError::DataSectionNotLast)); }
+                    errors.extend(validate_eof_code(insns,self.types(),section,sections).into_iter().map(|(o,e)| (pc+o,e)));
+                    section += 1;
+                    pc += insns.iter().map(|i| i.length()).sum::<usize>();
+                }
+                StructuredSection::Data(bytes) => {
+                    if data { errors.push((pc,ValidationError::MultipleDataSections)); }
+                    data = true;
+                    pc += bytes.len();
+                }
+            }
+        }
+        errors.sort_by_key(|(pc,_)| *pc);
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Check every instruction in every code section can be encoded,
+    /// where `eof` determines whether the relative jumps of EIP4200
+    /// are permitted.
+    fn validate_insns(&self, eof: bool) -> Vec<(usize,ValidationError)> {
+        let mut errors = Vec::new();
+        let mut pc = 0;
+        for section in &self.sections {
+            match section {
+                StructuredSection::Code(insns) => {
+                    for insn in insns {
+                        if let Err(e) = validate_insn(insn,eof) {
+                            errors.push((pc,e));
+                        }
+                        pc += insn.length();
+                    }
+                }
+                StructuredSection::Data(bytes) => {
+                    pc += bytes.len();
+                }
+            }
+        }
+        errors
+    }
+
     pub fn to_legacy_bytes(&self) -> Vec<u8> {
         legacy::to_bytes(self)
     }
 
     pub fn to_eof_bytes(&self) -> Vec<u8> {
         eof::to_bytes(self).unwrap()
+    }
+
+    /// Encode this assembly as an EOF container, failing if it cannot
+    /// be encoded (e.g. because there are no code sections, or a
+    /// section is too long).  The maximum stack height of each code
+    /// section is inferred and written into the type section.
+    pub fn try_to_eof_bytes(&self) -> Result<Vec<u8>,EncodingError> {
+        eof::to_bytes(self)
     }    
 }    
 
@@ -134,14 +262,137 @@ impl<'a> IntoIterator for &'a mut Assembly {
     }
 }
 
+// ============================================================================
+// Validation
+// ============================================================================
+
+/// Identifies a reason why an assembly cannot be encoded as valid
+/// bytecode.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum ValidationError {
+    /// A `PUSH` instruction with the given number of bytes (which is
+    /// either zero or exceeds 32).
+    InvalidPush(usize),
+    /// A `DUP` instruction with an index outside `1..=16`.
+    InvalidDup(u8),
+    /// A `SWAP` instruction with an index outside `1..=16`.
+    InvalidSwap(u8),
+    /// A `LOG` instruction with more than four topics.
+    InvalidLog(u8),
+    /// An instruction which has no encoding (e.g. a relative jump
+    /// in legacy bytecode).
+    Unencodable,
+    /// A code section follows a data section (EOF only).
+    DataSectionNotLast,
+    /// More than one data section (EOF only).
+    MultipleDataSections,
+    /// A relative jump (with the given relative offset) whose target
+    /// is not the start of an instruction within the enclosing code
+    /// section (EOF only).
+    InvalidRelativeJump(i16),
+    /// The start of a sequence of instructions which cannot be
+    /// reached (EOF only).
+    UnreachableCode,
+    /// An instruction which can be executed with too few operands on
+    /// the stack (EOF only).
+    StackUnderflow,
+    /// A function instruction (e.g. `CALLF`) whose target is not a
+    /// code section (EOF only).
+    InvalidSectionIndex(u16),
+    /// The declared maximum stack height of a code section differs
+    /// from that inferred, which is given (EOF only).
+    InvalidMaxStack(usize),
+    /// The analysis of a code section did not complete, hence its
+    /// reachability and stack usage could not be checked (EOF only).
+    AnalysisFailed
+}
+
+/// Check whether a given instruction can be encoded.  Relative jumps
+/// and function instructions can only be encoded within an EOF
+/// container and, furthermore, a jump table must have between 1 and
+/// 256 entries.
+fn validate_insn(insn: &Instruction, eof: bool) -> Result<(),ValidationError> {
+    match insn {
+        Instruction::PUSH(bs) if bs.is_empty() || bs.len() > 32 => Err(ValidationError::InvalidPush(bs.len())),
+        Instruction::DUP(n) if *n == 0 || *n > 16 => Err(ValidationError::InvalidDup(*n)),
+        Instruction::SWAP(n) if *n == 0 || *n > 16 => Err(ValidationError::InvalidSwap(*n)),
+        Instruction::LOG(n) if *n > 4 => Err(ValidationError::InvalidLog(*n)),
+        Instruction::RJUMP(_)|Instruction::RJUMPI(_) if !eof => Err(ValidationError::Unencodable),
+        Instruction::RJUMPV(_) if !eof => Err(ValidationError::Unencodable),
+        Instruction::CALLF(_)|Instruction::RETF|Instruction::JUMPF(_) if !eof => Err(ValidationError::Unencodable),
+        Instruction::RJUMPV(ts) if ts.is_empty() || ts.len() > 256 => Err(ValidationError::Unencodable),
+        _ => Ok(())
+    }
+}
+
+/// Statically check the given code section of an EOF container, where
+/// offsets are relative to the start of the section, `types` are the
+/// declared section types (if known), and `sections` is the number of
+/// code sections in the container.  If the underlying analysis fails,
+/// then this is reported and only relative jumps and function
+/// instructions are checked.
+fn validate_eof_code(insns: &[Instruction], types: Option<&[SectionType]>, section: usize, sections: usize) -> Vec<(usize,ValidationError)> {
+    let mut errors = Vec::new();
+    // Determine instruction boundaries
+    let offsets = determine_insn_offsets(insns);
+    // A relative jump target must be an instruction within range
+    let invalid = |pc: usize, o: &i16| offsets.binary_search(&from_rel_offset(pc,*o)).is_err();
+    // Check relative jumps
+    for (insn,&pc) in insns.iter().zip(&offsets) {
+        let next = pc + insn.length();
+        match insn {
+            Instruction::RJUMP(o)|Instruction::RJUMPI(o) if invalid(next,o) => {
+                errors.push((pc,ValidationError::InvalidRelativeJump(*o)));
+            }
+            Instruction::RJUMPV(os) => {
+                for o in os.iter().filter(|o| invalid(next,o)) {
+                    errors.push((pc,ValidationError::InvalidRelativeJump(*o)));
+                }
+            }
+            Instruction::CALLF(i)|Instruction::JUMPF(i) if *i as usize >= sections => {
+                errors.push((pc,ValidationError::InvalidSectionIndex(*i)));
+            }
+            _ => {}
+        }
+    }
+    // Determine initial state
+    let init = eof::section_state(types,section);
+    let config = eof::ANALYSIS_CONFIG;
+    // Run the analysis (once)
+    let Ok(states) = trace_with_config::<Vec<DefaultState>>(insns,init,&config) else {
+        errors.push((0,ValidationError::AnalysisFailed));
+        return errors;
+    };
+    // Check reachability.  Observe that the analysis cannot continue
+    // past a call whose type is unknown, though control does return.
+    let reached : Vec<bool> = states.iter().map(|sts| !sts.is_empty()).collect();
+    let returns = |i: usize| matches!(insns[i],Instruction::CALLF(_));
+    for (i,&pc) in offsets.iter().enumerate() {
+        if !reached[i] && (i == 0 || (reached[i-1] && !returns(i-1))) {
+            errors.push((pc,ValidationError::UnreachableCode));
+        }
+    }
+    // Check stack underflow
+    errors.extend(stack_underflows(insns,&states).into_iter().map(|pc| (pc,ValidationError::StackUnderflow)));
+    // Check declared maximum stack height (if known)
+    if let Some(ty) = types.and_then(|ts| ts.get(section)) {
+        let h = max_stack_height(&states);
+        if h != ty.max_stack as usize {
+            errors.push((0,ValidationError::InvalidMaxStack(h)));
+        }
+    }
+    errors
+}
+
 // ============================================================================
 // Section
 // ============================================================================
 
 #[derive(Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StructuredSection {
     /// A data section is simply a sequence of zero or more bytes.
-    Data(Vec<u8>),
+    Data(#[cfg_attr(feature = "serde", serde(with = "crate::util::serde_hex"))] Vec<u8>),
     /// A code section is a sequence of zero or more instructions
     /// along with appropriate _metadata_.
     Code(Vec<Instruction>)
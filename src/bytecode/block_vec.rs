@@ -61,6 +61,15 @@ impl<'a> BlockVec<'a> {
         &self.insns[m..n]
     }
 
+    /// Get the starting byte offset of the _ith_ block within this
+    /// decomposition.  Since blocks are numbered in the order they
+    /// appear in the underlying sequence, these offsets are strictly
+    /// increasing.
+    pub fn start_pc(&self, index: usize) -> usize {
+        let m = if index == 0 { 0 } else { self.insn_offsets[index-1] };
+        self.pc_offsets[m]
+    }
+
     /// Determine which block encloses a given instruction (as
     /// determined by its _instruction offset_).
     pub fn lookup_insn(&self, index: usize) -> usize {
@@ -11,7 +11,7 @@
 // limitations under the License.
 use crate::util;
 use super::{Instruction};
-use Instruction::{PUSH,RJUMPI,RJUMP};
+use Instruction::PUSH;
 
 /// Mechanism for constructing a bytecode `Assembly` by allowing
 /// instructions to be patched before the final assembly is built.
@@ -39,6 +39,9 @@ pub struct Builder {
     labels: Vec<(String,Option<usize>)>,
     /// Offsets of instructions requiring a patch.
     patches: Vec<usize>,
+    /// Offsets of labelled `PUSH` instructions whose width is fixed,
+    /// along with the index of their label and their width.
+    sized: Vec<(usize,usize,usize)>,
     /// Byte offset of current cursor position.
     byte_offset: usize,
     /// The set of (unpatched) instructions.  Every branch instruction
@@ -50,6 +53,7 @@ impl Builder {
     pub fn new() -> Self {
         Self{labels: Vec::new(),
              patches: Vec::new(),
+             sized: Vec::new(),
              byte_offset: 0,
              insns: Vec::new()
         }
@@ -110,7 +114,7 @@ impl Builder {
     pub fn push_labeled(&mut self, insn: Instruction) {
         // sanity check whether instruction can be patched.
         match insn {
-            PUSH(_)|RJUMP(_)|RJUMPI(_) => {}
+            PUSH(_) => {}
             _ => {
                 // do nothing
                 panic!("unpatchable instruction!");
@@ -122,20 +126,152 @@ impl Builder {
         self.push(insn);
     }
     
+    /// Push a `PUSH` of the label with a given index onto the
+    /// builder, which is patched with the offset of that label when
+    /// the final assembly is built.
+    pub fn push_label(&mut self, index: usize) {
+        // Always generate a push2 instruction
+        self.push_labeled(PUSH(vec![(index / 256) as u8, (index % 256) as u8]));
+    }
+
+    /// Push a `PUSH` of the label with a given index onto the
+    /// builder, such that it has a given width (e.g. as for `push1`)
+    /// regardless of the offset of that label.  Building fails if
+    /// this offset does not fit within the width.
+    pub fn push_sized_label(&mut self, index: usize, width: usize) {
+        self.sized.push((self.insns.len(),index,width));
+        self.push(PUSH(vec![0; width]));
+    }
+
     /// Push a new instruction onto the builder.  
     pub fn push(&mut self, insn: Instruction) {
         self.byte_offset += insn.length();
         self.insns.push(insn);
     }
 
+    /// Rebuild a bytecode sequence from a selection of its
+    /// instructions (identified by index, in order).  Here, `labels`
+    /// identifies those `PUSH` instructions which provide the target
+    /// of some jump, giving the (instruction) offset of that target.
+    /// Since the selection may change the byte offsets of the
+    /// instructions retained, every such `PUSH` is relocated.
+    /// However, other uses of code offsets (e.g. by `CODECOPY`) are
+    /// not.
+    pub fn relocate(insns: &[Instruction], labels: &[Option<usize>], selection: impl IntoIterator<Item=usize>) -> Vec<Instruction> {
+        // Identify jump targets
+        let mut targets = vec![false;insns.len()];
+        for t in labels.iter().flatten() { targets[*t] = true; }
+        //
+        let mut builder = Builder::new();
+        for i in selection {
+            if targets[i] {
+                builder.mark_label(&i.to_string()).unwrap();
+            }
+            if let Some(t) = labels[i] {
+                let index = builder.get_label(&t.to_string());
+                builder.push_label(index);
+            } else {
+                builder.push(insns[i].clone());
+            }
+        }
+        builder.to_insns()
+    }
+
+    /// Construct the final assembly by patching all labels used
+    /// within instructions.  This panics if the offset of a label
+    /// does not fit within the width of a sized `PUSH`.
+    pub fn to_insns(self) -> Vec<Instruction> {
+        self.try_to_insns().unwrap()
+    }
+
     /// Construct the final assembly by patching all labels used
-    /// within instructions.
-    pub fn to_insns(mut self) -> Vec<Instruction> {
+    /// within instructions, failing with the instruction offset of
+    /// any sized `PUSH` whose label does not fit within its width.
+    pub fn try_to_insns(mut self) -> Result<Vec<Instruction>,usize> {
         // Apply patches
         for i in &self.patches {
             self.insns[*i] = self.patch(&self.insns[*i]);
         }
-        self.insns
+        for &(i,lab,width) in &self.sized {
+            self.insns[i] = push_offset(self.labels[lab].1.unwrap(),width).ok_or(i)?;
+        }
+        Ok(self.insns)
+    }
+
+    /// Construct the final assembly by patching all labels used
+    /// within instructions, such that each labelled `PUSH` uses the
+    /// smallest width which fits its target.  Since narrowing one
+    /// `PUSH` can move the targets of others, widths are determined
+    /// by iterating to a fixed point.  Widths begin at one byte and
+    /// only ever grow, hence this always terminates.  This panics if
+    /// the offset of a label does not fit within the width of a sized
+    /// `PUSH`.
+    pub fn to_compact_insns(self) -> Vec<Instruction> {
+        self.try_to_compact_insns().unwrap()
+    }
+
+    /// Construct the final assembly as for `to_compact_insns()`,
+    /// failing with the instruction offset of any sized `PUSH` whose
+    /// label does not fit within its width.
+    pub fn try_to_compact_insns(mut self) -> Result<Vec<Instruction>,usize> {
+        // Determine instruction offset of each label
+        let targets : Vec<usize> = self.labels.iter().map(|(_,offset)| self.insn_offset(offset.unwrap())).collect();
+        // Initially assume every label fits in a single byte
+        let mut widths = vec![1; self.insns.len()];
+        loop {
+            // Determine byte offsets under the current widths
+            let mut offsets = Vec::with_capacity(self.insns.len()+1);
+            let mut pc = 0;
+            for (i,insn) in self.insns.iter().enumerate() {
+                offsets.push(pc);
+                pc += match insn {
+                    PUSH(_) if self.patches.contains(&i) => 1 + widths[i],
+                    _ => insn.length()
+                };
+            }
+            offsets.push(pc);
+            // Widen any push which no longer fits its target
+            let mut changed = false;
+            for &i in &self.patches {
+                if let PUSH(bytes) = &self.insns[i] {
+                    let lab = util::from_be_bytes(bytes) as usize;
+                    let offset = offsets[targets[lab]];
+                    let width = std::cmp::max(1,(usize::BITS - offset.leading_zeros()).div_ceil(8) as usize);
+                    if width > widths[i] {
+                        widths[i] = width;
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                // Apply patches
+                for &i in &self.patches {
+                    self.insns[i] = match &self.insns[i] {
+                        PUSH(bytes) => {
+                            let lab = util::from_be_bytes(bytes) as usize;
+                            let offset = offsets[targets[lab]].to_be_bytes();
+                            PUSH(offset[offset.len()-widths[i]..].to_vec())
+                        }
+                        _ => unreachable!()
+                    };
+                }
+                for &(i,lab,width) in &self.sized {
+                    self.insns[i] = push_offset(offsets[targets[lab]],width).ok_or(i)?;
+                }
+                return Ok(self.insns);
+            }
+        }
+    }
+
+    /// Determine the instruction offset corresponding to a given
+    /// byte offset within the (unpatched) instructions.
+    fn insn_offset(&self, byte_offset: usize) -> usize {
+        let mut pc = 0;
+        for (i,insn) in self.insns.iter().enumerate() {
+            if pc >= byte_offset { return i; }
+            pc += insn.length();
+        }
+        self.insns.len()
     }
 
     fn patch(&self, insn: &Instruction) -> Instruction {
@@ -147,15 +283,23 @@ impl Builder {
                 // Always generate a push2 instruction
                 PUSH(vec![(offset/256) as u8, (offset%256) as u8])
             }
-            RJUMP(lab) => {
-                RJUMP(self.labels[*lab].1.unwrap())                    
-            }
-            RJUMPI(lab) => {
-                RJUMPI(self.labels[*lab].1.unwrap())                                        
-            }
             _ => {
                 unreachable!()
             }
         } 
     }
 }
+
+/// Construct a `PUSH` of a given offset with a given width, or `None`
+/// if the offset does not fit within that width.
+fn push_offset(offset: usize, width: usize) -> Option<Instruction> {
+    let bytes = offset.to_be_bytes();
+    let n = std::cmp::min(width,bytes.len());
+    if bytes[..bytes.len()-n].iter().any(|b| *b != 0) {
+        None
+    } else {
+        let mut padded = vec![0; width - n];
+        padded.extend(&bytes[bytes.len()-n..]);
+        Some(PUSH(padded))
+    }
+}
@@ -0,0 +1,165 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::analysis::find_reachable_in;
+use crate::bytecode::{split_metadata,Assembly,Disassemble,Instruction,StructuredSection};
+
+/// Responsible for disassembling a legacy (i.e. non-EOF) byte
+/// sequence into an `Assembly`, according to a given set of options.
+/// For example:
+///
+/// ```
+/// use evmil::bytecode::DisassemblyBuilder;
+///
+/// let bytes = [0x00, 0x60, 0x01, 0x00];
+/// let asm = DisassemblyBuilder::new(&bytes)
+///     .with_budget(1000)
+///     .preserve_unreachable(true)
+///     .build();
+/// assert_eq!(asm.len(),1);
+/// ```
+///
+/// By default, unreachable instructions are marked as data, and any
+/// trailing unreachable bytes are split off into a separate data
+/// section.  Likewise, any trailing compiler metadata is split off
+/// into its own data section (and is not disassembled).
+pub struct DisassemblyBuilder<'a> {
+    /// The bytes being disassembled.
+    bytes: &'a [u8],
+    /// Byte offset where code begins.  Any bytes before this are
+    /// placed into a leading data section.
+    offset: usize,
+    /// Maximum number of steps the reachability analysis can take.
+    /// If this is exceeded, all instructions are considered
+    /// reachable.
+    budget: usize,
+    /// Signals whether or not unreachable instructions should be
+    /// left as-is, rather than being marked as data.
+    preserve: bool,
+    /// Signals whether or not trailing compiler metadata should be
+    /// split off into a separate data section.
+    metadata: bool
+}
+
+impl<'a> DisassemblyBuilder<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self{bytes, offset: 0, budget: usize::MAX, preserve: false, metadata: true}
+    }
+
+    /// Begin disassembling code from a given byte offset, treating
+    /// all bytes before this as data.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Limit the number of steps the reachability analysis can take.
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Determine whether or not unreachable instructions should be
+    /// preserved, rather than being marked as data.
+    pub fn preserve_unreachable(mut self, flag: bool) -> Self {
+        self.preserve = flag;
+        self
+    }
+
+    /// Determine whether or not trailing compiler metadata (see
+    /// `split_metadata`) should be split off into its own data
+    /// section, rather than being disassembled.
+    pub fn split_metadata(mut self, flag: bool) -> Self {
+        self.metadata = flag;
+        self
+    }
+
+    /// Disassemble the bytes according to the configured options.
+    pub fn build(self) -> Assembly {
+        let offset = std::cmp::min(self.offset,self.bytes.len());
+        let (prefix,bytes) = self.bytes.split_at(offset);
+        // Split off trailing metadata (if applicable)
+        let (bytes,metadata) = if self.metadata { split_metadata(bytes) } else { (bytes,&[][..]) };
+        let mut sections = Vec::new();
+        //
+        if !prefix.is_empty() {
+            sections.push(StructuredSection::Data(prefix.to_vec()));
+        }
+        // Disassemble bytes into instructions.  Any instruction
+        // truncated by the end of the bytes (e.g. a `PUSH` missing
+        // part of its operand) is not considered code, and instead
+        // ends up in the trailing data section.
+        let (mut insns,truncated) = bytes.disassemble_partial();
+        if truncated > 0 { insns.pop(); }
+        let code = &bytes[..bytes.len()-truncated];
+        // Compute reachability information (where possible).  Since
+        // the bytes are known, jumps to invalid destinations (e.g.
+        // into the operand of a `PUSH`) are not considered.
+        let reachable = match find_reachable_in(&insns, code, self.budget) {
+            Ok(reachable) if !self.preserve => reachable,
+            _ => vec![true; insns.len()]
+        };
+        // Mark all unreachable instructions
+        mark_unreachable(&mut insns,code,&reachable);
+        // Determine start of data section using reachability infor.
+        let (i,pc) = find_data_start(&insns,code,&reachable);
+        // Split contract
+        if pc < bytes.len() {
+            // Split code from data.
+            insns.truncate(i);
+            // Strip off invalid separator.
+            let data = bytes[pc..].to_vec();
+            sections.push(StructuredSection::Code(insns));
+            sections.push(StructuredSection::Data(data));
+        } else {
+            sections.push(StructuredSection::Code(insns));
+        }
+        if !metadata.is_empty() {
+            sections.push(StructuredSection::Data(metadata.to_vec()));
+        }
+        Assembly::new(sections)
+    }
+}
+
+/// Convert every unreachable instruction into a `DATA` instruction to
+/// signal that this is not executable code.
+fn mark_unreachable(insns: &mut [Instruction], bytes: &[u8], reachable: &[bool]) {
+    let mut pc = 0;
+    
+    for i in 0..insns.len() {
+        let len = insns[i].length();
+        // Check if instruction is reachable
+        if !reachable[i] {
+            // Determine end of instruction bytes
+            let end = std::cmp::min(bytes.len(),pc + len);
+            // Extract instruction bytes
+            let data = bytes[pc..end].to_vec();
+            // Replace instruction
+            insns[i] = Instruction::DATA(data);
+        }
+        pc += len;
+    }    
+}
+
+/// Find the start of the data section by traversing backwards from
+/// the end of the instruction sequence until the first reachable
+/// instruction is encountered.
+fn find_data_start(insns: &[Instruction], bytes: &[u8], reachable: &[bool]) -> (usize,usize) {
+    let mut i = insns.len();
+    let mut pc = bytes.len();
+    //
+    while i > 0 && !reachable[i-1] {
+        i -= 1;
+        pc -= insns[i].length();
+    }
+        //
+    (i,pc)
+}
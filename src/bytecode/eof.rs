@@ -10,12 +10,35 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use std::fmt;
+use crate::analysis::{find_max_stack_height_with,DefaultState,TraceConfig};
 use crate::util::{ByteEncoder,ByteDecoder};
 use crate::bytecode::{Assemble,Assembly,Disassemble,StructuredSection};
 
 /// The EOF magic prefix as dictated in EIP3540.
 pub const EOF_MAGIC : u16 = 0xEF00;
 
+/// The maximum number of code sections permitted in an EOF container
+/// (as dictated in EIP3540).
+pub const MAX_CODE_SECTIONS : u16 = 1024;
+
+/// The configuration used when statically analysing the code sections
+/// of an EOF container (e.g. to determine their maximum stack
+/// height).  Widening ensures the analysis terminates for loops whose
+/// bounds are unknown, whilst the limit guards against pathological
+/// cases.
+pub(crate) const ANALYSIS_CONFIG : TraceConfig = TraceConfig{limit: 1_000_000, widen_after: Some(3)};
+
+/// The type signature of a code section in an EOF container (as
+/// dictated in EIP4750).  This determines how many stack items a
+/// function consumes and produces when called (e.g. via `CALLF`).
+#[derive(Clone,Copy,Debug,Eq,Hash,Ord,PartialEq,PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SectionType {
+    pub inputs: u8,
+    pub outputs: u8,
+    pub max_stack: u16
+}
+
 // ============================================================================
 // Encoding Error
 // ============================================================================
@@ -24,9 +47,15 @@ pub const EOF_MAGIC : u16 = 0xEF00;
 /// structure.  This indicates the bytecode structure is malformed in
 /// some way.
 pub enum EncodingError {
+    /// Indicates there are no code sections, whereas at least one is
+    /// required in the EOF format.
+    NoCodeSections,
     /// Indicates there are too many code sections than can be encoded
     /// in the EOF format.
     TooManyCodeSections(usize),
+    /// Indicates the maximum stack height of a code section is too
+    /// large to be encoded.
+    StackTooHigh(usize),
     /// Indiciates a code section is too long
     CodeSectionTooLong(usize),
     /// Indicates the data section is too long
@@ -35,18 +64,33 @@ pub enum EncodingError {
     /// to be for EOF)
     DataSectionNotLast,
     /// Indicates more than one data section
-    MultipleDataSections
+    MultipleDataSections,
+    /// Indicates a jump table (at the given byte offset within its
+    /// code section) which is either empty or too large to be
+    /// encoded.
+    InvalidJumpTable(usize),
+    /// Indicates the number of declared section types does not match
+    /// the number of code sections.
+    InvalidTypeCount(usize),
+    /// Indicates the maximum stack height of the given code section
+    /// could not be determined (i.e. the analysis failed).
+    UnknownStackHeight(usize)
 }
 
 
 impl fmt::Debug for EncodingError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            EncodingError::NoCodeSections => write!(f,"no code sections"),
             EncodingError::TooManyCodeSections(w) => write!(f,"too many code sections ({:#x})",w),
+            EncodingError::StackTooHigh(w) => write!(f,"maximum stack height too large ({:#x})",w),
             EncodingError::CodeSectionTooLong(w) => write!(f,"code section too long ({:#x})",w),
             EncodingError::DataSectionTooLong(w) => write!(f,"data section too long ({:#x})",w),
             EncodingError::DataSectionNotLast => write!(f,"data section is not last"),
-            EncodingError::MultipleDataSections => write!(f,"multiple data sections")
+            EncodingError::MultipleDataSections => write!(f,"multiple data sections"),
+            EncodingError::InvalidJumpTable(pc) => write!(f,"invalid jump table ({pc:#x})"),
+            EncodingError::InvalidTypeCount(n) => write!(f,"invalid number of section types ({n})"),
+            EncodingError::UnknownStackHeight(i) => write!(f,"unknown maximum stack height for code section ({i})")
         }
     }
 }
@@ -58,6 +102,8 @@ impl fmt::Display for EncodingError {
     }
 }
 
+impl std::error::Error for EncodingError {}
+
 // ============================================================================
 // Decoding Error
 // ============================================================================
@@ -89,6 +135,12 @@ pub enum DecodingError {
     /// Indicates the given `type_size` field is not consistent with
     /// the number of code sections (it should be multiple of four).
     InvalidTypeSize(u16),
+    /// Indicates the number of code sections is invalid (i.e. it is
+    /// zero, or exceeds the maximum of `1024`).
+    InvalidCodeSectionCount(u16),
+    /// Indicates the given code section is empty, which is not
+    /// permitted.
+    EmptyCodeSection(usize),
     /// Indicates there were not enough bytes provide to complete
     /// decoding (i.e. the byte sequence is truncated in some way).
     UnexpectedEndOfFile,
@@ -113,6 +165,8 @@ impl fmt::Debug for DecodingError {
             DecodingError::InvalidKindData(w) => write!(f,"invalid kind marker for data section ({:#x})",w),
             DecodingError::InvalidTerminator(w) => write!(f,"invalid terminator for header ({:#x})",w),
             DecodingError::InvalidTypeSize(w) => write!(f,"invalid type section length ({:#x})",w),
+            DecodingError::InvalidCodeSectionCount(w) => write!(f,"invalid number of code sections ({:#x})",w),
+            DecodingError::EmptyCodeSection(i) => write!(f,"empty code section ({i})"),
             DecodingError::UnexpectedEndOfFile => write!(f,"unexpected end-of-bytes"),
             DecodingError::ExpectedEndOfFile => write!(f,"unexpected trailing bytes")
         }
@@ -148,15 +202,22 @@ pub fn from_bytes(bytes: &[u8]) -> Result<Assembly,DecodingError> {
     iter.match_u8(0x01, DecodingError::InvalidKindType)?;
     let type_len = iter.decode_u16()?;
     iter.match_u8(0x02, DecodingError::InvalidKindCode)?;
-    let num_code_sections = iter.decode_u16()? as usize;
+    let num_code_sections = iter.decode_u16()?;
+    // Sanity check number of code sections
+    if num_code_sections == 0 || num_code_sections > MAX_CODE_SECTIONS {
+        return Err(DecodingError::InvalidCodeSectionCount(num_code_sections));
+    }
+    let num_code_sections = num_code_sections as usize;
     // Sanity check length of type section
     if (type_len as usize) != (num_code_sections * 4) {
         return Err(DecodingError::InvalidTypeSize(type_len));
     }
     let mut code_sizes : Vec<usize> = Vec::new();
     // Extract code sizes
-    for _i in 0..num_code_sections {
-        code_sizes.push(iter.decode_u16()? as usize);
+    for i in 0..num_code_sections {
+        let size = iter.decode_u16()? as usize;
+        if size == 0 { return Err(DecodingError::EmptyCodeSection(i)); }
+        code_sizes.push(size);
     }
     iter.match_u8(0x03, DecodingError::InvalidKindData)?;
     let data_size = iter.decode_u16()? as usize;
@@ -167,19 +228,16 @@ pub fn from_bytes(bytes: &[u8]) -> Result<Assembly,DecodingError> {
         let inputs = iter.decode_u8()?;
         let outputs = iter.decode_u8()?;
         let max_stack = iter.decode_u16()?;
-        types.push((inputs,outputs,max_stack));
+        types.push(SectionType{inputs,outputs,max_stack});
     }
-    let mut code = Assembly::new(Vec::new());
+    let mut code = Assembly::new(Vec::new()).with_types(types);
     // parse code section(s)
-    for i in 0..num_code_sections {
-        let bytes = iter.decode_bytes(code_sizes[i])?;
-        // Recall type information
-        let (_inputs,_outputs,_max_stack) = types[i];
+    for size in code_sizes {
+        let bytes = iter.decode_bytes(size)?;
         // Convert byte sequence into an instruction sequence.
-        let insns = bytes.disassemble();
+        let insns = bytes.disassemble_eof();
         // Add code section
         code.add(StructuredSection::Code(insns));
-        // Validate types information?
     }
     // parse data sectin (if present)
     let data = iter.decode_bytes(data_size)?.to_vec();
@@ -194,8 +252,15 @@ pub fn from_bytes(bytes: &[u8]) -> Result<Assembly,DecodingError> {
 // Encoding (EOF)
 // ============================================================================
 
+/// Encode a bytecode container as an EOF formatted byte sequence.  The
+/// inputs and outputs of each code section are taken from the types
+/// declared in the container (or are zero if none are declared),
+/// whilst the maximum stack height of each code section is inferred
+/// (failing if this cannot be determined).
 pub fn to_bytes(bytecode: &Assembly) -> Result<Vec<u8>,EncodingError> {
+    let types = bytecode.types();
     let mut code_sections = Vec::new();
+    let mut code_types = Vec::new();
     let mut data_section : Option<Vec<u8>> = None;
     // Count number of code contracts (to be deprecated?)
     for section in bytecode {
@@ -204,8 +269,17 @@ pub fn to_bytes(bytecode: &Assembly) -> Result<Vec<u8>,EncodingError> {
                 if data_section.is_some() {
                     return Err(EncodingError::DataSectionNotLast)
                 }
-                let code_bytes = insns.assemble();
+                let i = code_sections.len();
+                let code_bytes = insns.try_assemble()?;
                 code_sections.push(code_bytes);
+                // Determine declared type (if any)
+                let (inputs,outputs) = match types {
+                    Some(ts) => ts.get(i).map_or((0,0),|ty| (ty.inputs,ty.outputs)),
+                    None => (0,0)
+                };
+                let max_stack = find_max_stack_height_with(insns,section_state(types,i),&ANALYSIS_CONFIG)
+                    .ok_or(EncodingError::UnknownStackHeight(i))?;
+                code_types.push((inputs,outputs,max_stack));
             }
             StructuredSection::Data(data_bytes) => {
                 if data_section.is_some() {
@@ -216,6 +290,13 @@ pub fn to_bytes(bytecode: &Assembly) -> Result<Vec<u8>,EncodingError> {
             }
         }
     }
+    if code_sections.is_empty() {
+        return Err(EncodingError::NoCodeSections);
+    } else if let Some(ts) = types.filter(|ts| ts.len() != code_sections.len()) {
+        return Err(EncodingError::InvalidTypeCount(ts.len()));
+    } else if code_sections.len() > MAX_CODE_SECTIONS as usize {
+        return Err(EncodingError::TooManyCodeSections(code_sections.len()));
+    }
     let data_len :usize = data_section.as_ref().map_or(0,|s| s.len());
     let mut bytes = ByteEncoder::new();
     // Magic
@@ -247,16 +328,12 @@ pub fn to_bytes(bytecode: &Assembly) -> Result<Vec<u8>,EncodingError> {
     // Header terminator
     bytes.encode_u8(0x00);
     // Write types data
-    for section in bytecode {
-        match section {
-            StructuredSection::Code(_) => {
-                // FIXME: infer necessary information.
-                bytes.encode_u8(0);
-                bytes.encode_u8(0);
-                bytes.encode_u16(0);
-            }
-            _ => {}
-        }
+    for (inputs,outputs,max_stack) in code_types {
+        bytes.encode_u8(inputs);
+        bytes.encode_u8(outputs);
+        bytes.encode_checked_u16(max_stack, |n| {
+            EncodingError::StackTooHigh(n)
+        })?;
     }
     // Write code bytes
     for code_bytes in code_sections {
@@ -267,3 +344,14 @@ pub fn to_bytes(bytecode: &Assembly) -> Result<Vec<u8>,EncodingError> {
     // Done
     Ok(bytes.to_vec())
 }
+
+
+/// Construct the initial state for analysing a given code section of
+/// an EOF container, where `types` are the declared section types (if
+/// known).
+pub(crate) fn section_state(types: Option<&[SectionType]>, section: usize) -> DefaultState {
+    match types {
+        Some(ts) => DefaultState::new().with_section(ts,section),
+        None => DefaultState::new()
+    }
+}
@@ -11,8 +11,11 @@
 // limitations under the License.
 use std::fmt;
 use std::fmt::{Debug};
-use crate::util::{ToHexString};
+use std::str::FromStr;
+use crate::util::{w256,FromHexString,FromWordString,ToHexString};
+use crate::fork::{Fork,PARIS};
 use super::opcode;
+use super::{DecodingError,EncodingError,ParseError};
 
 /// Instructions correspond (roughly speaking) to EVM bytecodes.
 /// There are a few points to make about this:
@@ -43,6 +46,7 @@ use super::opcode;
 /// under the EVM Object Format, it is represented by its own
 /// instruction.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Instruction {
     // ===============================================================
     // 0s: Stop and Arithmetic Operations
@@ -158,6 +162,9 @@ pub enum Instruction {
     GASLIMIT,
     CHAINID,
     SELFBALANCE,
+    BASEFEE, // EIP3198
+    BLOBHASH, // EIP4844
+    BLOBBASEFEE, // EIP7516
     // 50s: Stack, Memory, Storage and Flow Operations
     POP,
     MLOAD,
@@ -173,11 +180,16 @@ pub enum Instruction {
     JUMPDEST,
     TLOAD, // EIP1153
     TSTORE, // EIP1153
-    RJUMP(usize),  // EIP4200
-    RJUMPI(usize), // EIP4200
+    MCOPY, // EIP5656
+    RJUMP(i16),  // EIP4200
+    RJUMPI(i16), // EIP4200
+    RJUMPV(Vec<i16>), // EIP4200
+    CALLF(u16), // EIP4750
+    RETF, // EIP4750
+    JUMPF(u16), // EIP6206
     PUSH0, // EIP3855
     // 60 & 70s: Push Operations
-    PUSH(Vec<u8>),
+    PUSH(#[cfg_attr(feature = "serde", serde(with = "crate::util::serde_hex"))] Vec<u8>),
     // 80s: Duplicate Operations
     DUP(u8),
     // 90s: Exchange Operations
@@ -197,7 +209,7 @@ pub enum Instruction {
     SELFDESTRUCT,
     // Signals arbitrary data in the contract, rather than bytecode
     // instructions.
-    DATA(Vec<u8>),
+    DATA(#[cfg_attr(feature = "serde", serde(with = "crate::util::serde_hex"))] Vec<u8>),
     // (Virtual) Indicates a specific location on the stack should be
     // sent to *havoc*.  Here, `0` represents the top of the stack.
     HAVOC(usize)
@@ -214,6 +226,8 @@ impl Instruction {
             INVALID => false,
             JUMP => false,
             RJUMP(_) => false,
+            RETF => false,
+            JUMPF(_) => false,
             STOP => false,
             RETURN => false,
             REVERT => false,
@@ -225,25 +239,75 @@ impl Instruction {
     /// Determine whether or not this instruction can branch.  That
     /// is, whether or not it is a `JUMP` or `JUMPI` instruction.
     pub fn can_branch(&self) -> bool {
-       matches!(self, JUMP|JUMPI|RJUMP(_)|RJUMPI(_))
+       matches!(self, JUMP|JUMPI|RJUMP(_)|RJUMPI(_)|RJUMPV(_))
+    }
+
+    /// Determine the (intra-procedural) successors of this
+    /// instruction, given its byte offset (`pc`) within the enclosing
+    /// bytecode sequence.  Since the targets of dynamic branches
+    /// (i.e. `JUMP` and `JUMPI`) cannot be determined from the
+    /// instruction alone, these must be supplied (e.g. as resolved
+    /// by a static analysis).  Static branches (i.e. `RJUMP`,
+    /// `RJUMPI` and `RJUMPV`) determine their own target(s) from
+    /// their relative offsets.  Instructions which terminate
+    /// execution have no successors.
+    pub fn successors(&self, pc: usize, targets: &[usize]) -> Vec<usize> {
+        let next = pc + self.length();
+        //
+        match self {
+            JUMP => targets.to_vec(),
+            JUMPI => {
+                let mut succs = vec![next];
+                succs.extend_from_slice(targets);
+                succs.sort_unstable();
+                succs.dedup();
+                succs
+            }
+            RJUMP(offset) => vec![from_rel_offset(next,*offset)],
+            RJUMPI(0) => vec![next],
+            RJUMPI(offset) => vec![next,from_rel_offset(next,*offset)],
+            RJUMPV(offsets) => {
+                let mut succs = vec![next];
+                succs.extend(offsets.iter().map(|o| from_rel_offset(next,*o)));
+                succs.sort_unstable();
+                succs.dedup();
+                succs
+            }
+            _ if self.fallthru() => vec![next],
+            _ => Vec::new()
+        }
     }
     
-    /// Encode an instruction into a byte sequence, assuming a given
-    /// set of label offsets.
-    pub fn encode(&self, pc: usize, bytes: &mut Vec<u8>) {
+    /// Encode an instruction into a byte sequence, given its byte
+    /// offset (`pc`) within the enclosing bytecode sequence.  This
+    /// fails for a jump table which is either empty or has more than
+    /// 256 entries, since its size cannot be encoded.
+    pub fn encode(&self, pc: usize, bytes: &mut Vec<u8>) -> Result<(),EncodingError> {
         // Push operands (if applicable)
         match self {
             DATA(args) => {
                 // Push operands
                 bytes.extend(args);
             }
-            RJUMP(byte_offset)|RJUMPI(byte_offset) => {
-                // Convert absolute byte offset into relative offset.
-                let rel_offset = to_rel_offset(pc,*byte_offset);
+            RJUMP(offset)|RJUMPI(offset) => {
                 // Push opcode
                 bytes.push(self.opcode());
                 // Push operands
-                bytes.extend(&rel_offset.to_be_bytes());
+                bytes.extend(&offset.to_be_bytes());
+            }
+            RJUMPV(offsets) => {
+                // Determine maximum index
+                let max_index = offsets.len().checked_sub(1)
+                    .and_then(|n| u8::try_from(n).ok())
+                    .ok_or(EncodingError::InvalidJumpTable(pc))?;
+                // Push opcode
+                bytes.push(self.opcode());
+                // Push maximum index
+                bytes.push(max_index);
+                // Push operands
+                for offset in offsets {
+                    bytes.extend(&offset.to_be_bytes());
+                }
             }
             PUSH(args) => {
                 // Push opcode
@@ -251,6 +315,12 @@ impl Instruction {
                 // Push operands
                 bytes.extend(args);
             }
+            CALLF(index)|JUMPF(index) => {
+                // Push opcode
+                bytes.push(self.opcode());
+                // Push operands
+                bytes.extend(&index.to_be_bytes());
+            }
             HAVOC(_) => {
                 // Virtial instruction, so ignore
             }
@@ -259,6 +329,7 @@ impl Instruction {
                 bytes.push(self.opcode());
             }
         }
+        Ok(())
     }
 
     /// Determine the length of this instruction (in bytes).
@@ -268,6 +339,9 @@ impl Instruction {
             // Static jumps
             RJUMP(_) => 3,
             RJUMPI(_) => 3,
+            RJUMPV(targets) => 2 + (2 * targets.len()),
+            // Functions
+            CALLF(_)|JUMPF(_) => 3,
             // Push instructions
             PUSH(bs) => 1 + bs.len(),
             // Virtual instructions
@@ -278,6 +352,9 @@ impl Instruction {
     }    
 
     /// Determine how many stack operands this instruction consumes.
+    /// Observe that, for a function call (e.g. `CALLF`), this
+    /// excludes the inputs of the callee since these are determined
+    /// by its type (see `SectionType`).
     pub fn operands(&self) -> usize {
         match self {
             STOP => 0,
@@ -294,12 +371,17 @@ impl Instruction {
             // 40s: Block Information
             BLOCKHASH => 1,
             COINBASE|TIMESTAMP|NUMBER|DIFFICULTY|GASLIMIT|CHAINID|SELFBALANCE => 0,
+            BASEFEE|BLOBBASEFEE => 0,
+            BLOBHASH => 1,
             // 50s: Stack, Memory, Storage and Flow Operations
             MSIZE|PC|GAS|JUMPDEST|RJUMP(_) => 0,
-            MLOAD|SLOAD|JUMP|POP|TLOAD|RJUMPI(_) => 1,            
+            MLOAD|SLOAD|JUMP|POP|TLOAD|RJUMPI(_)|RJUMPV(_) => 1,            
             MSTORE|MSTORE8|SSTORE|JUMPI|TSTORE => 2,
+            MCOPY => 3,
             // 60s & 70s: Push Operations            
             PUSH0|PUSH(_) => 0,
+            // e0s: Functions
+            CALLF(_)|RETF|JUMPF(_) => 0,
             // 80s: Duplication Operations
             DUP(_) => 0,
             // 90s: Swap Operations
@@ -317,7 +399,6 @@ impl Instruction {
             // Virtual instructions
             HAVOC(_) => 0,
             DATA(_) => 0,
-            _ => { unreachable!("{:?}",self); }
         }
     }
     
@@ -383,6 +464,9 @@ impl Instruction {
             GASLIMIT => opcode::GASLIMIT,
             CHAINID => opcode::CHAINID,
             SELFBALANCE => opcode::SELFBALANCE,
+            BASEFEE => opcode::BASEFEE,
+            BLOBHASH => opcode::BLOBHASH,
+            BLOBBASEFEE => opcode::BLOBBASEFEE,
             // 50s: Stack, Memory, Storage and Flow Operations
             POP => opcode::POP,
             MLOAD => opcode::MLOAD,
@@ -398,8 +482,13 @@ impl Instruction {
             JUMPDEST => opcode::JUMPDEST,
 	    TLOAD => opcode::TLOAD,
 	    TSTORE => opcode::TSTORE,
-            // RJUMP(_) => opcode::RJUMP,
-            // RJUMPI(_) => opcode::RJUMPI,
+	    MCOPY => opcode::MCOPY,
+            RJUMP(_) => opcode::RJUMP,
+            RJUMPI(_) => opcode::RJUMPI,
+            RJUMPV(_) => opcode::RJUMPV,
+            CALLF(_) => opcode::CALLF,
+            RETF => opcode::RETF,
+            JUMPF(_) => opcode::JUMPF,
             PUSH0 => opcode::PUSH0,
             // 60s & 70s: Push Operations            
             PUSH(bs) => {
@@ -412,12 +501,12 @@ impl Instruction {
             }
             // 80s: Duplication Operations
             DUP(n) => {
-                if *n == 0 || *n > 32 { panic!("invalid dup"); }
+                if *n == 0 || *n > 16 { panic!("invalid dup"); }
                 opcode::DUP1 + (n-1)
             }
             // 90s: Swap Operations
             SWAP(n) => {
-                if *n == 0 || *n > 32 { panic!("invalid swap"); }
+                if *n == 0 || *n > 16 { panic!("invalid swap"); }
                 opcode::SWAP1 + (n-1)
             }
             // a0s: Log Operations
@@ -443,11 +532,80 @@ impl Instruction {
         }
     }
 
+    /// Decode the next instruction in a given sequence of bytes,
+    /// reporting an error if it is truncated.  In particular, a
+    /// `PUSHn` whose immediate extends beyond the end of the
+    /// sequence is rejected (rather than padded).  This ensures a
+    /// truncated `PUSH1` is never confused with a genuine `PUSH0`.
+    pub fn try_decode(pc: usize, bytes: &[u8]) -> Result<Instruction,DecodingError> {
+        if pc >= bytes.len() {
+            return Err(DecodingError::UnexpectedEndOfFile);
+        }
+        let insn = Instruction::decode(pc,bytes);
+        if pc + insn.length() > bytes.len() {
+            Err(DecodingError::UnexpectedEndOfFile)
+        } else {
+            Ok(insn)
+        }
+    }
+
+    /// Decode the next instruction in a given EOF code section.  This
+    /// differs from `decode()` only in that the relative jumps of
+    /// EIP4200 and the function instructions of EIP4750 (which are
+    /// not valid in legacy bytecode) are recognised.  Observe that,
+    /// as for `decode()`, a truncated immediate is padded with zeros.
+    pub fn decode_eof(pc: usize, bytes: &[u8]) -> Instruction {
+        let opcode = if pc < bytes.len() { bytes[pc] } else { 0x00 };
+        //
+        match opcode {
+            opcode::RJUMP => RJUMP(read_i16(pc+1,bytes)),
+            opcode::RJUMPI => RJUMPI(read_i16(pc+1,bytes)),
+            opcode::RJUMPV => {
+                let n = 1 + read_u8(pc+1,bytes) as usize;
+                let offsets = (0..n).map(|i| read_i16(pc+2+(2*i),bytes)).collect();
+                RJUMPV(offsets)
+            }
+            opcode::CALLF => CALLF(read_u16(pc+1,bytes)),
+            opcode::RETF => RETF,
+            opcode::JUMPF => JUMPF(read_u16(pc+1,bytes)),
+            _ => Instruction::decode(pc,bytes)
+        }
+    }
+
     /// Decode the next instruction in a given sequence of bytes.
+    /// Observe that the immediate of a truncated `PUSHn` is padded
+    /// with zeros (see `try_decode()` for a stricter alternative).
     pub fn decode(pc: usize, bytes: &[u8]) -> Instruction {
         let opcode = if pc < bytes.len() { bytes[pc] } else { 0x00 };
+        // Extract immediate (if applicable)
+        let immediate = match opcode {
+            opcode::PUSH1..=opcode::PUSH32 => {
+                let n = 1 + (opcode - opcode::PUSH1) as usize;
+                // Pad out with zeros (if it does overflow code)
+                (1..=n).map(|i| read_u8(pc+i,bytes)).collect()
+            }
+            _ => Vec::new()
+        };
         //
-        match opcode {
+        match Instruction::from_opcode(opcode,&immediate) {
+            Some(insn) => insn,
+            // Unknown
+            None => DATA(vec![opcode])
+        }
+    }
+
+    /// Construct the instruction for a given opcode and immediate.
+    /// The immediate must have the correct length for the opcode (for
+    /// example, exactly `n` bytes for a `PUSHn`, and none for `ADD`).
+    /// This returns `None` for an undefined opcode, or an immediate
+    /// of the wrong length.  Observe that instructions which are only
+    /// valid in EOF containers (e.g. `RJUMP`) are not recognised here
+    /// (see `decode_eof()`).
+    pub fn from_opcode(opcode: u8, immediate: &[u8]) -> Option<Instruction> {
+        if !matches!(opcode,opcode::PUSH1..=opcode::PUSH32) && !immediate.is_empty() {
+            return None;
+        }
+        let insn = match opcode {
             // 0s: Stop and Arithmetic Operations
             opcode::STOP => STOP,
             opcode::ADD => ADD,
@@ -504,6 +662,9 @@ impl Instruction {
             opcode::GASLIMIT => GASLIMIT,
             opcode::CHAINID => CHAINID,
             opcode::SELFBALANCE => SELFBALANCE,
+            opcode::BASEFEE => BASEFEE,
+            opcode::BLOBHASH => BLOBHASH,
+            opcode::BLOBBASEFEE => BLOBBASEFEE,
             // 50s: Stack, Memory, Storage and Flow Operations
             opcode::POP => POP,
             opcode::MLOAD => MLOAD,
@@ -519,36 +680,13 @@ impl Instruction {
             opcode::JUMPDEST => JUMPDEST,
 	    opcode::TLOAD => TLOAD,
 	    opcode::TSTORE => TSTORE,
-            // opcode::RJUMP => {
-            //     // NOTE: these instructions are not permitted to
-            //     // overflow, and therefore don't require padding.
-            //     let arg = [bytes[pc+1],bytes[pc+2]];
-            //     RJUMP(i16::from_be_bytes(arg))
-            // }
-            // opcode::RJUMPI => {
-            //     // NOTE: these instructions are not permitted to
-            //     // overflow, and therefore don't require padding.
-            //     let arg = [bytes[pc+1],bytes[pc+2]];
-            //     RJUMPI(i16::from_be_bytes(arg))
-            // }
+	    opcode::MCOPY => MCOPY,
             opcode::PUSH0 => PUSH0,
             // 60s & 70s: Push Operations
             opcode::PUSH1..=opcode::PUSH32 => {
-                let m = pc + 1;
-                let n = pc + 2 + ((opcode - opcode::PUSH1) as usize);
-                if n <= bytes.len() {
-                    // Simple case: does not overflow
-                    PUSH(bytes[m..n].to_vec())
-                } else {
-                    // Harder case: does overflow code.
-                    let mut bs = bytes[m..].to_vec();
-                    // Pad out with zeros
-                    for _i in 0..(n - bytes.len()) {
-                        bs.push(0);
-                    }
-                    // Done
-                    PUSH(bs)
-                }
+                let n = 1 + (opcode - opcode::PUSH1) as usize;
+                if immediate.len() != n { return None; }
+                PUSH(immediate.to_vec())
             }
             // 80s: Duplicate Operations
             opcode::DUP1..=opcode::DUP16 => DUP(opcode - 0x7f),
@@ -568,8 +706,9 @@ impl Instruction {
             opcode::INVALID => INVALID,
             opcode::SELFDESTRUCT => SELFDESTRUCT,
             // Unknown
-            _ => DATA(vec![opcode]),
-        }
+            _ => { return None; }
+        };
+        Some(insn)
     }    
 }
 
@@ -603,6 +742,16 @@ impl fmt::Display for Instruction {
             RJUMPI(offset) => {
                 write!(f, "rjumpi {offset}")
             }
+            CALLF(index) => {
+                write!(f, "callf {index}")
+            }
+            JUMPF(index) => {
+                write!(f, "jumpf {index}")
+            }
+            RJUMPV(offsets) => {
+                let offsets : Vec<String> = offsets.iter().map(|o| o.to_string()).collect();
+                write!(f, "rjumpv {}", offsets.join(","))
+            }
             SWAP(n) => {
                 write!(f, "swap{n}")
             }
@@ -617,6 +766,133 @@ impl fmt::Display for Instruction {
     }
 }
 
+impl Instruction {
+    /// Display this instruction as it would be written for a given
+    /// fork.  This differs from the default (fork-agnostic) display
+    /// only for opcodes which were renamed by a fork.  For example,
+    /// `DIFFICULTY` is shown as `prevrandao` from Paris onwards.
+    pub fn display<'a>(&'a self, fork: &'a Fork) -> impl fmt::Display + 'a {
+        ForkDisplay{insn: self, fork}
+    }
+}
+
+/// Helper for displaying an instruction in the context of a given
+/// fork.
+struct ForkDisplay<'a> {
+    insn: &'a Instruction,
+    fork: &'a Fork
+}
+
+impl<'a> fmt::Display for ForkDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.insn {
+            DIFFICULTY if *self.fork >= PARIS => write!(f, "prevrandao"),
+            insn => write!(f, "{insn}")
+        }
+    }
+}
+
+
+// ============================================================================
+// Mnemonics
+// ============================================================================
+
+/// Parse an instruction from its mnemonic and (where applicable) its
+/// operand, as for `Display` (e.g. `"push 0x20"` or `"PUSH1 32"`).
+/// Mnemonics are case insensitive, and operands can be given in
+/// decimal or hex.  The width of a `push` is determined by its
+/// operand, whilst that of a sized push (e.g. `push2`) is fixed and
+/// the operand is padded accordingly.
+impl FromStr for Instruction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self,Self::Err> {
+        let mut parts = s.split_whitespace();
+        let mnemonic = parts.next().ok_or(ParseError::InvalidInstruction)?;
+        let operand = parts.next();
+        if parts.next().is_some() { return Err(ParseError::UnexpectedToken); }
+        // Operand (if any) is last
+        let offset = operand.map_or(0,|op| s.trim_end().len() - op.len());
+        from_mnemonic(mnemonic,operand,offset)
+    }
+}
+
+/// Construct an instruction from its mnemonic and (where applicable)
+/// its operand, as for `FromStr`.  Here, `offset` gives the position
+/// of the operand within the input, as reported by any error.  This
+/// is shared with the assembly language parser.
+pub(crate) fn from_mnemonic(mnemonic: &str, operand: Option<&str>, offset: usize) -> Result<Instruction,ParseError> {
+    let mnemonic = mnemonic.to_lowercase();
+    //
+    let insn = match (mnemonic.as_str(),operand) {
+        ("push",Some(op)) => PUSH(parse_operand(op,offset)?),
+        (m,Some(op)) if push_width(m).is_some() => {
+            let width = push_width(m).unwrap();
+            let bytes = parse_operand(op,offset)?;
+            if bytes.len() > width { return Err(ParseError::PushOperandTooWide(offset)); }
+            let mut padded = vec![0; width - bytes.len()];
+            padded.extend(bytes);
+            PUSH(padded)
+        }
+        ("db",Some(op)) => DATA(op.from_hex_string().map_err(|_| ParseError::InvalidLiteralString(offset))?),
+        ("havoc",Some(op)) => HAVOC(parse_index(op,offset)?),
+        ("rjump",Some(op)) => RJUMP(parse_index(op,offset)?),
+        ("rjumpi",Some(op)) => RJUMPI(parse_index(op,offset)?),
+        ("rjumpv",Some(op)) => RJUMPV(op.split(',').map(|o| parse_index(o,offset)).collect::<Result<_,_>>()?),
+        ("callf",Some(op)) => CALLF(parse_index(op,offset)?),
+        ("jumpf",Some(op)) => JUMPF(parse_index(op,offset)?),
+        ("push"|"db"|"havoc"|"rjump"|"rjumpi"|"rjumpv"|"callf"|"jumpf",None) => {
+            return Err(ParseError::ExpectedOperand);
+        }
+        (m,None) if push_width(m).is_some() => { return Err(ParseError::ExpectedOperand); }
+        ("prevrandao",None) => DIFFICULTY,
+        ("retf",None) => RETF,
+        (m,None) => {
+            let op = opcode::MNEMONICS.iter().position(|n| n.as_deref() == Some(m)).ok_or(ParseError::InvalidInstruction)?;
+            Instruction::from_opcode(op as u8,&[]).ok_or(ParseError::InvalidInstruction)?
+        }
+        (m,Some(_)) if m == "prevrandao" || opcode::MNEMONICS.iter().any(|n| n.as_deref() == Some(m)) => {
+            return Err(ParseError::UnexpectedToken);
+        }
+        (_,Some(_)) => { return Err(ParseError::InvalidInstruction); }
+    };
+    Ok(insn)
+}
+
+/// Determine the width of a sized push mnemonic (e.g. `push2` or
+/// `PUSH2`), or `None` if the mnemonic is not a sized push.
+pub(crate) fn push_width(mnemonic: &str) -> Option<usize> {
+    let n = mnemonic.strip_prefix("push").or(mnemonic.strip_prefix("PUSH"))?;
+    match n.parse() {
+        Ok(w) if (1..=32).contains(&w) && !n.starts_with('0') => Some(w),
+        _ => None
+    }
+}
+
+/// Parse a `push` operand (at a given offset) given in hex (where
+/// leading zeros determine its width) or decimal (where the minimal
+/// width is used).
+fn parse_operand(operand: &str, offset: usize) -> Result<Vec<u8>,ParseError> {
+    let bytes = if operand.starts_with("0x") {
+        operand.from_hex_string().ok()
+    } else {
+        match w256::from_dec_str(operand) {
+            Ok(w) if w == w256::ZERO => Some(vec![0]),
+            Ok(w) => Some(w.to_be_bytes_trimmed_vec()),
+            Err(_) => None
+        }
+    };
+    match bytes {
+        Some(bytes) if !bytes.is_empty() && bytes.len() <= 32 => Ok(bytes),
+        _ => Err(ParseError::InvalidLiteralString(offset))
+    }
+}
+
+/// Parse a (decimal) index operand (at a given offset), such as a
+/// jump offset or section.
+fn parse_index<T:FromStr>(operand: &str, offset: usize) -> Result<T,ParseError> {
+    operand.parse().map_err(|_| ParseError::InvalidLiteralString(offset))
+}
 
 // ============================================================================
 // Disassemble
@@ -626,6 +902,18 @@ impl fmt::Display for Instruction {
 /// vector of instructions.
 pub trait Disassemble {
     fn disassemble(&self) -> Vec<Instruction>;
+
+    /// Disassemble a byte sequence which may be truncated (e.g. ends
+    /// part way through a `PUSH` immediate).  Rather than padding
+    /// the final instruction, any truncated instruction is returned
+    /// as a `DATA` remainder along with the number of bytes which
+    /// could not be decoded.
+    fn disassemble_partial(&self) -> (Vec<Instruction>,usize);
+
+    /// Disassemble the contents of an EOF code section.  In
+    /// particular, the relative jumps of EIP4200 are decoded (see
+    /// `Instruction::decode_eof()`).
+    fn disassemble_eof(&self) -> Vec<Instruction>;
 }
 
 impl Disassemble for [u8] {
@@ -642,6 +930,42 @@ impl Disassemble for [u8] {
         // Done
         insns
     }
+
+    fn disassemble_partial(&self) -> (Vec<Instruction>,usize) {
+        let mut insns = Vec::new();
+        let mut byte_offset = 0;
+        //
+        while byte_offset < self.len() {
+            match Instruction::try_decode(byte_offset,self) {
+                Ok(insn) => {
+                    byte_offset += insn.length();
+                    insns.push(insn);
+                }
+                Err(_) => {
+                    // Instruction is truncated
+                    let remainder = self[byte_offset..].to_vec();
+                    let n = remainder.len();
+                    insns.push(Instruction::DATA(remainder));
+                    return (insns,n);
+                }
+            }
+        }
+        // Done
+        (insns,0)
+    }
+
+    fn disassemble_eof(&self) -> Vec<Instruction> {
+        let mut insns = Vec::new();
+        let mut byte_offset = 0;
+        //
+        while byte_offset < self.len() {
+            let insn = Instruction::decode_eof(byte_offset,self);
+            byte_offset += insn.length();
+            insns.push(insn);
+        }
+        // Done
+        insns
+    }
 }
 
 // ============================================================================
@@ -651,21 +975,28 @@ impl Disassemble for [u8] {
 /// A trait for converting zero or more instructions into vector of
 /// bytes.
 pub trait Assemble {
-    fn assemble(&self) -> Vec<u8>;
+    /// Encode the instructions, panicking if any cannot be encoded.
+    fn assemble(&self) -> Vec<u8> {
+        self.try_assemble().unwrap()
+    }
+
+    /// Encode the instructions, failing if any cannot be encoded
+    /// (e.g. an empty jump table).
+    fn try_assemble(&self) -> Result<Vec<u8>,EncodingError>;
 }
 
 impl Assemble for [Instruction] {
-    fn assemble(&self) -> Vec<u8> {
+    fn try_assemble(&self) -> Result<Vec<u8>,EncodingError> {
         // Encode instructions
         let mut bytes : Vec<u8> = Vec::new();
         let mut pc = 0;
         //        
         for i in self {
-            i.encode(pc, &mut bytes);
+            i.encode(pc, &mut bytes)?;
             pc += i.length();
         }
         // Done
-        bytes
+        Ok(bytes)
     }
 }
 
@@ -673,14 +1004,32 @@ impl Assemble for [Instruction] {
 // Utilities
 // ============================================================================
 
-/// Calculate the relative offset for a given branch target expressed
-/// as an _abstolute byte offset_ from the program counter position
-/// where the instruction in question is being instantiated.
-fn to_rel_offset(pc: usize, target: usize) -> i16 {
-    let mut n = target as isize;
-    n -= pc as isize;
-    // Following should always be true!
-    n as i16
+/// Calculate the absolute byte offset for a given relative offset.
+/// Following EIP4200, the offset is relative to the program counter
+/// position immediately following the instruction in question
+/// (i.e. `pc` here).  A target before the start of the code section
+/// wraps around (i.e. it lies beyond the end of the section), such
+/// that it is rejected by validation.
+pub(crate) fn from_rel_offset(pc: usize, offset: i16) -> usize {
+    pc.wrapping_add_signed(offset as isize)
+}
+
+/// Read a byte from a given sequence, padding with zero if it is
+/// beyond the end.
+fn read_u8(pc: usize, bytes: &[u8]) -> u8 {
+    if pc < bytes.len() { bytes[pc] } else { 0 }
+}
+
+/// Read a signed (big endian) 16bit value from a given sequence,
+/// padding with zeros beyond the end.
+fn read_i16(pc: usize, bytes: &[u8]) -> i16 {
+    i16::from_be_bytes([read_u8(pc,bytes),read_u8(pc+1,bytes)])
+}
+
+/// Read an unsigned (big endian) 16bit value from a given sequence,
+/// padding with zeros beyond the end.
+fn read_u16(pc: usize, bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([read_u8(pc,bytes),read_u8(pc+1,bytes)])
 }
 
 /// Calculate the variable bytes for an absolute branch target.
@@ -0,0 +1,47 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::opcode;
+
+/// Determine the byte offsets of all valid jump destinations within a
+/// given (legacy) bytecode sequence, in ascending order.  As for
+/// `jumpdest_bitmap()`, a `0x5b` byte occurring within the operand of
+/// a `PUSH` instruction is not a valid jump destination.
+pub fn jumpdests(bytes: &[u8]) -> Vec<usize> {
+    let bitmap = jumpdest_bitmap(bytes);
+    (0..bitmap.len()).filter(|pc| bitmap[*pc]).collect()
+}
+
+/// Determine the set of valid jump destinations within a given
+/// (legacy) bytecode sequence.  This is returned as a bitmap where,
+/// for each byte offset, `true` indicates a `JUMPDEST` instruction.
+/// Observe that a `0x5b` byte occurring within the operand of a
+/// `PUSH` instruction is not a valid jump destination.  This is
+/// computed in a single linear pass without disassembling the
+/// bytecode.
+pub fn jumpdest_bitmap(bytes: &[u8]) -> Vec<bool> {
+    let mut bitmap = vec![false; bytes.len()];
+    let mut pc = 0;
+    //
+    while pc < bytes.len() {
+        match bytes[pc] {
+            opcode::JUMPDEST => { bitmap[pc] = true; }
+            op @ opcode::PUSH1..=opcode::PUSH32 => {
+                // Skip over operand bytes
+                pc += (op - opcode::PUSH1 + 1) as usize;
+            }
+            _ => {}
+        }
+        pc += 1;
+    }
+    //
+    bitmap
+}
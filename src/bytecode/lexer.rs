@@ -61,6 +61,11 @@ impl<'a> Lexer<'a> {
         Self{input, chars, index: 0}
     }
 
+    /// Determine the offset of the next token within the input.
+    pub fn offset(&self) -> usize {
+        self.skip_whitespace(self.index)
+    }
+
     pub fn lookahead(&self) -> Result<Token<'a>,ParseError> {
         // Skip any whitespace
         let start = self.skip_whitespace(self.index);
@@ -138,8 +143,8 @@ impl<'a> Lexer<'a> {
 
     fn skip_whitespace(&self, mut index: usize) -> usize {
         index = skip(&self.chars, index, |c| c.is_ascii_whitespace());
-        // Check for a comment
-        if self.chars[index..].starts_with(&[';']) {
+        // Check for a comment (either `;` or `//` style)
+        if self.chars[index..].starts_with(&[';']) || self.chars[index..].starts_with(&['/','/']) {
             // Skip to newline
             index = skip(&self.chars, index, |c| c != '\n');
             // Recursive call to handle trainling whitespace
@@ -0,0 +1,113 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#[cfg(feature = "keccak")]
+use crate::util::{keccak256,w256};
+
+/// The keys which can appear in the metadata appended by compilers
+/// such as `solc` (e.g. `ipfs` identifies the hash of the metadata
+/// file stored on IPFS).
+const METADATA_KEYS : [&[u8];5] = [b"ipfs",b"bzzr0",b"bzzr1",b"solc",b"experimental"];
+
+/// Strip any trailing (CBOR-encoded) compiler metadata from a given
+/// runtime bytecode sequence.  Compilers such as `solc` append a CBOR
+/// map to the runtime bytecode, followed by its length as a two byte
+/// (big endian) integer.  Since this metadata includes (for example)
+/// a hash of the source code, it frequently differs between otherwise
+/// identical contracts.  If no metadata is found, then the bytes are
+/// returned unchanged.
+pub fn strip_metadata(bytes: &[u8]) -> &[u8] {
+    split_metadata(bytes).0
+}
+
+/// Split a given runtime bytecode sequence into its code and any
+/// trailing (CBOR-encoded) compiler metadata, including the two byte
+/// length suffix.  For example, `solc` typically appends metadata of
+/// the form `a2 64 'i' 'p' 'f' 's' ... 64 's' 'o' 'l' 'c' ... 00 33`.
+/// The metadata is recognised by checking that the length suffix
+/// identifies a CBOR map which occupies exactly that many bytes, and
+/// whose keys are all known (e.g. `ipfs` or `solc`).  If no metadata
+/// is found, then the metadata returned is empty.
+pub fn split_metadata(bytes: &[u8]) -> (&[u8],&[u8]) {
+    let n = bytes.len();
+    if n < 2 {
+        return (bytes,&[]);
+    }
+    let len = u16::from_be_bytes([bytes[n-2],bytes[n-1]]) as usize;
+    // Check length is sensible, and that the metadata is a CBOR map
+    // of the expected form.
+    if len + 2 > n || !is_metadata_map(&bytes[n-2-len..n-2]) {
+        (bytes,&[])
+    } else {
+        bytes.split_at(n - 2 - len)
+    }
+}
+
+/// Check whether a given byte sequence is exactly one CBOR map (i.e.
+/// major type 5), where each key is a known text string and each
+/// value is either a byte string, a text string or a boolean.
+fn is_metadata_map(bytes: &[u8]) -> bool {
+    let mut i = 0;
+    let Some(n) = decode_cbor_header(bytes,&mut i,5) else { return false; };
+    for _ in 0..n {
+        // Decode key
+        let Some(len) = decode_cbor_header(bytes,&mut i,3) else { return false; };
+        match bytes.get(i..i+len) {
+            Some(key) if METADATA_KEYS.contains(&key) => { i += len; }
+            _ => { return false; }
+        }
+        // Decode value
+        if matches!(bytes.get(i),Some(0xf4|0xf5)) {
+            i += 1;
+        } else {
+            let Some(len) = decode_cbor_header(bytes,&mut i,2).or_else(|| decode_cbor_header(bytes,&mut i,3)) else { return false; };
+            i += len;
+        }
+    }
+    i == bytes.len()
+}
+
+/// Decode the header of a CBOR data item of a given major type at a
+/// given position, returning its argument (e.g. the length of a
+/// string, or the number of entries in a map) and advancing the
+/// position beyond the header.  Only arguments of up to two bytes are
+/// supported, which suffices for compiler metadata.
+fn decode_cbor_header(bytes: &[u8], i: &mut usize, major: u8) -> Option<usize> {
+    let b = *bytes.get(*i)?;
+    if (b >> 5) != major { return None; }
+    let (arg,width) = match b & 0x1f {
+        n @ 0..=23 => (n as usize,0),
+        24 => (*bytes.get(*i+1)? as usize,1),
+        25 => (u16::from_be_bytes([*bytes.get(*i+1)?,*bytes.get(*i+2)?]) as usize,2),
+        _ => { return None; }
+    };
+    *i += 1 + width;
+    Some(arg)
+}
+
+/// Compute the _code hash_ of a given runtime bytecode sequence.
+/// This matches that returned by `EXTCODEHASH` for an account
+/// holding this code and, hence, can be used to identify identical
+/// contracts.
+#[cfg(feature = "keccak")]
+pub fn code_hash(bytes: &[u8]) -> w256 {
+    keccak256(bytes)
+}
+
+/// Compute the _code hash_ of a given runtime bytecode sequence after
+/// first stripping any compiler metadata.  This allows contracts
+/// which differ only in their metadata (e.g. because they were
+/// compiled from differently formatted source files) to be
+/// identified.
+#[cfg(feature = "keccak")]
+pub fn stripped_code_hash(bytes: &[u8]) -> w256 {
+    keccak256(strip_metadata(bytes))
+}
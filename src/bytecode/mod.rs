@@ -12,17 +12,25 @@
 mod assembly;
 mod block_vec;
 mod builder;
+mod disassembler;
 mod eof;
 mod instruction;
 mod iterator;
+mod jumpdest;
 mod legacy;
 mod lexer;
+mod metadata;
 pub mod opcode;
 mod parser;
+mod size;
 
 pub use assembly::*;
 pub use block_vec::*;
 pub use builder::*;
+pub use disassembler::*;
 pub use instruction::*;
 pub use iterator::*;
+pub use jumpdest::*;
+pub use metadata::*;
+pub use size::*;
 pub use parser::ParseError;
@@ -9,6 +9,8 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::sync::LazyLock;
+use super::Instruction;
 
 // 0s: Stop and Arithmetic Operations
 pub const STOP: u8 = 0x0;
@@ -66,6 +68,9 @@ pub const DIFFICULTY: u8 = 0x44;
 pub const GASLIMIT: u8 = 0x45;
 pub const CHAINID: u8 = 0x46;
 pub const SELFBALANCE: u8 = 0x47;
+pub const BASEFEE: u8 = 0x48;
+pub const BLOBHASH: u8 = 0x49;
+pub const BLOBBASEFEE: u8 = 0x4a;
 // 50s: Stack, Memory Storage and Flow Operations
 pub const POP: u8 = 0x50;
 pub const MLOAD: u8 = 0x51;
@@ -81,8 +86,7 @@ pub const GAS: u8 = 0x5a;
 pub const JUMPDEST: u8 = 0x5b;
 pub const TLOAD: u8 = 0x5c;
 pub const TSTORE: u8 = 0x5d;
-// pub const RJUMP: u8 = 0x5c;
-// pub const RJUMPI: u8 = 0x5d;
+pub const MCOPY: u8 = 0x5e;
 pub const PUSH0: u8 = 0x5f;
 // 60s & 70s: Push Operations
 pub const PUSH1: u8 = 0x60;
@@ -157,7 +161,14 @@ pub const LOG1: u8 = 0xa1;
 pub const LOG2: u8 = 0xa2;
 pub const LOG3: u8 = 0xa3;
 pub const LOG4: u8 = 0xa4;
-// e0s
+// e0s: Relative Jumps (EIP4200)
+pub const RJUMP: u8 = 0xe0;
+pub const RJUMPI: u8 = 0xe1;
+pub const RJUMPV: u8 = 0xe2;
+// e3-e5: Functions (EIP4750 & EIP6206)
+pub const CALLF: u8 = 0xe3;
+pub const RETF: u8 = 0xe4;
+pub const JUMPF: u8 = 0xe5;
 pub const EOF: u8 = 0xef;
 // f0s: System operations
 pub const CREATE: u8 = 0xf0;
@@ -170,3 +181,35 @@ pub const STATICCALL: u8 = 0xfa;
 pub const REVERT: u8 = 0xfd;
 pub const INVALID: u8 = 0xfe;
 pub const SELFDESTRUCT: u8 = 0xff;
+
+// ============================================================================
+// Mnemonics
+// ============================================================================
+
+/// Maps each opcode to its (lowercase) mnemonic, or `None` for an
+/// undefined opcode.  This is derived from the instruction each
+/// opcode decodes into (see `Instruction::from_opcode()`), such that
+/// the two cannot disagree.
+pub static MNEMONICS: LazyLock<[Option<String>;256]> = LazyLock::new(|| {
+    std::array::from_fn(|op| derive_mnemonic(op as u8))
+});
+
+/// Determine the mnemonic of a given opcode (e.g. `"push1"` for
+/// `0x60`), or `None` if the opcode is undefined.
+pub fn mnemonic(opcode: u8) -> Option<&'static str> {
+    MNEMONICS[opcode as usize].as_deref()
+}
+
+/// Determine the mnemonic of a given opcode from the instruction it
+/// decodes into (as an EOF code section, so that the relative jumps
+/// and function instructions are included).  Enough zeros follow the
+/// opcode for any immediate.
+fn derive_mnemonic(opcode: u8) -> Option<String> {
+    let mut bytes = vec![0; 34];
+    bytes[0] = opcode;
+    match Instruction::decode_eof(0,&bytes) {
+        Instruction::DATA(_) => None,
+        Instruction::PUSH(imm) => Some(format!("push{}",imm.len())),
+        insn => insn.to_string().split(' ').next().map(str::to_string)
+    }
+}
@@ -12,9 +12,9 @@
 use std::fmt;
 use super::lexer::{Lexer,Token};
 use super::Builder;
+use super::instruction::{from_mnemonic,push_width};
 use crate::bytecode::{Assembly,Instruction,StructuredSection};
-use crate::bytecode::Instruction::*;
-use crate::util::{FromHexString};
+use crate::util::FromHexString;
 
 // ===================================================================
 // Parse Error
@@ -54,7 +54,11 @@ pub enum ParseError {
     InvalidRelativeOffset,
     /// When assembling a given assembly, the distance of a calculated
     /// offset exceeds the maximum permitted code size.
-    OffsetTooLarge
+    OffsetTooLarge,
+    /// When parsing some assembly language, the operand of a sized
+    /// push (e.g. `push1`) at the given offset was found not to fit
+    /// within its width.
+    PushOperandTooWide(usize)
 }
 
 impl fmt::Display for ParseError {
@@ -74,7 +78,10 @@ impl std::error::Error for ParseError {
 /// A simple assembly language parser.
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
-    assembly: Assembly
+    assembly: Assembly,
+    /// Indicates whether labelled pushes should use the smallest
+    /// width which fits (rather than always two bytes).
+    compact: bool
 }
 
 impl<'a> Parser<'a> {
@@ -82,11 +89,23 @@ impl<'a> Parser<'a> {
         let lexer = Lexer::new(input);
         let assembly = Assembly::new(vec![]);
         //
-        Self{lexer,assembly}
+        Self{lexer,assembly,compact:false}
     }
 
-    /// Parse assembly language to form an assembly    
+    /// Configure whether labelled pushes should use the smallest
+    /// width which fits their target.
+    pub fn compact(mut self, flag: bool) -> Self {
+        self.compact = flag;
+        self
+    }
+
+    /// Parse assembly language to form an assembly.  Input which
+    /// does not begin with a section header is treated as a single
+    /// code section.
     pub fn parse(mut self) -> Result<Assembly,ParseError> {
+        if !matches!(self.lexer.lookahead()?,Token::EOF|Token::Section(_)) {
+            self.parse_code_section()?;
+        }
         // Keep going until we reach the end.
         while self.lexer.lookahead()? != Token::EOF {
             self.parse_section()?;
@@ -113,17 +132,24 @@ impl<'a> Parser<'a> {
 
     fn parse_code_section(&mut self) -> Result<(),ParseError> {
         let mut builder = Builder::new();
+        // Input offset of each sized push of a label (by instruction)
+        let mut sized = Vec::new();
         loop {
             match self.lexer.lookahead()? {
-                Token::Identifier("havoc"|"HAVOC") => {
+                Token::Identifier(id@("havoc"|"HAVOC")) => {
                     _ = self.lexer.next();
+                    let offset = self.lexer.offset();
                     let operand = self.lexer.next()?;
-                    parse_havoc(&mut builder,operand)?;
+                    builder.push(parse_operand(id,operand,offset)?);
                 }                
-                Token::Identifier("push"|"PUSH") => {
+                Token::Identifier(id) if matches!(id,"push"|"PUSH") || push_width(id).is_some() => {
                     _ = self.lexer.next();
-                    let operand = self.lexer.next()?;                    
-                    parse_push(&mut builder,operand)?;
+                    let offset = self.lexer.offset();
+                    let operand = self.lexer.next()?;
+                    if matches!(operand,Token::Identifier(_)) && push_width(id).is_some() {
+                        sized.push((builder.len(),offset));
+                    }
+                    parse_push(&mut builder,id,operand,offset)?;
                 }
                 Token::Identifier("rjump"|"RJUMP") => {
                     _ = self.lexer.next();
@@ -133,13 +159,15 @@ impl<'a> Parser<'a> {
                     _ = self.lexer.next();
                     builder.push(parse_rjumpi(self.lexer.next()?)?);
                 }
-                Token::Identifier("db"|"DB") => {
+                Token::Identifier(id@("db"|"DB")) => {
                     _ = self.lexer.next();
-                    builder.push(parse_data(self.lexer.next()?)?);
+                    let offset = self.lexer.offset();
+                    let operand = self.lexer.next()?;
+                    builder.push(parse_operand(id,operand,offset)?);
                 }                
                 Token::Identifier(id) => {
                     _ = self.lexer.next();
-                    builder.push(parse_opcode(id)?);
+                    builder.push(from_mnemonic(id,None,0)?);
                 }
                 Token::Label(s) => {
                     _ = self.lexer.next();
@@ -154,7 +182,12 @@ impl<'a> Parser<'a> {
                 }
                 Token::EOF|Token::Section(_) => {
                     // Construct a code section
-                    self.assembly.add(StructuredSection::Code(builder.to_insns()));
+                    let insns = if self.compact { builder.try_to_compact_insns() } else { builder.try_to_insns() };
+                    let insns = insns.map_err(|i| {
+                        let (_,offset) = sized.iter().find(|(j,_)| *j == i).unwrap();
+                        ParseError::PushOperandTooWide(*offset)
+                    })?;
+                    self.assembly.add(StructuredSection::Code(insns));
                     // Done
                     return Ok(());
                 }
@@ -187,35 +220,27 @@ impl<'a> Parser<'a> {
     }
 }
 
-/// Parse a push instruction with a given operand.
-fn parse_push(builder: &mut Builder, operand: Token) -> Result<(),ParseError> {
+/// Parse a push instruction (e.g. `push` or `push2`) with a given
+/// operand (at a given offset in the input).  A label is patched once
+/// its offset is known, where a sized push must fit its width.
+fn parse_push(builder: &mut Builder, mnemonic: &str, operand: Token, offset: usize) -> Result<(),ParseError> {
     // Push always expects an argument, though it could be a
     // label or a hexadecimal operand.
     match operand {
-        Token::Hex(s) => {
-            builder.push(PUSH(parse_hex(s)?));
-            Ok(())
-        }
         Token::Identifier(s) => {
             // Determine label index
             let index = builder.get_label(s);
-            // PUsh instruction
-            builder.push_labeled(PUSH(label_bytes(index)));
+            // Push instruction
+            match push_width(mnemonic) {
+                Some(width) => builder.push_sized_label(index,width),
+                None => builder.push_label(index)
+            }
             Ok(())
         }
-        Token::EOF => Err(ParseError::ExpectedOperand),
-        _ => Err(ParseError::UnexpectedToken)
-    }
-}
-
-fn parse_havoc(builder: &mut Builder, operand: Token) -> Result<(),ParseError> {
-    match operand {
-        Token::Num(s) => {
-            builder.push(HAVOC(parse_num(s)?));
+        _ => {
+            builder.push(parse_operand(mnemonic,operand,offset)?);
             Ok(())
         }
-        Token::EOF => Err(ParseError::ExpectedOperand),
-        _ => Err(ParseError::UnexpectedToken)        
     }
 }
 
@@ -243,9 +268,11 @@ fn parse_rjumpi(operand: Token) -> Result<Instruction,ParseError> {
     }
 }
 
-fn parse_data(operand: Token) -> Result<Instruction,ParseError> {
+/// Parse an instruction with a given (literal) operand, at a given
+/// offset in the input.
+fn parse_operand(mnemonic: &str, operand: Token, offset: usize) -> Result<Instruction,ParseError> {
     match operand {
-        Token::Hex(s) => Ok(DATA(parse_hex(s)?)),
+        Token::Hex(s)|Token::Num(s) => from_mnemonic(mnemonic,Some(s),offset),
         Token::EOF => Err(ParseError::ExpectedOperand),
         _ => Err(ParseError::UnexpectedToken)
     }
@@ -262,158 +289,3 @@ fn parse_hex(hex: &str) -> Result<Vec<u8>,ParseError> {
         Err(_e) => Err(ParseError::InvalidLiteralString(0))
     }
 }
-
-/// Parse a (stack or memory) location index
-fn parse_num(num: &str) -> Result<usize,ParseError> {
-    match num.parse() {
-        Ok(val) => { Ok(val) }
-        Err(_e) => Err(ParseError::InvalidLiteralString(0))
-    }
-}
-
-/// Parse a given opcode from a string, and a given number of operand
-/// bytes.
-fn parse_opcode(insn: &str) -> Result<Instruction,ParseError> {
-    let insn = match insn {
-        // 0s: Stop and Arithmetic Operations
-        "stop"|"STOP" => STOP,
-        "add"|"ADD" => ADD,
-        "mul"|"MUL" => MUL,
-        "sub"|"SUB" => SUB,
-        "div"|"DIV" => DIV,
-        "sdiv"|"SDIV" => SDIV,
-        "mod"|"MOD" => MOD,
-        "smod"|"SMOD" => SMOD,
-        "addmod"|"ADDMOD" => ADDMOD,
-        "mulmod"|"MULMOD" => MULMOD,
-        "exp"|"EXP" => EXP,
-        "signextend"|"SIGNEXTEND" => SIGNEXTEND,
-        // 10s: Comparison & Bitwise Logic Operations
-        "lt"|"LT" => LT,
-        "gt"|"GT" => GT,
-        "slt"|"SLT" => SLT,
-        "sgt"|"SGT" => SGT,
-        "eq"|"EQ" => EQ,
-        "iszero"|"ISZERO" => ISZERO,
-        "and"|"AND" => AND,
-        "or"|"OR" => OR,
-        "xor"|"XOR" => XOR,
-        "not"|"NOT" => NOT,
-        "byte"|"BYTE" => BYTE,
-        "shl"|"SHL" => SHL,
-        "shr"|"SHR" => SHR,
-        "sar"|"SAR" => SAR,
-        // 20s: Keccak256
-        "keccak256"|"KECCAK256" => KECCAK256,
-        // 30s: Environmental Information
-        "address"|"ADDRESS" => ADDRESS,
-        "balance"|"BALANCE" => BALANCE,
-        "origin"|"ORIGIN" => ORIGIN,
-        "caller"|"CALLER" => CALLER,
-        "callvalue"|"CALLVALUE" => CALLVALUE,
-        "calldataload"|"CALLDATALOAD" => CALLDATALOAD,
-        "calldatasize"|"CALLDATASIZE" => CALLDATASIZE,
-        "calldatacopy"|"CALLDATACOPY" => CALLDATACOPY,
-        "codesize"|"CODESIZE" => CODESIZE,
-        "codecopy"|"CODECOPY" => CODECOPY,
-        "gasprice"|"GASPRICE" => GASPRICE,
-        "extcodesize"|"EXTCODESIZE" => EXTCODESIZE,
-        "extcodecopy"|"EXTCODECOPY" => EXTCODECOPY,
-        "returndatasize"|"RETURNDATASIZE" => RETURNDATASIZE,
-        "returndatacopy"|"RETURNDATACOPY" => RETURNDATACOPY,
-        "extcodehash"|"EXTCODEHASH" => EXTCODEHASH,
-        // 40s: Block Information
-        "blockhash"|"BLOCKHASH" => BLOCKHASH,
-        "coinbase"|"COINBASE" => COINBASE,
-        "timestamp"|"TIMESTAMP" => TIMESTAMP,
-        "number"|"NUMBER" => NUMBER,
-        "difficulty"|"DIFFICULTY" => DIFFICULTY,
-        "gaslimit"|"GASLIMIT" => GASLIMIT,
-        "chainid"|"CHAINID" => CHAINID,
-        "selfbalance"|"SELFBALANCE" => SELFBALANCE,
-        // 50s: Stack, Memory, Storage and Flow Operations
-        "pop"|"POP" => POP,
-        "mload"|"MLOAD" => MLOAD,
-        "mstore"|"MSTORE" => MSTORE,
-        "mstore8"|"MSTORE8" => MSTORE8,
-        "sload"|"SLOAD" => SLOAD,
-        "sstore"|"SSTORE" => SSTORE,
-        "jump"|"JUMP" => JUMP,
-        "jumpi"|"JUMPI" => JUMPI,
-        "pc"|"PC" => PC,
-        "msize"|"MSIZE" => MSIZE,
-        "gas"|"GAS" => GAS,
-        "jumpdest"|"JUMPDEST" => JUMPDEST,
-	"tload"|"TLOAD" => TLOAD,
-	"tstore"|"TSTORE" => TSTORE,
-        "push0" => PUSH0,
-        // 60s & 70s: Push Operations
-        "push"|"PUSH" => {
-            // Should be impossible to get here!
-            unreachable!();
-        }
-        // 80s: Duplication Operations
-        "dup1"|"DUP1" => DUP(1),
-        "dup2"|"DUP2" => DUP(2),
-        "dup3"|"DUP3" => DUP(3),
-        "dup4"|"DUP4" => DUP(4),
-        "dup5"|"DUP5" => DUP(5),
-        "dup6"|"DUP6" => DUP(6),
-        "dup7"|"DUP7" => DUP(7),
-        "dup8"|"DUP8" => DUP(8),
-        "dup9"|"DUP9" => DUP(9),
-        "dup10"|"DUP10" => DUP(10),
-        "dup11"|"DUP11" => DUP(11),
-        "dup12"|"DUP12" => DUP(12),
-        "dup13"|"DUP13" => DUP(13),
-        "dup14"|"DUP14" => DUP(14),
-        "dup15"|"DUP15" => DUP(15),
-        "dup16"|"DUP16" => DUP(16),
-        // 90s: Swap Operations
-        "swap1"|"SWAP1" => SWAP(1),
-        "swap2"|"SWAP2" => SWAP(2),
-        "swap3"|"SWAP3" => SWAP(3),
-        "swap4"|"SWAP4" => SWAP(4),
-        "swap5"|"SWAP5" => SWAP(5),
-        "swap6"|"SWAP6" => SWAP(6),
-        "swap7"|"SWAP7" => SWAP(7),
-        "swap8"|"SWAP8" => SWAP(8),
-        "swap9"|"SWAP9" => SWAP(9),
-        "swap10"|"SWAP10" => SWAP(10),
-        "swap11"|"SWAP11" => SWAP(11),
-        "swap12"|"SWAP12" => SWAP(12),
-        "swap13"|"SWAP13" => SWAP(13),
-        "swap14"|"SWAP14" => SWAP(14),
-        "swap15"|"SWAP15" => SWAP(15),
-        "swap16"|"SWAP16" => SWAP(16),
-        // a0s: Log Operations
-        "log0"|"LOG0" => LOG(0),
-        "log1"|"LOG1" => LOG(1),
-        "log2"|"LOG2" => LOG(2),
-        "log3"|"LOG3" => LOG(3),
-        "log4"|"LOG4" => LOG(4),
-        // f0s: System Operations
-        "create"|"CREATE" => CREATE,
-        "call"|"CALL" => CALL,
-        "callcode"|"CALLCODE" => CALLCODE,
-        "return"|"RETURN" => RETURN,
-        "delegatecall"|"DELEGATECALL" => DELEGATECALL,
-        "create2"|"CREATE2" => CREATE2,
-        "staticcall"|"STATICCALL" => STATICCALL,
-        "revert"|"REVERT" => REVERT,
-        "invalid"|"INVALID" => INVALID,
-        "selfdestruct"|"SELFDESTRUCT" => SELFDESTRUCT,
-        //
-        _ => {
-            println!("{insn}");
-            return Err(ParseError::InvalidInstruction);
-        }
-    };
-    //
-    Ok(insn)
-}
-
-fn label_bytes(index: usize) -> Vec<u8> {
-    // Always generate a push2 instruction
-    vec![(index / 256) as u8, (index % 256) as u8]
-}
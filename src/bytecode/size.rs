@@ -0,0 +1,59 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::{Assembly,Instruction,StructuredSection};
+
+/// Breaks down the size of a contract according to what its bytes
+/// are used for.  This distinguishes the bytes of opcodes from those
+/// of `PUSH` immediates, and from data (i.e. unreachable bytes which
+/// were classified as data during disassembly, or explicit data
+/// sections).  For example, in `push 0x0102; pop` there are two
+/// opcode bytes and two immediate bytes.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq)]
+pub struct SizeBreakdown {
+    /// Number of opcode bytes.
+    pub opcodes: usize,
+    /// Number of bytes of `PUSH` immediates.
+    pub immediates: usize,
+    /// Number of data bytes.
+    pub data: usize
+}
+
+impl SizeBreakdown {
+    /// Determine the total number of bytes.
+    pub fn total(&self) -> usize {
+        self.opcodes + self.immediates + self.data
+    }
+}
+
+impl From<&Assembly> for SizeBreakdown {
+    fn from(asm: &Assembly) -> Self {
+        let mut size = SizeBreakdown::default();
+        for section in asm {
+            match section {
+                StructuredSection::Code(insns) => {
+                    for insn in insns {
+                        match insn {
+                            Instruction::DATA(bytes) => size.data += bytes.len(),
+                            Instruction::HAVOC(_) => {}
+                            _ => {
+                                size.opcodes += 1;
+                                size.immediates += insn.length() - 1;
+                            }
+                        }
+                    }
+                }
+                StructuredSection::Data(bytes) => size.data += bytes.len()
+            }
+        }
+        size
+    }
+}
@@ -79,6 +79,31 @@ pub struct Fork {
     eips: &'static [EIP]
 }
 
+impl Fork {
+    /// Lookup a fork by its (case insensitive) name, such as
+    /// `"london"` or `"paris"`.  Returns `None` if no fork of that
+    /// name is known.
+    pub fn lookup(name: &str) -> Option<&'static Fork> {
+        let fork = match name.to_lowercase().as_str() {
+            "homestead" => &HOMESTEAD,
+            "tangerine_whistle" => &TANGERINE_WHISTLE,
+            "spurious_dragon" => &SUPRIOUS_DRAGON,
+            "byzantium" => &BYZANTIUM,
+            "constantinople"|"petersburg" => &CONSTANTINOPLE_PETERSBURG,
+            "istanbul" => &INSTANBUL,
+            "muir_glacier" => &MUIR_GLACIER,
+            "berlin" => &BERLIN,
+            "london" => &LONDON,
+            "arrow_glacier" => &ARROW_GLACIER,
+            "gray_glacier" => &GRAY_GLACIER,
+            "paris"|"merge" => &PARIS,
+            "shanghai" => &SHANGHAI,
+            _ => { return None; }
+        };
+        Some(fork)
+    }
+}
+
 impl PartialOrd for Fork {
     fn partial_cmp(&self, other: &Fork) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -9,7 +9,9 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
-use crate::il::{BinOp, Region, Term};
+use std::collections::HashMap;
+use crate::il::{BinOp, Region, Term, UnOp};
+use crate::il::fold::{fold_constants,from_digits,literal_value};
 use crate::bytecode::{Assembly,Builder,Instruction,StructuredSection};
 use crate::bytecode::Instruction::*;
 use crate::util::*;
@@ -28,8 +30,23 @@ pub enum CompilerError {
     InvalidMemoryAccess,
     /// Attempt to write something which doesn't exist, or is not an lval.
     InvalidLVal,
+    /// Attempt to access a local variable which has not been declared.
+    UnknownVariable(String),
+    /// A bytes (or string) literal was used where a word is expected.
+    InvalidBytesLiteral,
+    /// Attempt to access (at a constant address) a word of memory
+    /// which overlaps the frame holding local variables.
+    LocalMemoryAccess,
 }
 
+/// Memory address at which the frame holding local variables begins.
+/// Memory below this is used as scratch space (e.g. for returning
+/// values), whilst each local variable occupies one word above it in
+/// order of declaration.  Since the frame is reserved, accessing it
+/// at a constant address is an error (though this cannot be checked
+/// for addresses which are not constant).
+const LOCALS_BASE: usize = 0x80;
+
 // ============================================================================
 // Compiler
 // ============================================================================
@@ -38,14 +55,21 @@ pub struct Compiler {
     /// Instructions being constructed by this compiler.
     builder: Builder,
     /// Counts the number of labels in use
-    labels: usize
+    labels: usize,
+    /// Maps each declared local variable to its slot in the frame.
+    locals: HashMap<String,usize>,
+    /// The lowest constant address of a word accessed in memory
+    /// which would overlap the frame, were it large enough.
+    lowest_access: Option<w256>
 }
 
 impl Compiler {
     pub fn new() -> Self {
         Self {
             builder: Builder::new(),
-            labels: 0
+            labels: 0,
+            locals: HashMap::new(),
+            lowest_access: None
         }
     }
 
@@ -63,19 +87,25 @@ impl Compiler {
             Term::Fail => self.translate_fail(),
             Term::Goto(l) => self.translate_goto(l),
             Term::IfGoto(e, l) => self.translate_ifgoto(e, l),
+            Term::IfElse(e, tb, fb) => self.translate_ifelse(e, tb, fb),
             Term::Label(l) => self.translate_label(l),
+            Term::Let(n, e) => self.translate_let(n, e),
             Term::Return(es) => self.translate_return(es),
             Term::Revert(es) => self.translate_revert(es),
             Term::Succeed(es) => self.translate_succeed(es),
             Term::Stop => self.translate_stop(),
+            Term::While(e, body) => self.translate_while(e, body),
             // Expressions
             Term::Binary(bop, e1, e2) => self.translate_binary(*bop, e1, e2),
+            Term::Unary(uop, e) => self.translate_unary(*uop, e),
             Term::Call(n,es) => self.translate_call(n,es),
             Term::ArrayAccess(src, index) => self.translate_array_access(src, index),
             Term::MemoryAccess(_) => Err(CompilerError::InvalidMemoryAccess),
+            Term::Variable(n) => self.translate_variable(n),
             // Values
             Term::Int(bytes) => self.translate_literal(bytes, 10),
             Term::Hex(bytes) => self.translate_literal(bytes, 16),
+            Term::Bytes(_) => Err(CompilerError::InvalidBytesLiteral),
             //
         }
     }
@@ -114,6 +144,10 @@ impl Compiler {
             Term::ArrayAccess(src, idx) => {
                 self.translate_assignment_array(src, idx)?;
             }
+            Term::Variable(n) => {
+                let slot = self.lookup_local(n)?;
+                self.translate_store_local(slot)?;
+            }
             _ => {
                 return Err(CompilerError::InvalidLVal);
             }
@@ -130,6 +164,7 @@ impl Compiler {
     }
 
     fn translate_assignment_memory(&mut self, region: Region, address: &Term) -> Result {
+        if region == Region::Memory { self.check_constant_access(address)?; }
         // Translate index expression
         self.translate(address)?;
         // Dispatch based on region
@@ -151,9 +186,9 @@ impl Compiler {
         // Translate arguments
         for e in exprs { self.translate(e)?; }
         // Push return address (as a label)
-        self.builder.push_labeled(PUSH(label_bytes(retlab_index)));
+        self.builder.push_label(retlab_index);
         // Push function address (as a label)
-        self.builder.push_labeled(PUSH(label_bytes(name_index)));
+        self.builder.push_label(name_index);
         // Perform jump
         self.builder.push(JUMP);
         // Identify return point
@@ -172,7 +207,7 @@ impl Compiler {
     fn translate_goto(&mut self, label: &str) -> Result {
         let label_index = self.builder.get_label(label);        
         // Translate unconditional branch
-        self.builder.push_labeled(PUSH(label_bytes(label_index)));
+        self.builder.push_label(label_index);
         self.builder.push(JUMP);
         //
         Ok(())
@@ -183,6 +218,24 @@ impl Compiler {
         self.translate_conditional(expr, Some(label), None)
     }
 
+    fn translate_ifelse(&mut self, expr: &Term, true_blk: &[Term], false_blk: &[Term]) -> Result {
+        // Allocate labels for the false block and join point
+        let end_lab = self.fresh_label();
+        let false_lab = if false_blk.is_empty() { end_lab.clone() } else { self.fresh_label() };
+        // Translate conditional branch (taken when false)
+        self.translate_conditional(expr, None, Some(&false_lab))?;
+        // True block
+        for s in true_blk { self.translate(s)?; }
+        // False block (if applicable)
+        if !false_blk.is_empty() {
+            self.translate_goto(&end_lab)?;
+            self.translate_label(&false_lab)?;
+            for s in false_blk { self.translate(s)?; }
+        }
+        // Join point
+        self.translate_label(&end_lab)
+    }
+
     fn translate_label(&mut self, label: &str) -> Result {
         // Mark the label
         self.builder.mark_label(label).unwrap();
@@ -191,6 +244,20 @@ impl Compiler {
         Ok(())
     }
 
+    fn translate_let(&mut self, name: &str, expr: &Term) -> Result {
+        // Translate initialiser (before variable is in scope)
+        self.translate(expr)?;
+        // Allocate slot (unless redeclaring)
+        let next = self.locals.len();
+        let slot = *self.locals.entry(name.to_string()).or_insert(next);
+        // Check frame does not grow over memory already accessed
+        if self.lowest_access.is_some_and(|a| a < w256::from(self.frame_end())) {
+            return Err(CompilerError::LocalMemoryAccess);
+        }
+        // Store initial value
+        self.translate_store_local(slot)
+    }
+
     fn translate_return(&mut self, exprs: &[Term]) -> Result {
         if !exprs.is_empty() {
             // Translate each expression (except first)
@@ -220,12 +287,15 @@ impl Compiler {
     }
 
     fn translate_succeed_revert(&mut self, insn: Instruction, exprs: &[Term]) -> Result {
-        if exprs.is_empty() {
+        if let [Term::Bytes(bytes)] = exprs {
+            self.translate_bytes(bytes)?;
+        } else if exprs.is_empty() {
             self.builder.push(PUSH(vec![0]));
             self.builder.push(PUSH(vec![0]));
         } else {
             for (i,e) in exprs.iter().enumerate() {
                 let addr = (i * 0x20) as u128;
+                self.check_memory_access(w256::from(addr))?;
                 self.translate(e)?;
                 self.builder.push(make_push(addr)?);
                 self.builder.push(MSTORE);
@@ -243,6 +313,99 @@ impl Compiler {
         Ok(())
     }
 
+    fn translate_while(&mut self, expr: &Term, body: &[Term]) -> Result {
+        // Allocate labels for loop head and exit
+        let head_lab = self.fresh_label();
+        let exit_lab = self.fresh_label();
+        // Loop head (i.e. target of back edge)
+        self.translate_label(&head_lab)?;
+        // Translate loop condition (exiting when false)
+        self.translate_conditional(expr, None, Some(&exit_lab))?;
+        // Loop body
+        for s in body { self.translate(s)?; }
+        // Back edge
+        self.translate_goto(&head_lab)?;
+        // Loop exit
+        self.translate_label(&exit_lab)
+    }
+
+    // ============================================================================
+    // Bytes Literals
+    // ============================================================================
+
+    /// Materialise a bytes literal into memory, leaving its `offset`
+    /// on top of the stack followed by its `length` (i.e. as expected
+    /// by `RETURN`, `REVERT`, `LOG`, etc).  The literal is written in
+    /// word-sized chunks starting from address `0` (i.e. in scratch
+    /// space), with the final chunk being padded with zeros.
+    fn translate_bytes(&mut self, bytes: &[u8]) -> Result {
+        for (i,chunk) in bytes.chunks(32).enumerate() {
+            let mut word = chunk.to_vec();
+            word.resize(32, 0);
+            self.translate_word(w256::from_be_slice(&word));
+            self.builder.push(make_push((i * 0x20) as u128)?);
+            self.builder.push(MSTORE);
+        }
+        self.builder.push(make_push(bytes.len() as u128)?);
+        self.builder.push(PUSH(vec![0]));
+        Ok(())
+    }
+
+    // ============================================================================
+    // Local Variables
+    // ============================================================================
+
+    /// Determine the address immediately following the frame.
+    fn frame_end(&self) -> usize {
+        LOCALS_BASE + self.locals.len() * 0x20
+    }
+
+    /// Check a word of memory accessed at a given address (if it is
+    /// constant) does not overlap the frame.
+    fn check_constant_access(&mut self, address: &Term) -> Result {
+        match literal_value(&fold_constants(address)) {
+            Some(a) => self.check_memory_access(a),
+            None => Ok(())
+        }
+    }
+
+    /// Check a word of memory accessed at a given constant address
+    /// does not overlap the frame, either now or as it grows.
+    fn check_memory_access(&mut self, address: w256) -> Result {
+        if address < w256::from(LOCALS_BASE - 0x1f) {
+            // Entirely below the frame
+            Ok(())
+        } else if !self.locals.is_empty() && address < w256::from(self.frame_end()) {
+            Err(CompilerError::LocalMemoryAccess)
+        } else {
+            self.lowest_access = Some(self.lowest_access.map_or(address,|a| a.min(address)));
+            Ok(())
+        }
+    }
+
+    /// Determine the frame slot allocated to a given local variable.
+    fn lookup_local(&self, name: &str) -> std::result::Result<usize, CompilerError> {
+        match self.locals.get(name) {
+            Some(slot) => Ok(*slot),
+            None => Err(CompilerError::UnknownVariable(name.to_string())),
+        }
+    }
+
+    /// Store the value on top of the stack into a given frame slot.
+    fn translate_store_local(&mut self, slot: usize) -> Result {
+        self.builder.push(make_push((LOCALS_BASE + slot * 0x20) as u128)?);
+        self.builder.push(MSTORE);
+        Ok(())
+    }
+
+    /// Load the value of a given local variable onto the stack.
+    fn translate_variable(&mut self, name: &str) -> Result {
+        let slot = self.lookup_local(name)?;
+        self.builder.push(make_push((LOCALS_BASE + slot * 0x20) as u128)?);
+        self.builder.push(MLOAD);
+        Ok(())
+    }
+
     // ============================================================================
     // Conditional Expressions
     // ============================================================================
@@ -265,6 +428,10 @@ impl Compiler {
             Term::Binary(BinOp::LogicalOr, l, r) => {
                 self.translate_conditional_disjunct(l, r, true_lab, false_lab)
             }
+            Term::Unary(UnOp::LogicalNot, e) => {
+                // Negation simply swaps the targets
+                self.translate_conditional(e, false_lab, true_lab)
+            }
             _ => self.translate_conditional_other(expr, true_lab, false_lab),
         }
     }
@@ -327,13 +494,13 @@ impl Compiler {
         match (true_lab, false_lab) {
             (Some(lab), None) => {
                 let label_index = self.builder.get_label(lab);
-                self.builder.push_labeled(PUSH(label_bytes(label_index)));
+                self.builder.push_label(label_index);
                 self.builder.push(JUMPI);
             }
             (None, Some(lab)) => {
                 let label_index = self.builder.get_label(lab);
                 self.builder.push(ISZERO);
-                self.builder.push_labeled(PUSH(label_bytes(label_index)));
+                self.builder.push_label(label_index);
                 self.builder.push(JUMPI);
             }
             (_, _) => {
@@ -344,6 +511,21 @@ impl Compiler {
         Ok(())
     }
 
+    // ============================================================================
+    // Unary Expressions
+    // ============================================================================
+
+    /// Translate a unary operation.  Since the EVM has no boolean
+    /// type, logical negation maps any non-zero value to `0` and `0`
+    /// to `1`.
+    fn translate_unary(&mut self, uop: UnOp, expr: &Term) -> Result {
+        self.translate(expr)?;
+        match uop {
+            UnOp::LogicalNot => self.builder.push(ISZERO),
+        }
+        Ok(())
+    }
+
     // ============================================================================
     // Binary Expressions
     // ============================================================================
@@ -371,7 +553,7 @@ impl Compiler {
         // Allocate fresh label
         let lab = self.fresh_label();
         let lab_index = self.builder.get_label(&lab);
-        self.builder.push_labeled(PUSH(label_bytes(lab_index)));
+        self.builder.push_label(lab_index);
         self.builder.push(JUMPI);
         self.builder.push(POP);
         self.translate(rhs)?;
@@ -436,6 +618,7 @@ impl Compiler {
     }
 
     fn translate_memory_access(&mut self, region: Region, index: &Term) -> Result {
+        if region == Region::Memory { self.check_constant_access(index)?; }
         // Translate index expression
         self.translate(index)?;
         // Dispatch based on region
@@ -459,10 +642,19 @@ impl Compiler {
     // ============================================================================
 
     fn translate_literal(&mut self, digits: &[u8], radix: u32) -> Result {
-        let val = from_be_digits(digits, radix);
-        self.builder.push(make_push(val)?);
+        let val = from_digits(digits, radix as u64).ok_or(CompilerError::LiteralOverflow)?;
+        self.translate_word(val);
         Ok(())
     }
+
+    /// Push a given word using the smallest possible instruction.
+    fn translate_word(&mut self, val: w256) {
+        if val == w256::ZERO {
+            self.builder.push(PUSH(vec![0]));
+        } else {
+            self.builder.push(PUSH(val.to_be_bytes_trimmed_vec()));
+        }
+    }
 }
 
 // ===================================================================
@@ -493,11 +685,6 @@ impl<const N: usize> TryFrom<&[Term; N]> for Assembly {
 // Helpers
 // ===================================================================
 
-fn label_bytes(index: usize) -> Vec<u8> {
-    // Always generate a push2 instruction
-    vec![(index / 256) as u8, (index % 256) as u8]
-}
-
 fn try_from(terms: &[Term]) -> std::result::Result<Assembly, CompilerError> {
     let mut compiler = Compiler::new();
     // Translate statements one-by-one
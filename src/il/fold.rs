@@ -0,0 +1,94 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::il::{BinOp,Term};
+use crate::util::w256;
+
+/// Fold constant arithmetic within a given term.  That is, any
+/// arithmetic operation whose operands are (after folding) literals
+/// is replaced by its result.  For example, `(1+2)*3` is folded into
+/// the literal `9`, whilst `calldata[0] + (1+2)` is folded into
+/// `calldata[0] + 3`.  Arithmetic wraps modulo `2^256` and, as for
+/// the EVM, division (or remainder) by zero yields zero.
+/// Comparisons and logical operators are not folded, since their
+/// translation relies on branching.
+pub fn fold_constants(term: &Term) -> Term {
+    let fold = |t: &Term| Box::new(fold_constants(t));
+    let fold_all = |ts: &[Term]| ts.iter().map(fold_constants).collect();
+    match term {
+        // Statements
+        Term::Assert(e) => Term::Assert(fold(e)),
+        Term::Assignment(l,r) => Term::Assignment(fold(l),fold(r)),
+        Term::IfGoto(e,l) => Term::IfGoto(fold(e),l.clone()),
+        Term::Let(n,e) => Term::Let(n.clone(),fold(e)),
+        Term::IfElse(e,tb,fb) => Term::IfElse(fold(e),fold_all(tb),fold_all(fb)),
+        Term::Succeed(es) => Term::Succeed(fold_all(es)),
+        Term::Revert(es) => Term::Revert(fold_all(es)),
+        Term::Return(es) => Term::Return(fold_all(es)),
+        Term::While(e,body) => Term::While(fold(e),fold_all(body)),
+        // Expressions
+        Term::Binary(bop,l,r) => {
+            let (l,r) = (fold_constants(l),fold_constants(r));
+            match (literal_value(&l),literal_value(&r)) {
+                (Some(v),Some(w)) => match fold_binary(*bop,v,w) {
+                    Some(u) => to_literal(u),
+                    None => Term::Binary(*bop,Box::new(l),Box::new(r))
+                }
+                _ => Term::Binary(*bop,Box::new(l),Box::new(r))
+            }
+        }
+        Term::Unary(uop,e) => Term::Unary(*uop,fold(e)),
+        Term::ArrayAccess(src,index) => Term::ArrayAccess(fold(src),fold(index)),
+        Term::Call(n,es) => Term::Call(n.clone(),fold_all(es)),
+        _ => term.clone()
+    }
+}
+
+/// Determine the value of a literal term, or `None` if the term is
+/// not a literal (or its value exceeds `2^256`).
+pub(crate) fn literal_value(term: &Term) -> Option<w256> {
+    match term {
+        Term::Int(digits) => from_digits(digits,10),
+        Term::Hex(digits) => from_digits(digits,16),
+        _ => None
+    }
+}
+
+/// Convert a sequence of (big endian) digits in a given radix into a
+/// word, or `None` if this overflows.
+pub(crate) fn from_digits(digits: &[u8], radix: u64) -> Option<w256> {
+    let radix = w256::from(radix);
+    let mut acc = w256::ZERO;
+    for d in digits {
+        acc = acc.checked_mul(radix)?.checked_add(w256::from(*d))?;
+    }
+    Some(acc)
+}
+
+/// Fold a binary operation over constant operands, or `None` if this
+/// is not an arithmetic operation.
+fn fold_binary(bop: BinOp, lhs: w256, rhs: w256) -> Option<w256> {
+    let r = match bop {
+        BinOp::Add => lhs.wrapping_add(rhs),
+        BinOp::Subtract => lhs.wrapping_sub(rhs),
+        BinOp::Multiply => lhs.wrapping_mul(rhs),
+        BinOp::Divide => lhs.checked_div(rhs).unwrap_or(w256::ZERO),
+        BinOp::Remainder => lhs.checked_rem(rhs).unwrap_or(w256::ZERO),
+        _ => { return None; }
+    };
+    Some(r)
+}
+
+/// Construct a (hex) literal term from a given word.
+fn to_literal(w: w256) -> Term {
+    let digits = format!("{w:x}").chars().map(|c| c.to_digit(16).unwrap() as u8).collect();
+    Term::Hex(digits)
+}
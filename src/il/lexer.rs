@@ -24,6 +24,7 @@ pub enum Token {
     Call,
     Comma,
     Dot,
+    Else,
     EOF,
     Equals,
     EqualsEquals,
@@ -31,13 +32,16 @@ pub enum Token {
     Gap,
     Goto,
     Hex,
+    HexString,
     If,
     Identifier,
     Integer,
     LeftAngle,
     LeftAngleEquals,
     LeftBrace,
+    LeftCurly,
     LeftSquare,
+    Let,
     Minus,
     NewLine,
     Percent,
@@ -47,13 +51,17 @@ pub enum Token {
     RightAngle,
     RightAngleEquals,
     RightBrace,
+    RightCurly,
     RightSlash,
     RightSquare,
     SemiColon,
+    Shreak,
     ShreakEquals,
     Succeed,
     Star,
     Stop,
+    String,
+    While,
 }
 
 // ======================================================
@@ -62,13 +70,16 @@ pub enum Token {
 
 const ASSERT: &[char] = &['a', 's', 's', 'e', 'r', 't'];
 const CALL: &[char] = &['c', 'a', 'l', 'l'];
+const ELSE: &[char] = &['e', 'l', 's', 'e'];
 const FAIL: &[char] = &['f', 'a', 'i', 'l'];
 const GOTO: &[char] = &['g', 'o', 't', 'o'];
 const IF: &[char] = &['i', 'f'];
+const LET: &[char] = &['l', 'e', 't'];
 const REVERT: &[char] = &['r', 'e', 'v', 'e', 'r', 't'];
 const RETURN: &[char] = &['r', 'e', 't', 'u', 'r', 'n'];
 const SUCCEED: &[char] = &['s', 'u', 'c', 'c', 'e', 'e', 'd'];
 const STOP: &[char] = &['s', 't', 'o', 'p'];
+const WHILE: &[char] = &['w', 'h', 'i', 'l', 'e'];
 
 /// Handy type alias for the result type used for all of the lexical
 /// rules.
@@ -90,6 +101,34 @@ fn scan_hex_literal(input: &[char]) -> ScannerResult {
     }
 }
 
+/// Scan a string literal (e.g. `"hello"`), which is any sequence of
+/// characters (other than newlines) enclosed in double quotes.
+fn scan_string_literal(input: &[char]) -> ScannerResult {
+    if input.is_empty() || input[0] != '"' {
+        Err(())
+    } else {
+        match input[1..].iter().position(|c| *c == '"' || *c == '\n') {
+            Some(i) if input[i + 1] == '"' => Ok(Span::new(Token::String, 0..i + 2)),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Scan a hex string literal (e.g. `hex"00ff"`), which is an even
+/// number of hex digits enclosed in double quotes.
+fn scan_hex_string_literal(input: &[char]) -> ScannerResult {
+    if input.len() < 5 || input[0..4] != ['h', 'e', 'x', '"'] {
+        Err(())
+    } else {
+        let n = input[4..].iter().take_while(|c| c.is_ascii_hexdigit()).count();
+        if n % 2 == 0 && input.get(n + 4) == Some(&'"') {
+            Ok(Span::new(Token::HexString, 0..n + 5))
+        } else {
+            Err(())
+        }
+    }
+}
+
 /// Scan a keyword, which is simple identifier matching a predefined
 /// pattern.
 fn scan_keyword(input: &[char]) -> ScannerResult {
@@ -99,13 +138,16 @@ fn scan_keyword(input: &[char]) -> ScannerResult {
     let t = match &input[r.range()] {
         ASSERT => Token::Assert,
         CALL => Token::Call,
+        ELSE => Token::Else,
         FAIL => Token::Fail,
         GOTO => Token::Goto,
         IF => Token::If,
+        LET => Token::Let,
         REVERT => Token::Revert,
         RETURN => Token::Return,
         SUCCEED => Token::Succeed,
         STOP => Token::Stop,
+        WHILE => Token::While,
         _ => {
             return Err(());
         }
@@ -136,15 +178,18 @@ fn scan_single_operators(input: &[char]) -> ScannerResult {
             '=' => Token::Equals,
             '<' => Token::LeftAngle,
             '(' => Token::LeftBrace,
+            '{' => Token::LeftCurly,
             '[' => Token::LeftSquare,
             '-' => Token::Minus,
             '%' => Token::Percent,
             '+' => Token::Plus,
             '>' => Token::RightAngle,
             ')' => Token::RightBrace,
+            '}' => Token::RightCurly,
             '/' => Token::RightSlash,
             ']' => Token::RightSquare,
             ';' => Token::SemiColon,
+            '!' => Token::Shreak,
             '*' => Token::Star,
             _ => {
                 return Err(());
@@ -239,6 +284,8 @@ fn scan_one(input: &[char], t: Token, c: char) -> ScannerResult {
 static RULES: &[Scanner<char, Token>] = &[
     scan_double_operators,
     scan_single_operators,
+    scan_string_literal,
+    scan_hex_string_literal,
     scan_keyword,
     scan_identifier,
     scan_hex_literal,
@@ -587,4 +634,67 @@ mod tests {
         assert_ok!(l.snap(Token::LeftBrace));
         assert_ok!(l.snap(Token::EOF));
     }
+
+    #[test]
+    fn test_62() {
+        let mut l = Lexer::new("{else}");
+        assert_ok!(l.snap(Token::LeftCurly));
+        assert_ok!(l.snap(Token::Else));
+        assert_ok!(l.snap(Token::RightCurly));
+        assert_ok!(l.snap(Token::EOF));
+    }
+
+    #[test]
+    fn test_63() {
+        let mut l = Lexer::new("while whiles");
+        assert_ok!(l.snap(Token::While));
+        assert_ok!(l.snap(Token::Gap));
+        assert_ok!(l.snap(Token::Identifier));
+        assert_ok!(l.snap(Token::EOF));
+    }
+
+    #[test]
+    fn test_64() {
+        let mut l = Lexer::new("let letx");
+        assert_ok!(l.snap(Token::Let));
+        assert_ok!(l.snap(Token::Gap));
+        assert_ok!(l.snap(Token::Identifier));
+        assert_ok!(l.snap(Token::EOF));
+    }
+
+    #[test]
+    fn test_65() {
+        let mut l = Lexer::new("!!=");
+        assert_ok!(l.snap(Token::Shreak));
+        assert_ok!(l.snap(Token::ShreakEquals));
+        assert_ok!(l.snap(Token::EOF));
+    }
+
+    #[test]
+    fn test_66() {
+        let mut l = Lexer::new("\"hi\"\"\"");
+        assert_ok!(l.snap(Token::String));
+        assert_ok!(l.snap(Token::String));
+        assert_ok!(l.snap(Token::EOF));
+    }
+
+    #[test]
+    fn test_67() {
+        let mut l = Lexer::new("hex\"00ff\" hex\"\" hex");
+        assert_ok!(l.snap(Token::HexString));
+        assert_ok!(l.snap(Token::Gap));
+        assert_ok!(l.snap(Token::HexString));
+        assert_ok!(l.snap(Token::Gap));
+        assert_ok!(l.snap(Token::Identifier));
+        assert_ok!(l.snap(Token::EOF));
+    }
+
+    #[test]
+    fn test_68() {
+        // Odd number of hex digits
+        let mut l = Lexer::new("hex\"0\"");
+        assert_ok!(l.snap(Token::Identifier));
+        assert_ok!(l.snap(Token::String));
+        assert_ok!(l.snap(Token::EOF));
+    }
 }
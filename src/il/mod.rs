@@ -10,10 +10,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 mod compiler;
+mod fold;
 mod lexer;
+mod optimize;
 mod parser;
 mod term;
 
 pub use compiler::*;
+pub use fold::fold_constants;
+pub use optimize::optimize;
 pub use parser::Parser;
 pub use term::*;
@@ -0,0 +1,80 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::analysis::{find_dependencies,find_labels};
+use crate::bytecode::{Builder,Instruction};
+
+use Instruction::*;
+
+/// Apply peephole optimisations to a given bytecode sequence.  That
+/// is, short sequences of instructions which have no overall effect
+/// are removed, and this is repeated until no further rewrites apply.
+/// The rewrites are:
+///
+/// ```txt
+///    push x; pop    ==>  (nothing)
+///    dupN; pop      ==>  (nothing)
+///    swapN; swapN   ==>  (nothing)
+///    not; not       ==>  (nothing)
+/// ```
+///
+/// No rewrite involves a `jumpdest` or a push of some jump target
+/// and, since removing instructions changes the byte offsets of
+/// those which follow, all pushes of jump targets are relocated.  If
+/// no rewrite applies, the bytecode sequence is returned unchanged.
+/// This returns `None` if the underlying analysis fails, or if the
+/// jump targets of the bytecode sequence cannot all be resolved to
+/// specific `push` instructions.
+pub fn optimize(insns: &[Instruction], limit: usize) -> Option<Vec<Instruction>> {
+    let deps = find_dependencies(insns,limit).ok()?;
+    // Identify pushes of jump targets
+    let labels = find_labels(insns,&deps)?;
+    let mut targets = vec![false;insns.len()];
+    for t in labels.iter().flatten() { targets[*t] = true; }
+    // Track the original index of each remaining instruction
+    let mut live : Vec<usize> = (0..insns.len()).collect();
+    // Iterate rewrites to a fixed point
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let mut i = 0;
+        while i < live.len() {
+            let window : Vec<&Instruction> = live[i..].iter().take(2).map(|j| &insns[*j]).collect();
+            let fixed = live[i..].iter().take(2).any(|j| targets[*j] || labels[*j].is_some());
+            match rewrite(&window) {
+                Some(n) if !fixed => {
+                    live.drain(i..i+n);
+                    changed = true;
+                    // Step back, as this may enable an earlier rewrite
+                    i = i.saturating_sub(1);
+                }
+                _ => { i += 1; }
+            }
+        }
+    }
+    // Check whether anything changed
+    if live.len() == insns.len() { return Some(insns.to_vec()); }
+    // Rebuild the instruction sequence, relocating jump targets.
+    Some(Builder::relocate(insns,&labels,live))
+}
+
+/// Determine whether a rewrite applies at the start of a given
+/// window of instructions and, if so, how many instructions it
+/// removes.
+fn rewrite(window: &[&Instruction]) -> Option<usize> {
+    match window {
+        [PUSH(_)|PUSH0, POP] => Some(2),
+        [DUP(_), POP] => Some(2),
+        [SWAP(n), SWAP(m)] if n == m => Some(2),
+        [NOT, NOT] => Some(2),
+        _ => None
+    }
+}
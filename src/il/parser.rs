@@ -10,7 +10,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use crate::il::lexer;
-use crate::il::{BinOp, Region, Term};
+use crate::il::{BinOp, Region, Term, UnOp};
 use super::lexer::{Lexer, Span, Token};
 use std::fmt;
 
@@ -144,9 +144,11 @@ impl Parser {
             Token::Goto => self.parse_stmt_goto(),
             Token::If => self.parse_stmt_if(),
             Token::Dot => self.parse_stmt_label(),
+            Token::Let => self.parse_stmt_let(),
             Token::Return => self.parse_stmt_return(),
             Token::Revert => self.parse_stmt_revert(),
             Token::Succeed => self.parse_stmt_succeed(),
+            Token::While => self.parse_stmt_while(),
             _ => self.parse_stmt_assign(),
         }
     }
@@ -203,6 +205,10 @@ impl Parser {
         self.lexer.snap(Token::If)?;
         let expr = self.parse_expr()?;
         self.skip_whitespace();
+        // Distinguish conditional branches from if/else blocks
+        if self.lexer.peek().kind == Token::LeftCurly {
+            return self.parse_stmt_ifelse(expr);
+        }
         self.lexer.snap(Token::Goto)?;
         self.skip_whitespace();
         let target = self.lexer.snap(Token::Identifier)?;
@@ -210,12 +216,53 @@ impl Parser {
         Ok(Term::IfGoto(Box::new(expr), self.lexer.get_str(target)))
     }
 
+    /// Parse the remainder of an `if (cond) { ... } else { ... }`
+    /// statement, where the condition has already been parsed.  The
+    /// `else` block is optional and, if omitted, is empty.
+    pub fn parse_stmt_ifelse(&mut self, cond: Term) -> Result<Term> {
+        let true_blk = self.parse_block()?;
+        self.skip_whitespace();
+        let false_blk = if self.lexer.peek().kind == Token::Else {
+            self.lexer.snap(Token::Else)?;
+            self.skip_whitespace();
+            self.parse_block()?
+        } else {
+            Vec::new()
+        };
+        Ok(Term::IfElse(Box::new(cond), true_blk, false_blk))
+    }
+
+    /// Parse a sequence of zero or more statements enclosed in curly
+    /// braces.
+    pub fn parse_block(&mut self) -> Result<Vec<Term>> {
+        let mut stmts = Vec::new();
+        self.lexer.snap(Token::LeftCurly)?;
+        self.skip_whitespace();
+        while !self.lexer.is_eof() && self.lexer.peek().kind != Token::RightCurly {
+            stmts.push(self.parse_stmt()?);
+            self.skip_whitespace();
+        }
+        self.lexer.snap(Token::RightCurly)?;
+        Ok(stmts)
+    }
+
     pub fn parse_stmt_label(&mut self) -> Result<Term> {
         self.lexer.snap(Token::Dot)?;
         let target = self.lexer.snap(Token::Identifier)?;
         Ok(Term::Label(self.lexer.get_str(target)))
     }
 
+    pub fn parse_stmt_let(&mut self) -> Result<Term> {
+        self.lexer.snap(Token::Let)?;
+        self.skip_whitespace();
+        let name = self.lexer.snap(Token::Identifier)?;
+        self.skip_whitespace();
+        self.lexer.snap(Token::Equals)?;
+        let expr = self.parse_expr()?;
+        self.lexer.snap(Token::SemiColon)?;
+        Ok(Term::Let(self.lexer.get_str(name), Box::new(expr)))
+    }
+
     pub fn parse_stmt_return(&mut self) -> Result<Term> {
         self.lexer.snap(Token::Return)?;
         let exprs = self.parse_expr_list(Token::SemiColon)?;
@@ -237,6 +284,14 @@ impl Parser {
         Ok(Term::Succeed(exprs))
     }
 
+    pub fn parse_stmt_while(&mut self) -> Result<Term> {
+        self.lexer.snap(Token::While)?;
+        let expr = self.parse_expr()?;
+        self.skip_whitespace();
+        let body = self.parse_block()?;
+        Ok(Term::While(Box::new(expr), body))
+    }
+
     // =========================================================================
     // Expressions
     // =========================================================================
@@ -304,8 +359,11 @@ impl Parser {
         let expr = match lookahead.kind {
             Token::Integer => self.parse_literal_int()?,
             Token::Hex => self.parse_literal_hex()?,
+            Token::HexString => self.parse_literal_hex_string()?,
+            Token::String => self.parse_literal_string()?,
             Token::Identifier => self.parse_variable_access()?,
             Token::LeftBrace => self.parse_expr_bracketed()?,
+            Token::Shreak => self.parse_expr_not()?,
             _ => {
                 return Err(Error::new(lookahead, ErrorCode::UnexpectedToken));
             }
@@ -314,6 +372,12 @@ impl Parser {
         Ok(expr)
     }
 
+    pub fn parse_expr_not(&mut self) -> Result<Term> {
+        self.lexer.snap(Token::Shreak)?;
+        let expr = self.parse_expr_postfix()?;
+        Ok(Term::Unary(UnOp::LogicalNot, Box::new(expr)))
+    }
+
     pub fn parse_literal_int(&mut self) -> Result<Term> {
         let tok = self.lexer.snap(Token::Integer)?;
         // Extract characters making up literal
@@ -340,6 +404,28 @@ impl Parser {
         Ok(Term::Hex(digits))
     }
 
+    pub fn parse_literal_hex_string(&mut self) -> Result<Term> {
+        let tok = self.lexer.snap(Token::HexString)?;
+        // Extract characters between quotes
+        let s = self.lexer.get_str(tok);
+        let chars: Vec<u8> = s[4..s.len() - 1].bytes().collect();
+        // Convert each pair of hex digits into a byte
+        let bytes = chars
+            .chunks(2)
+            .map(|p| u8::from_str_radix(std::str::from_utf8(p).unwrap(), 16).unwrap())
+            .collect();
+        // All good!
+        Ok(Term::Bytes(bytes))
+    }
+
+    pub fn parse_literal_string(&mut self) -> Result<Term> {
+        let tok = self.lexer.snap(Token::String)?;
+        // Extract characters between quotes
+        let s = self.lexer.get_str(tok);
+        // All good!
+        Ok(Term::Bytes(s.as_bytes()[1..s.len() - 1].to_vec()))
+    }
+
     pub fn parse_variable_access(&mut self) -> Result<Term> {
         let tok = self.lexer.snap(Token::Identifier)?;
         // Extract characters making up literal
@@ -349,9 +435,7 @@ impl Parser {
             "memory" => Term::MemoryAccess(Region::Memory),
             "storage" => Term::MemoryAccess(Region::Storage),
             "calldata" => Term::MemoryAccess(Region::CallData),
-            _ => {
-                return Err(Error::new(tok, ErrorCode::UnexpectedToken));
-            }
+            _ => Term::Variable(chars)
         };
         //
         Ok(expr)
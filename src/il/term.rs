@@ -21,20 +21,26 @@ pub enum Term {
     Assignment(Box<Term>, Box<Term>),
     Goto(String),
     IfGoto(Box<Term>, String),
+    IfElse(Box<Term>, Vec<Term>, Vec<Term>),
     Label(String),
+    Let(String, Box<Term>),
     Succeed(Vec<Term>),
     Revert(Vec<Term>),
     Return(Vec<Term>),
     Fail,
     Stop,
+    While(Box<Term>, Vec<Term>),
     // Expressions
     Binary(BinOp, Box<Term>, Box<Term>),
+    Unary(UnOp, Box<Term>),
     ArrayAccess(Box<Term>, Box<Term>),
     MemoryAccess(Region),
     Call(String,Vec<Term>),
+    Variable(String),
     // Values
     Int(Vec<u8>),
     Hex(Vec<u8>),
+    Bytes(Vec<u8>),
 }
 
 // ============================================================================
@@ -61,6 +67,16 @@ pub enum BinOp {
     LogicalOr,
 }
 
+// ============================================================================
+// Unary Operators
+// ============================================================================
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum UnOp {
+    // Logical
+    LogicalNot,
+}
+
 // ============================================================================
 // Memory Regions
 // ============================================================================
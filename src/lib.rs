@@ -90,6 +90,11 @@ pub mod bytecode;
 /// EVM.  This includes mechanisms for identifying what EIPs are
 /// active in the current execution.
 pub mod fork;
+/// Functionality for retrieving contracts directly from an Ethereum
+/// node via its [JSON-RPC](https://ethereum.org/en/developers/docs/apis/json-rpc/)
+/// interface.  This requires the `rpc` feature.
+#[cfg(feature = "rpc")]
+pub mod rpc;
 /// A low-level intermediate language which has close correspondence
 /// with bytecode.
 pub mod il;
@@ -0,0 +1,91 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::fmt;
+use serde_json::{json,Value};
+use crate::util::FromHexString;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors which can arise when fetching information from a JSON-RPC
+/// node.
+#[derive(Debug)]
+pub enum RpcError {
+    /// The given account address was malformed.
+    InvalidAddress(String),
+    /// The request could not be sent, or no response was received.
+    Transport(String),
+    /// The node responded with an error message.
+    Node(String),
+    /// The response received from the node could not be understood.
+    InvalidResponse(String)
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Just reuse debug formatting.
+        write!(f,"{:?}",self)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+// ============================================================================
+// Requests
+// ============================================================================
+
+/// Fetch the runtime bytecode of the contract at a given address
+/// using the `eth_getCode` method of a given JSON-RPC node.  The
+/// address is expected to be a hex string (e.g. `0x5f5e...`), and the
+/// code is taken from the latest block.
+pub fn fetch_code(rpc_url: &str, address: &str) -> Result<Vec<u8>,RpcError> {
+    // Sanity check address
+    match address.from_hex_string() {
+        Ok(bytes) if bytes.len() == 20 => {}
+        _ => { return Err(RpcError::InvalidAddress(address.to_string())); }
+    }
+    // Construct request
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getCode",
+        "params": [address,"latest"]
+    });
+    // Send it
+    let response = ureq::post(rpc_url)
+        .set("Content-Type","application/json")
+        .send_string(&request.to_string())
+        .map_err(|e| RpcError::Transport(e.to_string()))?;
+    let body = response.into_string().map_err(|e| RpcError::Transport(e.to_string()))?;
+    // Decode the response
+    parse_code_response(&body)
+}
+
+/// Extract the bytecode from the JSON response to an `eth_getCode`
+/// request.  For example, `{"jsonrpc":"2.0","id":1,"result":"0x6000"}`
+/// gives the bytes `[0x60,0x00]`.
+pub fn parse_code_response(json: &str) -> Result<Vec<u8>,RpcError> {
+    let invalid = || RpcError::InvalidResponse(json.to_string());
+    let response : Value = serde_json::from_str(json).map_err(|_| invalid())?;
+    if let Some(result) = response.get("result") {
+        let result = result.as_str().ok_or_else(invalid)?;
+        // Accounts without code are reported as "0x"
+        if result == "0x" { return Ok(Vec::new()); }
+        result.from_hex_string().map_err(|_| invalid())
+    } else if let Some(message) = response.pointer("/error/message").and_then(Value::as_str) {
+        // Error responses have the form {"error":{"message":...}}
+        Err(RpcError::Node(message.to_string()))
+    } else {
+        Err(invalid())
+    }
+}
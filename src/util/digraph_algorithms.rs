@@ -72,7 +72,10 @@ pub fn transitive_closure<T:Seq>(graph: &Digraph<T>) -> Vec<EdgeSet> {
         //
         for i in 0..graph.len() {
             for j in graph.outgoing(i) {
-                if i != *j {
+                // NOTE: edges may target nodes beyond the end of the
+                // sequence (e.g. the exit of a block graph).  Such
+                // nodes have no outgoing edges of their own.
+                if i != *j && *j < graph.len() {
                     unsafe {
                         // This is safe because we know that i != j, and
                         // hence the two sets are actually disjoint.
@@ -88,6 +91,78 @@ pub fn transitive_closure<T:Seq>(graph: &Digraph<T>) -> Vec<EdgeSet> {
     closure
 }
 
+// ===================================================================
+// Connected Components
+// ===================================================================
+
+/// Determine the number of (weakly) connected components in a graph.
+/// That is, the number of maximal sets of nodes which are connected
+/// when ignoring the direction of edges.
+pub fn connected_components<T:Seq>(graph: &Digraph<T>) -> usize {
+    let n = graph.len();
+    let mut visited = vec![false; n];
+    let mut count = 0;
+    //
+    for i in 0..n {
+        if !visited[i] {
+            count += 1;
+            visited[i] = true;
+            let mut worklist = vec![i];
+            while let Some(j) = worklist.pop() {
+                for k in graph.outgoing(j).iter().chain(graph.incoming(j)) {
+                    if *k < n && !visited[*k] {
+                        visited[*k] = true;
+                        worklist.push(*k);
+                    }
+                }
+            }
+        }
+    }
+    //
+    count
+}
+
+// ===================================================================
+// Cyclomatic Complexity
+// ===================================================================
+
+/// Compute the _cyclomatic complexity_ of a graph.  This is given by
+/// `E - N + 2P`, where `E` is the number of edges, `N` the number of
+/// nodes and `P` the number of connected components.  Roughly
+/// speaking, this counts the number of linearly independent paths
+/// through the graph.
+pub fn cyclomatic_complexity<T:Seq>(graph: &Digraph<T>) -> usize {
+    let n = graph.len();
+    let e : usize = (0..n).map(|i| graph.outgoing(i).iter().filter(|j| **j < n).count()).sum();
+    let p = connected_components(graph);
+    //
+    (e + 2*p) - n
+}
+
+/// Compute the _cyclomatic complexity_ of the subgraph reachable from
+/// a given entry node (e.g. the entry block of a function).  Since
+/// this subgraph is connected by construction, this is `E - N + 2`.
+pub fn cyclomatic_complexity_from<T:Seq>(graph: &Digraph<T>, entry: usize) -> usize {
+    let n = graph.len();
+    let mut visited = vec![false; n];
+    let mut worklist = vec![entry];
+    let (mut nodes, mut edges) = (1, 0);
+    visited[entry] = true;
+    //
+    while let Some(i) = worklist.pop() {
+        for j in graph.outgoing(i).iter().filter(|j| **j < n) {
+            edges += 1;
+            if !visited[*j] {
+                visited[*j] = true;
+                nodes += 1;
+                worklist.push(*j);
+            }
+        }
+    }
+    //
+    (edges + 2) - nodes
+}
+
 // ===================================================================
 // Helpers
 // ===================================================================
@@ -101,8 +176,8 @@ unsafe fn dom_intersect(n: usize, dom: *mut EdgeSet, pred: *const EdgeSet) -> bo
     let mut j = 0;
     //
     while i < (*dom).len() && j < (*pred).len() {
-        let ith = (*dom)[i];
-        let jth = (*pred)[j];
+        let ith = (&(*dom))[i];
+        let jth = (&(*pred))[j];
         //
         if ith == n {
             i += 1;
@@ -73,3 +73,25 @@ impl FromHexString for str {
         Ok(bytes)
     }
 }
+
+/// Serialises byte sequences as hex strings (e.g. `"0x6080"`), rather
+/// than as arrays of numbers.  This is intended for use with
+/// `#[serde(with = "...")]`.
+#[cfg(feature = "serde")]
+pub mod serde_hex {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+    use super::{FromHexString, ToHexString};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&bytes.to_hex_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if !s.starts_with("0x") {
+            return Err(D::Error::custom(format!("invalid hex string \"{s}\"")));
+        }
+        s.from_hex_string().map_err(D::Error::custom)
+    }
+}
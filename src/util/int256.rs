@@ -0,0 +1,148 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::cmp::Ordering;
+use std::ops::{Div,Rem,Shr};
+use super::w256;
+
+/// Represents a _signed_ `256` bit word using a two's complement
+/// representation.  This is primarily useful for implementing the
+/// signed operations of the EVM (e.g. `SDIV`, `SMOD`, etc), and
+/// simply provides a different view of an underlying `w256`.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+#[allow(non_camel_case_types)]
+pub struct i256(w256);
+
+impl i256 {
+    /// The zero value.
+    pub const ZERO : i256 = i256(w256::ZERO);
+    /// The smallest representable value (i.e. `-2^255`).
+    pub const MIN : i256 = i256(w256::from_limbs([0,0,0,1<<63]));
+    /// The largest representable value (i.e. `2^255 - 1`).
+    pub const MAX : i256 = i256(w256::from_limbs([u64::MAX,u64::MAX,u64::MAX,u64::MAX>>1]));
+    /// The value `-1`.
+    pub const MINUS_ONE : i256 = i256(w256::MAX);
+
+    /// Determine whether this value is negative (i.e. its sign bit
+    /// is set).
+    pub fn is_negative(&self) -> bool {
+        self.0.bit(255)
+    }
+
+    /// Negate this value, where `-MIN` wraps around to `MIN`.
+    pub fn wrapping_neg(self) -> i256 {
+        i256(self.0.wrapping_neg())
+    }
+
+    /// Interpret the lowest `k+1` bytes of a given word as a signed
+    /// value, sign extending it to the full `256` bits (as for
+    /// `SIGNEXTEND`).  When `k >= 31` the word is returned unchanged.
+    pub fn sign_extend(word: w256, k: w256) -> i256 {
+        if k >= w256::from(31) {
+            i256(word)
+        } else {
+            // Identify the sign bit
+            let bit = (8 * k.to::<usize>()) + 7;
+            let mask = (w256::from(1) << (bit + 1)) - w256::from(1);
+            // Extend it
+            if word.bit(bit) { i256(word | !mask) } else { i256(word & mask) }
+        }
+    }
+
+    /// Get the absolute value of this value as an unsigned word.
+    /// Observe this cannot overflow (i.e. since `|MIN| = 2^255`).
+    pub fn unsigned_abs(self) -> w256 {
+        if self.is_negative() { self.0.wrapping_neg() } else { self.0 }
+    }
+}
+
+impl From<w256> for i256 {
+    fn from(word: w256) -> i256 {
+        i256(word)
+    }
+}
+
+impl From<i64> for i256 {
+    /// Sign extend a (small) signed value to the full `256` bits.
+    fn from(value: i64) -> i256 {
+        let word = w256::from(value.unsigned_abs());
+        if value < 0 { i256(word.wrapping_neg()) } else { i256(word) }
+    }
+}
+
+impl From<i256> for w256 {
+    fn from(word: i256) -> w256 {
+        word.0
+    }
+}
+
+impl Ord for i256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.is_negative(),other.is_negative()) {
+            (true,false) => Ordering::Less,
+            (false,true) => Ordering::Greater,
+            // Same sign, hence unsigned comparison suffices
+            (_,_) => self.0.cmp(&other.0)
+        }
+    }
+}
+
+impl PartialOrd for i256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Div for i256 {
+    type Output = i256;
+
+    /// Signed division which rounds towards zero, as for `SDIV`.
+    /// Division by zero gives zero, whilst `MIN / -1` overflows to
+    /// give `MIN`.
+    fn div(self, rhs: i256) -> i256 {
+        if rhs == i256::ZERO {
+            i256::ZERO
+        } else {
+            let q = i256(self.unsigned_abs() / rhs.unsigned_abs());
+            // Sign of quotient determined by signs of operands
+            if self.is_negative() != rhs.is_negative() { q.wrapping_neg() } else { q }
+        }
+    }
+}
+
+impl Rem for i256 {
+    type Output = i256;
+
+    /// Signed remainder, as for `SMOD`.  The result takes the sign of
+    /// the dividend, whilst a modulus of zero gives zero.
+    fn rem(self, rhs: i256) -> i256 {
+        if rhs == i256::ZERO {
+            i256::ZERO
+        } else {
+            let r = i256(self.unsigned_abs() % rhs.unsigned_abs());
+            // Sign of remainder determined by dividend
+            if self.is_negative() { r.wrapping_neg() } else { r }
+        }
+    }
+}
+
+impl Shr<usize> for i256 {
+    type Output = i256;
+
+    /// Arithmetic shift right, as for `SAR`.  This preserves the sign
+    /// bit and, hence, shifting by `256` or more gives either `0` or
+    /// `-1` (depending on the sign).
+    fn shr(self, rhs: usize) -> i256 {
+        let n = std::cmp::min(rhs,255);
+        //
+        if self.is_negative() { i256(!((!self.0) >> n)) } else { i256(self.0 >> n) }
+    }
+}
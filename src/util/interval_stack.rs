@@ -123,6 +123,12 @@ where
 
     /// Join two abstract stacks together.
     pub fn join(self, other: &IntervalStack<T>) -> Self {
+        // Bottom is the identity for join
+        if self == Self::BOTTOM {
+            return other.clone();
+        } else if other == &Self::BOTTOM {
+            return self;
+        }
         let slen = self.upper.len();
         let olen = other.upper.len();
         // Determine common upper length
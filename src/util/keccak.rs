@@ -0,0 +1,46 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::w256;
+
+/// The keccak256 hash of the empty byte sequence.  This is the code
+/// hash of any existing account which has no code (see EIP-1052).
+pub const KECCAK_EMPTY : w256 = w256::from_be_bytes([
+    0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c,
+    0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b,
+    0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70
+]);
+
+/// Compute the keccak256 hash of a given sequence of bytes, returning
+/// it as a `256` bit word.
+#[cfg(feature = "keccak")]
+pub fn keccak256(bytes: &[u8]) -> w256 {
+    use tiny_keccak::{Hasher,Keccak};
+    let mut hasher = Keccak::v256();
+    let mut output = [0u8; 32];
+    hasher.update(bytes);
+    hasher.finalize(&mut output);
+    w256::from_be_bytes(output)
+}
+
+/// Compute the keccak256 hash of a given sequence of bytes, returning
+/// `None` if this cannot be computed (i.e. keccak support is
+/// disabled).
+#[cfg(feature = "keccak")]
+pub fn try_keccak256(bytes: &[u8]) -> Option<w256> {
+    Some(keccak256(bytes))
+}
+
+#[cfg(not(feature = "keccak"))]
+pub fn try_keccak256(_bytes: &[u8]) -> Option<w256> {
+    None
+}
@@ -85,6 +85,12 @@ pub trait Concretizable {
 
     /// Extract constant value.
     fn constant(&self) -> Self::Item;
+
+    /// Extract constant value, or `None` if this abstract value is
+    /// not a constant.
+    fn try_constant(&self) -> Option<Self::Item> {
+        if self.is_constant() { Some(self.constant()) } else { None }
+    }
 }
 
 // ===================================================================
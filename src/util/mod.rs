@@ -15,8 +15,10 @@ mod byte_utils;
 mod digraph;
 mod digraph_algorithms;
 mod hex;
+mod int256;
 mod interval;
 mod interval_stack;
+mod keccak;
 mod lattice;
 mod numeric;
 mod word256;
@@ -30,8 +32,10 @@ pub use byte_utils::*;
 pub use digraph::*;
 pub use digraph_algorithms::*;
 pub use hex::*;
+pub use int256::*;
 pub use interval::*;
 pub use interval_stack::*;
+pub use keccak::*;
 pub use lattice::*;
 pub use numeric::*;
 pub use word256::*;
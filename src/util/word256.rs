@@ -14,6 +14,12 @@ use crate::util;
 
 /// Represents a `256` bit word.  This is very similar what a `u256`
 /// would be, but where all operations employ modulo arithmetic.
+/// That is, the standard operators (e.g. `+`, `*`, `<<`) wrap at
+/// `2^256`.  However, division (and remainder) by zero panics, and it
+/// is left to callers to handle this (e.g. using `checked_div()`).
+/// Finally, `Display` formats in decimal whilst `LowerHex` and
+/// `UpperHex` format in hex (with a `0x` prefix under the `#` flag),
+/// all using minimal width.
 #[allow(non_camel_case_types)]
 pub type w256 = Uint<256,4>;
 
@@ -34,3 +40,59 @@ impl util::Max for w256 {
 impl util::Min for w256 {
     const MIN: Self = w256::MIN;
 }
+
+// =====================================================================
+// Parsing
+// =====================================================================
+
+/// Errors which can arise when parsing a `w256` from a string.
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub enum W256ParseError {
+    /// The string contained no digits.
+    Empty,
+    /// The string contained a character which is not a digit in the
+    /// given radix.
+    InvalidDigit(char),
+    /// The value does not fit into `256` bits.
+    Overflow
+}
+
+/// A simple trait allowing a `w256` to be parsed from a decimal or
+/// hex string of arbitrary length.  Leading zeros are permitted,
+/// whilst values which do not fit into `256` bits are rejected
+/// (rather than wrapped).  Observe that `w256` also implements
+/// `FromStr`, which accepts decimal strings or hex strings with a
+/// `0x` prefix.
+pub trait FromWordString: Sized {
+    /// Parse a decimal string (e.g. `"1234"`).
+    fn from_dec_str(s: &str) -> Result<Self,W256ParseError>;
+
+    /// Parse a hex string, where the `0x` prefix is optional
+    /// (e.g. `"0x4d2"` or `"4d2"`).
+    fn from_hex_str(s: &str) -> Result<Self,W256ParseError>;
+}
+
+impl FromWordString for w256 {
+    fn from_dec_str(s: &str) -> Result<Self,W256ParseError> {
+        from_digits(s,10)
+    }
+
+    fn from_hex_str(s: &str) -> Result<Self,W256ParseError> {
+        let s = s.strip_prefix("0x").or(s.strip_prefix("0X")).unwrap_or(s);
+        from_digits(s,16)
+    }
+}
+
+/// Parse a sequence of digits in a given radix, checking for
+/// overflow.
+fn from_digits(s: &str, radix: u32) -> Result<w256,W256ParseError> {
+    if s.is_empty() { return Err(W256ParseError::Empty); }
+    let mut acc = W256_ZERO;
+    for c in s.chars() {
+        let d = c.to_digit(radix).ok_or(W256ParseError::InvalidDigit(c))?;
+        acc = acc.checked_mul(w256::from(radix))
+            .and_then(|acc| acc.checked_add(w256::from(d)))
+            .ok_or(W256ParseError::Overflow)?;
+    }
+    Ok(acc)
+}
@@ -1,6 +1,8 @@
 use std::fs;
 use std::path::{PathBuf};
-use evmil::bytecode::{Assembly};
+use evmil::bytecode::{Assembly,Instruction,ParseError,SectionType,StructuredSection,ValidationError};
+use evmil::bytecode::Instruction::*;
+use StructuredSection::*;
 use evmil::util::{FromHexString};
 
 pub static TESTS_DIR: &str = "tests/files";
@@ -41,3 +43,190 @@ fn to_binfile(test: &str) -> PathBuf {
     path.set_extension("bin");
     path
 }
+
+// ============================================================================
+// Sized Pushes
+// ============================================================================
+
+#[test]
+fn test_sized_push_01() {
+    let asm = Assembly::from_str("push3 0x01\npush1 255\n").unwrap();
+    assert_eq!(asm.to_legacy_bytes(),[0x62,0x00,0x00,0x01,0x60,0xff]);
+}
+
+#[test]
+fn test_sized_push_02() {
+    // Operands must fit within the given width
+    assert!(matches!(Assembly::from_str("push1 0x0102\n"),Err(ParseError::PushOperandTooWide(6))));
+    assert!(matches!(Assembly::from_str("stop\npush1 256\n"),Err(ParseError::PushOperandTooWide(11))));
+}
+
+#[test]
+fn test_sized_push_03() {
+    // Labels are pushed with the given width
+    let asm = ".code\n push1 lab\n jump\nlab:\n jumpdest\n";
+    assert_eq!(Assembly::from_str(asm).unwrap().to_legacy_bytes(),[0x60,0x03,0x56,0x5b]);
+    assert_eq!(Assembly::from_str_compact(asm).unwrap().to_legacy_bytes(),[0x60,0x03,0x56,0x5b]);
+    let asm = ".code\n push3 lab\n jump\nlab:\n jumpdest\n";
+    assert_eq!(Assembly::from_str(asm).unwrap().to_legacy_bytes(),[0x62,0x00,0x00,0x05,0x56,0x5b]);
+    assert_eq!(Assembly::from_str_compact(asm).unwrap().to_legacy_bytes(),[0x62,0x00,0x00,0x05,0x56,0x5b]);
+}
+
+#[test]
+fn test_sized_push_04() {
+    // Labels must fit within the given width
+    let filler = "00".repeat(256);
+    let asm = format!(".code\n push1 lab\n jump\n db 0x{filler}\nlab:\n jumpdest\n");
+    assert!(matches!(Assembly::from_str(&asm),Err(ParseError::PushOperandTooWide(13))));
+    assert!(matches!(Assembly::from_str_compact(&asm),Err(ParseError::PushOperandTooWide(13))));
+}
+
+#[test]
+fn test_mnemonics_01() {
+    // Assembly agrees with parsing each instruction individually
+    let lines = ["PUSH2 0x20","push 256","Dup3","swap16","log2","db 0x0c","havoc 1","prevrandao","SELFDESTRUCT"];
+    let asm = Assembly::from_str(&lines.join("\n")).unwrap();
+    let insns : Vec<Instruction> = lines.iter().map(|l| l.parse().unwrap()).collect();
+    assert_eq!(asm.into_instructions(),insns);
+}
+
+// ============================================================================
+// Compact Labels
+// ============================================================================
+
+#[test]
+fn test_compact_labels_01() {
+    let asm = ".code\n push lab\n jump\nlab:\n jumpdest\n";
+    let bytes = Assembly::from_str_compact(asm).unwrap().to_legacy_bytes();
+    assert_eq!(bytes,[0x60,0x03,0x56,0x5b]);
+    // Default always uses two bytes
+    let bytes = Assembly::from_str(asm).unwrap().to_legacy_bytes();
+    assert_eq!(bytes,[0x61,0x00,0x04,0x56,0x5b]);
+}
+
+#[test]
+fn test_compact_labels_02() {
+    // Backward jump
+    let asm = ".code\nlab:\n jumpdest\n push lab\n jump\n";
+    let bytes = Assembly::from_str_compact(asm).unwrap().to_legacy_bytes();
+    assert_eq!(bytes,[0x5b,0x60,0x00,0x56]);
+}
+
+#[test]
+fn test_compact_labels_03() {
+    // Widening `push far` moves `near` from 0xff to 0x100, so that
+    // `push near` only stabilises on a subsequent pass.
+    let filler = "00".repeat(250);
+    let asm = format!(".code\n push far\n push near\n jump\n db 0x{filler}\nnear:\n jumpdest\nfar:\n jumpdest\n");
+    let bytes = Assembly::from_str_compact(&asm).unwrap().to_legacy_bytes();
+    assert_eq!(bytes[0..7],[0x61,0x01,0x02,0x61,0x01,0x01,0x56]);
+    assert_eq!(bytes.len(),0x103);
+    assert_eq!(bytes[0x101],0x5b);
+    assert_eq!(bytes[0x102],0x5b);
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+#[test]
+fn test_validate_01() {
+    let asm = Assembly::from_str(".code\n push 0x01\n dup1\n dup1\n dup1\n log2\n stop\n.data\n 0x0102\n").unwrap();
+    assert_eq!(asm.validate(),Ok(()));
+    assert_eq!(asm.validate_eof(),Ok(()));
+}
+
+#[test]
+fn test_validate_02() {
+    // Over-wide push and invalid dup
+    let asm = Assembly::new(vec![Code(vec![PUSH(vec![0;33]),DUP(17),STOP])]);
+    assert_eq!(asm.validate(),Err(vec![(0,ValidationError::InvalidPush(33)),(34,ValidationError::InvalidDup(17))]));
+}
+
+#[test]
+fn test_validate_03() {
+    // Offsets account for preceding data sections
+    let asm = Assembly::new(vec![Code(vec![STOP]),Data(vec![0xfe;3]),Code(vec![SWAP(0),LOG(5),PUSH(vec![])])]);
+    assert_eq!(asm.validate(),Err(vec![(4,ValidationError::InvalidSwap(0)),(5,ValidationError::InvalidLog(5)),(6,ValidationError::InvalidPush(0))]));
+}
+
+#[test]
+fn test_validate_04() {
+    // Legacy contracts may interleave code and data, but EOF cannot
+    let asm = Assembly::new(vec![Code(vec![STOP]),Data(vec![0xfe]),Code(vec![STOP]),Data(vec![0xfe])]);
+    assert_eq!(asm.validate(),Ok(()));
+    assert_eq!(asm.validate_eof(),Err(vec![(2,ValidationError::DataSectionNotLast),(3,ValidationError::MultipleDataSections)]));
+}
+
+#[test]
+fn test_validate_05() {
+    // Stack underflow, after which code is unreachable (EOF only)
+    let asm = Assembly::new(vec![Code(vec![PUSH(vec![0x01]),ADD,STOP])]);
+    assert_eq!(asm.validate(),Ok(()));
+    assert_eq!(asm.validate_eof(),Err(vec![(2,ValidationError::StackUnderflow),(3,ValidationError::UnreachableCode)]));
+}
+
+#[test]
+fn test_validate_06() {
+    // Unreachable code (EOF only)
+    let asm = Assembly::new(vec![Code(vec![STOP,PUSH(vec![0x01]),POP,STOP])]);
+    assert_eq!(asm.validate(),Ok(()));
+    assert_eq!(asm.validate_eof(),Err(vec![(1,ValidationError::UnreachableCode)]));
+}
+
+#[test]
+fn test_validate_07() {
+    // Relative jump outside section (EOF only)
+    let asm = Assembly::new(vec![Code(vec![RJUMP(1),STOP])]);
+    let errs = asm.validate_eof().unwrap_err();
+    assert!(errs.contains(&(0,ValidationError::InvalidRelativeJump(1))));
+}
+
+#[test]
+fn test_validate_08() {
+    // Only the second section underflows, and offsets account for the first
+    let asm = Assembly::new(vec![Code(vec![PUSH(vec![0x01]),PUSH(vec![0x02]),ADD,POP,STOP]),Code(vec![POP,STOP])]);
+    assert_eq!(asm.validate_eof(),Err(vec![(7,ValidationError::StackUnderflow),(8,ValidationError::UnreachableCode)]));
+}
+
+#[test]
+fn test_validate_09() {
+    // Function instructions are not permitted in legacy bytecode
+    let asm = Assembly::new(vec![Code(vec![CALLF(1),STOP]),Code(vec![RETF])]);
+    assert_eq!(asm.validate(),Err(vec![(0,ValidationError::Unencodable),(4,ValidationError::Unencodable)]));
+    assert_eq!(asm.validate_eof(),Ok(()));
+}
+
+#[test]
+fn test_validate_10() {
+    // Called function does not exist (EOF only)
+    let asm = Assembly::new(vec![Code(vec![CALLF(1),STOP])]);
+    assert_eq!(asm.validate_eof(),Err(vec![(0,ValidationError::InvalidSectionIndex(1))]));
+}
+
+#[test]
+fn test_validate_11() {
+    // Inputs of a function are on the stack (EOF only)
+    let asm = Assembly::new(vec![Code(vec![PUSH(vec![0x01]),PUSH(vec![0x02]),CALLF(1),POP,STOP]),Code(vec![ADD,RETF])]);
+    let asm = asm.with_types(vec![ty(0,0x80,2),ty(2,1,2)]);
+    assert_eq!(asm.validate_eof(),Ok(()));
+}
+
+#[test]
+fn test_validate_12() {
+    // Declared maximum stack height must match (EOF only)
+    let asm = Assembly::new(vec![Code(vec![PUSH(vec![0x01]),POP,STOP]),Code(vec![ADD,RETF])]);
+    let asm = asm.with_types(vec![ty(0,0x80,2),ty(2,1,3)]);
+    assert_eq!(asm.validate_eof(),Err(vec![(0,ValidationError::InvalidMaxStack(1)),(4,ValidationError::InvalidMaxStack(2))]));
+}
+
+#[test]
+fn test_validate_13() {
+    // Validation terminates for a loop with an unknown bound (EOF only)
+    let asm = Assembly::new(vec![Code(vec![PUSH0,PUSH(vec![0x01]),ADD,DUP(1),CALLDATASIZE,GT,RJUMPI(-9),STOP])]);
+    assert_eq!(asm.validate_eof(),Ok(()));
+}
+
+fn ty(inputs: u8, outputs: u8, max_stack: u16) -> SectionType {
+    SectionType{inputs,outputs,max_stack}
+}
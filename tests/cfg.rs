@@ -1,5 +1,6 @@
-use evmil::bytecode::{Assembly,Instruction,StructuredSection};
-use evmil::analysis::{BlockGraph};
+use std::collections::HashMap;
+use evmil::bytecode::{Assembly,BlockVec,Instruction,StructuredSection};
+use evmil::analysis::{find_unresolved_jumps,find_unresolved_jumps_with_hints,BlockGraph,EdgeOrigin};
 
 #[test]
 fn test_cfg_01() {
@@ -183,6 +184,336 @@ fn test_cfg_15() {
     check_asm(&asm,&[(0,1)]);
 }
 
+// ============================================================================
+// Merging
+// ============================================================================
+
+const MERGE_ASM : &str = r#"
+.code
+   push lab
+   jump
+lab:
+   jumpdest
+   stop
+   jumpdest
+   stop
+"#;
+
+#[test]
+fn test_cfg_merge_01() {
+    let insns = code(MERGE_ASM);
+    let precise = BlockGraph::from(insns.as_slice());
+    let approx = over_approximate(&insns);
+    let merged = precise.merge(&approx).unwrap();
+    let edges : Vec<_> = merged.graph.out_iter().collect();
+    assert_eq!(edges,[(0,1),(0,2)]);
+    assert_eq!(merged.origins,[(0,1,EdgeOrigin::Both),(0,2,EdgeOrigin::Second)]);
+}
+
+#[test]
+fn test_cfg_merge_02() {
+    let insns = code(MERGE_ASM);
+    let precise = BlockGraph::from(insns.as_slice());
+    let approx = over_approximate(&insns);
+    let merged = approx.merge(&precise).unwrap();
+    assert_eq!(merged.origin(0,1),Some(EdgeOrigin::Both));
+    assert_eq!(merged.origin(0,2),Some(EdgeOrigin::First));
+    assert_eq!(merged.origin(1,2),None);
+}
+
+#[test]
+fn test_cfg_merge_03() {
+    let insns = code(MERGE_ASM);
+    let precise = BlockGraph::from(insns.as_slice());
+    let merged = precise.merge(&precise).unwrap();
+    assert_eq!(merged.origins,[(0,1,EdgeOrigin::Both)]);
+}
+
+#[test]
+fn test_cfg_merge_04() {
+    // Graphs with different block boundaries cannot be merged.
+    let insns1 = code(MERGE_ASM);
+    let insns2 = code(".code\n push 0x1\n stop\n");
+    let g1 = BlockGraph::from(insns1.as_slice());
+    let g2 = BlockGraph::from(insns2.as_slice());
+    assert!(g1.merge(&g2).is_none());
+}
+
+// ============================================================================
+// Block Identifiers
+// ============================================================================
+
+const BLOCKS_ASM : &str = r#"
+.code
+   calldatasize
+   push l2
+   jumpi
+   push l1
+   jump
+l1:
+   jumpdest
+   push l2
+   jump
+   push 0x0
+   stop
+l2:
+   jumpdest
+   stop
+"#;
+
+#[test]
+fn test_cfg_ids_01() {
+    let insns = code(BLOCKS_ASM);
+    let cfg = BlockGraph::from(insns.as_slice());
+    let pcs : Vec<_> = (0..cfg.len()).map(|b| cfg.nodes().start_pc(b)).collect();
+    assert_eq!(pcs,[0x00,0x09,0x0e,0x11]);
+}
+
+#[test]
+fn test_cfg_ids_02() {
+    // Graphs are stable across builds
+    let insns = code(BLOCKS_ASM);
+    let cfg1 = BlockGraph::from(insns.as_slice());
+    let cfg2 = BlockGraph::from(insns.as_slice());
+    assert!(cfg1.nodes() == cfg2.nodes());
+    let edges1 : Vec<_> = cfg1.out_iter().collect();
+    let edges2 : Vec<_> = cfg2.out_iter().collect();
+    assert_eq!(edges1,edges2);
+    assert_eq!(edges1,[(0,1),(0,3),(1,3)]);
+}
+
+// ============================================================================
+// Unresolved Jumps
+// ============================================================================
+
+#[test]
+fn test_cfg_unresolved_01() {
+    let insns = code(".code\n push 0x00\n calldataload\n jump\n jumpdest\n push 0x00\n calldataload\n jumpi\n");
+    assert_eq!(find_unresolved_jumps(&insns,usize::MAX),Some(vec![0x03]));
+}
+
+#[test]
+fn test_cfg_unresolved_02() {
+    let insns = code(BLOCKS_ASM);
+    assert_eq!(find_unresolved_jumps(&insns,usize::MAX),Some(vec![]));
+}
+
+#[test]
+fn test_cfg_unresolved_03() {
+    // Jump target depends on calldata
+    let insns = code(".code\n push 0x00\n calldataload\n jump\n jumpdest\n stop\n");
+    let cfg = BlockGraph::from(insns.as_slice());
+    let edges : Vec<_> = cfg.out_iter().collect();
+    assert!(edges.is_empty());
+    assert_eq!(cfg.unresolved_jumps(usize::MAX).unwrap().iter().copied().collect::<Vec<_>>(),[0]);
+}
+
+#[test]
+fn test_cfg_unresolved_04() {
+    // Conditional jump target depends on calldata
+    let insns = code(".code\n push 0x00\n calldataload\n calldatasize\n swap1\n jumpi\n push l1\n jump\nl1:\n jumpdest\n stop\n");
+    let cfg = BlockGraph::from(insns.as_slice());
+    let edges : Vec<_> = cfg.out_iter().collect();
+    assert_eq!(edges,[(0,1)]);
+    assert_eq!(cfg.unresolved_jumps(usize::MAX).unwrap().iter().copied().collect::<Vec<_>>(),[0]);
+}
+
+// ============================================================================
+// Computed Jumps
+// ============================================================================
+
+/// A jump whose target is one of two constants, depending on which
+/// path was taken to reach it.
+const COMPUTED_ASM : &str = r#"
+.code
+   calldatasize
+   push l1
+   jumpi
+   push la
+   push lj
+   jump
+l1:
+   jumpdest
+   push lb
+lj:
+   jumpdest
+   jump
+la:
+   jumpdest
+   stop
+lb:
+   jumpdest
+   push 0x00
+   dup1
+   revert
+"#;
+
+#[test]
+fn test_cfg_computed_01() {
+    let insns = code(COMPUTED_ASM);
+    let cfg = BlockGraph::from(insns.as_slice());
+    let edges : Vec<_> = cfg.out_iter().collect();
+    assert_eq!(edges,[(0,1),(0,2),(1,2),(2,3),(2,4)]);
+    assert!(cfg.unresolved_jumps(usize::MAX).unwrap().is_empty());
+}
+
+#[test]
+fn test_cfg_computed_02() {
+    let insns = code(COMPUTED_ASM);
+    assert_eq!(find_unresolved_jumps(&insns,usize::MAX),Some(vec![]));
+}
+
+#[test]
+fn test_cfg_computed_03() {
+    // Jump target computed from a chain of arithmetic
+    let insns = code(".code\n push 0x04\n push 0x02\n mul\n push 0x03\n xor\n jump\n push 0x00\n jumpdest\n stop\n");
+    let cfg = BlockGraph::from(insns.as_slice());
+    let edges : Vec<_> = cfg.out_iter().collect();
+    assert_eq!(edges,[(0,2),(1,2)]);
+    assert_eq!(find_unresolved_jumps(&insns,usize::MAX),Some(vec![]));
+}
+
+// ============================================================================
+// DOT
+// ============================================================================
+
+#[test]
+fn test_cfg_dot_01() {
+    let insns = code(BLOCKS_ASM);
+    let dot = BlockGraph::from(insns.as_slice()).to_dot();
+    assert!(dot.starts_with("digraph {"));
+    assert_eq!(dot.matches("[label=").count(),4);
+    assert_eq!(dot.matches(" -> ").count(),3);
+    assert!(dot.contains("b1 [label=\"0x0009 (3)\"];"));
+    assert!(dot.contains("b0 -> b1 [style=solid];"));
+    assert!(dot.contains("b0 -> b3 [style=solid];"));
+    assert!(dot.contains("b1 -> b3 [style=solid];"));
+}
+
+#[test]
+fn test_cfg_dot_02() {
+    // Jumping to the following block is not a fall through
+    let insns = code(".code\n push lab\n jump\nlab:\n jumpdest\n stop\n");
+    let dot = BlockGraph::from(insns.as_slice()).to_dot();
+    assert_eq!(dot,"digraph {\n    node [shape=box];\n    b0 [label=\"0x0000 (2)\"];\n    b1 [label=\"0x0004 (2)\"];\n    b0 -> b1 [style=solid];\n}\n");
+    // Falling through into a jumpdest
+    let insns = code(".code\n push 0x01\n jumpdest\n stop\n");
+    let dot = BlockGraph::from(insns.as_slice()).to_dot();
+    assert!(dot.contains("b0 -> b1 [style=dashed];"));
+}
+
+// ============================================================================
+// Jump Hints
+// ============================================================================
+
+const HINTS_ASM : &str = r#"
+.code
+   push 0x00
+   calldataload
+   jump
+l1:
+   jumpdest
+   push l2
+   jump
+   push 0x0
+   stop
+l2:
+   jumpdest
+   stop
+"#;
+
+#[test]
+fn test_cfg_hints_01() {
+    // Without hints, nothing beyond the entry block is discovered
+    let insns = code(HINTS_ASM);
+    let cfg = hinted(&insns,&HashMap::new());
+    let edges : Vec<_> = cfg.out_iter().collect();
+    assert!(edges.is_empty());
+}
+
+#[test]
+fn test_cfg_hints_02() {
+    // Hint gives the edge from the unresolved jump, and the edge from
+    // the hinted block is discovered downstream.
+    let insns = code(HINTS_ASM);
+    let hints = HashMap::from([(0x03,vec![0x04])]);
+    let cfg = hinted(&insns,&hints);
+    let edges : Vec<_> = cfg.out_iter().collect();
+    assert_eq!(edges,[(0,1),(1,3)]);
+}
+
+#[test]
+fn test_cfg_hints_03() {
+    // Hints for resolved jumps are ignored
+    let insns = code(BLOCKS_ASM);
+    let hints = HashMap::from([(0x0d,vec![0x09])]);
+    let cfg = hinted(&insns,&hints);
+    let edges : Vec<_> = cfg.out_iter().collect();
+    assert_eq!(edges,[(0,1),(0,3),(1,3)]);
+}
+
+#[test]
+fn test_cfg_hints_04() {
+    // Hints which are not jump destinations are dropped, including
+    // those in the middle of an instruction or beyond the code.
+    let insns = code(HINTS_ASM);
+    let hints = HashMap::from([(0x03,vec![0x05,0x06,0x04,0x0b,0xff])]);
+    let cfg = hinted(&insns,&hints);
+    let edges : Vec<_> = cfg.out_iter().collect();
+    assert_eq!(edges,[(0,1),(1,3)]);
+}
+
+#[test]
+fn test_cfg_hints_05() {
+    // Jumps resolved by hints are not reported as unresolved
+    let insns = code(HINTS_ASM);
+    let hints = HashMap::from([(0x03,vec![0x04])]);
+    let cfg = hinted(&insns,&hints);
+    assert_eq!(cfg.unresolved_jumps(usize::MAX).unwrap().iter().copied().collect::<Vec<_>>(),[0]);
+    assert!(cfg.unresolved_jumps_with_hints(usize::MAX,&hints).unwrap().is_empty());
+    assert_eq!(find_unresolved_jumps_with_hints(&insns,usize::MAX,&hints),Some(vec![]));
+}
+
+#[test]
+fn test_cfg_hints_06() {
+    // Jumps whose hints are all invalid remain unresolved
+    let insns = code(HINTS_ASM);
+    let hints = HashMap::from([(0x03,vec![0x05,0xff])]);
+    assert_eq!(find_unresolved_jumps_with_hints(&insns,usize::MAX,&hints),Some(vec![0x03]));
+}
+
+fn hinted<'a>(insns: &'a [Instruction], hints: &HashMap<usize,Vec<usize>>) -> BlockGraph<'a> {
+    match BlockGraph::from_blocks_with_hints(BlockVec::new(insns),usize::MAX,hints) {
+        Ok(cfg) => cfg,
+        Err(_) => panic!("analysis did not complete")
+    }
+}
+
+fn code(asm: &str) -> Vec<Instruction> {
+    let assembly = Assembly::from_str(asm).unwrap();
+    match assembly.iter().next() {
+        Some(StructuredSection::Code(insns)) => insns.clone(),
+        _ => panic!("expected code section")
+    }
+}
+
+/// Construct an over-approximate graph where every jump may reach
+/// every jumpdest.
+fn over_approximate(insns: &[Instruction]) -> BlockGraph<'_> {
+    let mut graph = BlockGraph::from(insns);
+    let n = graph.len();
+    for b in 0..n {
+        if graph.get(b).last() == Some(&Instruction::JUMP) {
+            for c in 0..n {
+                if graph.get(c).first() == Some(&Instruction::JUMPDEST) {
+                    graph.connect(b,c);
+                }
+            }
+        }
+    }
+    graph
+}
+
 fn check_asm(asm: &str, blocks: &[(usize,usize)]) {
     // Convert assembly into instructions
     let assembly = Assembly::from_str(&asm).unwrap();
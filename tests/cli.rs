@@ -0,0 +1,383 @@
+use std::process::{Command,Output};
+use evmil::bytecode::Assembly;
+use evmil::util::ToHexString;
+
+// ============================================================================
+// Dispatch
+// ============================================================================
+
+#[test]
+fn test_dispatch_01() {
+    let asm = r#"
+.code
+   push 0x00
+   calldataload
+   push 0xe0
+   shr
+   dup1
+   push 0x60fe47b1
+   eq
+   push set
+   jumpi
+   dup1
+   push 0x6d4ce63c
+   eq
+   push get
+   jumpi
+   push 0x00
+   dup1
+   revert
+set:
+   jumpdest
+   push 0x04
+   calldataload
+   push 0x00
+   sstore
+   stop
+get:
+   jumpdest
+   push 0x00
+   sload
+   push 0x00
+   mstore
+   push 0x20
+   push 0x00
+   return
+"#;
+    let out = disassemble(asm,&["--dispatch"]);
+    assert_eq!(out, "0x60fe47b1 _0x0020 set(uint256)\n0x6d4ce63c _0x0028 get()\n");
+}
+
+#[test]
+fn test_dispatch_02() {
+    let asm = r#"
+.code
+   push 0x00
+   calldataload
+   push 0x00
+   sstore
+   stop
+"#;
+    let out = disassemble(asm,&["--dispatch"]);
+    assert_eq!(out, "No dispatch table found\n");
+}
+
+// ============================================================================
+// Heights
+// ============================================================================
+
+#[test]
+fn test_heights_01() {
+    let asm = r#"
+.code
+   push 0x01
+   push lab
+   jump
+lab:
+   jumpdest
+   stop
+"#;
+    let out = disassemble(asm,&["--heights"]);
+    assert_eq!(out, ".code\n\tpush 0x01\n\tpush 0x0006\n\tjump\n_0x0006: ;; height=[1]\n\tjumpdest\n\tstop\n");
+}
+
+// ============================================================================
+// Program Counters
+// ============================================================================
+
+#[test]
+fn test_pc_01() {
+    // Labels reflect the true byte offsets of jumpdests following
+    // pushes of various sizes.
+    let w = "0x0102030405060708091011121314151617181920212223242526272829303132";
+    let asm = format!(".code\n push 0x01\n jumpdest\n push 0x0102\n jumpdest\n push {w}\n jumpdest\n push0\n jumpdest\n");
+    let out = disassemble(&asm,&[]);
+    let labels : Vec<&str> = out.lines().filter(|l| l.starts_with('_')).collect();
+    assert_eq!(labels,["_0x0002:","_0x0006:","_0x0028:","_0x002a:"]);
+}
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+#[test]
+fn test_constants_01() {
+    let asm = r#"
+.code
+   push 0x01
+   push 0xa0
+   shl
+   push 0x01
+   or
+   pop
+"#;
+    let out = disassemble(asm,&["--constants"]);
+    assert_eq!(out, ".code\n\tpush 0x01\n\tpush 0xa0\n\tshl\n\tpush 0x01\n\tor // = 0x10000000000000000000000000000000000000001\n\tpop\n");
+}
+
+// ============================================================================
+// Signatures
+// ============================================================================
+
+#[test]
+fn test_signatures_01() {
+    let asm = r#"
+.code
+   push 0x01
+   push lab
+   jump
+lab:
+   jumpdest
+   stop
+"#;
+    let out = disassemble(asm,&["--signatures"]);
+    assert_eq!(out, "pc entry exit\n0x0000 [0] [1]\n0x0006 [1] []\n");
+}
+
+// ============================================================================
+// Complexity
+// ============================================================================
+
+#[test]
+fn test_complexity_01() {
+    let asm = r#"
+.code
+   push 0x00
+   calldataload
+   push 0xe0
+   shr
+   dup1
+   push 0x60fe47b1
+   eq
+   push set
+   jumpi
+   dup1
+   push 0x6d4ce63c
+   eq
+   push get
+   jumpi
+   push 0x00
+   dup1
+   revert
+set:
+   jumpdest
+   push 0x04
+   calldataload
+   push set_end
+   jumpi
+   push 0x00
+   sstore
+   stop
+set_end:
+   jumpdest
+   stop
+get:
+   jumpdest
+   push 0x00
+   sload
+   push 0x00
+   mstore
+   push 0x20
+   push 0x00
+   return
+"#;
+    let out = disassemble(asm,&["--complexity"]);
+    assert_eq!(out, "contract 4\n0x60fe47b1 _0x0020 set(uint256) 2\n0x6d4ce63c _0x002e get() 1\n");
+}
+
+#[test]
+fn test_complexity_02() {
+    // Constructing the block graph should not write anything to
+    // stdout, other than the requested output.
+    let asm = r#"
+.code
+   calldatasize
+   push l2
+   jumpi
+   push l1
+   jump
+l1:
+   jumpdest
+   push l2
+   jump
+l2:
+   jumpdest
+   stop
+"#;
+    let out = disassemble(asm,&["--complexity"]);
+    assert_eq!(out, "contract 2\n");
+}
+
+#[test]
+#[cfg(feature = "keccak")]
+fn test_debug_01() {
+    // Hashing, calls and logging are all traced through
+    let asm = r#"
+.code
+   push 0x20
+   push 0x00
+   keccak256
+   push 0x00
+   push 0x00
+   push 0x00
+   push 0x00
+   push 0x00
+   caller
+   gas
+   call
+   push 0x00
+   push 0x00
+   push 0x00
+   log2
+   stop
+"#;
+    let out = disassemble(asm,&["--debug"]);
+    assert!(out.ends_with("\t;; |0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563||\n\tstop\n"));
+}
+
+#[test]
+fn test_domain_01() {
+    let asm = ".code\n push 0x00\n calldataload\n push 0x0f\n and\n stop\n";
+    let out = disassemble(asm,&["--debug","--domain","interval"]);
+    assert!(out.contains(";; |0..15||"));
+    let out = disassemble(asm,&["--debug"]);
+    assert!(out.contains(";; |??||"));
+}
+
+#[test]
+fn test_domain_02() {
+    let output = run_disassemble(".code\n stop\n",&["--debug","--domain","foo"]);
+    assert!(!output.status.success());
+}
+
+// ============================================================================
+// Forks
+// ============================================================================
+
+#[test]
+fn test_fork_01() {
+    let asm = ".code\n difficulty\n";
+    assert_eq!(disassemble(asm,&[]), ".code\n\tdifficulty\n");
+    assert_eq!(disassemble(asm,&["--fork","london"]), ".code\n\tdifficulty\n");
+    assert_eq!(disassemble(asm,&["--fork","paris"]), ".code\n\tprevrandao\n");
+}
+
+// ============================================================================
+// JSON
+// ============================================================================
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_json_01() {
+    let asm = r#"
+.code
+   push 0x01
+   push lab
+   jump
+lab:
+   jumpdest
+   stop
+.data
+   0x1234
+"#;
+    let out = disassemble(asm,&["--json"]);
+    let json : serde_json::Value = serde_json::from_str(&out).unwrap();
+    let expected = serde_json::json!([
+        {"pc": 0, "opcode": 0x60, "mnemonic": "push", "operand": "0x01"},
+        {"pc": 2, "opcode": 0x61, "mnemonic": "push", "operand": "0x0006"},
+        {"pc": 5, "opcode": 0x56, "mnemonic": "jump"},
+        {"pc": 6, "opcode": 0x5b, "mnemonic": "jumpdest", "heights": [1]},
+        {"pc": 7, "opcode": 0x00, "mnemonic": "stop"},
+        {"pc": 8, "data": "0x1234"}
+    ]);
+    assert_eq!(json,expected);
+}
+
+// ============================================================================
+// Assemble
+// ============================================================================
+
+#[test]
+fn test_assemble_01() {
+    let asm = "PUSH1 0x80\nPUSH1 64 // free memory pointer\nMSTORE\nPUSH lab\nJUMP\nlab:\nJUMPDEST\nSTOP\n";
+    let hex = assemble(asm,"test_assemble_01.asm");
+    assert_eq!(hex,"0x6080604052610009565b00");
+    // Round trip through the disassembler
+    let out = disassemble(asm,&[]);
+    assert_eq!(out,".code\n\tpush 0x80\n\tpush 0x40\n\tmstore\n\tpush 0x0009\n\tjump\n_0x0009:\n\tjumpdest\n\tstop\n");
+}
+
+// ============================================================================
+// Resolved Jumps
+// ============================================================================
+
+#[test]
+fn test_require_resolved_01() {
+    let asm = ".code\n push 0x00\n calldataload\n jump\n jumpdest\n stop\n";
+    let output = run_disassemble(asm,&["--require-resolved"]);
+    assert!(!output.status.success());
+    let err = String::from_utf8(output.stderr).unwrap();
+    assert!(err.contains("unresolved jump(s) at 0x0003"),"{err}");
+}
+
+#[test]
+fn test_require_resolved_02() {
+    let asm = ".code\n push lab\n jump\nlab:\n jumpdest\n stop\n";
+    let out = disassemble(asm,&["--require-resolved"]);
+    assert_eq!(out, ".code\n\tpush 0x0004\n\tjump\n_0x0004:\n\tjumpdest\n\tstop\n");
+}
+
+#[test]
+fn test_strict_01() {
+    let asm = ".code\n calldatasize\n push lab\n jumpi\n push 0x01\nlab:\n jumpdest\n stop\n";
+    let output = run_disassemble(asm,&["--strict"]);
+    assert!(!output.status.success());
+    let err = String::from_utf8(output.stderr).unwrap();
+    assert!(err.contains("stack height mismatch(es) at 0x0007 (0 vs 1)"),"{err}");
+}
+
+#[test]
+fn test_strict_02() {
+    let asm = ".code\n push lab\n jump\nlab:\n jumpdest\n stop\n";
+    let out = disassemble(asm,&["--strict"]);
+    assert_eq!(out, ".code\n\tpush 0x0004\n\tjump\n_0x0004:\n\tjumpdest\n\tstop\n");
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Assemble a given assembly program, and then disassemble it using
+/// the command-line tool with the given flags, returning the output.
+fn disassemble(asm: &str, flags: &[&str]) -> String {
+    let output = run_disassemble(asm,flags);
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Assemble a given assembly program, and then run the disassembler
+/// on it with the given flags, returning the raw output.
+fn run_disassemble(asm: &str, flags: &[&str]) -> Output {
+    let hex = Assembly::from_str(asm).unwrap().to_legacy_bytes().to_hex_string();
+    Command::new(env!("CARGO_BIN_EXE_evmil"))
+        .arg("disassemble")
+        .args(flags)
+        .arg("--code")
+        .arg(hex)
+        .output()
+        .unwrap()
+}
+
+/// Assemble a given assembly program using the command-line tool,
+/// returning the resulting hex string.
+fn assemble(asm: &str, name: &str) -> String {
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path,asm).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_evmil"))
+        .arg("asm")
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
@@ -1,9 +1,13 @@
 use std::fs;
 use std::path::{PathBuf};
-use evmil::bytecode::{Assembly};
-use evmil::il::Parser;
+use evmil::analysis::{aw256,execute,trace,trace_with_config,TraceConfig,ConcreteMemory,ConcreteStack,ConcreteState,Outcome,UnknownStorage};
+use evmil::bytecode::{Assembly,Disassemble,Instruction};
+use evmil::bytecode::Instruction::*;
+use evmil::il::{fold_constants,CompilerError,Parser,Term};
 use evmil::util::{FromHexString};
 
+type State = ConcreteState<ConcreteStack<aw256>,ConcreteMemory<aw256>,UnknownStorage<aw256>>;
+
 pub static TESTS_DIR: &str = "tests/files";
 
 // Include the programmatically generated test file.
@@ -44,3 +48,430 @@ fn to_binfile(test: &str) -> PathBuf {
     path.set_extension("bin");
     path
 }
+
+// ============================================================================
+// Short Circuiting
+// ============================================================================
+
+#[test]
+fn test_short_circuit_01() {
+    // Left-hand side is false, so right-hand side is never evaluated.
+    let states = check_rhs("assert (2 < 1) && (calldata[0] < 3);");
+    assert!(states.is_empty());
+}
+
+#[test]
+fn test_short_circuit_02() {
+    // Left-hand side is true, so right-hand side is evaluated.
+    let states = check_rhs("assert (1 < 2) && (calldata[0] < 3);");
+    assert_eq!(states.len(),1);
+}
+
+#[test]
+fn test_short_circuit_03() {
+    // Left-hand side is true, so right-hand side is never evaluated.
+    let states = check_rhs("assert (1 < 2) || (calldata[0] < 3);");
+    assert!(states.is_empty());
+}
+
+#[test]
+fn test_short_circuit_04() {
+    // Left-hand side is false, so right-hand side is evaluated.
+    let states = check_rhs("assert (2 < 1) || (calldata[0] < 3);");
+    assert_eq!(states.len(),1);
+}
+
+#[test]
+fn test_short_circuit_05() {
+    // Short circuiting in a value (rather than conditional) context
+    assert!(check_rhs("memory[0] = (2 < 1) && (calldata[0] < 3);").is_empty());
+    assert_eq!(check_rhs("memory[0] = (1 < 2) && (calldata[0] < 3);").len(),1);
+    assert!(check_rhs("memory[0] = (1 < 2) || (calldata[0] < 3);").is_empty());
+    assert_eq!(check_rhs("memory[0] = (2 < 1) || (calldata[0] < 3);").len(),1);
+}
+
+#[test]
+fn test_short_circuit_06() {
+    // Negation swaps branch targets without losing short circuiting
+    assert!(check_rhs("assert !((1 < 2) || (calldata[0] < 3));").is_empty());
+    assert_eq!(check_rhs("assert !((2 < 1) || (calldata[0] < 3));").len(),1);
+    assert!(check_rhs("assert !((2 < 1) && (calldata[0] < 3));").is_empty());
+}
+
+// ============================================================================
+// Operators
+// ============================================================================
+
+/// Each operator expression (over `x`), along with an argument for
+/// which it holds and one for which it does not.
+static OPERATORS: &[(&str,u8,u8)] = &[
+    ("x < 3",2,3),
+    ("x <= 3",3,4),
+    ("x > 3",4,3),
+    ("x >= 3",3,2),
+    ("x == 3",3,2),
+    ("x != 3",2,3),
+    ("!x",0,1),
+    ("!(x == 3)",2,3),
+    ("!!x",7,0),
+    ("(x > 1) && (x < 4)",3,4),
+    ("(x > 1) && (x < 4)",2,1),
+    ("(x < 2) || (x > 4)",1,2),
+    ("(x < 2) || (x > 4)",5,4),
+    ("!((x < 2) || (x > 4))",3,5),
+];
+
+#[test]
+fn test_operators_01() {
+    // Operators in a conditional context
+    for (expr,t,f) in OPERATORS {
+        let eil = format!("let x = calldata[0];\nif {expr} {{ fail; }}\nstop;");
+        assert_eq!(check_branch(&eil,*t),(true,false),"{expr}");
+        assert_eq!(check_branch(&eil,*f),(false,true),"{expr}");
+    }
+}
+
+#[test]
+fn test_operators_02() {
+    // Operators in a value context
+    for (expr,t,f) in OPERATORS {
+        let eil = format!("let x = calldata[0];\nlet r = {expr};\nif r == 1 {{ fail; }}\nif r != 0 {{ fail; }}\nstop;");
+        assert_eq!(check_branch(&eil,*t),(true,false),"{expr}");
+        assert_eq!(check_branch(&eil,*f),(false,true),"{expr}");
+    }
+}
+
+#[test]
+fn test_operators_03() {
+    // Synthesised comparisons
+    assert_eq!(fold("memory[0] = calldata[0] <= 1;"),[PUSH(vec![0x01]),PUSH(vec![0x00]),CALLDATALOAD,GT,ISZERO,PUSH(vec![0x00]),MSTORE]);
+    assert_eq!(fold("memory[0] = calldata[0] >= 1;"),[PUSH(vec![0x01]),PUSH(vec![0x00]),CALLDATALOAD,LT,ISZERO,PUSH(vec![0x00]),MSTORE]);
+    assert_eq!(fold("memory[0] = !calldata[0];"),[PUSH(vec![0x00]),CALLDATALOAD,ISZERO,PUSH(vec![0x00]),MSTORE]);
+}
+
+// ============================================================================
+// Instructions
+// ============================================================================
+
+#[test]
+fn test_instructions_01() {
+    let terms = Parser::new("memory[0+1] = 2;").parse().unwrap();
+    let asm = Assembly::try_from(terms.as_slice()).unwrap();
+    let expected = vec![PUSH(vec![0x02]),PUSH(vec![0x01]),PUSH(vec![0x00]),ADD,MSTORE];
+    assert!(asm.instructions().eq(expected.iter()));
+    assert_eq!(asm.into_instructions(),expected);
+}
+
+#[test]
+fn test_instructions_02() {
+    // Data sections are ignored
+    let asm = Assembly::from_str(".code\n stop\n.data\n 0x01\n.code\n invalid\n").unwrap();
+    assert_eq!(asm.instructions().count(),2);
+    assert_eq!(asm.into_instructions(),[STOP,INVALID]);
+}
+
+// ============================================================================
+// Constant Folding
+// ============================================================================
+
+#[test]
+fn test_fold_01() {
+    assert_eq!(fold("memory[0] = (1+2)*3;"),[PUSH(vec![0x09]),PUSH(vec![0x00]),MSTORE]);
+}
+
+#[test]
+fn test_fold_02() {
+    // Operands are ordered correctly
+    assert_eq!(fold("memory[0] = 0x10 - 1;"),[PUSH(vec![0x0f]),PUSH(vec![0x00]),MSTORE]);
+    assert_eq!(fold("memory[0] = 7 / 2;"),[PUSH(vec![0x03]),PUSH(vec![0x00]),MSTORE]);
+    assert_eq!(fold("memory[0] = 7 % 0;"),[PUSH(vec![0x00]),PUSH(vec![0x00]),MSTORE]);
+}
+
+#[test]
+fn test_fold_03() {
+    // Subtraction wraps around
+    assert_eq!(fold("memory[0] = 0 - 1;"),[PUSH(vec![0xff;32]),PUSH(vec![0x00]),MSTORE]);
+    // Addition wraps around
+    let max = format!("0x{}","f".repeat(64));
+    assert_eq!(fold(&format!("memory[0] = {max} + 2;")),[PUSH(vec![0x01]),PUSH(vec![0x00]),MSTORE]);
+}
+
+#[test]
+fn test_fold_04() {
+    // Non-constant subtrees are left alone
+    assert_eq!(fold("memory[0] = calldata[1+1] + (2*2);"),[PUSH(vec![0x04]),PUSH(vec![0x02]),CALLDATALOAD,ADD,PUSH(vec![0x00]),MSTORE]);
+}
+
+// ============================================================================
+// If / Else
+// ============================================================================
+
+#[test]
+fn test_ifelse_01() {
+    let eil = "if (calldata[0] == 1) { fail; } else { stop; }";
+    assert_eq!(check_branch(eil,1),(true,false));
+    assert_eq!(check_branch(eil,2),(false,true));
+}
+
+#[test]
+fn test_ifelse_02() {
+    // Empty else falls through
+    let eil = "if (calldata[0] == 1) { fail; }\nstop;";
+    assert_eq!(check_branch(eil,1),(true,false));
+    assert_eq!(check_branch(eil,0),(false,true));
+}
+
+#[test]
+fn test_ifelse_03() {
+    // Both blocks fall through to the join point
+    let eil = "if (calldata[0] < 3) {\n memory[0] = 1;\n} else {\n memory[0] = 2;\n}\nif (memory[0] == 1) { fail; }\nstop;";
+    assert_eq!(check_branch(eil,2),(true,false));
+    assert_eq!(check_branch(eil,3),(false,true));
+}
+
+#[test]
+fn test_ifelse_04() {
+    // Short circuiting conditions
+    let eil = "if (calldata[0] > 1) && (calldata[0] < 4) { fail; } else { stop; }";
+    assert_eq!(check_branch(eil,1),(false,true));
+    assert_eq!(check_branch(eil,2),(true,false));
+    assert_eq!(check_branch(eil,4),(false,true));
+}
+
+#[test]
+fn test_ifelse_05() {
+    // Empty blocks
+    let terms = Parser::new("if 1 {} else {}").parse().unwrap();
+    let asm = Assembly::try_from(terms.as_slice()).unwrap();
+    assert_eq!(asm.into_instructions().last(),Some(&JUMPDEST));
+    // Conditional branches are unaffected
+    assert!(Parser::new("if 1 goto l;").parse().is_ok());
+    // Unterminated block
+    assert!(Parser::new("if 1 { stop;").parse().is_err());
+}
+
+// ============================================================================
+// While Loops
+// ============================================================================
+
+static COUNTDOWN: &str = "memory[0] = calldata[0];\nwhile memory[0] != 0 {\n memory[0] = memory[0] - 1;\n}\nif memory[0] != 0 { fail; }\nstop;";
+
+#[test]
+fn test_while_01() {
+    assert_eq!(check_branch(COUNTDOWN,0),(false,true));
+    assert_eq!(check_branch(COUNTDOWN,1),(false,true));
+    assert_eq!(check_branch(COUNTDOWN,10),(false,true));
+}
+
+#[test]
+fn test_while_02() {
+    // Sufficient gas to complete
+    assert_eq!(check_exits(COUNTDOWN,data(5),Some(1000)),(false,true));
+    // Insufficient gas to complete
+    assert_eq!(check_exits(COUNTDOWN,data(5),Some(100)),(false,false));
+}
+
+#[test]
+fn test_while_03() {
+    // Back edge lands on a JUMPDEST
+    let terms = Parser::new("while calldata[0] { memory[0] = 1; }").parse().unwrap();
+    let insns = Assembly::try_from(terms.as_slice()).unwrap().into_instructions();
+    assert_eq!(insns[0],JUMPDEST);
+    assert_eq!(&insns[insns.len()-3..],[PUSH(vec![0x00,0x00]),JUMP,JUMPDEST]);
+}
+
+#[test]
+fn test_while_04() {
+    // Nested loops
+    let eil = "memory[0] = calldata[0];\nmemory[32] = 0;\nwhile memory[0] != 0 {\n memory[0] = memory[0] - 1;\n memory[64] = 3;\n while memory[64] > 0 {\n  memory[64] = memory[64] - 1;\n  memory[32] = memory[32] + 1;\n }\n}\nif memory[32] == 12 { fail; }\nstop;";
+    assert_eq!(check_branch(eil,4),(true,false));
+    assert_eq!(check_branch(eil,3),(false,true));
+}
+
+#[test]
+fn test_while_05() {
+    // Loops over memory-backed locals converge under widening
+    let eil = "let i = 0;\nwhile i < calldata[0] {\n i = i + 1;\n}\nstop;";
+    let terms = Parser::new(eil).parse().unwrap();
+    let insns : Vec<Instruction> = Assembly::try_from(terms.as_slice()).unwrap().to_legacy_bytes().disassemble();
+    let config = TraceConfig::new().with_limit(50_000);
+    assert!(trace_with_config::<Vec<State>>(&insns,State::new(),&config).is_err());
+    let config = config.with_widening(3);
+    let states : Vec<Vec<State>> = trace_with_config(&insns,State::new(),&config).unwrap();
+    assert!(!states[insns.len()-1].is_empty());
+}
+
+// ============================================================================
+// Local Variables
+// ============================================================================
+
+#[test]
+fn test_let_01() {
+    // Variable read after intervening expression pushes onto stack
+    let eil = "let x = calldata[0];\nif (2 * 3) + x != 7 { fail; }\nstop;";
+    assert_eq!(check_branch(eil,1),(false,true));
+    assert_eq!(check_branch(eil,2),(true,false));
+}
+
+#[test]
+fn test_let_02() {
+    // Multiple variables occupy distinct slots
+    let eil = "let x = calldata[0];\nlet y = x + 1;\nmemory[0] = 5;\nif (x * 2) + y != memory[0] { fail; }\nstop;";
+    assert_eq!(check_branch(eil,1),(true,false));
+    assert_eq!(check_branch(eil,2),(true,false));
+    assert_eq!(check_branch(eil,0),(true,false));
+    let eil = "let x = calldata[0];\nlet y = x + 1;\nif (x * 2) + y != 4 { fail; }\nstop;";
+    assert_eq!(check_branch(eil,1),(false,true));
+}
+
+#[test]
+fn test_let_03() {
+    // Variables can be assigned and used in loops
+    let eil = "let i = calldata[0];\nlet total = 0;\nwhile i != 0 {\n total = total + i;\n i = i - 1;\n}\nif total != 15 { fail; }\nstop;";
+    assert_eq!(check_branch(eil,5),(false,true));
+    assert_eq!(check_branch(eil,4),(true,false));
+}
+
+#[test]
+fn test_let_04() {
+    // Frame layout
+    let terms = Parser::new("let x = 1;\nlet y = x;").parse().unwrap();
+    let insns = Assembly::try_from(terms.as_slice()).unwrap().into_instructions();
+    assert_eq!(insns,[PUSH(vec![0x01]),PUSH(vec![0x80]),MSTORE,PUSH(vec![0x80]),MLOAD,PUSH(vec![0xa0]),MSTORE]);
+}
+
+#[test]
+fn test_let_05() {
+    // Undeclared variables
+    for eil in ["memory[0] = x;", "x = 1;", "let x = x;"] {
+        let terms = Parser::new(eil).parse().unwrap();
+        assert!(matches!(Assembly::try_from(terms.as_slice()),Err(CompilerError::UnknownVariable(_))));
+    }
+}
+
+#[test]
+fn test_let_06() {
+    // Memory holding local variables is reserved
+    for eil in ["let x = 1;\nmemory[0x80] = 2;", "let x = 1;\nlet y = memory[0x80+0x20];",
+                "let x = 1;\nmemory[0x61] = 2;", "memory[0x80] = 2;\nlet x = 1;",
+                "let x = 1;\nsucceed 1,2,3,4,x;"] {
+        let terms = Parser::new(eil).parse().unwrap();
+        assert!(matches!(Assembly::try_from(terms.as_slice()),Err(CompilerError::LocalMemoryAccess)),"{eil}");
+    }
+    // Memory outside the frame is not
+    for eil in ["let x = 1;\nmemory[0x60] = 2;", "let x = 1;\nmemory[0xa0] = 2;",
+                "memory[0xc0] = 2;\nlet x = 1;\nlet y = 2;", "memory[0x80] = 2;",
+                "let x = 1;\nsucceed 1,2,3,4;"] {
+        let terms = Parser::new(eil).parse().unwrap();
+        assert!(Assembly::try_from(terms.as_slice()).is_ok(),"{eil}");
+    }
+    let terms = Parser::new("memory[0xa0] = 2;\nlet x = 1;\nlet y = 2;").parse().unwrap();
+    assert!(matches!(Assembly::try_from(terms.as_slice()),Err(CompilerError::LocalMemoryAccess)));
+}
+
+// ============================================================================
+// Bytes Literals
+// ============================================================================
+
+#[test]
+fn test_bytes_01() {
+    assert_eq!(check_data("succeed \"hi\";"),Some(b"hi".to_vec()));
+    assert_eq!(check_data("revert \"hi\";"),Some(b"hi".to_vec()));
+}
+
+#[test]
+fn test_bytes_02() {
+    assert_eq!(check_data("succeed hex\"00ff10\";"),Some(vec![0x00,0xff,0x10]));
+    assert_eq!(check_data("succeed hex\"\";"),Some(vec![]));
+    assert_eq!(check_data("succeed \"\";"),Some(vec![]));
+}
+
+#[test]
+fn test_bytes_03() {
+    // Literals spanning multiple words
+    let text = "The quick brown fox jumps over the lazy dog";
+    assert_eq!(check_data(&format!("succeed \"{text}\";")),Some(text.as_bytes().to_vec()));
+    let zeros = "00".repeat(64);
+    assert_eq!(check_data(&format!("revert hex\"{zeros}\";")),Some(vec![0;64]));
+}
+
+#[test]
+fn test_bytes_04() {
+    // Literal materialised into memory as (offset,length)
+    let terms = Parser::new("succeed \"hi\";").parse().unwrap();
+    let insns = Assembly::try_from(terms.as_slice()).unwrap().into_instructions();
+    let mut word = vec![0x68,0x69];
+    word.resize(32,0);
+    assert_eq!(insns,[PUSH(word),PUSH(vec![0x00]),MSTORE,PUSH(vec![0x02]),PUSH(vec![0x00]),RETURN]);
+}
+
+#[test]
+fn test_bytes_05() {
+    // Bytes literals cannot be used as words
+    for eil in ["memory[0] = \"hi\";", "succeed \"hi\", 1;", "let x = hex\"01\";"] {
+        let terms = Parser::new(eil).parse().unwrap();
+        assert!(matches!(Assembly::try_from(terms.as_slice()),Err(CompilerError::InvalidBytesLiteral)));
+    }
+}
+
+/// Compile a given EvmIL program after constant folding, returning
+/// the resulting instructions.
+fn fold(eil: &str) -> Vec<Instruction> {
+    let terms : Vec<Term> = Parser::new(eil).parse().unwrap().iter().map(fold_constants).collect();
+    Assembly::try_from(terms.as_slice()).unwrap().into_instructions()
+}
+
+/// Compile a given EvmIL program, trace its execution and return the
+/// states reaching the (only) `CALLDATALOAD`, which is assumed to
+/// belong to the right-hand side of a logical connective.
+fn check_rhs(eil: &str) -> Vec<State> {
+    let terms = Parser::new(eil).parse().unwrap();
+    let bytes = Assembly::try_from(terms.as_slice()).unwrap().to_legacy_bytes();
+    let insns : Vec<Instruction> = bytes.disassemble();
+    let mut states = trace(&insns,State::new(),usize::MAX).unwrap();
+    let index = insns.iter().position(|i| *i == Instruction::CALLDATALOAD).unwrap();
+    states.swap_remove(index)
+}
+
+/// Compile a given EvmIL program and execute it with calldata whose
+/// first word is `arg`, returning whether a `REVERT` and/or `STOP`
+/// is reached.
+fn check_branch(eil: &str, arg: u8) -> (bool,bool) {
+    check_exits(eil,data(arg),None)
+}
+
+/// Compile a given EvmIL program and execute it with the given
+/// calldata and (optional) gas bound, returning whether a `REVERT`
+/// and/or `STOP` is reached.
+fn check_exits(eil: &str, calldata: [u8;32], gas: Option<u64>) -> (bool,bool) {
+    let terms = Parser::new(eil).parse().unwrap();
+    let bytes = Assembly::try_from(terms.as_slice()).unwrap().to_legacy_bytes();
+    let insns : Vec<Instruction> = bytes.disassemble();
+    let mut init = State::new().with_calldata(&calldata);
+    if let Some(g) = gas { init = init.with_gas(g); }
+    let states : Vec<Vec<State>> = trace(&insns,init,usize::MAX).unwrap();
+    let reached = |insn| insns.iter().zip(&states).any(|(i,s)| *i == insn && !s.is_empty());
+    (reached(REVERT),reached(STOP))
+}
+
+/// Construct calldata whose first word is `arg`.
+fn data(arg: u8) -> [u8;32] {
+    let mut data = [0u8;32];
+    data[31] = arg;
+    data
+}
+
+/// Compile a given EvmIL program, execute it and return the data
+/// produced by the (only) `RETURN` or `REVERT` reached.
+fn check_data(eil: &str) -> Option<Vec<u8>> {
+    let terms = Parser::new(eil).parse().unwrap();
+    let bytes = Assembly::try_from(terms.as_slice()).unwrap().to_legacy_bytes();
+    let insns : Vec<Instruction> = bytes.disassemble();
+    let mut states : Vec<Vec<State>> = trace(&insns,State::new(),usize::MAX).unwrap();
+    let index = insns.iter().position(|i| *i == RETURN || *i == REVERT).unwrap();
+    let st = states.swap_remove(index).pop().unwrap();
+    match execute(&insns[index],st) {
+        Outcome::Return{data,..} | Outcome::Revert{data,..} => data,
+        _ => None
+    }
+}
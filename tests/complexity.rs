@@ -0,0 +1,105 @@
+use evmil::analysis::{block_complexity,function_complexity,BlockGraph};
+use evmil::bytecode::{Assembly,Disassemble,Instruction};
+use evmil::util::{connected_components,cyclomatic_complexity,cyclomatic_complexity_from,Digraph};
+
+const NODES :&[usize] = &[0,1,2,3,4,5];
+
+// ============================================================================
+// Graphs
+// ============================================================================
+
+#[test]
+fn test_components_01() {
+    assert_eq!(connected_components(&from_edges(&[])),6);
+    assert_eq!(connected_components(&from_edges(&[(0,1),(2,1),(3,4)])),3);
+}
+
+#[test]
+fn test_complexity_01() {
+    // Straight line: 5 - 6 + 2
+    let g = from_edges(&[(0,1),(1,2),(2,3),(3,4),(4,5)]);
+    assert_eq!(cyclomatic_complexity(&g),1);
+}
+
+#[test]
+fn test_complexity_02() {
+    // If-then-else: 6 - 6 + 2
+    let g = from_edges(&[(0,1),(0,2),(1,3),(2,3),(3,4),(4,5)]);
+    assert_eq!(cyclomatic_complexity(&g),2);
+}
+
+#[test]
+fn test_complexity_03() {
+    // Loop with nested branch: 7 - 6 + 2
+    let g = from_edges(&[(0,1),(1,2),(1,3),(2,4),(3,4),(4,1),(4,5)]);
+    assert_eq!(cyclomatic_complexity(&g),3);
+}
+
+#[test]
+fn test_complexity_04() {
+    // Two disjoint functions, each with one branch: 6 - 6 + 2*2
+    let g = from_edges(&[(0,1),(0,2),(1,2),(3,4),(3,5),(4,5)]);
+    assert_eq!(cyclomatic_complexity(&g),4);
+    assert_eq!(cyclomatic_complexity_from(&g,0),2);
+    assert_eq!(cyclomatic_complexity_from(&g,3),2);
+}
+
+// ============================================================================
+// Blocks
+// ============================================================================
+
+#[test]
+fn test_block_complexity_01() {
+    // Two conditional branches, with everything terminating.
+    let asm = r#"
+.code
+   calldatasize
+   push l1
+   jumpi
+   callvalue
+   push l2
+   jumpi
+   stop
+l1:
+   jumpdest
+   stop
+l2:
+   jumpdest
+   push l1
+   jump
+"#;
+    let insns = disassemble(asm);
+    let graph = BlockGraph::from(insns.as_slice());
+    assert_eq!(block_complexity(&graph),3);
+    assert_eq!(function_complexity(&graph,2),1);
+    assert_eq!(function_complexity(&graph,0),3);
+}
+
+#[test]
+fn test_block_complexity_02() {
+    // A loop
+    let asm = r#"
+.code
+lab:
+   jumpdest
+   calldatasize
+   push lab
+   jumpi
+   stop
+"#;
+    let insns = disassemble(asm);
+    let graph = BlockGraph::from(insns.as_slice());
+    assert_eq!(block_complexity(&graph),2);
+}
+
+fn disassemble(asm: &str) -> Vec<Instruction> {
+    Assembly::from_str(asm).unwrap().to_legacy_bytes().disassemble()
+}
+
+fn from_edges(edges: &[(usize,usize)]) -> Digraph<&'static [usize]> {
+    let mut graph = Digraph::new(NODES.len(),NODES);
+    for (n,m) in edges {
+        graph.connect(*n,*m);
+    }
+    graph
+}
@@ -0,0 +1,92 @@
+use evmil::analysis::find_constants;
+use evmil::bytecode::{Assembly,Instruction,StructuredSection};
+use evmil::util::w256;
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[test]
+fn test_constants_01() {
+    // Address mask (1 << 160usize) - 1
+    let asm = r#"
+.code
+   push 0x1
+   push 0xa0
+   shl
+   push 0x1
+   swap1
+   sub
+   calldataload
+"#;
+    let mask = (w256::from(1u64) << 160usize) - w256::from(1u64);
+    check(asm,&[None,None,None,None,None,Some(mask),None]);
+}
+
+#[test]
+fn test_constants_02() {
+    // Only the last instruction of a sequence is annotated
+    let asm = r#"
+.code
+   push 0x1
+   push 0xa0
+   shl
+   push 0x1
+   or
+   not
+"#;
+    let w = !((w256::from(1u64) << 160usize) | w256::from(1u64));
+    check(asm,&[None,None,None,None,None,Some(w)]);
+}
+
+#[test]
+fn test_constants_03() {
+    // Unknown operands are not constant
+    let asm = r#"
+.code
+   push 0x1
+   calldatasize
+   add
+   push 0x1
+   push 0x2
+   add
+"#;
+    check(asm,&[None,None,None,None,None,Some(w256::from(3))]);
+}
+
+#[test]
+fn test_constants_04() {
+    // Differing values along different paths are not constant
+    let asm = r#"
+.code
+   push 0x1
+   calldatasize
+   push lab
+   jumpi
+   pop
+   push 0x2
+lab:
+   jumpdest
+   push 0x1
+   add
+"#;
+    check(asm,&[None,None,None,None,None,None,None,None,None]);
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn check(asm: &str, expected: &[Option<w256>]) {
+    let insns = code(asm);
+    let constants = find_constants(&insns,usize::MAX).unwrap();
+    assert_eq!(constants,expected);
+}
+
+fn code(asm: &str) -> Vec<Instruction> {
+    let assembly = Assembly::from_str(asm).unwrap();
+    match assembly.iter().next() {
+        Some(StructuredSection::Code(insns)) => insns.clone(),
+        _ => panic!("expected code section")
+    }
+}
@@ -1,7 +1,7 @@
 use std::fs;
 use std::path::{PathBuf};
 use evmil::util::{FromHexString};
-use evmil::bytecode::{Assembly};
+use evmil::bytecode::{split_metadata,Assembly,DisassemblyBuilder,Instruction,StructuredSection};
 
 pub static TESTS_DIR: &str = "tests/files";
 
@@ -47,3 +47,173 @@ fn to_binfile(test: &str) -> PathBuf {
     path.set_extension("bin");
     path
 }
+
+// ============================================================================
+// Builder
+// ============================================================================
+
+#[test]
+fn test_builder_01() {
+    // Default options match legacy disassembly
+    let bytes = "0x0060016000".from_hex_string().unwrap();
+    let asm = DisassemblyBuilder::new(&bytes).build();
+    assert_eq!(asm,Assembly::from_legacy_bytes(&bytes));
+    assert_eq!(sections(&asm),vec![Code(vec![Instruction::STOP]),Data(vec![0x60,0x01,0x60,0x00])]);
+}
+
+#[test]
+fn test_builder_02() {
+    // Preserve unreachable instructions
+    let bytes = "0x00600100".from_hex_string().unwrap();
+    let asm = DisassemblyBuilder::new(&bytes).preserve_unreachable(true).build();
+    let insns = vec![Instruction::STOP,Instruction::PUSH(vec![1]),Instruction::STOP];
+    assert_eq!(sections(&asm),vec![Code(insns)]);
+}
+
+#[test]
+fn test_builder_03() {
+    // Leading bytes treated as data
+    let bytes = "0xfefe00".from_hex_string().unwrap();
+    let asm = DisassemblyBuilder::new(&bytes).with_offset(2).build();
+    assert_eq!(sections(&asm),vec![Data(vec![0xfe,0xfe]),Code(vec![Instruction::STOP])]);
+}
+
+#[test]
+fn test_builder_04() {
+    // Exhausted budget means everything is considered reachable
+    let bytes = "0x600456005b00600100".from_hex_string().unwrap();
+    let asm = DisassemblyBuilder::new(&bytes).with_budget(1).build();
+    assert_eq!(sections(&asm).len(),1);
+    let asm = DisassemblyBuilder::new(&bytes).with_budget(1000).build();
+    assert_eq!(sections(&asm).len(),2);
+}
+
+#[test]
+fn test_builder_05() {
+    // Several options together
+    let bytes = "0xfe00600100".from_hex_string().unwrap();
+    let asm = DisassemblyBuilder::new(&bytes)
+        .with_offset(1)
+        .with_budget(1000)
+        .preserve_unreachable(true)
+        .build();
+    let insns = vec![Instruction::STOP,Instruction::PUSH(vec![1]),Instruction::STOP];
+    assert_eq!(sections(&asm),vec![Data(vec![0xfe]),Code(insns)]);
+}
+
+#[test]
+fn test_builder_06() {
+    // Jump into PUSH operand which happens to be 0x5b
+    let bytes = "0x600456605b00".from_hex_string().unwrap();
+    let asm = DisassemblyBuilder::new(&bytes).build();
+    let insns = vec![Instruction::PUSH(vec![4]),Instruction::JUMP];
+    assert_eq!(sections(&asm),vec![Code(insns),Data(vec![0x60,0x5b,0x00])]);
+}
+
+#[test]
+fn test_builder_truncated_01() {
+    // PUSH32 with only one operand byte
+    let bytes = [0x7f,0x01];
+    let asm = DisassemblyBuilder::new(&bytes).build();
+    assert_eq!(sections(&asm),vec![Code(vec![]),Data(vec![0x7f,0x01])]);
+    assert_eq!(asm.to_legacy_bytes(),bytes.to_vec());
+}
+
+#[test]
+fn test_builder_truncated_02() {
+    // Truncated PUSH following reachable code
+    let bytes = [0x60,0x01,0x7f,0x01];
+    let asm = DisassemblyBuilder::new(&bytes).build();
+    assert_eq!(sections(&asm),vec![Code(vec![Instruction::PUSH(vec![1])]),Data(vec![0x7f,0x01])]);
+    assert_eq!(asm.to_legacy_bytes(),bytes.to_vec());
+}
+
+#[test]
+fn test_builder_truncated_03() {
+    // Truncated PUSH following unreachable code
+    let bytes = [0x00,0x60,0x01,0x7f,0x01];
+    let asm = DisassemblyBuilder::new(&bytes).build();
+    assert_eq!(sections(&asm),vec![Code(vec![Instruction::STOP]),Data(vec![0x60,0x01,0x7f,0x01])]);
+    let asm = DisassemblyBuilder::new(&bytes).preserve_unreachable(true).build();
+    let insns = vec![Instruction::STOP,Instruction::PUSH(vec![1])];
+    assert_eq!(sections(&asm),vec![Code(insns),Data(vec![0x7f,0x01])]);
+}
+
+// ============================================================================
+// Metadata
+// ============================================================================
+
+/// Metadata appended by solc 0.8.17 (i.e. an IPFS hash and compiler
+/// version).
+const METADATA : &str = "0xa26469706673582212207b57e8eca1da96d5c0491373554b699c55b7dfcbe6779ae889f253ae5f8366cd64736f6c63430008110033";
+
+#[test]
+fn test_metadata_01() {
+    // Runtime code ending with `invalid` followed by metadata
+    let metadata = METADATA.from_hex_string().unwrap();
+    let mut bytes = vec![0x60,0x00,0x80,0xfd,0xfe];
+    bytes.extend(&metadata);
+    assert_eq!(split_metadata(&bytes),(&bytes[..5],&metadata[..]));
+    let asm = DisassemblyBuilder::new(&bytes).build();
+    let insns = vec![Instruction::PUSH(vec![0]),Instruction::DUP(1),Instruction::REVERT];
+    assert_eq!(sections(&asm),vec![Code(insns),Data(vec![0xfe]),Data(metadata)]);
+    assert_eq!(asm.to_legacy_bytes(),bytes);
+}
+
+#[test]
+fn test_metadata_02() {
+    // Metadata immediately following reachable code
+    let metadata = METADATA.from_hex_string().unwrap();
+    let mut bytes = vec![0x00];
+    bytes.extend(&metadata);
+    let asm = DisassemblyBuilder::new(&bytes).build();
+    assert_eq!(sections(&asm),vec![Code(vec![Instruction::STOP]),Data(metadata.clone())]);
+}
+
+#[test]
+fn test_metadata_03() {
+    // Splitting can be disabled
+    let metadata = METADATA.from_hex_string().unwrap();
+    let mut bytes = vec![0x00,0xfe];
+    bytes.extend(&metadata);
+    let asm = DisassemblyBuilder::new(&bytes).split_metadata(false).build();
+    assert_eq!(sections(&asm),vec![Code(vec![Instruction::STOP]),Data(bytes[1..].to_vec())]);
+}
+
+#[test]
+fn test_metadata_04() {
+    // No metadata present
+    let bytes = [0x60,0x00,0x00];
+    assert_eq!(split_metadata(&bytes),(&bytes[..],&[][..]));
+    // Length suffix doesn't identify a CBOR map with a text key
+    let bytes = [0x00,0xa1,0x01,0x00,0x02];
+    assert_eq!(split_metadata(&bytes),(&bytes[..],&[][..]));
+}
+
+#[test]
+fn test_metadata_05() {
+    // LOG1 followed by PUSH4 resembles a map with a text key
+    let bytes = "0x6000600060006000a163aabbccdd0006".from_hex_string().unwrap();
+    assert_eq!(split_metadata(&bytes),(&bytes[..],&[][..]));
+    // Unknown key
+    let bytes = "0x00a1646970667842aabb0008".from_hex_string().unwrap();
+    assert_eq!(split_metadata(&bytes),(&bytes[..],&[][..]));
+    // Map doesn't occupy the given length
+    let metadata = METADATA.replace("0033","0034").from_hex_string().unwrap();
+    let mut bytes = vec![0x00,0x00];
+    bytes.extend(&metadata);
+    assert_eq!(split_metadata(&bytes),(&bytes[..],&[][..]));
+}
+
+#[test]
+fn test_metadata_06() {
+    // Prerelease compiler version is a text string
+    let bytes = "0x00a164736f6c63657665727369000c".from_hex_string().unwrap();
+    assert_eq!(split_metadata(&bytes),(&bytes[..1],&bytes[1..]));
+}
+
+use StructuredSection::*;
+
+fn sections(asm: &Assembly) -> Vec<StructuredSection> {
+    asm.iter().cloned().collect()
+}
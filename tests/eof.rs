@@ -0,0 +1,336 @@
+use evmil::bytecode::{Assemble,Assembly,DecodingError,Disassemble,EncodingError,Instruction,SectionType,StructuredSection,ValidationError};
+use evmil::bytecode::Instruction::*;
+use evmil::util::{FromHexString,ToHexString};
+
+/// A minimal valid EOF container, consisting of a single code section
+/// (containing `STOP`) and an empty data section.
+const MINIMAL : &str = "0xef000101000402000100010300000000000000_00";
+
+// ============================================================================
+// Valid Containers
+// ============================================================================
+
+#[test]
+fn test_eof_decode_01() {
+    let asm = decode(MINIMAL).unwrap();
+    let sections = vec![StructuredSection::Code(vec![STOP]),StructuredSection::Data(vec![])];
+    assert_eq!(asm,Assembly::new(sections).with_types(vec![ty(0,0,0)]));
+}
+
+#[test]
+fn test_eof_decode_02() {
+    // Two code sections and some data
+    let asm = decode("0xef000101000802000200010003030002_00_0000000000000000_00_600100_abcd").unwrap();
+    assert_eq!(asm,Assembly::new(vec![
+        StructuredSection::Code(vec![STOP]),
+        StructuredSection::Code(vec![PUSH(vec![0x01]),STOP]),
+        StructuredSection::Data(vec![0xab,0xcd])
+    ]).with_types(vec![ty(0,0,0),ty(0,0,0)]));
+}
+
+#[test]
+fn test_eof_decode_03() {
+    // Decoding is the inverse of encoding
+    let asm = decode(MINIMAL).unwrap();
+    assert_eq!(decode(&asm.to_eof_bytes().to_hex_string()).unwrap(),asm);
+}
+
+// ============================================================================
+// Malformed Headers
+// ============================================================================
+
+#[test]
+fn test_eof_decode_04() {
+    let err = decode("0xef010101000402000100010300000000000000_00");
+    assert!(matches!(err,Err(DecodingError::InvalidMagicNumber(0xef01))));
+}
+
+#[test]
+fn test_eof_decode_05() {
+    let err = decode("0xef000201000402000100010300000000000000_00");
+    assert!(matches!(err,Err(DecodingError::UnsupportedEofVersion(2))));
+}
+
+#[test]
+fn test_eof_decode_06() {
+    let err = decode("0xef000104000402000100010300000000000000_00");
+    assert!(matches!(err,Err(DecodingError::InvalidKindType(4))));
+}
+
+#[test]
+fn test_eof_decode_07() {
+    let err = decode("0xef000101000403000100010300000000000000_00");
+    assert!(matches!(err,Err(DecodingError::InvalidKindCode(3))));
+}
+
+#[test]
+fn test_eof_decode_08() {
+    let err = decode("0xef000101000402000100010400000000000000_00");
+    assert!(matches!(err,Err(DecodingError::InvalidKindData(4))));
+}
+
+#[test]
+fn test_eof_decode_09() {
+    let err = decode("0xef000101000402000100010300000100000000_00");
+    assert!(matches!(err,Err(DecodingError::InvalidTerminator(1))));
+}
+
+#[test]
+fn test_eof_decode_10() {
+    // Type section size inconsistent with number of code sections
+    let err = decode("0xef000101000802000100010300000000000000_00");
+    assert!(matches!(err,Err(DecodingError::InvalidTypeSize(8))));
+}
+
+#[test]
+fn test_eof_decode_11() {
+    // No code sections
+    let err = decode("0xef00010100000200000300000000");
+    assert!(matches!(err,Err(DecodingError::InvalidCodeSectionCount(0))));
+}
+
+#[test]
+fn test_eof_decode_12() {
+    // Empty code section
+    let err = decode("0xef000101000402000100000300000000000000");
+    assert!(matches!(err,Err(DecodingError::EmptyCodeSection(0))));
+}
+
+#[test]
+fn test_eof_decode_13() {
+    // Declared code size exceeds payload
+    let err = decode("0xef000101000402000100020300000000000000_00");
+    assert!(matches!(err,Err(DecodingError::UnexpectedEndOfFile)));
+}
+
+#[test]
+fn test_eof_decode_14() {
+    // Declared data size exceeds payload
+    let err = decode("0xef000101000402000100010300020000000000_00_ab");
+    assert!(matches!(err,Err(DecodingError::UnexpectedEndOfFile)));
+}
+
+#[test]
+fn test_eof_decode_15() {
+    // Trailing bytes after payload
+    let err = decode("0xef000101000402000100010300000000000000_00_ab");
+    assert!(matches!(err,Err(DecodingError::ExpectedEndOfFile)));
+}
+
+#[test]
+fn test_eof_decode_16() {
+    // Truncated header
+    let err = decode("0xef00010100040200");
+    assert!(matches!(err,Err(DecodingError::UnexpectedEndOfFile)));
+}
+
+// ============================================================================
+// Encoding
+// ============================================================================
+
+#[test]
+fn test_eof_encode_01() {
+    let asm = Assembly::new(vec![StructuredSection::Code(vec![STOP]),StructuredSection::Data(vec![])]);
+    let bytes = asm.try_to_eof_bytes().unwrap();
+    assert_eq!(bytes.to_hex_string(),MINIMAL.replace('_',""));
+}
+
+#[test]
+fn test_eof_encode_02() {
+    // Maximum stack height is written into type section
+    let asm = Assembly::new(vec![
+        StructuredSection::Code(vec![PUSH(vec![0x01]),PUSH(vec![0x02]),ADD,POP,STOP]),
+        StructuredSection::Code(vec![PUSH(vec![0x01]),POP,STOP]),
+        StructuredSection::Data(vec![0xab])
+    ]);
+    let bytes = asm.try_to_eof_bytes().unwrap();
+    assert_eq!(bytes.to_hex_string(),"0xef000101000802000200070004030001_00_00000002_00000001_60016002015000_60015000_ab".replace('_',""));
+    // Decoding is the inverse of encoding
+    assert_eq!(Assembly::from_eof_bytes(&bytes).unwrap(),asm.with_types(vec![ty(0,0,2),ty(0,0,1)]));
+}
+
+#[test]
+fn test_eof_encode_05() {
+    // Analysis of a loop with an unknown bound terminates
+    let insns = vec![PUSH0,PUSH(vec![0x01]),ADD,DUP(1),CALLDATASIZE,GT,RJUMPI(-9),STOP];
+    let asm = Assembly::new(vec![StructuredSection::Code(insns),StructuredSection::Data(vec![])]);
+    let bytes = asm.try_to_eof_bytes().unwrap();
+    assert_eq!(bytes.to_hex_string(),"0xef0001010004020001000b03000000_00000003_5f600101803611e1fff700".replace('_',""));
+}
+
+#[test]
+fn test_eof_encode_06() {
+    // Declared inputs and outputs are written back
+    let hex = "0xef000101000802000200090002030000_00_00800002_02010002_60016002e300015000_01e4";
+    let asm = decode(hex).unwrap();
+    assert_eq!(asm.types(),Some([ty(0,0x80,2),ty(2,1,2)].as_slice()));
+    assert_eq!(asm.to_eof_bytes().to_hex_string(),hex.replace('_',""));
+}
+
+#[test]
+fn test_eof_encode_03() {
+    // At least one code section is required
+    let asm = Assembly::new(vec![StructuredSection::Data(vec![0xab])]);
+    assert!(matches!(asm.try_to_eof_bytes(),Err(EncodingError::NoCodeSections)));
+}
+
+#[test]
+fn test_eof_encode_04() {
+    // Data section must come last
+    let asm = Assembly::new(vec![StructuredSection::Data(vec![0xab]),StructuredSection::Code(vec![STOP])]);
+    assert!(matches!(asm.try_to_eof_bytes(),Err(EncodingError::DataSectionNotLast)));
+}
+
+// ============================================================================
+// Relative Jumps
+// ============================================================================
+
+/// A code section containing a forward `rjumpi`, a backward `rjump`
+/// and an `rjumpv` whose table contains both directions.
+const RJUMPS : &str = "0xef0001010004020001001103000000_00000001_6000_e10003_e0fff8_6000_e2010000fff0_00";
+
+#[test]
+fn test_eof_rjump_01() {
+    let asm = decode(RJUMPS).unwrap();
+    let insns = vec![PUSH(vec![0]),RJUMPI(3),RJUMP(-8),PUSH(vec![0]),RJUMPV(vec![0,-16]),STOP];
+    let sections = vec![StructuredSection::Code(insns),StructuredSection::Data(vec![])];
+    assert_eq!(asm,Assembly::new(sections).with_types(vec![ty(0,0,1)]));
+}
+
+#[test]
+fn test_eof_rjump_02() {
+    // Encoding is the inverse of decoding
+    let asm = decode(RJUMPS).unwrap();
+    assert_eq!(asm.to_eof_bytes().to_hex_string(),RJUMPS.replace('_',""));
+}
+
+#[test]
+fn test_eof_rjump_03() {
+    assert_eq!(RJUMP(0).length(),3);
+    assert_eq!(RJUMPI(0).length(),3);
+    assert_eq!(RJUMPV(vec![0]).length(),4);
+    assert_eq!(RJUMPV(vec![0,1,2]).length(),8);
+}
+
+#[test]
+fn test_eof_rjump_04() {
+    // Offsets are relative to the following instruction
+    assert_eq!(RJUMP(-8).to_string(),"rjump -8");
+    assert_eq!(RJUMPI(3).to_string(),"rjumpi 3");
+    assert_eq!(RJUMPV(vec![0,-16]).to_string(),"rjumpv 0,-16");
+    assert_eq!("rjumpv 0,-16".parse::<Instruction>().unwrap(),RJUMPV(vec![0,-16]));
+}
+
+#[test]
+fn test_eof_rjump_05() {
+    // Relative jumps are not recognised in legacy bytecode
+    let bytes = "0xe0fffd".from_hex_string().unwrap();
+    assert_eq!(bytes.disassemble(),vec![DATA(vec![0xe0]),SELFDESTRUCT,REVERT]);
+    assert_eq!(bytes.disassemble_eof(),vec![RJUMP(-3)]);
+}
+
+#[test]
+fn test_eof_rjump_06() {
+    // Target before the start of the section is retained (not clamped)
+    let bytes = "0xe0ff00".from_hex_string().unwrap();
+    let insns = bytes.disassemble_eof();
+    assert_eq!(insns,vec![RJUMP(-256)]);
+    assert_eq!(insns.assemble(),bytes);
+    // And rejected by validation
+    let asm = Assembly::new(vec![StructuredSection::Code(insns)]);
+    assert!(asm.validate_eof().unwrap_err().contains(&(0,ValidationError::InvalidRelativeJump(-256))));
+}
+
+#[test]
+fn test_eof_rjump_07() {
+    // Empty jump table cannot be encoded
+    let asm = Assembly::new(vec![StructuredSection::Code(vec![PUSH0,RJUMPV(vec![]),STOP])]);
+    assert!(matches!(asm.try_to_eof_bytes(),Err(EncodingError::InvalidJumpTable(1))));
+    assert!(asm.validate_eof().unwrap_err().contains(&(1,ValidationError::Unencodable)));
+    assert!(matches!([RJUMPV(vec![])].try_assemble(),Err(EncodingError::InvalidJumpTable(0))));
+    // Nor can one with too many entries
+    assert!(matches!([RJUMPV(vec![0;257])].try_assemble(),Err(EncodingError::InvalidJumpTable(0))));
+    assert!([RJUMPV(vec![0;256])].try_assemble().is_ok());
+}
+
+// ============================================================================
+// Functions
+// ============================================================================
+
+/// Two code sections, where the first calls the second.
+const FUNCTIONS : &str = "0xef000101000802000200040001030000_00_00000000_00000000_e3000100_e4";
+
+#[test]
+fn test_eof_callf_01() {
+    let asm = decode(FUNCTIONS).unwrap();
+    let sections = vec![
+        StructuredSection::Code(vec![CALLF(1),STOP]),
+        StructuredSection::Code(vec![RETF]),
+        StructuredSection::Data(vec![])
+    ];
+    assert_eq!(asm,Assembly::new(sections).with_types(vec![ty(0,0,0),ty(0,0,0)]));
+}
+
+#[test]
+fn test_eof_callf_02() {
+    // Operand is the section index
+    let bytes = "0xe30102".from_hex_string().unwrap();
+    assert_eq!(bytes.disassemble_eof(),vec![CALLF(0x0102)]);
+    let bytes = "0xe50003".from_hex_string().unwrap();
+    assert_eq!(bytes.disassemble_eof(),vec![JUMPF(3)]);
+}
+
+#[test]
+fn test_eof_callf_03() {
+    // Encoding is the inverse of decoding
+    let asm = decode(FUNCTIONS).unwrap();
+    assert_eq!(asm.to_eof_bytes().to_hex_string(),FUNCTIONS.replace('_',""));
+}
+
+#[test]
+fn test_eof_callf_04() {
+    assert_eq!(CALLF(1).length(),3);
+    assert_eq!(JUMPF(1).length(),3);
+    assert_eq!(RETF.length(),1);
+    assert_eq!(CALLF(1).to_string(),"callf 1");
+    assert_eq!(JUMPF(2).to_string(),"jumpf 2");
+    assert_eq!(RETF.to_string(),"retf");
+}
+
+// ============================================================================
+// Section Types
+// ============================================================================
+
+/// Two code sections, where the first (non-returning) section passes
+/// one item to the second (which returns it).
+const TYPES : &str = "0xef000101000802000200070001030000_00_00800001_01010001_6001e300015000_e4";
+
+#[test]
+fn test_eof_types_01() {
+    // Types are retained when decoding
+    let asm = decode(TYPES).unwrap();
+    assert_eq!(asm.types(),Some([ty(0,0x80,1),ty(1,1,1)].as_slice()));
+}
+
+#[test]
+fn test_eof_types_02() {
+    // Encoding is the inverse of decoding
+    let asm = decode(TYPES).unwrap();
+    assert_eq!(asm.to_eof_bytes().to_hex_string(),TYPES.replace('_',""));
+}
+
+#[test]
+fn test_eof_types_03() {
+    // Declared types must match code sections
+    let asm = Assembly::new(vec![StructuredSection::Code(vec![STOP])]).with_types(vec![]);
+    assert!(matches!(asm.try_to_eof_bytes(),Err(EncodingError::InvalidTypeCount(0))));
+}
+
+fn decode(hex: &str) -> Result<Assembly,DecodingError> {
+    let bytes = hex.replace('_',"").from_hex_string().unwrap();
+    Assembly::from_eof_bytes(&bytes)
+}
+
+fn ty(inputs: u8, outputs: u8, max_stack: u16) -> SectionType {
+    SectionType{inputs,outputs,max_stack}
+}
@@ -2,8 +2,8 @@
         push 0x8451
         push lab0
         jumpi
-        push 0x00
-        push 0x00
-        revert
+        db 0x6000
+        db 0x6000
+        db 0xfd
 lab0:
         jumpdest
@@ -213,4 +213,6 @@ lab17:
 	pop
 	jump
 .data
-	0xfea26469706673582212207b57e8eca1da96d5c0491373554b699c55b7dfcbe6779ae889f253ae5f8366cd64736f6c63430008110033
+	0xfe
+.data
+	0xa26469706673582212207b57e8eca1da96d5c0491373554b699c55b7dfcbe6779ae889f253ae5f8366cd64736f6c63430008110033
@@ -0,0 +1,16 @@
+// Increment a counter, branching once it exceeds 10
+PUSH1 0x00
+SLOAD
+PUSH1 1          // decimal operand
+ADD
+DUP1
+PUSH1 0x00
+SSTORE
+PUSH2 10
+GT
+PUSH lab
+JUMPI
+STOP
+lab:
+JUMPDEST
+STOP
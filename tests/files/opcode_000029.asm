@@ -1,6 +1,6 @@
 .code
         push lab0
-        push 0x01
+        push 0x00
         dup1
         dup1
         returndatacopy
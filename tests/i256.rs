@@ -0,0 +1,133 @@
+use evmil::util::{i256,w256};
+
+// ============================================================================
+// Conversions
+// ============================================================================
+
+#[test]
+fn test_i256_from_01() {
+    assert_eq!(i256::from(0),i256::ZERO);
+    assert_eq!(i256::from(-1),i256::MINUS_ONE);
+    let w : w256 = i256::from(-1).into();
+    assert_eq!(w,w256::MAX);
+    let w : w256 = i256::from(42).into();
+    assert_eq!(w,w256::from(42));
+    assert_eq!(i256::from(w256::MAX),i256::MINUS_ONE);
+}
+
+#[test]
+fn test_i256_from_02() {
+    let w : w256 = i256::from(i64::MIN).into();
+    assert_eq!(w,!w256::from(i64::MAX));
+    assert!(i256::from(-5).is_negative());
+    assert!(!i256::from(5).is_negative());
+    assert!(!i256::ZERO.is_negative());
+    assert!(i256::MIN.is_negative());
+    assert!(!i256::MAX.is_negative());
+}
+
+// ============================================================================
+// Comparison
+// ============================================================================
+
+#[test]
+fn test_i256_cmp_01() {
+    assert!(i256::from(-1) < i256::ZERO);
+    assert!(i256::from(-2) < i256::from(-1));
+    assert!(i256::from(1) > i256::from(-1));
+    assert!(i256::MIN < i256::MINUS_ONE);
+    assert!(i256::MIN < i256::MAX);
+    assert!(i256::MAX > i256::ZERO);
+}
+
+// ============================================================================
+// Division / Remainder
+// ============================================================================
+
+#[test]
+fn test_i256_div_01() {
+    // Rounding towards zero
+    assert_eq!(i256::from(7) / i256::from(2),i256::from(3));
+    assert_eq!(i256::from(-7) / i256::from(2),i256::from(-3));
+    assert_eq!(i256::from(7) / i256::from(-2),i256::from(-3));
+    assert_eq!(i256::from(-7) / i256::from(-2),i256::from(3));
+}
+
+#[test]
+fn test_i256_div_02() {
+    // Division by zero gives zero
+    assert_eq!(i256::from(-7) / i256::ZERO,i256::ZERO);
+    assert_eq!(i256::MIN / i256::ZERO,i256::ZERO);
+}
+
+#[test]
+fn test_i256_div_03() {
+    // MIN / -1 overflows to MIN
+    assert_eq!(i256::MIN / i256::MINUS_ONE,i256::MIN);
+    assert_eq!(i256::MIN / i256::from(1),i256::MIN);
+    assert_eq!(i256::MIN / i256::MIN,i256::from(1));
+    assert_eq!(i256::MAX / i256::MIN,i256::ZERO);
+}
+
+#[test]
+fn test_i256_rem_01() {
+    // Remainder takes sign of dividend
+    assert_eq!(i256::from(7) % i256::from(2),i256::from(1));
+    assert_eq!(i256::from(-7) % i256::from(2),i256::from(-1));
+    assert_eq!(i256::from(7) % i256::from(-2),i256::from(1));
+    assert_eq!(i256::from(-7) % i256::from(-2),i256::from(-1));
+    assert_eq!(i256::from(-7) % i256::ZERO,i256::ZERO);
+}
+
+#[test]
+fn test_i256_rem_02() {
+    assert_eq!(i256::MIN % i256::MINUS_ONE,i256::ZERO);
+    assert_eq!(i256::MIN % i256::MAX,i256::MINUS_ONE);
+}
+
+// ============================================================================
+// Shifts
+// ============================================================================
+
+#[test]
+fn test_i256_sar_01() {
+    assert_eq!(i256::from(-8) >> 1,i256::from(-4));
+    assert_eq!(i256::from(-7) >> 1,i256::from(-4));
+    assert_eq!(i256::from(8) >> 1,i256::from(4));
+    assert_eq!(i256::MIN >> 255,i256::MINUS_ONE);
+    assert_eq!(i256::MAX >> 255,i256::ZERO);
+}
+
+#[test]
+fn test_i256_sar_02() {
+    // Shifting beyond the width saturates
+    assert_eq!(i256::from(-1) >> 256,i256::MINUS_ONE);
+    assert_eq!(i256::MIN >> 1000,i256::MINUS_ONE);
+    assert_eq!(i256::MAX >> 1000,i256::ZERO);
+}
+
+// ============================================================================
+// Sign Extension
+// ============================================================================
+
+#[test]
+fn test_i256_signextend_01() {
+    assert_eq!(i256::sign_extend(w256::from(0xff),w256::ZERO),i256::MINUS_ONE);
+    assert_eq!(i256::sign_extend(w256::from(0x7f),w256::ZERO),i256::from(0x7f));
+    assert_eq!(i256::sign_extend(w256::from(0x80ff),w256::ZERO),i256::MINUS_ONE);
+    assert_eq!(i256::sign_extend(w256::from(0x8000),w256::from(1)),i256::from(-0x8000));
+}
+
+#[test]
+fn test_i256_signextend_02() {
+    // Beyond the width is unchanged
+    assert_eq!(i256::sign_extend(w256::from(0xff),w256::from(31)),i256::from(0xff));
+    assert_eq!(i256::sign_extend(w256::from(0xff),w256::MAX),i256::from(0xff));
+}
+
+#[test]
+fn test_i256_abs_01() {
+    assert_eq!(i256::from(-5).unsigned_abs(),w256::from(5));
+    assert_eq!(i256::MIN.unsigned_abs(),w256::from(1) << 255);
+    assert_eq!(i256::MIN.wrapping_neg(),i256::MIN);
+}
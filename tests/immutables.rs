@@ -0,0 +1,58 @@
+use evmil::analysis::{find_immutables,Immutable};
+use evmil::util::{w256,FromHexString};
+
+/// Runtime template with a single immutable, corresponding to:
+///
+/// ```txt
+///    push32 0x00..00 ;; immutable
+///    push1 0x00
+///    sstore
+///    stop
+/// ```
+const TEMPLATE : &str = "7f000000000000000000000000000000000000000000000000000000000000000060005500";
+
+#[test]
+fn test_immutables_01() {
+    // push1 0x24; dup1; push1 0x13; push1 0x00; codecopy;
+    // push1 0x15; push1 0x02; mul; push1 0x01; mstore;
+    // push1 0x00; return
+    let constructor = "0x602480601360003960156002026001526000f3";
+    let immutables = check(constructor, TEMPLATE);
+    assert_eq!(immutables, vec![Immutable{offset: 1, value: w256::from(0x2a)}]);
+}
+
+#[test]
+fn test_immutables_02() {
+    // As above, but with a (non-payable) guard
+    //
+    // callvalue; dup1; iszero; push1 0x0a; jumpi; push1 0x00; dup1; revert;
+    // jumpdest; pop; ...
+    let constructor = "0x348015600a57600080fd5b50602480601f60003960156002026001526000f3";
+    let immutables = check(constructor, TEMPLATE);
+    assert_eq!(immutables, vec![Immutable{offset: 1, value: w256::from(0x2a)}]);
+}
+
+#[test]
+fn test_immutables_03() {
+    // Constructor which returns the template unchanged.
+    //
+    // push1 0x24; dup1; push1 0x0b; push1 0x00; codecopy; push1 0x00; return
+    let constructor = "0x602480600b6000396000f3";
+    let immutables = check(constructor, TEMPLATE);
+    assert_eq!(immutables, vec![]);
+}
+
+#[test]
+fn test_immutables_04() {
+    // Constructor which always reverts.
+    //
+    // push1 0x00; dup1; revert
+    let bytes = format!("0x600080fd{TEMPLATE}").from_hex_string().unwrap();
+    assert_eq!(find_immutables(&bytes, usize::MAX), None);
+}
+
+fn check(constructor: &str, template: &str) -> Vec<Immutable> {
+    let hex = format!("{constructor}{template}");
+    let bytes = hex.from_hex_string().unwrap();
+    find_immutables(&bytes, usize::MAX).unwrap()
+}
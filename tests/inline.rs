@@ -0,0 +1,195 @@
+use evmil::analysis::{inline_functions,BlockGraph};
+use evmil::bytecode::{Assembly,Instruction,StructuredSection};
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[test]
+fn test_inline_01() {
+    // Single call site
+    let asm = r#"
+.code
+   push ret
+   push 0x1
+   push f
+   jump
+ret:
+   jumpdest
+   stop
+f:
+   jumpdest
+   swap1
+   jump
+"#;
+    let expected = r#"
+.code
+   push ret
+   push 0x1
+   swap1
+   jump
+ret:
+   jumpdest
+   stop
+"#;
+    check(asm,10,1,expected);
+}
+
+#[test]
+fn test_inline_02() {
+    // Function too large
+    let asm = r#"
+.code
+   push ret
+   push 0x1
+   push f
+   jump
+ret:
+   jumpdest
+   stop
+f:
+   jumpdest
+   push 0x2
+   add
+   swap1
+   jump
+"#;
+    check(asm,4,1,asm);
+}
+
+#[test]
+fn test_inline_03() {
+    // Too many call sites
+    let asm = r#"
+.code
+   push ret1
+   push f
+   jump
+ret1:
+   jumpdest
+   push ret2
+   push f
+   jump
+ret2:
+   jumpdest
+   stop
+f:
+   jumpdest
+   jump
+"#;
+    check(asm,10,1,asm);
+    // Sufficient call sites
+    let expected = r#"
+.code
+   push ret1
+   jump
+ret1:
+   jumpdest
+   push ret2
+   jump
+ret2:
+   jumpdest
+   stop
+"#;
+    check(asm,10,2,expected);
+}
+
+#[test]
+fn test_inline_04() {
+    // Function referenced other than by a direct call
+    let asm = r#"
+.code
+   push ret
+   push f
+   dup1
+   pop
+   jump
+ret:
+   jumpdest
+   stop
+f:
+   jumpdest
+   jump
+"#;
+    check(asm,10,1,asm);
+}
+
+#[test]
+fn test_inline_05() {
+    // Control-flow graph is rebuilt with correct edges.
+    let asm = r#"
+.code
+   push ret
+   push 0x1
+   push f
+   jump
+ret:
+   jumpdest
+   stop
+f:
+   jumpdest
+   swap1
+   jump
+"#;
+    let insns = inline_functions(&code(asm),10,1,usize::MAX).unwrap();
+    let graph = BlockGraph::from(insns.as_slice());
+    assert_eq!(graph.len(),2);
+    let edges : Vec<_> = graph.out_iter().collect();
+    assert_eq!(edges,[(0,1)]);
+}
+
+#[test]
+fn test_inline_06() {
+    // Function which calls another function.  Only the latter is
+    // inlined, since the former contains a jump target.
+    let asm = r#"
+.code
+   push ret
+   push 0x1
+   push f
+   jump
+ret:
+   jumpdest
+   stop
+f:
+   jumpdest
+   push g
+   jump
+g:
+   jumpdest
+   swap1
+   jump
+"#;
+    let expected = r#"
+.code
+   push ret
+   push 0x1
+   push f
+   jump
+ret:
+   jumpdest
+   stop
+f:
+   jumpdest
+   swap1
+   jump
+"#;
+    check(asm,10,1,expected);
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn check(asm: &str, max_size: usize, max_sites: usize, expected: &str) {
+    let insns = inline_functions(&code(asm),max_size,max_sites,usize::MAX).unwrap();
+    assert_eq!(insns,code(expected));
+}
+
+fn code(asm: &str) -> Vec<Instruction> {
+    let assembly = Assembly::from_str(asm).unwrap();
+    match assembly.iter().next() {
+        Some(StructuredSection::Code(insns)) => insns.clone(),
+        _ => panic!("expected code section")
+    }
+}
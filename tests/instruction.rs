@@ -0,0 +1,383 @@
+use evmil::bytecode::{opcode,Assemble,Assembly,DecodingError,Disassemble,Instruction,ParseError};
+use evmil::bytecode::Instruction::*;
+use evmil::fork::{Fork,LONDON,PARIS,SHANGHAI};
+
+// ============================================================================
+// Push
+// ============================================================================
+
+#[test]
+fn test_push0_01() {
+    check(PUSH0, 0x5f);
+    assert_eq!(PUSH0.length(),1);
+    assert_eq!(PUSH0.to_string(),"push0");
+}
+
+#[test]
+fn test_push0_02() {
+    // Distinct from a push of zero
+    let bytes = [0x5f,0x60,0x00];
+    assert_eq!(bytes.disassemble(),vec![PUSH0,PUSH(vec![0])]);
+    assert_eq!([PUSH0,PUSH(vec![0])].assemble(),bytes.to_vec());
+}
+
+#[test]
+fn test_push0_03() {
+    // Assembly roundtrip
+    let asm = Assembly::from_str(".code\npush0\npop\nstop").unwrap();
+    let bytes = asm.to_legacy_bytes();
+    assert_eq!(bytes,vec![0x5f,0x50,0x00]);
+    assert_eq!(Assembly::from_legacy_bytes(&bytes),asm);
+}
+
+#[test]
+fn test_mcopy_01() {
+    check(MCOPY, 0x5e);
+    assert_eq!(MCOPY.length(),1);
+    assert_eq!(MCOPY.operands(),3);
+    assert_eq!(MCOPY.to_string(),"mcopy");
+}
+
+// ============================================================================
+// Block Information
+// ============================================================================
+
+#[test]
+fn test_basefee_01() {
+    check(BASEFEE, 0x48);
+    assert_eq!(BASEFEE.operands(),0);
+    assert_eq!(BASEFEE.to_string(),"basefee");
+}
+
+#[test]
+fn test_blobhash_01() {
+    check(BLOBHASH, 0x49);
+    assert_eq!(BLOBHASH.operands(),1);
+    assert_eq!(BLOBHASH.to_string(),"blobhash");
+}
+
+#[test]
+fn test_blobbasefee_01() {
+    check(BLOBBASEFEE, 0x4a);
+    assert_eq!(BLOBBASEFEE.operands(),0);
+    assert_eq!(BLOBBASEFEE.to_string(),"blobbasefee");
+}
+
+#[test]
+fn test_block_info_01() {
+    // Post-Cancun bytecode disassembles without invalid instructions
+    let bytes = [0x48,0x5f,0x49,0x4a];
+    assert_eq!(bytes.disassemble(),vec![BASEFEE,PUSH0,BLOBHASH,BLOBBASEFEE]);
+    let asm = Assembly::from_str(".code\n basefee\n push0\n blobhash\n blobbasefee\n").unwrap();
+    assert_eq!(asm.to_legacy_bytes(),bytes.to_vec());
+}
+
+// ============================================================================
+// Duplication
+// ============================================================================
+
+#[test]
+fn test_dup_01() {
+    for n in 1..=16u8 {
+        check(DUP(n), 0x80 + n - 1);
+    }
+}
+
+#[test]
+fn test_dup_02() {
+    assert_eq!(Instruction::decode(0,&[0x80]),DUP(1));
+    assert_eq!(Instruction::decode(0,&[0x8f]),DUP(16));
+}
+
+#[test]
+#[should_panic]
+fn test_dup_03() {
+    DUP(17).opcode();
+}
+
+#[test]
+#[should_panic]
+fn test_dup_04() {
+    DUP(0).opcode();
+}
+
+// ============================================================================
+// Swap
+// ============================================================================
+
+#[test]
+fn test_swap_01() {
+    for n in 1..=16u8 {
+        check(SWAP(n), 0x90 + n - 1);
+    }
+}
+
+#[test]
+fn test_swap_02() {
+    assert_eq!(Instruction::decode(0,&[0x90]),SWAP(1));
+    assert_eq!(Instruction::decode(0,&[0x9f]),SWAP(16));
+}
+
+#[test]
+#[should_panic]
+fn test_swap_03() {
+    SWAP(17).opcode();
+}
+
+#[test]
+#[should_panic]
+fn test_swap_04() {
+    SWAP(0).opcode();
+}
+
+// ============================================================================
+// Truncation
+// ============================================================================
+
+#[test]
+fn test_truncated_01() {
+    // PUSH32 truncated after three bytes
+    let bytes = [0x60,0x01,0x7f,0x01,0x02,0x03];
+    let (insns,n) = bytes.disassemble_partial();
+    assert_eq!(insns,vec![PUSH(vec![0x01]),DATA(vec![0x7f,0x01,0x02,0x03])]);
+    assert_eq!(n,4);
+}
+
+#[test]
+fn test_truncated_02() {
+    // Nothing truncated
+    let bytes = [0x60,0x01,0x00];
+    let (insns,n) = bytes.disassemble_partial();
+    assert_eq!(insns,bytes.disassemble());
+    assert_eq!(n,0);
+}
+
+#[test]
+fn test_truncated_03() {
+    // Trailing PUSH1 with no operand
+    let (insns,n) = [0x00,0x60].disassemble_partial();
+    assert_eq!(insns,vec![STOP,DATA(vec![0x60])]);
+    assert_eq!(n,1);
+}
+
+#[test]
+fn test_truncated_04() {
+    // Trailing PUSH1 with no operand is rejected
+    assert!(matches!(Instruction::try_decode(1,&[0x00,0x60]),Err(DecodingError::UnexpectedEndOfFile)));
+    // Trailing PUSH2 with only one operand byte is rejected
+    assert!(matches!(Instruction::try_decode(0,&[0x61,0x01]),Err(DecodingError::UnexpectedEndOfFile)));
+    // Decoding beyond the end is rejected
+    assert!(matches!(Instruction::try_decode(2,&[0x00,0x5f]),Err(DecodingError::UnexpectedEndOfFile)));
+}
+
+#[test]
+fn test_truncated_05() {
+    // Trailing PUSH0 is valid
+    assert!(matches!(Instruction::try_decode(1,&[0x00,0x5f]),Ok(PUSH0)));
+    assert!(matches!(Instruction::try_decode(0,&[0x60,0x01]),Ok(PUSH(bs)) if bs == [0x01]));
+    assert_eq!([0x00,0x5f].disassemble_partial(),(vec![STOP,PUSH0],0));
+}
+
+// ============================================================================
+// Forks
+// ============================================================================
+
+#[test]
+fn test_prevrandao_01() {
+    let insns = [0x44].disassemble();
+    assert_eq!(insns,vec![DIFFICULTY]);
+    assert_eq!(insns[0].display(&LONDON).to_string(),"difficulty");
+    assert_eq!(insns[0].display(&PARIS).to_string(),"prevrandao");
+    assert_eq!(insns[0].display(&SHANGHAI).to_string(),"prevrandao");
+}
+
+#[test]
+fn test_prevrandao_02() {
+    // Fork-agnostic display is unchanged
+    assert_eq!(DIFFICULTY.to_string(),"difficulty");
+    // Other instructions are unaffected by the fork
+    assert_eq!(PUSH0.display(&PARIS).to_string(),"push0");
+}
+
+#[test]
+fn test_prevrandao_03() {
+    // Both names assemble to the same opcode
+    let asm = Assembly::from_str(".code\n prevrandao\n difficulty\n").unwrap();
+    assert_eq!(asm.to_legacy_bytes(),vec![0x44,0x44]);
+}
+
+#[test]
+fn test_fork_lookup_01() {
+    assert_eq!(Fork::lookup("london"),Some(&LONDON));
+    assert_eq!(Fork::lookup("Paris"),Some(&PARIS));
+    assert_eq!(Fork::lookup("unknown"),None);
+}
+
+// ============================================================================
+// Opcode Table
+// ============================================================================
+
+#[test]
+fn test_from_opcode_01() {
+    // Every defined opcode round trips
+    for op in 0..=255u8 {
+        let imm = vec![0u8;push_width(op)];
+        if let Some(insn) = Instruction::from_opcode(op,&imm) {
+            assert_eq!(insn.opcode(),op);
+            assert_eq!(insn.length(),1 + imm.len());
+            assert!(opcode::mnemonic(op).is_some());
+        }
+    }
+}
+
+#[test]
+fn test_from_opcode_02() {
+    // Undefined opcodes
+    assert_eq!(Instruction::from_opcode(0x0c,&[]),None);
+    assert_eq!(Instruction::from_opcode(0x21,&[]),None);
+    assert_eq!(Instruction::from_opcode(0xfb,&[]),None);
+    assert_eq!(opcode::mnemonic(0x0c),None);
+    // Only valid within EOF containers
+    assert_eq!(Instruction::from_opcode(opcode::RETF,&[]),None);
+}
+
+#[test]
+fn test_from_opcode_03() {
+    // Immediates must have the correct length
+    assert_eq!(Instruction::from_opcode(opcode::PUSH2,&[1,2]),Some(PUSH(vec![1,2])));
+    assert_eq!(Instruction::from_opcode(opcode::PUSH2,&[1]),None);
+    assert_eq!(Instruction::from_opcode(opcode::PUSH1,&[]),None);
+    assert_eq!(Instruction::from_opcode(opcode::ADD,&[1]),None);
+}
+
+#[test]
+fn test_from_opcode_04() {
+    // Decoding agrees with the opcode table
+    for op in 0..=255u8 {
+        let mut bytes = vec![op];
+        bytes.extend(vec![0u8;push_width(op)]);
+        let insn = Instruction::decode(0,&bytes);
+        match Instruction::from_opcode(op,&bytes[1..]) {
+            Some(expected) => assert_eq!(insn,expected),
+            None => assert_eq!(insn,DATA(vec![op]))
+        }
+    }
+}
+
+#[test]
+fn test_mnemonic_01() {
+    assert_eq!(opcode::mnemonic(opcode::STOP),Some("stop"));
+    assert_eq!(opcode::mnemonic(opcode::PUSH1),Some("push1"));
+    assert_eq!(opcode::mnemonic(opcode::PUSH32),Some("push32"));
+    assert_eq!(opcode::mnemonic(opcode::DUP16),Some("dup16"));
+    assert_eq!(opcode::mnemonic(opcode::LOG4),Some("log4"));
+    assert_eq!(opcode::mnemonic(opcode::RJUMP),Some("rjump"));
+    assert_eq!(opcode::mnemonic(opcode::EOF),None);
+}
+
+#[test]
+fn test_mnemonic_02() {
+    // Mnemonics agree with the opcode table
+    for op in 0..=255u8 {
+        let imm = vec![0u8;push_width(op)];
+        match Instruction::from_opcode(op,&imm) {
+            Some(_) => assert!(opcode::mnemonic(op).is_some()),
+            None if (opcode::RJUMP..=opcode::JUMPF).contains(&op) => assert!(opcode::mnemonic(op).is_some()),
+            None => assert_eq!(opcode::mnemonic(op),None)
+        }
+    }
+    assert_eq!(opcode::mnemonic(opcode::DIFFICULTY),Some("difficulty"));
+    assert_eq!(opcode::mnemonic(opcode::SWAP1),Some("swap1"));
+    assert_eq!(opcode::mnemonic(opcode::PUSH0),Some("push0"));
+    assert_eq!(opcode::mnemonic(opcode::RJUMPV),Some("rjumpv"));
+    assert_eq!(opcode::mnemonic(opcode::INVALID),Some("invalid"));
+}
+
+// ============================================================================
+// Mnemonic Parsing
+// ============================================================================
+
+#[test]
+fn test_from_str_01() {
+    assert_eq!("add".parse::<Instruction>().unwrap(),ADD);
+    assert_eq!("ADD".parse::<Instruction>().unwrap(),ADD);
+    assert_eq!("push0".parse::<Instruction>().unwrap(),PUSH0);
+    assert_eq!("prevrandao".parse::<Instruction>().unwrap(),DIFFICULTY);
+    assert_eq!("  selfdestruct ".parse::<Instruction>().unwrap(),SELFDESTRUCT);
+}
+
+#[test]
+fn test_from_str_02() {
+    // Push family
+    assert_eq!("PUSH1 0x20".parse::<Instruction>().unwrap(),PUSH(vec![0x20]));
+    assert_eq!("push1 32".parse::<Instruction>().unwrap(),PUSH(vec![0x20]));
+    assert_eq!("push2 0x20".parse::<Instruction>().unwrap(),PUSH(vec![0x00,0x20]));
+    assert_eq!("push32 1".parse::<Instruction>().unwrap(),PUSH([vec![0;31],vec![1]].concat()));
+    assert_eq!("push 0x0020".parse::<Instruction>().unwrap(),PUSH(vec![0x00,0x20]));
+    assert_eq!("push 256".parse::<Instruction>().unwrap(),PUSH(vec![0x01,0x00]));
+    assert_eq!("push 0".parse::<Instruction>().unwrap(),PUSH(vec![0x00]));
+}
+
+#[test]
+fn test_from_str_03() {
+    // Dup, swap and log families
+    for n in 1..=16 {
+        assert_eq!(format!("dup{n}").parse::<Instruction>().unwrap(),DUP(n));
+        assert_eq!(format!("SWAP{n}").parse::<Instruction>().unwrap(),SWAP(n));
+    }
+    for n in 0..=4 {
+        assert_eq!(format!("log{n}").parse::<Instruction>().unwrap(),LOG(n));
+    }
+}
+
+#[test]
+fn test_from_str_04() {
+    // Unknown mnemonics
+    assert!(matches!("push33 0x01".parse::<Instruction>(),Err(ParseError::InvalidInstruction)));
+    assert!(matches!("dup17".parse::<Instruction>(),Err(ParseError::InvalidInstruction)));
+    assert!(matches!("swap0".parse::<Instruction>(),Err(ParseError::InvalidInstruction)));
+    assert!(matches!("log5".parse::<Instruction>(),Err(ParseError::InvalidInstruction)));
+    assert!(matches!("push01 0x01".parse::<Instruction>(),Err(ParseError::InvalidInstruction)));
+    assert!(matches!("foo".parse::<Instruction>(),Err(ParseError::InvalidInstruction)));
+    assert!(matches!("".parse::<Instruction>(),Err(ParseError::InvalidInstruction)));
+}
+
+#[test]
+fn test_from_str_05() {
+    // Invalid operands
+    assert!(matches!("push1 0x0102".parse::<Instruction>(),Err(ParseError::PushOperandTooWide(6))));
+    assert!(matches!(" push1  256 ".parse::<Instruction>(),Err(ParseError::PushOperandTooWide(8))));
+    assert!(matches!("push 0xzz".parse::<Instruction>(),Err(ParseError::InvalidLiteralString(5))));
+    assert!(matches!("push1".parse::<Instruction>(),Err(ParseError::ExpectedOperand)));
+    assert!(matches!("push".parse::<Instruction>(),Err(ParseError::ExpectedOperand)));
+    assert!(matches!("add 1".parse::<Instruction>(),Err(ParseError::UnexpectedToken)));
+    assert!(matches!("push1 1 2".parse::<Instruction>(),Err(ParseError::UnexpectedToken)));
+}
+
+#[test]
+fn test_from_str_06() {
+    // Parsing is the inverse of display
+    let insns = [STOP,PUSH(vec![0x00,0x20]),DUP(3),SWAP(16),LOG(2),DATA(vec![0x0c]),HAVOC(1),
+                 RJUMP(-8),RJUMPI(0),RJUMPV(vec![16,-16]),CALLF(1),RETF,JUMPF(2),DIFFICULTY,KECCAK256];
+    for insn in insns {
+        assert_eq!(insn.to_string().parse::<Instruction>().unwrap(),insn);
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Check that a given instruction encodes to a single given opcode,
+/// and that this decodes back into the original instruction.
+fn check(insn: Instruction, opcode: u8) {
+    assert_eq!(insn.opcode(),opcode);
+    let bytes = [insn.clone()].assemble();
+    assert_eq!(bytes,vec![opcode]);
+    assert_eq!(bytes.disassemble(),vec![insn]);
+}
+
+/// Determine the width of the immediate for a given opcode.
+fn push_width(op: u8) -> usize {
+    if (opcode::PUSH1..=opcode::PUSH32).contains(&op) { (1 + op - opcode::PUSH1) as usize } else { 0 }
+}
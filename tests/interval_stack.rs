@@ -1,4 +1,4 @@
-use evmil::util::{w256, Interval, IntervalStack, W256_ONE, W256_TWO, W256_THREE, Top};
+use evmil::util::{w256, Bottom, Interval, IntervalStack, JoinInto, W256_ONE, W256_TWO, W256_THREE, Top};
 
 type Word = Interval<w256>;
 
@@ -187,6 +187,40 @@ fn test_abstract_stack_17() {
     assert_eq!(st1.join(&st2), IntervalStack::new(0..=1, vec![ONETWO, ONE]));
 }
 
+// Tests for join_into()
+#[test]
+fn test_abstract_stack_18() {
+    // Differing stacks report a change
+    let mut st1 = IntervalStack::new(0, vec![ONE, ONE]);
+    let st2 = IntervalStack::new(0, vec![TWO, ONE]);
+    assert!(st1.join_into(&st2));
+    assert_eq!(st1, IntervalStack::new(0, vec![ONETWO, ONE]));
+}
+#[test]
+fn test_abstract_stack_19() {
+    // Differing heights report a change
+    let mut st1 = IntervalStack::new(0, vec![ONE]);
+    let st2 = IntervalStack::new(0, vec![TWO, ONE]);
+    assert!(st1.join_into(&st2));
+    assert_eq!(st1, IntervalStack::new(0..=1, vec![ONE]));
+}
+#[test]
+fn test_abstract_stack_1a() {
+    // Subsumed stacks report no change
+    let mut st1 = IntervalStack::new(0, vec![ONETWO, ONE]);
+    let st2 = IntervalStack::new(0, vec![TWO, ONE]);
+    assert!(!st1.join_into(&st2));
+    assert!(!st1.join_into(&st1.clone()));
+}
+#[test]
+fn test_abstract_stack_1b() {
+    // Bottom always changes
+    let mut st1 = IntervalStack::BOTTOM;
+    let st2 = IntervalStack::new(0, vec![ONE]);
+    assert!(st1.join_into(&st2));
+    assert_eq!(st1, st2);
+}
+
 // Tests for set()
 // Force upper expansion
 // Force rebalance
@@ -0,0 +1,180 @@
+use evmil::analysis::{find_jumpdest_heights,find_stack_mismatches,JumpDest,StackMismatch};
+use evmil::bytecode::{Assembly,Disassemble,jumpdest_bitmap,jumpdests};
+use evmil::util::FromHexString;
+
+// ============================================================================
+// Heights
+// ============================================================================
+
+#[test]
+fn test_jumpdest_height_01() {
+    let asm = r#"
+.code
+   push 0x01
+   push lab
+   jump
+lab:
+   jumpdest
+   stop
+"#;
+    check(asm, &[(6,&[1])]);
+}
+
+#[test]
+fn test_jumpdest_height_02() {
+    let asm = r#"
+.code
+   push 0x01
+   push 0x02
+   push lab
+   jump
+lab:
+   jumpdest
+   pop
+   stop
+"#;
+    check(asm, &[(8,&[2])]);
+}
+
+#[test]
+fn test_jumpdest_height_03() {
+    // Two paths reaching the same jumpdest with different heights.
+    let asm = r#"
+.code
+   calldatasize
+   push lab
+   jumpi
+   push 0x01
+   push 0x02
+lab:
+   jumpdest
+   stop
+"#;
+    check(asm, &[(9,&[0,2])]);
+}
+
+#[test]
+fn test_jumpdest_height_04() {
+    // Unreachable jumpdests are not reported.
+    let asm = r#"
+.code
+   stop
+lab:
+   jumpdest
+   stop
+"#;
+    check(asm, &[]);
+}
+
+#[test]
+fn test_jumpdest_height_05() {
+    let jd = JumpDest{pc: 0, heights: vec![2]};
+    assert_eq!(jd.height(), Some(2));
+    let jd = JumpDest{pc: 0, heights: vec![1,2]};
+    assert_eq!(jd.height(), None);
+}
+
+// ============================================================================
+// Mismatches
+// ============================================================================
+
+#[test]
+fn test_stack_mismatch_01() {
+    // Unbalanced predecessors
+    let asm = r#"
+.code
+   calldatasize
+   push lab
+   jumpi
+   push 0x01
+lab:
+   jumpdest
+   stop
+"#;
+    let insns = Assembly::from_str(asm).unwrap().to_legacy_bytes().disassemble();
+    let mismatches = find_stack_mismatches(&insns,usize::MAX).unwrap();
+    assert_eq!(mismatches,[StackMismatch{pc:7,min:0,max:1}]);
+}
+
+#[test]
+fn test_stack_mismatch_02() {
+    // Balanced predecessors
+    let asm = r#"
+.code
+   calldatasize
+   push 0x01
+   swap1
+   push lab
+   jumpi
+lab:
+   jumpdest
+   stop
+"#;
+    let insns = Assembly::from_str(asm).unwrap().to_legacy_bytes().disassemble();
+    assert!(find_stack_mismatches(&insns,usize::MAX).unwrap().is_empty());
+}
+
+// ============================================================================
+// Bitmap
+// ============================================================================
+
+#[test]
+fn test_jumpdest_bitmap_01() {
+    // push1 0x5b; jumpdest
+    let bytes = "0x605b5b".from_hex_string().unwrap();
+    assert_eq!(jumpdest_bitmap(&bytes), vec![false,false,true]);
+}
+
+#[test]
+fn test_jumpdest_bitmap_02() {
+    // push2 0x5b5b; jumpdest; push32 ...; jumpdest
+    let mut hex = "0x615b5b5b7f".to_string();
+    hex.push_str(&"5b".repeat(32));
+    hex.push_str("5b");
+    let bytes = hex.from_hex_string().unwrap();
+    let bitmap = jumpdest_bitmap(&bytes);
+    let valid : Vec<usize> = (0..bitmap.len()).filter(|i| bitmap[*i]).collect();
+    assert_eq!(valid, vec![3,37]);
+}
+
+#[test]
+fn test_jumpdest_bitmap_03() {
+    // Truncated push
+    let bytes = "0x5b625b5b".from_hex_string().unwrap();
+    assert_eq!(jumpdest_bitmap(&bytes), vec![true,false,false,false]);
+}
+
+// ============================================================================
+// Offsets
+// ============================================================================
+
+#[test]
+fn test_jumpdests_01() {
+    // push1 0x5b; jumpdest; stop
+    let bytes = "0x605b5b00".from_hex_string().unwrap();
+    assert_eq!(jumpdests(&bytes), vec![2]);
+}
+
+#[test]
+fn test_jumpdests_02() {
+    // jumpdest; push2 0x5b5b; jumpdest; push1 0x5b; jumpdest
+    let bytes = "0x5b615b5b5b605b5b".from_hex_string().unwrap();
+    assert_eq!(jumpdests(&bytes), vec![0,4,7]);
+}
+
+#[test]
+fn test_jumpdests_03() {
+    assert_eq!(jumpdests(&[]), Vec::<usize>::new());
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn check(asm: &str, expected: &[(usize,&[usize])]) {
+    let bytes = Assembly::from_str(asm).unwrap().to_legacy_bytes();
+    let insns = bytes.disassemble();
+    let jumpdests = find_jumpdest_heights(&insns, usize::MAX).unwrap();
+    let actual : Vec<(usize,&[usize])> = jumpdests.iter().map(|jd| (jd.pc,jd.heights.as_slice())).collect();
+    assert_eq!(actual, expected);
+}
@@ -0,0 +1,105 @@
+use evmil::analysis::{find_infinite_loops};
+use evmil::bytecode::{Assembly,Disassemble,Instruction};
+
+#[test]
+fn test_loops_01() {
+    // Self loop
+    let asm = r#"
+.code
+lab:
+   jumpdest
+   push lab
+   jump
+"#;
+    check(asm, &[0]);
+}
+
+#[test]
+fn test_loops_02() {
+    // Self loop after some setup
+    let asm = r#"
+.code
+   push 0x01
+   pop
+lab:
+   jumpdest
+   push lab
+   jump
+"#;
+    check(asm, &[1]);
+}
+
+#[test]
+fn test_loops_03() {
+    // Cycle with no exit
+    let asm = r#"
+.code
+l1:
+   jumpdest
+   push l2
+   jump
+l2:
+   jumpdest
+   push l1
+   jump
+"#;
+    check(asm, &[0,1]);
+}
+
+#[test]
+fn test_loops_04() {
+    // Loop with conditional exit
+    let asm = r#"
+.code
+lab:
+   jumpdest
+   calldatasize
+   push lab
+   jumpi
+   stop
+"#;
+    check(asm, &[]);
+}
+
+#[test]
+fn test_loops_05() {
+    // Cycle with an exit from the second block
+    let asm = r#"
+.code
+l1:
+   jumpdest
+   push l2
+   jump
+l2:
+   jumpdest
+   calldatasize
+   push l1
+   jumpi
+   stop
+"#;
+    check(asm, &[]);
+}
+
+#[test]
+fn test_loops_06() {
+    // Conditionally entered self loop
+    let asm = r#"
+.code
+   calldatasize
+   push lab
+   jumpi
+   stop
+lab:
+   jumpdest
+   push lab
+   jump
+"#;
+    check(asm, &[1]);
+}
+
+fn check(asm: &str, expected: &[usize]) {
+    let bytes = Assembly::from_str(asm).unwrap().to_legacy_bytes();
+    let insns : Vec<Instruction> = bytes.disassemble();
+    let blocks = find_infinite_loops(&insns,usize::MAX).unwrap();
+    assert_eq!(blocks,expected);
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "keccak")]
+use evmil::bytecode::{code_hash,strip_metadata,stripped_code_hash};
+use evmil::util::{w256,FromHexString,KECCAK_EMPTY};
+
+/// Runtime code of the deterministic deployment proxy deployed at
+/// `0x4e59b44847b379578588920ca78fbf26c0b4956c`.
+const PROXY : &str = "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe03601600081602082378035828234f58015156039578182fd5b8082525050506014600cf3";
+
+/// On-chain code hash of the deterministic deployment proxy.
+const PROXY_HASH : &str = "0x2fa86add0aed31f33a762c9d88e807c475bd51d0f52bd0955754b2608f7e4989";
+
+#[test]
+fn test_code_hash_01() {
+    assert_eq!(code_hash(&[]),KECCAK_EMPTY);
+}
+
+#[test]
+fn test_code_hash_02() {
+    let bytes = PROXY.from_hex_string().unwrap();
+    assert_eq!(code_hash(&bytes),from_hex(PROXY_HASH));
+    // No metadata, hence stripping has no effect
+    assert_eq!(stripped_code_hash(&bytes),from_hex(PROXY_HASH));
+}
+
+#[test]
+fn test_code_hash_03() {
+    // Contracts differing only in metadata
+    let b1 = "0x6000a164736f6c6343000813000a".from_hex_string().unwrap();
+    let b2 = "0x6000a164736f6c6343000814000a".from_hex_string().unwrap();
+    assert_ne!(code_hash(&b1),code_hash(&b2));
+    assert_eq!(stripped_code_hash(&b1),stripped_code_hash(&b2));
+    assert_eq!(stripped_code_hash(&b1),code_hash(&[0x60,0x00]));
+}
+
+#[test]
+fn test_strip_metadata_01() {
+    let bytes = "0x6000a164736f6c6343000813000a".from_hex_string().unwrap();
+    assert_eq!(strip_metadata(&bytes),&[0x60,0x00]);
+}
+
+#[test]
+fn test_strip_metadata_02() {
+    // Length too large
+    let bytes = "0x6000a1ff00".from_hex_string().unwrap();
+    assert_eq!(strip_metadata(&bytes),&bytes[..]);
+    // Not a CBOR map
+    let bytes = "0x60000102".from_hex_string().unwrap();
+    assert_eq!(strip_metadata(&bytes),&bytes[..]);
+    // Too short
+    assert_eq!(strip_metadata(&[0x00]),&[0x00]);
+}
+
+fn from_hex(hex: &str) -> w256 {
+    let bytes = hex.from_hex_string().unwrap();
+    w256::from_be_slice(&bytes)
+}
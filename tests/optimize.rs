@@ -0,0 +1,84 @@
+use evmil::bytecode::{Assembly,Instruction,StructuredSection};
+use evmil::bytecode::Instruction::*;
+use evmil::il::optimize;
+
+// ============================================================================
+// Rewrites
+// ============================================================================
+
+#[test]
+fn test_optimize_01() {
+    check(".code\n calldatasize\n push 0x01\n pop\n stop\n", &[CALLDATASIZE,STOP]);
+}
+
+#[test]
+fn test_optimize_02() {
+    check(".code\n calldatasize\n push0\n pop\n stop\n", &[CALLDATASIZE,STOP]);
+}
+
+#[test]
+fn test_optimize_03() {
+    check(".code\n calldatasize\n dup1\n pop\n stop\n", &[CALLDATASIZE,STOP]);
+}
+
+#[test]
+fn test_optimize_04() {
+    check(".code\n calldatasize\n callvalue\n swap1\n swap1\n stop\n", &[CALLDATASIZE,CALLVALUE,STOP]);
+    // Different swaps are retained
+    check(".code\n calldatasize\n callvalue\n gas\n swap1\n swap2\n stop\n", &[CALLDATASIZE,CALLVALUE,GAS,SWAP(1),SWAP(2),STOP]);
+}
+
+#[test]
+fn test_optimize_05() {
+    check(".code\n calldatasize\n not\n not\n stop\n", &[CALLDATASIZE,STOP]);
+}
+
+#[test]
+fn test_optimize_06() {
+    // Rewrites are applied to a fixed point
+    check(".code\n calldatasize\n push 0x01\n dup1\n swap1\n swap1\n pop\n pop\n stop\n", &[CALLDATASIZE,STOP]);
+}
+
+// ============================================================================
+// Jumps
+// ============================================================================
+
+#[test]
+fn test_optimize_07() {
+    // A jumpdest prevents a rewrite
+    check(".code\n push 0x01\n jumpdest\n pop\n stop\n", &[PUSH(vec![0x01]),JUMPDEST,POP,STOP]);
+}
+
+#[test]
+fn test_optimize_08() {
+    // Jump targets are relocated
+    let asm = ".code\n calldatasize\n push lab\n jumpi\n push 0x01\n pop\nlab:\n jumpdest\n stop\n";
+    check(asm, &[CALLDATASIZE,PUSH(vec![0x00,0x05]),JUMPI,JUMPDEST,STOP]);
+}
+
+#[test]
+fn test_optimize_09() {
+    // Pushes of jump targets are never removed
+    let asm = ".code\n push lab\n dup1\n pop\n jump\nlab:\n jumpdest\n stop\n";
+    check(asm, &[PUSH(vec![0x00,0x04]),JUMP,JUMPDEST,STOP]);
+}
+
+#[test]
+fn test_optimize_10() {
+    // Nothing to rewrite, hence jump targets are not widened
+    let insns = [PUSH(vec![0x03]),JUMP,JUMPDEST,STOP];
+    assert_eq!(optimize(&insns,usize::MAX).unwrap(),insns);
+}
+
+fn check(asm: &str, expected: &[Instruction]) {
+    let insns = code(asm);
+    assert_eq!(optimize(&insns,usize::MAX).unwrap(),expected);
+}
+
+fn code(asm: &str) -> Vec<Instruction> {
+    let assembly = Assembly::from_str(asm).unwrap();
+    match assembly.iter().next() {
+        Some(StructuredSection::Code(insns)) => insns.clone(),
+        _ => panic!("expected code section")
+    }
+}
@@ -0,0 +1,64 @@
+use evmil::analysis::is_pure;
+use evmil::util::FromHexString;
+
+#[test]
+fn test_pure_01() {
+    // push1 0x1; push1 0x2; add; stop
+    assert!(check("0x600160020100"));
+}
+
+#[test]
+fn test_pure_02() {
+    // push1 0x4; calldataload; push1 0x2; mul; push1 0x0; mstore; push1 0x20; push1 0x0; return
+    assert!(check("0x60043560020260005260206000f3"));
+}
+
+#[test]
+fn test_pure_03() {
+    // push1 0x1; push1 0x0; sstore; stop
+    assert!(!check("0x600160005500"));
+}
+
+#[test]
+fn test_pure_04() {
+    // push1 0x0; sload; stop
+    assert!(!check("0x60005400"));
+}
+
+#[test]
+fn test_pure_05() {
+    // push1 0x0; push1 0x0; log0
+    assert!(!check("0x60006000a0"));
+}
+
+#[test]
+fn test_pure_06() {
+    // Sstore byte within push data is not an instruction.
+    // push1 0x55; stop
+    assert!(check("0x605500"));
+}
+
+#[test]
+fn test_pure_07() {
+    // address; caller; origin; stop
+    assert!(!check("0x30333200"));
+}
+
+#[test]
+fn test_pure_08() {
+    // timestamp; number; stop
+    assert!(!check("0x424300"));
+}
+
+#[test]
+fn test_pure_09() {
+    // gasprice; stop
+    assert!(!check("0x3a00"));
+    // address; balance; stop
+    assert!(!check("0x303100"));
+}
+
+fn check(hex: &str) -> bool {
+    let bytes = hex.from_hex_string().unwrap();
+    is_pure(&bytes)
+}
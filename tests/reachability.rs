@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+use evmil::analysis::{reachable_opcodes_from,strip_dead_code,BlockGraph};
+use evmil::bytecode::{Assembly,Instruction,StructuredSection};
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+const BRANCHY_ASM : &str = r#"
+.code
+   calldatasize
+   push l1
+   jumpi
+   push 0x01
+   push l2
+   jump
+l1:
+   jumpdest
+   push 0x02
+   pop
+   push l3
+   jump
+l2:
+   jumpdest
+   pop
+l3:
+   jumpdest
+   stop
+"#;
+
+#[test]
+fn test_reachable_from_01() {
+    // Mid-block in the `l1` branch
+    check(BRANCHY_ASM,0x0c,false,Some(&[0x0c,0x0e,0x0f,0x12,0x15,0x16]));
+}
+
+#[test]
+fn test_reachable_from_02() {
+    // Fall through branch
+    check(BRANCHY_ASM,0x05,false,Some(&[0x05,0x07,0x0a,0x13,0x14,0x15,0x16]));
+}
+
+#[test]
+fn test_reachable_from_03() {
+    // Entry reaches everything
+    check(BRANCHY_ASM,0x00,false,Some(&[0x00,0x01,0x04,0x05,0x07,0x0a,0x0b,0x0c,0x0e,0x0f,0x12,0x13,0x14,0x15,0x16]));
+}
+
+#[test]
+fn test_reachable_from_04() {
+    // Over-approximation assumes jumps reach any jumpdest
+    check(BRANCHY_ASM,0x0c,true,Some(&[0x0b,0x0c,0x0e,0x0f,0x12,0x13,0x14,0x15,0x16]));
+}
+
+#[test]
+fn test_reachable_from_05() {
+    // Offset within a push immediate
+    check(BRANCHY_ASM,0x02,false,None);
+}
+
+// ============================================================================
+// Dead Code
+// ============================================================================
+
+#[test]
+fn test_dead_code_01() {
+    // Unreachable block between two returns
+    let asm = r#"
+.code
+   calldatasize
+   push lab
+   jumpi
+   push 0x00
+   push 0x00
+   return
+   push 0x01
+   push 0x01
+   return
+lab:
+   jumpdest
+   push 0x20
+   push 0x00
+   return
+"#;
+    let insns = code(asm);
+    let cfg = BlockGraph::from(insns.as_slice());
+    assert_eq!(cfg.reachable().iter().copied().collect::<Vec<_>>(),[0,2]);
+    let stripped = strip_dead_code(&insns,usize::MAX).unwrap();
+    let expected = code(".code\n calldatasize\n push 0x000a\n jumpi\n push 0x00\n push 0x00\n return\n jumpdest\n push 0x20\n push 0x00\n return\n");
+    assert_eq!(stripped,expected);
+}
+
+#[test]
+fn test_dead_code_02() {
+    // Nothing to remove
+    let stripped = strip_dead_code(&code(BRANCHY_ASM),usize::MAX).unwrap();
+    assert_eq!(stripped,code(BRANCHY_ASM));
+}
+
+#[test]
+fn test_dead_code_03() {
+    // Dead code containing jumps is removed entirely
+    let asm = ".code\n push lab\n jump\n push lab\n jump\nlab:\n jumpdest\n stop\n";
+    let stripped = strip_dead_code(&code(asm),usize::MAX).unwrap();
+    assert_eq!(stripped,code(".code\n push 0x0004\n jump\n jumpdest\n stop\n"));
+}
+
+fn check(asm: &str, pc: usize, over_approximate: bool, expected: Option<&[usize]>) {
+    let insns = code(asm);
+    let reached = reachable_opcodes_from(&insns,pc,over_approximate,usize::MAX);
+    let expected : Option<HashSet<usize>> = expected.map(|pcs| pcs.iter().copied().collect());
+    assert_eq!(reached,expected);
+}
+
+fn code(asm: &str) -> Vec<Instruction> {
+    let assembly = Assembly::from_str(asm).unwrap();
+    match assembly.iter().next() {
+        Some(StructuredSection::Code(insns)) => insns.clone(),
+        _ => panic!("expected code section")
+    }
+}
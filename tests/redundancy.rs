@@ -0,0 +1,79 @@
+use evmil::analysis::find_redundant_sloads;
+use evmil::bytecode::Disassemble;
+use evmil::util::FromHexString;
+
+#[test]
+fn test_redundant_sload_01() {
+    // push1 0x0; sload; push1 0x0; sload
+    check("0x6000546000546000", &[5]);
+}
+
+#[test]
+fn test_redundant_sload_02() {
+    // push1 0x0; sload; push1 0x1; sload
+    check("0x60005460015400", &[]);
+}
+
+#[test]
+fn test_redundant_sload_03() {
+    // push1 0x1; push1 0x0; sstore; push1 0x0; sload
+    check("0x60016000556000546000", &[7]);
+}
+
+#[test]
+fn test_redundant_sload_04() {
+    // Slot 0 written after first read, but second read follows the
+    // write.
+    // push1 0x0; sload; push1 0x1; push1 0x0; sstore; push1 0x0; sload
+    check("0x60005460016000556000546000", &[10]);
+}
+
+#[test]
+fn test_redundant_sload_05() {
+    // push1 0x0; sload; push1 0x0; push1 0x0; push1 0x0; push1 0x0;
+    // push1 0x0; push1 0x0; push1 0x0; call; push1 0x0; sload
+    check("0x6000546000600060006000600060006000f1600054", &[]);
+}
+
+#[test]
+fn test_redundant_sload_06() {
+    // push1 0x0; sload; push1 0x9; jump; invalid; invalid; invalid;
+    // jumpdest; push1 0x0; sload
+    check("0x600054600956fefefe5b60005400", &[12]);
+}
+
+#[test]
+fn test_redundant_sload_07() {
+    // Load only on one branch, so second load is not dominated.
+    // calldatasize; push1 0x8; jumpi; push1 0x0; sload; pop;
+    // jumpdest; push1 0x0; sload
+    check("0x36600857600054505b60005400", &[]);
+}
+
+#[test]
+fn test_redundant_sload_08() {
+    // Store to unknown slot in between.
+    // push1 0x0; sload; push1 0x1; calldatasize; sstore; push1 0x0; sload
+    check("0x600054600136556000546000", &[]);
+}
+
+#[test]
+fn test_redundant_sload_09() {
+    // Store to slot 0 between two loads of slot 1.
+    // push1 0x1; sload; push1 0x1; push1 0x0; sstore; push1 0x1; sload
+    check("0x60015460016000556001546000", &[10]);
+}
+
+#[test]
+fn test_redundant_sload_10() {
+    // Store to slot 0 on a path between two loads of slot 0.
+    // push1 0x0; sload; calldatasize; push1 0xc; jumpi; push1 0x1;
+    // push1 0x0; sstore; jumpdest; push1 0x0; sload
+    check("0x60005436600c5760016000555b60005400", &[]);
+}
+
+fn check(hex: &str, expected: &[usize]) {
+    let bytes = hex.from_hex_string().unwrap();
+    let insns = bytes.disassemble();
+    assert_eq!(find_redundant_sloads(&insns,usize::MAX).unwrap(),expected);
+}
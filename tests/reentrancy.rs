@@ -0,0 +1,149 @@
+use evmil::analysis::{find_reentrancy,Reentrancy};
+use evmil::bytecode::{Assembly,Instruction,StructuredSection};
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[test]
+fn test_reentrancy_01() {
+    // Classic withdraw: read balance, send it, then zero it.
+    let asm = r#"
+.code
+   push 0x0
+   sload       ;; balance
+   push 0x0
+   dup1
+   dup1
+   dup1
+   dup5        ;; value
+   caller
+   gas
+   call
+   pop
+   push 0x0
+   push 0x0
+   sstore
+   stop
+"#;
+    check(asm,&[Reentrancy{call: 0x0b, sstore: 0x11}]);
+}
+
+#[test]
+fn test_reentrancy_02() {
+    // Checks-effects-interactions: storage written before call.
+    let asm = r#"
+.code
+   push 0x0
+   sload
+   push 0x0
+   push 0x0
+   sstore
+   push 0x0
+   dup1
+   dup1
+   dup1
+   dup5
+   caller
+   gas
+   call
+   stop
+"#;
+    check(asm,&[]);
+}
+
+#[test]
+fn test_reentrancy_03() {
+    // Call with zero value.
+    let asm = r#"
+.code
+   push 0x0
+   sload
+   push 0x0
+   dup1
+   dup1
+   dup1
+   push 0x0
+   caller
+   gas
+   call
+   pop
+   push 0x0
+   push 0x0
+   sstore
+   stop
+"#;
+    check(asm,&[]);
+}
+
+#[test]
+fn test_reentrancy_04() {
+    // Different storage slots.
+    let asm = r#"
+.code
+   push 0x0
+   sload
+   push 0x0
+   dup1
+   dup1
+   dup1
+   dup5
+   caller
+   gas
+   call
+   pop
+   push 0x0
+   push 0x1
+   sstore
+   stop
+"#;
+    check(asm,&[]);
+}
+
+#[test]
+fn test_reentrancy_05() {
+    // Unknown slot (e.g. mapping entry) written after call, where the
+    // store follows a branch.
+    let asm = r#"
+.code
+   caller
+   sload
+   push 0x0
+   dup1
+   dup1
+   dup1
+   dup5
+   caller
+   gas
+   call
+   push lab
+   jumpi
+   stop
+lab:
+   jumpdest
+   push 0x0
+   push 0x0
+   calldataload
+   sstore
+   stop
+"#;
+    check(asm,&[Reentrancy{call: 0x0a, sstore: 0x16}]);
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn check(asm: &str, expected: &[Reentrancy]) {
+    let insns = code(asm);
+    let warnings = find_reentrancy(&insns,usize::MAX).unwrap();
+    assert_eq!(warnings,expected);
+}
+
+fn code(asm: &str) -> Vec<Instruction> {
+    let assembly = Assembly::from_str(asm).unwrap();
+    match assembly.iter().next() {
+        Some(StructuredSection::Code(insns)) => insns.clone(),
+        _ => panic!("expected code section")
+    }
+}
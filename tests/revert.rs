@@ -0,0 +1,121 @@
+use evmil::analysis::{aw256,execute,ConcreteMemory,ConcreteStack,ConcreteState,Outcome,RevertReason,UnknownStorage};
+use evmil::bytecode::Instruction;
+use evmil::bytecode::Instruction::*;
+use evmil::util::{w256,FromHexString};
+
+type State = ConcreteState<ConcreteStack<aw256>,ConcreteMemory<aw256>,UnknownStorage<aw256>>;
+
+// ============================================================================
+// Error(string)
+// ============================================================================
+
+#[test]
+fn test_revert_error_01() {
+    // require(false,"Ownable: caller is not the owner")
+    let data = "0x08c379a0\
+                0000000000000000000000000000000000000000000000000000000000000020\
+                0000000000000000000000000000000000000000000000000000000000000020\
+                4f776e61626c653a2063616c6c6572206973206e6f7420746865206f776e6572";
+    let reason = revert_reason(data);
+    assert_eq!(reason,RevertReason::Error("Ownable: caller is not the owner".to_string()));
+    assert_eq!(reason.to_string(),"Error(\"Ownable: caller is not the owner\")");
+}
+
+#[test]
+fn test_revert_error_02() {
+    // require(false,"")
+    let data = "0x08c379a0\
+                0000000000000000000000000000000000000000000000000000000000000020\
+                0000000000000000000000000000000000000000000000000000000000000000";
+    assert_eq!(revert_reason(data),RevertReason::Error(String::new()));
+}
+
+#[test]
+fn test_revert_error_03() {
+    // Length exceeds available data
+    let data = "0x08c379a0\
+                0000000000000000000000000000000000000000000000000000000000000020\
+                0000000000000000000000000000000000000000000000000000000000000040\
+                4f776e61626c653a2063616c6c6572206973206e6f7420746865206f776e6572";
+    assert!(matches!(revert_reason(data),RevertReason::Custom(_)));
+}
+
+// ============================================================================
+// Panic(uint256)
+// ============================================================================
+
+#[test]
+fn test_revert_panic_01() {
+    // Arithmetic overflow
+    let data = "0x4e487b71\
+                0000000000000000000000000000000000000000000000000000000000000011";
+    let reason = revert_reason(data);
+    assert_eq!(reason,RevertReason::Panic(w256::from(0x11)));
+    assert_eq!(reason.to_string(),"Panic(0x11)");
+}
+
+// ============================================================================
+// Custom
+// ============================================================================
+
+#[test]
+fn test_revert_custom_01() {
+    // Custom error `Unauthorized()`
+    let reason = revert_reason("0x82b42900");
+    assert_eq!(reason,RevertReason::Custom(vec![0x82,0xb4,0x29,0x00]));
+    assert_eq!(reason.to_string(),"0x82b42900");
+}
+
+#[test]
+fn test_revert_custom_02() {
+    // Empty revert (e.g. `revert()`)
+    let outcome = execute(&REVERT,run(&[push(0),push(0)]));
+    assert_eq!(outcome.revert_reason(),Some(RevertReason::Custom(Vec::new())));
+}
+
+#[test]
+fn test_revert_unknown_01() {
+    // Revert data in unknown region of memory
+    let outcome = execute(&REVERT,run(&[push(4),CALLVALUE]));
+    assert!(outcome.revert_reason().is_none());
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Execute a revert of the given data (which is first written into
+/// memory), and decode the resulting revert reason.
+fn revert_reason(hex: &str) -> RevertReason {
+    let data = hex.from_hex_string().unwrap();
+    let mut insns = Vec::new();
+    // Write data into memory
+    for (i,chunk) in data.chunks(32).enumerate() {
+        let mut word = chunk.to_vec();
+        word.resize(32,0);
+        insns.push(PUSH(word));
+        insns.push(push(i as u8 * 32));
+        insns.push(MSTORE);
+    }
+    insns.push(push(data.len() as u8));
+    insns.push(push(0));
+    //
+    let outcome = execute(&REVERT,run(&insns));
+    assert!(matches!(outcome,Outcome::Revert{..}));
+    outcome.revert_reason().unwrap()
+}
+
+fn run(insns: &[Instruction]) -> State {
+    let mut state = State::new();
+    for insn in insns {
+        state = match execute(insn,state) {
+            Outcome::Continue(st) => st,
+            _ => panic!("unexpected outcome executing {insn}")
+        };
+    }
+    state
+}
+
+fn push(n: u8) -> Instruction {
+    PUSH(vec![n])
+}
@@ -0,0 +1,110 @@
+use proptest::prelude::*;
+use evmil::bytecode::{Assemble,Disassemble,Instruction};
+use evmil::bytecode::Instruction::*;
+
+// ============================================================================
+// Generators
+// ============================================================================
+
+/// Every instruction which is encoded as a single byte (e.g. `ADD`,
+/// `PUSH0`, `DUP1`, `LOG4`).  This is determined by decoding every
+/// possible byte, and discarding those which are not valid opcodes or
+/// which expect an immediate.
+fn single_byte_insns() -> Vec<Instruction> {
+    (0..=255u8).map(|b| Instruction::decode(0,&[b]))
+        .filter(|i| !matches!(i,DATA(_)) && i.length() == 1)
+        .collect()
+}
+
+/// Generate an arbitrary `PUSH` instruction with a valid immediate
+/// (i.e. between `1` and `32` bytes).
+fn arb_push() -> impl Strategy<Value = Instruction> {
+    prop::collection::vec(any::<u8>(),1..=32).prop_map(PUSH)
+}
+
+/// Generate an arbitrary instruction which can be encoded as legacy
+/// bytecode.
+fn arb_insn() -> impl Strategy<Value = Instruction> {
+    prop_oneof![
+        prop::sample::select(single_byte_insns()),
+        arb_push()
+    ]
+}
+
+/// Generate an arbitrary sequence of instructions which can be
+/// encoded as legacy bytecode.
+fn arb_insns() -> impl Strategy<Value = Vec<Instruction>> {
+    prop::collection::vec(arb_insn(),0..64)
+}
+
+// ============================================================================
+// Properties
+// ============================================================================
+
+proptest! {
+    #[test]
+    fn test_roundtrip_01(insns in arb_insns()) {
+        // Encode each instruction individually
+        let mut bytes = Vec::new();
+        let mut pc = 0;
+        for insn in &insns {
+            insn.encode(pc,&mut bytes).unwrap();
+            pc += insn.length();
+        }
+        prop_assert_eq!(bytes.len(),pc);
+        check_roundtrip(&insns,&bytes)?;
+    }
+
+    #[test]
+    fn test_roundtrip_02(insns in arb_insns()) {
+        // Encode whole sequence at once
+        let bytes = insns.assemble();
+        check_roundtrip(&insns,&bytes)?;
+    }
+
+    #[test]
+    fn test_roundtrip_03(insn in arb_insn()) {
+        // Encoding matches the declared length
+        let mut bytes = Vec::new();
+        insn.encode(0,&mut bytes).unwrap();
+        prop_assert_eq!(bytes.len(),insn.length());
+        prop_assert_eq!(bytes[0],insn.opcode());
+    }
+}
+
+#[test]
+fn test_roundtrip_04() {
+    // Sanity check the generator covers every single byte opcode.
+    let insns = single_byte_insns();
+    assert!(insns.contains(&STOP));
+    assert!(insns.contains(&PUSH0));
+    assert!(insns.contains(&DUP(16)));
+    assert!(insns.contains(&SWAP(16)));
+    assert!(insns.contains(&LOG(4)));
+    assert!(insns.contains(&SELFDESTRUCT));
+    assert!(!insns.iter().any(|i| matches!(i,PUSH(_))));
+}
+
+/// Check that disassembling some bytes gives back a given instruction
+/// sequence.  On failure, the byte offset of the first diverging
+/// instruction is reported.
+fn check_roundtrip(insns: &[Instruction], bytes: &[u8]) -> Result<(),TestCaseError> {
+    let actual = bytes.disassemble();
+    let mut pc = 0;
+    for (i,expected) in insns.iter().enumerate() {
+        match actual.get(i) {
+            Some(insn) if insn == expected => {}
+            Some(insn) => {
+                return Err(TestCaseError::fail(format!("diverged at byte offset {pc:#06x}: expected {expected}, got {insn}")));
+            }
+            None => {
+                return Err(TestCaseError::fail(format!("diverged at byte offset {pc:#06x}: expected {expected}, got nothing")));
+            }
+        }
+        pc += expected.length();
+    }
+    if actual.len() != insns.len() {
+        return Err(TestCaseError::fail(format!("diverged at byte offset {pc:#06x}: unexpected {}",actual[insns.len()])));
+    }
+    Ok(())
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "rpc")]
+use std::io::{Read,Write};
+use std::net::TcpListener;
+use std::thread;
+use evmil::rpc::{fetch_code,parse_code_response,RpcError};
+
+const ADDRESS: &str = "0x5f5e0d8b9e2a0ef5b0e6f1d2a0b3c4d5e6f70819";
+
+// ============================================================================
+// Responses
+// ============================================================================
+
+#[test]
+fn test_rpc_parse_01() {
+    let json = r#"{"jsonrpc":"2.0","id":1,"result":"0x6000"}"#;
+    assert_eq!(parse_code_response(json).unwrap(),vec![0x60,0x00]);
+}
+
+#[test]
+fn test_rpc_parse_02() {
+    // Account without code
+    let json = r#"{"jsonrpc":"2.0","id":1,"result":"0x"}"#;
+    assert_eq!(parse_code_response(json).unwrap(),Vec::<u8>::new());
+}
+
+#[test]
+fn test_rpc_parse_03() {
+    let json = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"invalid argument"}}"#;
+    assert!(matches!(parse_code_response(json),Err(RpcError::Node(m)) if m == "invalid argument"));
+}
+
+#[test]
+fn test_rpc_parse_04() {
+    let json = r#"{"jsonrpc":"2.0","id":1,"result":"0xzz"}"#;
+    assert!(matches!(parse_code_response(json),Err(RpcError::InvalidResponse(_))));
+}
+
+#[test]
+fn test_rpc_parse_05() {
+    // Malformed JSON
+    assert!(matches!(parse_code_response(r#"{"result":"0x6000""#),Err(RpcError::InvalidResponse(_))));
+    // Result is not a string
+    assert!(matches!(parse_code_response(r#"{"result":1}"#),Err(RpcError::InvalidResponse(_))));
+}
+
+#[test]
+fn test_rpc_parse_06() {
+    // Whitespace and field order are irrelevant
+    let json = "{ \"id\" : 1 ,\n \"result\" : \"0x6000\", \"jsonrpc\": \"2.0\" }";
+    assert_eq!(parse_code_response(json).unwrap(),vec![0x60,0x00]);
+}
+
+// ============================================================================
+// Requests
+// ============================================================================
+
+#[test]
+fn test_rpc_fetch_01() {
+    let url = mock_node(r#"{"jsonrpc":"2.0","id":1,"result":"0x6080604052"}"#);
+    assert_eq!(fetch_code(&url,ADDRESS).unwrap(),vec![0x60,0x80,0x60,0x40,0x52]);
+}
+
+#[test]
+fn test_rpc_fetch_02() {
+    let url = mock_node(r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"header not found"}}"#);
+    assert!(matches!(fetch_code(&url,ADDRESS),Err(RpcError::Node(_))));
+}
+
+#[test]
+fn test_rpc_fetch_03() {
+    // Invalid address is rejected without contacting the node
+    assert!(matches!(fetch_code("http://127.0.0.1:1","0x1234"),Err(RpcError::InvalidAddress(_))));
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Start a (single shot) HTTP server which responds to any request
+/// with the given JSON body, returning its url.
+fn mock_node(body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        // Read request headers and body
+        let mut buffer = [0u8; 4096];
+        let mut request = Vec::new();
+        loop {
+            let n = stream.read(&mut buffer).unwrap();
+            request.extend_from_slice(&buffer[..n]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some(i) = text.find("\r\n\r\n") {
+                let len = content_length(&text[..i]);
+                if request.len() >= i + 4 + len { break; }
+            }
+            if n == 0 { break; }
+        }
+        assert!(String::from_utf8_lossy(&request).contains("eth_getCode"));
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),body);
+        stream.write_all(response.as_bytes()).unwrap();
+    });
+    format!("http://{addr}")
+}
+
+fn content_length(headers: &str) -> usize {
+    headers.lines()
+        .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap()))
+        .unwrap_or(0)
+}
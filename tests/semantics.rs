@@ -0,0 +1,1603 @@
+use evmil::analysis::{aw256,sw256,execute,ConcreteMemory,ConcreteStack,ConcreteState,MAX_STACK_DEPTH};
+use evmil::analysis::{Account,ConcreteStorage,EvmException,EvmStack,EvmState,Outcome,UnknownStorage,WorldState};
+use evmil::analysis::{memory_cost,memory_expansion_cost,static_gas};
+use evmil::bytecode::{Disassemble,Instruction,SectionType};
+use evmil::bytecode::Instruction::*;
+use evmil::util::{i256,w256,FromHexString,Top,KECCAK_EMPTY};
+
+type State = ConcreteState<ConcreteStack<aw256>,ConcreteMemory<aw256>,UnknownStorage<aw256>>;
+type SymbolicState = ConcreteState<ConcreteStack<sw256>,ConcreteMemory<sw256>,UnknownStorage<sw256>>;
+type TransientState = ConcreteState<ConcreteStack<aw256>,ConcreteMemory<aw256>,UnknownStorage<aw256>,ConcreteStorage<aw256>>;
+
+const CONTRACT : u64 = 0x1234;
+const EMPTY : u64 = 0x5678;
+const EXISTING : u64 = 0x9abc;
+const MISSING : u64 = 0xdef0;
+
+// ============================================================================
+// Arithmetic
+// ============================================================================
+
+#[test]
+fn test_add_01() {
+    check(&[push(2),push(3),ADD], &[w256::from(5)]);
+}
+
+#[test]
+fn test_add_02() {
+    // Addition wraps at 2^256
+    check(&[push(1),PUSH(vec![0xff;32]),ADD], &[w256::ZERO]);
+}
+
+#[test]
+fn test_sub_01() {
+    check(&[push(2),push(3),SUB], &[w256::from(1)]);
+}
+
+#[test]
+fn test_sub_02() {
+    // Subtraction wraps at 2^256
+    check(&[push(3),push(2),SUB], &[w256::MAX]);
+}
+
+#[test]
+fn test_mul_01() {
+    check(&[push(2),push(3),MUL], &[w256::from(6)]);
+}
+
+#[test]
+fn test_mul_02() {
+    // Multiplication wraps at 2^256
+    check(&[push(2),PUSH(vec![0xff;32]),MUL], &[w256::MAX - w256::from(1)]);
+}
+
+#[test]
+fn test_div_01() {
+    check(&[push(3),push(7),DIV], &[w256::from(2)]);
+}
+
+#[test]
+fn test_mod_01() {
+    check(&[push(3),push(7),MOD], &[w256::from(1)]);
+}
+
+#[test]
+fn test_div_02() {
+    // Division by zero gives zero
+    check(&[push(0),push(5),DIV], &[w256::ZERO]);
+}
+
+#[test]
+fn test_mod_02() {
+    // Modulus by zero gives zero
+    check(&[push(0),push(5),MOD], &[w256::ZERO]);
+}
+
+#[test]
+fn test_addmod_01() {
+    check(&[push(0),push(5),push(5),ADDMOD], &[w256::ZERO]);
+    check(&[push(0),push(5),push(5),MULMOD], &[w256::ZERO]);
+}
+
+#[test]
+fn test_div_03() {
+    // Division of unknown by zero still gives zero
+    check(&[push(0),CALLVALUE,DIV,push(0),CALLVALUE,MOD], &[w256::ZERO,w256::ZERO]);
+}
+
+#[test]
+fn test_chain_01() {
+    // Constants propagate through a chain of arithmetic
+    check(&[push(2),push(3),MUL,push(1),ADD,push(2),SWAP(1),DIV,push(1),SWAP(1),SUB], &[w256::from(2)]);
+}
+
+#[test]
+fn test_chain_02() {
+    // Constants propagate through a chain of bitwise operations
+    check(&[push(0x0f),push(0xf0),OR,push(0x0f),XOR,push(4),SHR,push(4),SHL,push(0x3c),AND], &[w256::from(0x30)]);
+}
+
+#[test]
+fn test_chain_03() {
+    // Chain involving an unknown is unknown
+    let st = run(&[push(2),CALLVALUE,MUL,push(1),ADD], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::Unknown);
+}
+
+#[test]
+fn test_exp_01() {
+    check(&[push(8),push(2),EXP], &[w256::from(256)]);
+}
+
+#[test]
+fn test_exp_02() {
+    // Exponentiation wraps at 2^256
+    check(&[push(255),push(2),EXP], &[w256::from(1) << 255]);
+    check(&[push(256),push(2),EXP], &[w256::ZERO]);
+}
+
+#[test]
+fn test_exp_03() {
+    // Anything to the power of zero is one
+    check(&[push(0),push(0),EXP], &[w256::from(1)]);
+    check(&[push(0),push_word(w256::MAX),EXP], &[w256::from(1)]);
+}
+
+#[test]
+fn test_exp_04() {
+    // Large exponents
+    check(&[push_word(w256::MAX),push(2),EXP], &[w256::ZERO]);
+    check(&[push_word(w256::MAX),push_word(w256::MAX),EXP], &[w256::MAX]);
+}
+
+#[test]
+fn test_signextend_01() {
+    // Negative byte extends to all ones
+    check(&[push(0xff),push(0),SIGNEXTEND], &[w256::MAX]);
+    check(&[push(0x80),push(0),SIGNEXTEND], &[neg(0x80)]);
+}
+
+#[test]
+fn test_signextend_02() {
+    // Positive value left untouched
+    check(&[push(0x7f),push(0),SIGNEXTEND], &[w256::from(0x7f)]);
+    check(&[push(0x7fff),push(1),SIGNEXTEND], &[w256::from(0x7fff)]);
+}
+
+#[test]
+fn test_signextend_03() {
+    // Higher bytes are discarded
+    check(&[push(0x12ff),push(0),SIGNEXTEND], &[w256::MAX]);
+    check(&[push(0x127f),push(0),SIGNEXTEND], &[w256::from(0x7f)]);
+    check(&[push(0xff8000),push(1),SIGNEXTEND], &[neg(0x8000)]);
+}
+
+#[test]
+fn test_signextend_04() {
+    // Byte index of 31 or more leaves value unchanged
+    check(&[push(0xff),push(31),SIGNEXTEND], &[w256::from(0xff)]);
+    check(&[push(0xff),push_word(w256::MAX),SIGNEXTEND], &[w256::from(0xff)]);
+    check(&[push_word(neg(1)),push(30),SIGNEXTEND], &[w256::MAX]);
+}
+
+#[test]
+fn test_arithmetic_unknown_01() {
+    // Arithmetic over unknown operands gives unknown
+    for op in [ADD,SUB,MUL,DIV,MOD,SDIV,SMOD,EXP,SIGNEXTEND] {
+        let st = run(&[push(1),CALLVALUE,op], State::new());
+        assert_eq!(st.stack().peek(0),&aw256::TOP);
+    }
+}
+
+// ============================================================================
+// Comparators
+// ============================================================================
+
+#[test]
+fn test_lt_01() {
+    check(&[push(2),push(1),LT], &[w256::from(1)]);
+}
+
+#[test]
+fn test_lt_02() {
+    check(&[push(1),push(2),LT], &[w256::from(0)]);
+}
+
+#[test]
+fn test_gt_01() {
+    check(&[push(1),push(2),GT], &[w256::from(1)]);
+}
+
+#[test]
+fn test_gt_02() {
+    check(&[push(2),push(2),GT], &[w256::from(0)]);
+}
+
+#[test]
+fn test_slt_01() {
+    // -1 < 0 (signed)
+    check(&[push(0),push_word(neg(1)),SLT], &[w256::from(1)]);
+}
+
+#[test]
+fn test_slt_02() {
+    // -1 > 0 (unsigned)
+    check(&[push(0),push_word(neg(1)),LT], &[w256::from(0)]);
+}
+
+#[test]
+fn test_sgt_01() {
+    // 0 > -1 (signed)
+    check(&[push_word(neg(1)),push(0),SGT], &[w256::from(1)]);
+}
+
+#[test]
+fn test_sgt_02() {
+    // 0 < -1 (unsigned)
+    check(&[push_word(neg(1)),push(0),GT], &[w256::from(0)]);
+}
+
+#[test]
+fn test_sgt_03() {
+    // -2 > -3 (signed)
+    check(&[push_word(neg(3)),push_word(neg(2)),SGT], &[w256::from(1)]);
+}
+
+#[test]
+fn test_eq_01() {
+    check(&[push(3),push(3),EQ], &[w256::from(1)]);
+}
+
+#[test]
+fn test_eq_02() {
+    check(&[push(3),push(4),EQ], &[w256::from(0)]);
+}
+
+#[test]
+fn test_iszero_01() {
+    check(&[push(0),ISZERO], &[w256::from(1)]);
+}
+
+#[test]
+fn test_iszero_02() {
+    check(&[push(5),ISZERO], &[w256::from(0)]);
+}
+
+// ============================================================================
+// Signed Arithmetic
+// ============================================================================
+
+#[test]
+fn test_sdiv_01() {
+    check(&[push(2),push_word(neg(7)),SDIV], &[neg(3)]);
+}
+
+#[test]
+fn test_sdiv_02() {
+    check(&[push_word(neg(1)),push_word(neg(1)),SDIV], &[w256::from(1)]);
+}
+
+#[test]
+fn test_sdiv_03() {
+    // Overflow case: MIN / -1 == MIN
+    let min : w256 = i256::MIN.into();
+    check(&[push_word(neg(1)),push_word(min),SDIV], &[min]);
+}
+
+#[test]
+fn test_sdiv_04() {
+    // Division by zero
+    check(&[push(0),push_word(neg(7)),SDIV], &[w256::ZERO]);
+}
+
+#[test]
+fn test_smod_01() {
+    // Sign of result follows dividend
+    check(&[push(2),push_word(neg(7)),SMOD], &[neg(1)]);
+    check(&[push_word(neg(2)),push(7),SMOD], &[w256::from(1)]);
+}
+
+#[test]
+fn test_smod_02() {
+    check(&[push_word(neg(1)),push_word(neg(1)),SMOD], &[w256::ZERO]);
+}
+
+#[test]
+fn test_smod_03() {
+    let min : w256 = i256::MIN.into();
+    check(&[push_word(neg(1)),push_word(min),SMOD], &[w256::ZERO]);
+}
+
+#[test]
+fn test_smod_04() {
+    // Modulus by zero
+    check(&[push(0),push_word(neg(7)),SMOD], &[w256::ZERO]);
+}
+
+// ============================================================================
+// Bitwise
+// ============================================================================
+
+#[test]
+fn test_byte_01() {
+    let w = w256::from_be_bytes::<32>(core::array::from_fn(|i| i as u8));
+    for i in 0..32 {
+        check(&[push_word(w),push(i),BYTE], &[w256::from(i)]);
+    }
+}
+
+#[test]
+fn test_byte_02() {
+    // Out-of-range index gives zero
+    check(&[push_word(w256::MAX),push(32),BYTE], &[w256::ZERO]);
+    check(&[push_word(w256::MAX),push_word(w256::MAX),BYTE], &[w256::ZERO]);
+}
+
+#[test]
+fn test_shl_01() {
+    check(&[push(1),push(8),SHL], &[w256::from(256)]);
+    check(&[push(1),push(255),SHL], &[w256::from(1) << 255]);
+    check(&[push_word(w256::MAX),push(4),SHL], &[w256::MAX - w256::from(0xf)]);
+}
+
+#[test]
+fn test_shl_02() {
+    // Shifting by 256 or more gives zero
+    check(&[push(1),push(256),SHL], &[w256::ZERO]);
+    check(&[push_word(w256::MAX),push_word(w256::MAX),SHL], &[w256::ZERO]);
+}
+
+#[test]
+fn test_shr_01() {
+    check(&[push(256),push(8),SHR], &[w256::from(1)]);
+    check(&[push_word(w256::MAX),push(252),SHR], &[w256::from(0xf)]);
+}
+
+#[test]
+fn test_shr_02() {
+    // Shifting by 256 or more gives zero
+    check(&[push_word(w256::MAX),push(256),SHR], &[w256::ZERO]);
+    check(&[push_word(w256::MAX),push(257),SHR], &[w256::ZERO]);
+}
+
+#[test]
+fn test_sar_01() {
+    // Positive values behave as logical shift
+    check(&[push(256),push(8),SAR], &[w256::from(1)]);
+    check(&[push(256),push(256),SAR], &[w256::ZERO]);
+}
+
+#[test]
+fn test_sar_02() {
+    // Negative values preserve the sign bit
+    check(&[push_word(neg(16)),push(2),SAR], &[neg(4)]);
+    check(&[push_word(neg(1)),push(8),SAR], &[neg(1)]);
+    check(&[push_word(w256::from(1) << 255),push(255),SAR], &[neg(1)]);
+}
+
+#[test]
+fn test_sar_03() {
+    // Shifting a negative value by 256 or more gives -1
+    check(&[push_word(neg(16)),push(256),SAR], &[w256::MAX]);
+    check(&[push_word(neg(16)),push_word(w256::MAX),SAR], &[w256::MAX]);
+}
+
+#[test]
+fn test_bitwise_unknown_01() {
+    // Bitwise operations over unknown operands gives unknown
+    for op in [BYTE,SHL,SHR,SAR] {
+        let st = run(&[push(1),CALLVALUE,op.clone()], State::new());
+        assert_eq!(st.stack().peek(0),&aw256::TOP);
+        let st = run(&[CALLVALUE,push(1),op], State::new());
+        assert_eq!(st.stack().peek(0),&aw256::TOP);
+    }
+}
+
+// ============================================================================
+// Memory
+// ============================================================================
+
+#[test]
+fn test_mstore8_01() {
+    // Byte store at last byte of first word
+    let st = run(&[push(0xff),push(31),MSTORE8,MSIZE], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::from(w256::from(32)));
+    assert_eq!(memory_expansion_cost(0,32),3);
+}
+
+#[test]
+fn test_mstore8_02() {
+    // Byte store at first byte of second word
+    let st = run(&[push(0xff),push(31),MSTORE8,push(0xff),push(32),MSTORE8,MSIZE], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::from(w256::from(64)));
+    assert_eq!(memory_expansion_cost(32,64),3);
+    assert_eq!(memory_expansion_cost(0,64),6);
+}
+
+#[test]
+fn test_mstore8_03() {
+    // Storing at a lower address does not shrink memory
+    let st = run(&[push(0xff),push(32),MSTORE8,push(0xff),push(0),MSTORE8,MSIZE], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::from(w256::from(64)));
+    assert_eq!(memory_expansion_cost(64,32),0);
+}
+
+#[test]
+fn test_msize_01() {
+    check(&[MSIZE], &[w256::ZERO]);
+}
+
+#[test]
+fn test_msize_02() {
+    // Byte store at unknown address
+    let st = run(&[push(0xff),CALLVALUE,MSTORE8,MSIZE], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+}
+
+#[test]
+fn test_msize_03() {
+    // Word store at offset 0
+    check(&[push(0xff),push(0),MSTORE,MSIZE], &[w256::from(32)]);
+}
+
+#[test]
+fn test_msize_04() {
+    // Word store at offset 33 touches bytes 33..65
+    check(&[push(0xff),push(33),MSTORE,MSIZE], &[w256::from(96)]);
+    // Byte store at offset 33
+    check(&[push(0xff),push(33),MSTORE8,MSIZE], &[w256::from(64)]);
+}
+
+#[test]
+fn test_mcopy_01() {
+    // Overlapping copy forwards
+    let w = from_hex("0x0102030405060708000000000000000000000000000000000000000000000000");
+    let r = from_hex("0x0101020304060708000000000000000000000000000000000000000000000000");
+    check(&[push_word(w),push(0),MSTORE,push(4),push(0),push(1),MCOPY,push(0),MLOAD,MSIZE], &[r,w256::from(32)]);
+}
+
+#[test]
+fn test_mcopy_02() {
+    // Overlapping copy backwards
+    let w = from_hex("0x0102030405060708000000000000000000000000000000000000000000000000");
+    let r = from_hex("0x0203040505060708000000000000000000000000000000000000000000000000");
+    check(&[push_word(w),push(0),MSTORE,push(4),push(1),push(0),MCOPY,push(0),MLOAD,MSIZE], &[r,w256::from(32)]);
+}
+
+#[test]
+fn test_mcopy_03() {
+    // Copy across words expands memory
+    let w = from_hex("0x0102030405060708000000000000000000000000000000000000000000000000");
+    let r1 = from_hex("0x0102030405060708000000000000000000000000000000000000000000000102");
+    let r2 = from_hex("0x0304000000000000000000000000000000000000000000000000000000000000");
+    let insns = [push_word(w),push(0),MSTORE,push(4),push(0),push(30),MCOPY,push(0),MLOAD,push(0x20),MLOAD,MSIZE];
+    check(&insns, &[r1,r2,w256::from(64)]);
+}
+
+#[test]
+fn test_mcopy_04() {
+    // Copy to unknown destination
+    let st = run(&[push(0xff),push(0),MSTORE,push(4),push(0),CALLVALUE,MCOPY,push(0),MLOAD], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+}
+
+#[test]
+fn test_mcopy_05() {
+    // Copying nothing to a huge destination leaves memory untouched
+    let insns = [push(0xff),push(0),MSTORE,push(0),push(0),push_word(w256::MAX),MCOPY,push(0),MLOAD,MSIZE];
+    check(&insns, &[w256::from(0xff),w256::from(32)]);
+}
+
+#[test]
+fn test_mcopy_06() {
+    // Copying to (or from) a huge address exhausts gas
+    let st = run(&[push(1),push(0),push_word(w256::MAX)], State::new());
+    assert!(matches!(execute(&MCOPY,st),Outcome::Exception(EvmException::InsufficientGas)));
+    let st = run(&[push(1),push_word(w256::MAX),push(0)], State::new());
+    assert!(matches!(execute(&MCOPY,st),Outcome::Exception(EvmException::InsufficientGas)));
+}
+
+#[test]
+fn test_mcopy_07() {
+    // Memory expands to cover exactly the source and destination
+    check(&[push(0x20),push(0),push(0),MCOPY,MSIZE], &[w256::from(32)]);
+    check(&[push(0x20),push(0x20),push(0),MCOPY,MSIZE], &[w256::from(64)]);
+    check(&[push(0x1),push(0),push(0x40),MCOPY,MSIZE], &[w256::from(96)]);
+}
+
+#[test]
+fn test_memory_cost_01() {
+    assert_eq!(memory_cost(0),0);
+    assert_eq!(memory_cost(1),3);
+    assert_eq!(memory_cost(32),3);
+    assert_eq!(memory_cost(33),6);
+    // Quadratic component kicks in
+    assert_eq!(memory_cost(32 * 1024),(3 * 1024) + 2048);
+}
+
+// ============================================================================
+// Stack
+// ============================================================================
+
+#[test]
+fn test_push0_01() {
+    let st = run(&[PUSH0], State::new());
+    assert_eq!(st.pc(),1);
+    assert_eq!(st.stack().peek(0),&aw256::from(w256::ZERO));
+}
+
+#[test]
+fn test_stack_overflow_01() {
+    let mut st = State::new();
+    for _i in 0..MAX_STACK_DEPTH {
+        st = match execute(&push(1),st) {
+            Outcome::Continue(st) => st,
+            _ => panic!("unexpected outcome")
+        };
+    }
+    assert_eq!(st.stack().size(),MAX_STACK_DEPTH);
+    // Final push overflows
+    assert!(matches!(execute(&push(1),st),Outcome::Exception(EvmException::StackOverflow)));
+}
+
+#[test]
+fn test_stack_overflow_02() {
+    let mut st = State::new();
+    for _i in 0..MAX_STACK_DEPTH { st.stack_mut().push(aw256::from(w256::from(1))); }
+    assert!(matches!(execute(&DUP(1),st.clone()),Outcome::Exception(EvmException::StackOverflow)));
+    assert!(matches!(execute(&CALLDATASIZE,st.clone()),Outcome::Exception(EvmException::StackOverflow)));
+    // Consumer-producers are unaffected
+    assert!(matches!(execute(&ADD,st),Outcome::Continue(_)));
+}
+
+// ============================================================================
+// Calldata
+// ============================================================================
+
+#[test]
+fn test_calldataload_01() {
+    let data = "0x0102030405".from_hex_string().unwrap();
+    // Bytes beyond the end of calldata are zero
+    let expected = from_hex("0x0102030405000000000000000000000000000000000000000000000000000000");
+    check_state(&[push(0),CALLDATALOAD], State::new().with_calldata(&data), &[expected]);
+}
+
+#[test]
+fn test_calldataload_02() {
+    let data = "0x0102030405".from_hex_string().unwrap();
+    let expected = from_hex("0x0405000000000000000000000000000000000000000000000000000000000000");
+    check_state(&[push(3),CALLDATALOAD], State::new().with_calldata(&data), &[expected]);
+}
+
+#[test]
+fn test_calldataload_03() {
+    let data = "0x0102030405".from_hex_string().unwrap();
+    // Offset beyond the end of calldata
+    check_state(&[push(0x100),CALLDATALOAD], State::new().with_calldata(&data), &[w256::ZERO]);
+    check_state(&[push_word(w256::MAX),CALLDATALOAD], State::new().with_calldata(&data), &[w256::ZERO]);
+}
+
+#[test]
+fn test_calldataload_04() {
+    // Calldata unknown
+    let st = run(&[push(0),CALLDATALOAD], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+}
+
+#[test]
+fn test_calldatasize_01() {
+    let data = "0x0102030405".from_hex_string().unwrap();
+    check_state(&[CALLDATASIZE], State::new().with_calldata(&data), &[w256::from(5)]);
+    check_state(&[CALLDATASIZE], State::new().with_calldata(&[]), &[w256::ZERO]);
+}
+
+#[test]
+fn test_calldatacopy_01() {
+    let data = "0x0102030405".from_hex_string().unwrap();
+    // Copy past end of calldata into memory
+    let insns = [push(0x8),push(0x2),push(0x0),CALLDATACOPY,push(0),MLOAD];
+    let expected = from_hex("0x0304050000000000000000000000000000000000000000000000000000000000");
+    check_state(&insns, State::new().with_calldata(&data), &[expected]);
+}
+
+#[test]
+fn test_calldatacopy_02() {
+    let data = "0x0102030405".from_hex_string().unwrap();
+    // Overwriting existing memory with padding
+    let insns = [push_word(w256::MAX),push(0),MSTORE,push(0x4),push(0x3),push(0x1e),CALLDATACOPY,push(0),MLOAD];
+    let expected = from_hex("0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0405");
+    check_state(&insns, State::new().with_calldata(&data), &[expected]);
+}
+
+#[test]
+fn test_calldatacopy_03() {
+    // Calldata unknown
+    let insns = [push(0x20),push(0x0),push(0x0),CALLDATACOPY,push(0),MLOAD];
+    let st = run(&insns, State::new());
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+}
+
+#[test]
+fn test_calldatacopy_04() {
+    // Copying nothing to a huge destination leaves memory untouched
+    let insns = [push(0xff),push(0),MSTORE,push(0),push(0),push_word(w256::MAX),CALLDATACOPY,push(0),MLOAD,MSIZE];
+    check(&insns, &[w256::from(0xff),w256::from(32)]);
+}
+
+#[test]
+fn test_calldatacopy_05() {
+    // Copying to a huge destination exhausts gas
+    let st = run(&[push(1),push(0),push_word(w256::MAX)], State::new());
+    assert!(matches!(execute(&CALLDATACOPY,st),Outcome::Exception(EvmException::InsufficientGas)));
+}
+
+// ============================================================================
+// Code
+// ============================================================================
+
+#[test]
+fn test_codesize_01() {
+    let code = "0x600160020100".from_hex_string().unwrap();
+    check_state(&[CODESIZE], State::new().with_code(&code), &[w256::from(6)]);
+}
+
+#[test]
+fn test_codesize_02() {
+    // Code unknown
+    let st = run(&[CODESIZE], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+}
+
+#[test]
+fn test_codecopy_01() {
+    let code = "0x600160020100".from_hex_string().unwrap();
+    // Copy code[2..5] to memory[0..3]
+    let insns = [push(0x3),push(0x2),push(0x0),CODECOPY,push(0),MLOAD];
+    let expected = from_hex("0x6002010000000000000000000000000000000000000000000000000000000000");
+    check_state(&insns, State::new().with_code(&code), &[expected]);
+}
+
+#[test]
+fn test_codecopy_02() {
+    let code = "0x600160020100".from_hex_string().unwrap();
+    // Copy past end of code (which is zero padded)
+    let insns = [push_word(w256::MAX),push(0),MSTORE,push(0x4),push(0x4),push(0x1c),CODECOPY,push(0),MLOAD];
+    let expected = from_hex("0xffffffffffffffffffffffffffffffffffffffffffffffffffffffff01000000");
+    check_state(&insns, State::new().with_code(&code), &[expected]);
+}
+
+#[test]
+fn test_codecopy_03() {
+    // Code unknown
+    let insns = [push(0x20),push(0x0),push(0x0),CODECOPY,push(0),MLOAD];
+    let st = run(&insns, State::new());
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+}
+
+// ============================================================================
+// Transient Storage
+// ============================================================================
+
+#[test]
+fn test_tstore_01() {
+    // Store then load a slot
+    let st = run_transient(TransientState::new(),&[push(0x7b),push(1),TSTORE,push(1),TLOAD]);
+    assert_eq!(st.stack().size(),1);
+    assert_eq!(st.stack().peek(0),&aw256::from(w256::from(0x7bu64)));
+}
+
+#[test]
+fn test_tstore_02() {
+    // Unwritten slots are unknown (i.e. since they may have been
+    // written earlier in the transaction)
+    let st = run_transient(TransientState::new(),&[push(0x7b),push(1),TSTORE,push(2),TLOAD]);
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+    // Unless storage is known to be empty
+    let st = TransientState::new().with_transient(ConcreteStorage::zeroed());
+    let st = run_transient(st,&[push(0x7b),push(1),TSTORE,push(2),TLOAD]);
+    assert_eq!(st.stack().peek(0),&aw256::from(w256::ZERO));
+}
+
+#[test]
+fn test_tstore_03() {
+    // Transient storage is independent of persistent storage
+    let st = TransientState::new().with_transient(ConcreteStorage::zeroed());
+    let st = run_transient(st,&[push(0x7b),push(1),SSTORE,push(1),TLOAD]);
+    assert_eq!(st.stack().peek(0),&aw256::from(w256::ZERO));
+    let st = run_transient(TransientState::new(),&[push(0x7b),push(1),TSTORE,push(1),SLOAD]);
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+}
+
+#[test]
+fn test_tstore_04() {
+    // Writing an unknown slot invalidates everything
+    let st = run_transient(TransientState::new(),&[push(0x7b),push(1),TSTORE,push(5),push(0),CALLDATALOAD,TSTORE,push(1),TLOAD]);
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+}
+
+#[test]
+fn test_tstore_05() {
+    // Without tracking, transient storage is unknown
+    check_delta(TLOAD,1);
+    let st = run(&[push(1)],State::new());
+    assert!(matches!(execute(&TSTORE,st),Outcome::Exception(EvmException::StackUnderflow)));
+}
+
+// ============================================================================
+// Block Information
+// ============================================================================
+
+#[test]
+fn test_basefee_01() {
+    check_delta(BASEFEE,0);
+}
+
+#[test]
+fn test_blobhash_01() {
+    check_delta(BLOBHASH,1);
+}
+
+#[test]
+fn test_blobbasefee_01() {
+    check_delta(BLOBBASEFEE,0);
+}
+
+// ============================================================================
+// World State
+// ============================================================================
+
+#[test]
+fn test_extcodesize_01() {
+    let insns = [push(CONTRACT),EXTCODESIZE];
+    check_world(&insns, world(), &[w256::from(6)]);
+}
+
+#[test]
+fn test_extcodesize_02() {
+    let insns = [push(EXISTING),EXTCODESIZE,push(EMPTY),EXTCODESIZE,push(MISSING),EXTCODESIZE];
+    check_world(&insns, world(), &[w256::ZERO,w256::ZERO,w256::ZERO]);
+}
+
+#[test]
+fn test_extcodesize_03() {
+    // Without a world state, nothing is known.
+    let st = run(&[push(CONTRACT),EXTCODESIZE], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+}
+
+#[test]
+#[cfg(feature = "keccak")]
+fn test_extcodehash_01() {
+    let hash = from_hex("0xb726aeff8988a40969adeca5f5d9bfcb9b65fba4dd6fd7b249b984e3bb91d9b6");
+    check_world(&[push(CONTRACT),EXTCODEHASH], world(), &[hash]);
+}
+
+#[test]
+fn test_extcodehash_02() {
+    // An existing account without code hashes to the empty keccak.
+    check_world(&[push(EXISTING),EXTCODEHASH], world(), &[KECCAK_EMPTY]);
+}
+
+#[test]
+fn test_extcodehash_03() {
+    // Empty and nonexistent accounts both give zero.
+    let insns = [push(EMPTY),EXTCODEHASH,push(MISSING),EXTCODEHASH];
+    check_world(&insns, world(), &[w256::ZERO,w256::ZERO]);
+}
+
+#[test]
+fn test_extcodehash_04() {
+    // Address is truncated to 160 bits.
+    let mut bytes = vec![0xff;12];
+    bytes.extend_from_slice(&[0;18]);
+    bytes.extend_from_slice(&[0x56,0x78]);
+    check_world(&[PUSH(bytes),EXTCODEHASH], world(), &[w256::ZERO]);
+}
+
+#[test]
+fn test_extcodehash_05() {
+    // Without a world state, nothing is known.
+    let st = run(&[push(EXISTING),EXTCODEHASH], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+}
+
+// ============================================================================
+// Control Flow
+// ============================================================================
+
+#[test]
+fn test_jumpi_01() {
+    // Known zero condition only falls through
+    let st = jumpi(&[push(0),push(0x20)]);
+    assert!(matches!(&st,Outcome::Continue(s) if s.pc() == 1));
+}
+
+#[test]
+fn test_jumpi_02() {
+    // Known one condition only branches
+    let st = jumpi(&[push(1),push(0x20)]);
+    assert!(matches!(&st,Outcome::Continue(s) if s.pc() == 0x20));
+}
+
+#[test]
+fn test_jumpi_03() {
+    // Any nonzero condition only branches
+    let st = jumpi(&[push(2),push(0x20)]);
+    assert!(matches!(&st,Outcome::Continue(s) if s.pc() == 0x20));
+}
+
+#[test]
+fn test_jumpi_04() {
+    let st = jumpi(&[push(3),push(3),EQ,push(0x20)]);
+    assert!(matches!(&st,Outcome::Continue(s) if s.pc() == 0x20));
+}
+
+#[test]
+fn test_jumpi_05() {
+    // Unknown condition explores both branches
+    let st = jumpi(&[CALLDATASIZE,push(0x20)]);
+    assert!(matches!(&st,Outcome::Split(l,r) if l.pc() == 1 && r.pc() == 0x20));
+}
+
+#[test]
+fn test_jump_01() {
+    // push1 0x5b; push1 0x01; jump
+    let code = "0x605b600156".from_hex_string().unwrap();
+    // Target lies within push data
+    let st = run(&[push(0x1)], State::new().with_code(&code));
+    assert!(matches!(execute(&JUMP,st),Outcome::Exception(EvmException::InvalidJumpDest)));
+}
+
+#[test]
+fn test_jump_02() {
+    // push1 0x04; jump; invalid; jumpdest
+    let code = "0x600456fe5b".from_hex_string().unwrap();
+    let st = run(&[push(0x4)], State::new().with_code(&code));
+    assert!(matches!(execute(&JUMP,st),Outcome::Continue(s) if s.pc() == 4));
+}
+
+#[test]
+fn test_jump_03() {
+    // push1 0x04; jump; invalid; jumpdest
+    let code = "0x600456fe5b".from_hex_string().unwrap();
+    // Target lies beyond end of code
+    let st = run(&[push(0x40)], State::new().with_code(&code));
+    assert!(matches!(execute(&JUMP,st),Outcome::Exception(EvmException::InvalidJumpDest)));
+}
+
+#[test]
+fn test_jumpi_06() {
+    // push1 0x5b; push1 0x01; push1 0x01; jumpi
+    let code = "0x605b6001600157".from_hex_string().unwrap();
+    let st = run(&[push(1),push(0x1)], State::new().with_code(&code));
+    assert!(matches!(execute(&JUMPI,st),Outcome::Exception(EvmException::InvalidJumpDest)));
+}
+
+#[test]
+fn test_jumpi_07() {
+    // push1 0x5b; push1 0x01; push1 0x01; jumpi
+    let code = "0x605b6001600157".from_hex_string().unwrap();
+    // Invalid target is irrelevant when branch not taken
+    let st = run(&[push(0),push(0x1)], State::new().with_code(&code));
+    assert!(matches!(execute(&JUMPI,st),Outcome::Continue(_)));
+}
+
+#[test]
+fn test_jumpi_08() {
+    // push1 0x5b; push1 0x01; push1 0x01; jumpi
+    let code = "0x605b6001600157".from_hex_string().unwrap();
+    // Unknown condition only falls through
+    let mut st = run(&[CALLDATASIZE,push(0x1)], State::new().with_code(&code));
+    st.goto(0);
+    assert!(matches!(execute(&JUMPI,st),Outcome::Continue(s) if s.pc() == 1));
+}
+
+#[test]
+fn test_jump_04() {
+    // Unknown jump target
+    let st = run(&[CALLDATASIZE], State::new());
+    assert!(matches!(execute(&JUMP,st),Outcome::Unknown(None)));
+}
+
+#[test]
+fn test_jumpi_09() {
+    // Unknown jump target, but branch not taken
+    let st = jumpi(&[push(0),CALLDATASIZE]);
+    assert!(matches!(&st,Outcome::Continue(s) if s.pc() == 1));
+}
+
+#[test]
+fn test_jumpi_10() {
+    // Unknown jump target, and branch taken
+    let st = jumpi(&[push(1),CALLDATASIZE]);
+    assert!(matches!(&st,Outcome::Unknown(None)));
+}
+
+#[test]
+fn test_jumpi_11() {
+    // Unknown jump target, and branch may be taken
+    let st = jumpi(&[CALLDATASIZE,CALLDATASIZE]);
+    assert!(matches!(&st,Outcome::Unknown(Some(s)) if s.pc() == 1));
+}
+
+#[test]
+fn test_rjump_01() {
+    // Backward jump
+    let mut st = State::new();
+    st.goto(10);
+    assert!(matches!(execute(&RJUMP(-11),st),Outcome::Continue(s) if s.pc() == 2));
+}
+
+#[test]
+fn test_rjumpi_01() {
+    // Known zero condition only falls through
+    let st = rjump(&[push(0)],&RJUMPI(0x1d));
+    assert!(matches!(&st,Outcome::Continue(s) if s.pc() == 3));
+}
+
+#[test]
+fn test_rjumpi_02() {
+    // Known nonzero condition only branches
+    let st = rjump(&[push(2)],&RJUMPI(0x1d));
+    assert!(matches!(&st,Outcome::Continue(s) if s.pc() == 0x20));
+}
+
+#[test]
+fn test_rjumpi_03() {
+    // Unknown condition explores both branches
+    let st = rjump(&[CALLDATASIZE],&RJUMPI(0x1d));
+    assert!(matches!(&st,Outcome::Split(l,r) if l.pc() == 3 && r.pc() == 0x20));
+}
+
+#[test]
+fn test_rjumpi_04() {
+    let st = rjump(&[],&RJUMPI(0x1d));
+    assert!(matches!(&st,Outcome::Exception(EvmException::StackUnderflow)));
+}
+
+#[test]
+fn test_rjumpv_01() {
+    // Known index selects table entry
+    let st = rjump(&[push(1)],&RJUMPV(vec![0x0a,0x1a]));
+    assert!(matches!(&st,Outcome::Continue(s) if s.pc() == 0x20));
+}
+
+#[test]
+fn test_rjumpv_02() {
+    // Index beyond table falls through
+    let st = rjump(&[push(2)],&RJUMPV(vec![0x0a,0x1a]));
+    assert!(matches!(&st,Outcome::Continue(s) if s.pc() == 6));
+}
+
+#[test]
+fn test_rjumpv_03() {
+    // Unknown index explores every entry, and the fall through
+    let st = rjump(&[CALLDATASIZE],&RJUMPV(vec![0x0a,0x1a]));
+    match st {
+        Outcome::Branch(sts) => {
+            let pcs : Vec<usize> = sts.iter().map(|s| s.pc()).collect();
+            assert_eq!(pcs,vec![0x10,0x20,6]);
+        }
+        _ => panic!("expected branch")
+    }
+}
+
+#[test]
+fn test_callf_01() {
+    // Inputs replaced by outputs
+    let types = [SectionType{inputs:0,outputs:0,max_stack:2},SectionType{inputs:2,outputs:1,max_stack:2}];
+    let mut st = run(&[push(1),push(2),push(3)], State::new().with_types(&types));
+    st.goto(0);
+    match execute(&CALLF(1),st) {
+        Outcome::Continue(s) => {
+            assert_eq!(s.pc(),3);
+            assert_eq!(s.stack().size(),2);
+            assert_eq!(s.stack().peek(0),&aw256::Unknown);
+            assert_eq!(s.stack().peek(1),&aw256::from(w256::from(1)));
+        }
+        _ => panic!("expected continue")
+    }
+}
+
+#[test]
+fn test_callf_02() {
+    // Too few inputs
+    let types = [SectionType{inputs:0,outputs:0,max_stack:0},SectionType{inputs:2,outputs:1,max_stack:2}];
+    let st = run(&[push(1)], State::new().with_types(&types));
+    assert!(matches!(execute(&CALLF(1),st),Outcome::Exception(EvmException::StackUnderflow)));
+}
+
+#[test]
+fn test_callf_03() {
+    // Unknown types give no following state
+    let mut st = run(&[push(1)], State::new());
+    st.goto(0);
+    assert!(matches!(execute(&CALLF(1),st),Outcome::Unknown(None)));
+}
+
+#[test]
+fn test_retf_01() {
+    assert!(matches!(execute(&RETF,State::new()),Outcome::Unknown(None)));
+}
+
+#[test]
+fn test_jumpf_01() {
+    let types = [SectionType{inputs:0,outputs:0,max_stack:0},SectionType{inputs:1,outputs:0,max_stack:1}];
+    let st = State::new().with_types(&types);
+    assert!(matches!(execute(&JUMPF(1),st.clone()),Outcome::Exception(EvmException::StackUnderflow)));
+    let st = run(&[push(1)], st);
+    assert!(matches!(execute(&JUMPF(1),st),Outcome::Unknown(None)));
+}
+
+#[test]
+fn test_return_01() {
+    let st = run(&[push(0x2a),push(0),MSTORE,push(0x20),push(0)], State::new());
+    let mut expected = vec![0u8;32];
+    expected[31] = 0x2a;
+    match execute(&RETURN,st) {
+        Outcome::Return{offset,size,data} => {
+            assert_eq!(offset,aw256::from(w256::ZERO));
+            assert_eq!(size,aw256::from(w256::from(0x20)));
+            assert_eq!(data,Some(expected));
+        }
+        _ => panic!("expected return")
+    }
+}
+
+#[test]
+fn test_return_02() {
+    // Unaligned return of part of a word
+    let st = run(&[push(0x1234),push(0),MSTORE,push(0x2),push(0x1e)], State::new());
+    assert!(matches!(execute(&RETURN,st),Outcome::Return{data: Some(d),..} if d == vec![0x12,0x34]));
+}
+
+#[test]
+fn test_return_03() {
+    // Return data unknown
+    let st = run(&[push(0x4),CALLDATALOAD,push(0),MSTORE,push(0x20),push(0)], State::new());
+    assert!(matches!(execute(&RETURN,st),Outcome::Return{data: None,..}));
+}
+
+#[test]
+fn test_return_04() {
+    let st = run(&[push(0x1)], State::new());
+    assert!(matches!(execute(&STOP,st),Outcome::Return{data: Some(d),..} if d.is_empty()));
+}
+
+#[test]
+fn test_return_05() {
+    // Returning (or reverting) from a huge offset exhausts gas
+    let st = run(&[push(1),push_word(w256::MAX)], State::new());
+    assert!(matches!(execute(&RETURN,st.clone()),Outcome::Exception(EvmException::InsufficientGas)));
+    assert!(matches!(execute(&REVERT,st),Outcome::Exception(EvmException::InsufficientGas)));
+}
+
+#[test]
+fn test_revert_01() {
+    let st = run(&[push(0x2a),push(0),MSTORE8,push(0x1),push(0)], State::new());
+    assert!(matches!(execute(&REVERT,st),Outcome::Revert{data: Some(d),..} if d == vec![0x2a]));
+}
+
+#[test]
+fn test_revert_02() {
+    let st = run(&[push(0x0),push(0)], State::new());
+    assert!(matches!(execute(&REVERT,st),Outcome::Revert{data: Some(d),..} if d.is_empty()));
+}
+
+// ============================================================================
+// System Operations
+// ============================================================================
+
+#[test]
+fn test_call_01() {
+    check_delta(CALL, 7);
+}
+
+#[test]
+fn test_callcode_01() {
+    check_delta(CALLCODE, 7);
+}
+
+#[test]
+fn test_delegatecall_01() {
+    check_delta(DELEGATECALL, 6);
+}
+
+#[test]
+fn test_staticcall_01() {
+    check_delta(STATICCALL, 6);
+}
+
+#[test]
+fn test_call_02() {
+    // Return data size is unknown after a call
+    let st = run(&[push(0),push(0),push(0),push(0),push(0),push(0),push(0),CALL,RETURNDATASIZE], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::Unknown);
+}
+
+#[test]
+fn test_call_03() {
+    // Insufficient operands
+    let st = run(&[push(0),push(0),push(0),push(0),push(0),push(0)], State::new());
+    assert!(matches!(execute(&CALL,st.clone()),Outcome::Exception(EvmException::StackUnderflow)));
+    assert!(matches!(execute(&STATICCALL,st),Outcome::Continue(_)));
+}
+
+#[test]
+fn test_call_04() {
+    // Return data is written into memory at the given region
+    let mut insns = vec![push(0xff),push(0),MSTORE,push(0xee),push(0x20),MSTORE];
+    insns.extend([push(0x20),push(0),push(0),push(0),push(0),push(0),push(0),CALL,POP,push(0x20),MLOAD,push(0),MLOAD]);
+    let st = run(&insns, State::new());
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+    assert_eq!(st.stack().peek(1),&aw256::from(w256::from(0xee)));
+}
+
+#[test]
+fn test_call_05() {
+    // Return data written into an unknown region of memory
+    let mut insns = vec![push(0xff),push(0),MSTORE];
+    insns.extend([push(0x20),CALLVALUE,push(0),push(0),push(0),push(0),STATICCALL,POP,push(0),MLOAD]);
+    let st = run(&insns, State::new());
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+}
+
+#[test]
+fn test_call_06() {
+    // No return data is written, hence memory is unchanged
+    let mut insns = vec![push(0xff),push(0),MSTORE];
+    insns.extend([push(0),CALLVALUE,push(0),push(0),push(0),push(0),STATICCALL,POP,push(0),MLOAD]);
+    check(&insns, &[w256::from(0xff)]);
+}
+
+#[test]
+fn test_create_01() {
+    // Net stack change of -2
+    check_delta(CREATE, 3);
+}
+
+#[test]
+fn test_create2_01() {
+    // Net stack change of -3
+    check_delta(CREATE2, 4);
+}
+
+#[test]
+fn test_create_02() {
+    // Return data size is unknown after a create
+    let st = run(&[push(0),push(0),push(0),CREATE,RETURNDATASIZE], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::Unknown);
+}
+
+#[test]
+fn test_create2_02() {
+    // Insufficient operands
+    let st = run(&[push(0),push(0),push(0)], State::new());
+    assert!(matches!(execute(&CREATE2,st.clone()),Outcome::Exception(EvmException::StackUnderflow)));
+    assert!(matches!(execute(&CREATE,st),Outcome::Continue(_)));
+}
+
+#[test]
+fn test_returndatasize_01() {
+    // Return data is initially empty
+    check(&[RETURNDATASIZE], &[w256::ZERO]);
+}
+
+#[test]
+fn test_returndatacopy_01() {
+    // Copying nothing from empty return data is fine
+    check(&[push(0),push(0),push(0),RETURNDATACOPY], &[]);
+}
+
+#[test]
+fn test_returndatacopy_02() {
+    // Copying past the end of empty return data overflows
+    let st = run(&[push(1),push(0),push(0)], State::new());
+    assert!(matches!(execute(&RETURNDATACOPY,st),Outcome::Exception(EvmException::ReturnDataOverflow)));
+}
+
+#[test]
+fn test_returndatacopy_03() {
+    // Overflow when offset + length wraps around
+    let st = run(&[push(1),push_word(w256::MAX),push(0)], State::new());
+    assert!(matches!(execute(&RETURNDATACOPY,st),Outcome::Exception(EvmException::ReturnDataOverflow)));
+}
+
+#[test]
+fn test_returndatacopy_04() {
+    // Return data size unknown after a call
+    let mut insns = vec![push(0);7];
+    insns.extend([CALL,POP,push(0x20),push(0),push(0),RETURNDATACOPY]);
+    check(&insns, &[]);
+}
+
+#[test]
+fn test_returndatacopy_05() {
+    // Bytes copied are unknown
+    let mut insns = vec![push(0);7];
+    insns.extend([CALL,POP,push(0xff),push(0),MSTORE,push(0x20),push(0),push(0),RETURNDATACOPY,push(0),MLOAD]);
+    let st = run(&insns, State::new());
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+}
+
+#[test]
+fn test_selfdestruct_01() {
+    let st = run(&[push(0)], State::new());
+    assert!(matches!(execute(&SELFDESTRUCT,st),Outcome::Return{data: Some(d),..} if d.is_empty()));
+}
+
+#[test]
+fn test_selfdestruct_02() {
+    assert!(matches!(execute(&SELFDESTRUCT,State::new()),Outcome::Exception(EvmException::StackUnderflow)));
+}
+
+// ============================================================================
+// Invalid
+// ============================================================================
+
+#[test]
+fn test_invalid_01() {
+    // Designated invalid instruction (0xfe)
+    let insn = [0xfe].disassemble().remove(0);
+    assert!(matches!(execute(&insn,State::new()),Outcome::Exception(EvmException::InvalidOpcode)));
+}
+
+#[test]
+fn test_invalid_02() {
+    // Undefined opcode byte
+    let insn = [0x0c].disassemble().remove(0);
+    assert!(matches!(execute(&insn,State::new()),Outcome::Exception(EvmException::InvalidOpcode)));
+}
+
+// ============================================================================
+// Logging
+// ============================================================================
+
+#[test]
+fn test_log_01() {
+    // LOG2 pops offset, length and two topics
+    check(&[push(1),push(2),push(3),push(4),push(5),LOG(2)], &[w256::from(1)]);
+}
+
+#[test]
+fn test_log_02() {
+    for k in 0..=4u8 {
+        // Exactly enough operands
+        let mut insns : Vec<Instruction> = (0..(k+2) as u64).map(push).collect();
+        insns.push(LOG(k));
+        check(&insns, &[]);
+    }
+}
+
+#[test]
+fn test_log_03() {
+    // Insufficient operands for LOG2
+    let st = run(&[push(1),push(2),push(3)], State::new());
+    assert!(matches!(execute(&LOG(2),st),Outcome::Exception(EvmException::StackUnderflow)));
+}
+
+// ============================================================================
+// Hashing
+// ============================================================================
+
+#[test]
+#[cfg(feature = "keccak")]
+fn test_keccak_concrete_01() {
+    // Empty region hashes to the empty keccak
+    check(&[push(0),push(0),KECCAK256], &[KECCAK_EMPTY]);
+}
+
+#[test]
+#[cfg(feature = "keccak")]
+fn test_keccak_concrete_02() {
+    // Hash of a single zero word
+    let hash = from_hex("0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563");
+    check(&[push(0x20),push(0),KECCAK256], &[hash]);
+}
+
+#[test]
+#[cfg(feature = "keccak")]
+fn test_keccak_concrete_03() {
+    // Partial word region (i.e. the byte 0x12)
+    let hash = from_hex("0x5fa2358263196dbbf23d1ca7a509451f7a2f64c15837bfbb81298b1e3e24e4fa");
+    check(&[push(0x12),push(0x1f),MSTORE8,push(0x1),push(0x1f),KECCAK256], &[hash]);
+}
+
+#[test]
+fn test_keccak_concrete_04() {
+    // Region containing an unknown value
+    let st = run(&[CALLVALUE,push(0),MSTORE,push(0x20),push(0),KECCAK256], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::TOP);
+}
+
+#[test]
+fn test_keccak_concrete_05() {
+    // Memory expands to cover exactly the region being hashed
+    let st = run(&[push(0x10),push(0x10),KECCAK256,MSIZE], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::from(w256::from(0x20)));
+    // Hashing nothing does not expand memory
+    let st = run(&[push(0),push(0x40),KECCAK256,MSIZE], State::new());
+    assert_eq!(st.stack().peek(0),&aw256::from(w256::ZERO));
+}
+
+#[test]
+fn test_keccak_concrete_06() {
+    // Hashing from a huge offset exhausts gas
+    let st = run(&[push(1),push_word(w256::MAX)], State::new());
+    assert!(matches!(execute(&KECCAK256,st),Outcome::Exception(EvmException::InsufficientGas)));
+}
+
+#[test]
+fn test_keccak_01() {
+    // Hashing the same (concrete) key twice gives the same value,
+    // which is computed directly (where possible) as for `aw256`.
+    let insns = [push(0x1234),push(0),MSTORE,push(0x20),push(0),KECCAK256,push(0x20),push(0),KECCAK256];
+    let st = run_symbolic(&insns);
+    assert_eq!(st.stack().peek(0),st.stack().peek(1));
+    #[cfg(feature = "keccak")]
+    {
+        let expected = *run(&insns, State::new()).stack().peek(0);
+        assert!(matches!(expected,aw256::Word(_)));
+        assert_eq!(st.stack().peek(0),&sw256::from(expected));
+    }
+    #[cfg(not(feature = "keccak"))]
+    assert!(matches!(st.stack().peek(0),sw256::Keccak(..)));
+}
+
+#[test]
+fn test_keccak_02() {
+    // Equality of identical hashes is known
+    let st = run_symbolic(&[push(0x1234),push(0),MSTORE,push(0x20),push(0),KECCAK256,
+                            push(0x20),push(0),KECCAK256,EQ]);
+    assert_eq!(st.stack().peek(0),&sw256::from(w256::from(1)));
+}
+
+#[test]
+fn test_keccak_03() {
+    // Hashing the same (symbolic) key twice gives the same value.
+    // This is the slot for a nested mapping (i.e. m[k][0x2]).
+    let st = run_symbolic(&[push(0x4),CALLDATALOAD,push(0),MSTORE,push(0x0),push(0x20),MSTORE,
+                            push(0x40),push(0),KECCAK256,push(0x20),MSTORE,push(0x2),push(0),MSTORE,
+                            push(0x40),push(0),KECCAK256,
+                            push(0x40),push(0),KECCAK256]);
+    assert!(matches!(st.stack().peek(0),sw256::Keccak(..)));
+    assert_eq!(st.stack().peek(0),st.stack().peek(1));
+}
+
+#[test]
+fn test_keccak_04() {
+    // Hashing different (symbolic) keys gives different values,
+    // whose equality is unknown.
+    let st = run_symbolic(&[push(0x4),CALLDATALOAD,push(0),MSTORE,push(0x20),push(0),KECCAK256,
+                            push(0x24),CALLDATALOAD,push(0),MSTORE,push(0x20),push(0),KECCAK256]);
+    assert_ne!(st.stack().peek(0),st.stack().peek(1));
+    let st = run_symbolic(&[push(0x4),CALLDATALOAD,push(0),MSTORE,push(0x20),push(0),KECCAK256,
+                            push(0x24),CALLDATALOAD,push(0),MSTORE,push(0x20),push(0),KECCAK256,EQ]);
+    assert_eq!(st.stack().peek(0),&sw256::Unknown);
+}
+
+#[test]
+fn test_keccak_05() {
+    // Hashing the same symbolic key (i.e. loaded twice from the same
+    // calldata offset) gives the same value.
+    let st = run_symbolic(&[push(0x4),CALLDATALOAD,push(0),MSTORE,push(0x20),push(0),KECCAK256,
+                            push(0x4),CALLDATALOAD,push(0),MSTORE,push(0x20),push(0),KECCAK256]);
+    assert!(matches!(st.stack().peek(0),sw256::Keccak(..)));
+    assert_eq!(st.stack().peek(0),st.stack().peek(1));
+    let st = run_symbolic(&[push(0x4),CALLDATALOAD,push(0),MSTORE,push(0x20),push(0),KECCAK256,
+                            push(0x4),CALLDATALOAD,push(0),MSTORE,push(0x20),push(0),KECCAK256,EQ]);
+    assert_eq!(st.stack().peek(0),&sw256::from(w256::from(1)));
+    // Whereas hashing an unknown key gives a fresh unknown.
+    let st = run_symbolic(&[CALLVALUE,push(0),MSTORE,push(0x20),push(0),KECCAK256,
+                            push(0x20),push(0),KECCAK256,EQ]);
+    assert_eq!(st.stack().peek(0),&sw256::Unknown);
+}
+
+#[test]
+fn test_keccak_06() {
+    // Bytes beyond the hashed region are ignored.
+    let st = run_symbolic(&[push(0x1234),push(0),MSTORE,push(0x1e),push(0),KECCAK256,
+                            push(0x1299),push(0),MSTORE,push(0x1e),push(0),KECCAK256,EQ]);
+    assert_eq!(st.stack().peek(0),&sw256::from(w256::from(1)));
+}
+
+#[test]
+fn test_keccak_07() {
+    // Hashing expands memory
+    let st = run_symbolic(&[push(0x21),push(0),KECCAK256,MSIZE]);
+    assert_eq!(st.stack().peek(0),&sw256::from(w256::from(0x40)));
+}
+
+// ============================================================================
+// Gas
+// ============================================================================
+
+#[test]
+fn test_gas_01() {
+    // Enough gas for everything
+    let st = run(&[push(1),push(2),ADD], State::new().with_gas(9));
+    assert_eq!(st.gas(),Some(0));
+}
+
+#[test]
+fn test_gas_02() {
+    // Runs out of gas on the third instruction
+    let insns = [push(1),push(2),ADD,POP];
+    assert_eq!(out_of_gas(&insns,8),Some(2));
+    assert_eq!(out_of_gas(&insns,10),Some(3));
+    assert_eq!(out_of_gas(&insns,11),None);
+}
+
+#[test]
+fn test_gas_03() {
+    // Gas remaining after GAS itself is charged
+    check_state(&[push(1),GAS], State::new().with_gas(100), &[w256::from(1),w256::from(95)]);
+}
+
+#[test]
+fn test_gas_04() {
+    // Gas is not tracked by default
+    let st = run(&[push(1),push(2),ADD], State::new());
+    assert_eq!(st.gas(),None);
+    assert_eq!(st.stack().size(),1);
+}
+
+#[test]
+fn test_gas_05() {
+    // Memory expansion of one word costs 3 (in addition to 3+3+3)
+    let st = run(&[push(1),push(0),MSTORE], State::new().with_gas(12));
+    assert_eq!(st.gas(),Some(0));
+    assert_eq!(out_of_gas(&[push(1),push(0),MSTORE], 11),Some(2));
+}
+
+#[test]
+fn test_gas_06() {
+    // No charge when memory is not expanded
+    let st = run(&[push(1),push(0),MSTORE,push(0),MLOAD], State::new().with_gas(100));
+    assert_eq!(st.gas(),Some(100 - 12 - 6));
+}
+
+#[test]
+fn test_gas_07() {
+    // Expansion to two words
+    let st = run(&[push(1),push(33),MSTORE8], State::new().with_gas(100));
+    assert_eq!(st.gas(),Some(100 - 9 - 6));
+}
+
+#[test]
+fn test_gas_08() {
+    // Absurdly large expansion
+    assert_eq!(out_of_gas(&[push(1),push(1 << 40),MSTORE], 1_000_000),Some(2));
+}
+
+#[test]
+fn test_static_gas_01() {
+    assert_eq!(static_gas(0x00),0);
+    assert_eq!(static_gas(0x01),3);
+    assert_eq!(static_gas(0x56),8);
+    assert_eq!(static_gas(0x57),10);
+    assert_eq!(static_gas(0x5b),1);
+    assert_eq!(static_gas(0x7f),3);
+    assert_eq!(static_gas(0xa0),375);
+    assert_eq!(static_gas(0xa4),1875);
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Construct a simple world state containing: a contract account; an
+/// existing account without code; and an empty account.
+fn world() -> WorldState {
+    let mut world = WorldState::new();
+    let code = "0x600160020100".from_hex_string().unwrap();
+    world.insert(w256::from(CONTRACT), Account::new(w256::ZERO,1,code));
+    world.insert(w256::from(EXISTING), Account::new(w256::from(100),0,Vec::new()));
+    world.insert(w256::from(EMPTY), Account::new(w256::ZERO,0,Vec::new()));
+    world
+}
+
+/// Construct a push instruction for a given constant.
+fn push(n: u64) -> Instruction {
+    let bytes = n.to_be_bytes();
+    let i = bytes.iter().position(|b| *b != 0).unwrap_or(7);
+    PUSH(bytes[i..].to_vec())
+}
+
+/// Construct a push instruction for a given word.
+fn push_word(w: w256) -> Instruction {
+    PUSH(w.to_be_bytes::<32>().to_vec())
+}
+
+/// Construct the (two's complement) negation of a given constant.
+fn neg(n: u64) -> w256 {
+    w256::from(n).wrapping_neg()
+}
+
+fn from_hex(hex: &str) -> w256 {
+    let bytes = hex.from_hex_string().unwrap();
+    w256::from_be_slice(&bytes)
+}
+
+/// Execute a straight-line sequence of instructions with a given gas
+/// budget, returning the index of the instruction which ran out of
+/// gas (if any).
+fn out_of_gas(insns: &[Instruction], gas: u64) -> Option<usize> {
+    let mut state = State::new().with_gas(gas);
+    for (i,insn) in insns.iter().enumerate() {
+        state = match execute(insn,state) {
+            Outcome::Continue(st) => st,
+            Outcome::Exception(EvmException::InsufficientGas) => return Some(i),
+            _ => panic!("unexpected outcome executing {insn}")
+        };
+    }
+    None
+}
+
+/// Execute a straight-line sequence of instructions from a given
+/// state, returning the final state.
+fn run(insns: &[Instruction], mut state: State) -> State {
+    for insn in insns {
+        state = match execute(insn,state) {
+            Outcome::Continue(st) => st,
+            _ => panic!("unexpected outcome executing {insn}")
+        };
+    }
+    state
+}
+
+/// Check that executing a given sequence of instructions produces a
+/// stack matching the expected words (where the first word is the
+/// bottom of the stack).
+fn check(insns: &[Instruction], expected: &[w256]) {
+    check_state(insns, State::new(), expected)
+}
+
+/// Check that executing a given sequence of instructions within a
+/// given world state produces a stack matching the expected words.
+fn check_world(insns: &[Instruction], world: WorldState, expected: &[w256]) {
+    check_state(insns, State::new().with_world(world), expected)
+}
+
+/// Check that a given instruction consumes `n` operands and produces
+/// a single unknown word.  An additional item is placed at the bottom
+/// of the stack to ensure nothing else is consumed.
+fn check_delta(insn: Instruction, n: u64) {
+    let mut insns : Vec<Instruction> = (0..=n).map(push).collect();
+    insns.push(insn);
+    let st = run(&insns, State::new());
+    assert_eq!(st.stack().size(),2);
+    assert_eq!(st.stack().peek(0),&aw256::Unknown);
+    assert_eq!(st.stack().peek(1),&aw256::from(w256::ZERO));
+}
+
+/// Execute a given sequence of instructions and then a `JUMPI`, with
+/// the program counter positioned at `0`.
+fn jumpi(insns: &[Instruction]) -> Outcome<State> {
+    let mut st = run(insns, State::new());
+    st.goto(0);
+    execute(&JUMPI,st)
+}
+
+/// Execute a given sequence of instructions and then a relative
+/// jump, with the program counter positioned at `0`.
+fn rjump(insns: &[Instruction], insn: &Instruction) -> Outcome<State> {
+    let mut st = run(insns, State::new());
+    st.goto(0);
+    execute(insn,st)
+}
+
+/// Execute a straight-line sequence of instructions using symbolic
+/// words, returning the final state.
+fn run_symbolic(insns: &[Instruction]) -> SymbolicState {
+    let mut state = SymbolicState::new();
+    for insn in insns {
+        state = match execute(insn,state) {
+            Outcome::Continue(st) => st,
+            _ => panic!("unexpected outcome executing {insn}")
+        };
+    }
+    state
+}
+
+/// Execute a straight-line sequence of instructions from a given
+/// state where transient storage is tracked, returning the final
+/// state.
+fn run_transient(mut state: TransientState, insns: &[Instruction]) -> TransientState {
+    for insn in insns {
+        state = match execute(insn,state) {
+            Outcome::Continue(st) => st,
+            _ => panic!("unexpected outcome executing {insn}")
+        };
+    }
+    state
+}
+
+fn check_state(insns: &[Instruction], state: State, expected: &[w256]) {
+    let st = run(insns, state);
+    let stack = st.stack();
+    assert_eq!(stack.size(),expected.len());
+    for (i,w) in expected.iter().rev().enumerate() {
+        assert_eq!(stack.peek(i),&aw256::from(*w));
+    }
+}
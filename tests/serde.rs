@@ -0,0 +1,69 @@
+#![cfg(feature = "serde")]
+use evmil::bytecode::{Assembly,Instruction,StructuredSection};
+use evmil::bytecode::Instruction::*;
+
+// ============================================================================
+// Instructions
+// ============================================================================
+
+#[test]
+fn test_serde_insn_01() {
+    check_insn(STOP, "\"STOP\"");
+}
+
+#[test]
+fn test_serde_insn_02() {
+    // Push immediates are hex strings
+    check_insn(PUSH(vec![0x60,0x80]), "{\"PUSH\":\"0x6080\"}");
+}
+
+#[test]
+fn test_serde_insn_03() {
+    check_insn(DUP(2), "{\"DUP\":2}");
+}
+
+#[test]
+fn test_serde_insn_04() {
+    // Immediates must be hex strings
+    assert!(serde_json::from_str::<Instruction>("{\"PUSH\":[96,128]}").is_err());
+    assert!(serde_json::from_str::<Instruction>("{\"PUSH\":\"6080\"}").is_err());
+}
+
+// ============================================================================
+// Assemblies
+// ============================================================================
+
+#[test]
+fn test_serde_asm_01() {
+    let asm = r#"
+.code
+   push 0x80
+   push lab
+   jump
+lab:
+   jumpdest
+   stop
+.data
+   0x00ff
+"#;
+    let asm = Assembly::from_str(asm).unwrap();
+    let json = serde_json::to_string(&asm).unwrap();
+    assert!(json.contains("\"Data\":\"0x00ff\""));
+    let asm2 : Assembly = serde_json::from_str(&json).unwrap();
+    assert_eq!(asm,asm2);
+}
+
+#[test]
+fn test_serde_asm_02() {
+    let asm = Assembly::new(vec![StructuredSection::Code(vec![PUSH0,DATA(vec![0xfe])])]);
+    let json = serde_json::to_string(&asm).unwrap();
+    let asm2 : Assembly = serde_json::from_str(&json).unwrap();
+    assert_eq!(asm,asm2);
+}
+
+fn check_insn(insn: Instruction, expected: &str) {
+    let json = serde_json::to_string(&insn).unwrap();
+    assert_eq!(json,expected);
+    let insn2 : Instruction = serde_json::from_str(&json).unwrap();
+    assert_eq!(insn,insn2);
+}
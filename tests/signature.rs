@@ -0,0 +1,148 @@
+use evmil::analysis::{find_block_signatures,BlockSignature};
+use evmil::bytecode::{Assembly,Disassemble,Instruction};
+
+#[test]
+fn test_signature_01() {
+    let asm = r#"
+.code
+   push 0x01
+   push lab
+   jump
+lab:
+   jumpdest
+   pop
+   stop
+"#;
+    check(asm, &[(0x00,&[0],&[1]),(0x06,&[1],&[])]);
+}
+
+#[test]
+fn test_signature_02() {
+    // Block reached with different heights
+    let asm = r#"
+.code
+   push 0x01
+   calldatasize
+   push lab
+   jumpi
+   push 0x02
+lab:
+   jumpdest
+   stop
+"#;
+    check(asm, &[(0x00,&[0],&[1,2]),(0x09,&[1,2],&[])]);
+}
+
+#[test]
+fn test_signature_03() {
+    // Unreachable block
+    let asm = r#"
+.code
+   stop
+   push 0x01
+   stop
+"#;
+    let sigs = check(asm, &[(0x00,&[0],&[]),(0x01,&[],&[])]);
+    assert!(sigs[0].is_reachable());
+    assert!(!sigs[1].is_reachable());
+}
+
+#[test]
+fn test_signature_04() {
+    let asm = r#"
+.code
+   push 0x01
+   push 0x02
+   push lab
+   jump
+lab:
+   jumpdest
+   add
+   push 0x00
+   mstore
+   push 0x20
+   push 0x00
+   return
+"#;
+    let sigs = check(asm, &[(0x00,&[0],&[2]),(0x08,&[2],&[])]);
+    assert_eq!(sigs[0].to_string(), "0x0000 [0] [2]");
+}
+
+#[test]
+fn test_signature_05() {
+    // Block falling through into a jumpdest
+    let asm = r#"
+.code
+   push 0x01
+lab:
+   jumpdest
+   pop
+   stop
+"#;
+    check(asm, &[(0x00,&[0],&[1]),(0x02,&[1],&[])]);
+}
+
+#[test]
+fn test_signature_06() {
+    // Loop whose back edge leaves from the middle of its block
+    let asm = r#"
+.code
+   push 0x03
+lab:
+   jumpdest
+   push 0x01
+   swap1
+   sub
+   dup1
+   push lab
+   jumpi
+   stop
+"#;
+    check(asm, &[(0x00,&[0],&[1]),(0x02,&[1],&[1])]);
+}
+
+#[test]
+fn test_signature_07() {
+    // Function call and return
+    let asm = r#"
+.code
+   push ret
+   push fn
+   jump
+ret:
+   jumpdest
+   stop
+fn:
+   jumpdest
+   jump
+"#;
+    check(asm, &[(0x00,&[0],&[1]),(0x07,&[0],&[]),(0x09,&[1],&[0])]);
+}
+
+#[test]
+fn test_signature_08() {
+    // Blocks ending in an exception have no exit
+    let asm = r#"
+.code
+   push 0x01
+   calldatasize
+   push lab
+   jumpi
+   invalid
+lab:
+   jumpdest
+   push 0x00
+   push 0x00
+   revert
+"#;
+    check(asm, &[(0x00,&[0],&[1]),(0x08,&[1],&[])]);
+}
+
+fn check(asm: &str, expected: &[(usize,&[usize],&[usize])]) -> Vec<BlockSignature> {
+    let bytes = Assembly::from_str(asm).unwrap().to_legacy_bytes();
+    let insns : Vec<Instruction> = bytes.disassemble();
+    let sigs = find_block_signatures(&insns,usize::MAX).unwrap();
+    let actual : Vec<(usize,&[usize],&[usize])> = sigs.iter().map(|s| (s.pc,s.entry.as_slice(),s.exit.as_slice())).collect();
+    assert_eq!(actual,expected);
+    sigs
+}
@@ -0,0 +1,35 @@
+use evmil::bytecode::{Assembly,SizeBreakdown};
+use evmil::util::FromHexString;
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[test]
+fn test_size_01() {
+    let asm = Assembly::from_str(".code\n push 0x01\n push0\n stop\n").unwrap();
+    let size = SizeBreakdown::from(&asm);
+    assert_eq!(size,SizeBreakdown{opcodes: 3, immediates: 1, data: 0});
+}
+
+#[test]
+fn test_size_02() {
+    // Several large pushes
+    let w = "0102030405060708091011121314151617181920212223242526272829303132";
+    let hex = format!("0x7f{w}7f{w}6a0102030405060708091011505050610102600101600055000000");
+    let bytes = hex.from_hex_string().unwrap();
+    let size = SizeBreakdown::from(&Assembly::from_legacy_bytes(&bytes));
+    assert_eq!(size.immediates,32+32+11+2+1+1);
+    assert_eq!(size.opcodes,12);
+    assert_eq!(size.data,2);
+    assert_eq!(size.total(),bytes.len());
+}
+
+#[test]
+fn test_size_03() {
+    // Explicit data sections
+    let asm = Assembly::from_str(".code\n push 0x0102\n stop\n.data\n 0x010203\n").unwrap();
+    let size = SizeBreakdown::from(&asm);
+    assert_eq!(size,SizeBreakdown{opcodes: 2, immediates: 2, data: 3});
+    assert_eq!(size.total(),asm.to_legacy_bytes().len());
+}
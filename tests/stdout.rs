@@ -0,0 +1,42 @@
+use std::fs;
+use std::path::Path;
+
+/// Library code should never write directly to stdout (or stderr).
+/// Instead, any informational output should be routed through the
+/// `log` crate.  Only the command-line tool may print.
+#[test]
+fn test_no_print_in_library() {
+    let mut offenders = Vec::new();
+    check_dir(Path::new("src"), &mut offenders);
+    assert!(offenders.is_empty(), "direct output found: {offenders:?}");
+}
+
+fn check_dir(dir: &Path, offenders: &mut Vec<String>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            // The command-line tool is allowed to print
+            if path.file_name().unwrap() != "bin" {
+                check_dir(&path, offenders);
+            }
+        } else if path.extension().is_some_and(|e| e == "rs") {
+            check_file(&path, offenders);
+        }
+    }
+}
+
+fn check_file(path: &Path, offenders: &mut Vec<String>) {
+    let contents = fs::read_to_string(path).unwrap();
+    for (i,line) in contents.lines().enumerate() {
+        // Strip comments
+        let code = match line.find("//") {
+            Some(j) => &line[..j],
+            None => line
+        };
+        for m in ["println!","print!","eprintln!","eprint!","dbg!"] {
+            if code.contains(m) {
+                offenders.push(format!("{}:{}",path.display(),i+1));
+            }
+        }
+    }
+}
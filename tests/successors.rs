@@ -0,0 +1,54 @@
+use evmil::bytecode::Instruction;
+use evmil::bytecode::Instruction::*;
+
+#[test]
+fn test_successors_01() {
+    // Instructions which fall through
+    check(&ADD, 0, &[], &[1]);
+    check(&JUMPDEST, 3, &[], &[4]);
+    check(&PUSH(vec![1,2]), 3, &[], &[6]);
+    check(&PUSH0, 3, &[], &[4]);
+    check(&SSTORE, 3, &[], &[4]);
+}
+
+#[test]
+fn test_successors_02() {
+    // Instructions which terminate
+    check(&STOP, 3, &[], &[]);
+    check(&RETURN, 3, &[], &[]);
+    check(&REVERT, 3, &[], &[]);
+    check(&INVALID, 3, &[], &[]);
+    check(&SELFDESTRUCT, 3, &[], &[]);
+    check(&DATA(vec![1,2,3]), 3, &[], &[]);
+}
+
+#[test]
+fn test_successors_03() {
+    // Dynamic jump
+    check(&JUMP, 3, &[], &[]);
+    check(&JUMP, 3, &[10], &[10]);
+    check(&JUMP, 3, &[10,20], &[10,20]);
+}
+
+#[test]
+fn test_successors_04() {
+    // Dynamic conditional jump
+    check(&JUMPI, 3, &[], &[4]);
+    check(&JUMPI, 3, &[10], &[4,10]);
+    check(&JUMPI, 3, &[4], &[4]);
+    check(&JUMPI, 3, &[20,10], &[4,10,20]);
+}
+
+#[test]
+fn test_successors_05() {
+    // Static jumps ignore supplied targets
+    check(&RJUMP(4), 3, &[], &[10]);
+    check(&RJUMP(4), 3, &[20], &[10]);
+    check(&RJUMP(-6), 3, &[], &[0]);
+    check(&RJUMPI(4), 3, &[], &[6,10]);
+    check(&RJUMPI(0), 3, &[], &[6]);
+}
+
+fn check(insn: &Instruction, pc: usize, targets: &[usize], expected: &[usize]) {
+    assert_eq!(insn.successors(pc,targets),expected);
+}
@@ -0,0 +1,350 @@
+use evmil::analysis::{aw256,iw256,trace,ConcreteMemory,ConcreteStack,ConcreteState,EvmMemory,EvmStack,EvmState,EvmStateSet,Trace,TraceConfig,UnknownStorage,trace_with_config};
+use evmil::bytecode::{Assembly,Disassemble,Instruction};
+use evmil::util::{w256,Bottom,Concretizable,Interval,IsTop,Top};
+
+type State = ConcreteState<ConcreteStack<aw256>,ConcreteMemory<aw256>,UnknownStorage<aw256>>;
+type IntervalState = ConcreteState<ConcreteStack<iw256>,ConcreteMemory<iw256>,UnknownStorage<iw256>>;
+
+#[test]
+fn test_trace_dedup_01() {
+    // Two paths reaching lab with identical states.
+    let asm = r#"
+.code
+   calldatasize
+   push lab
+   jumpi
+   push lab
+   jump
+lab:
+   jumpdest
+   stop
+"#;
+    let states = check(asm);
+    assert_eq!(states[5].len(),1);
+}
+
+#[test]
+fn test_trace_dedup_02() {
+    // Two paths reaching lab with identical states, despite
+    // computing them differently.
+    let asm = r#"
+.code
+   push 0x03
+   calldatasize
+   push lab
+   jumpi
+   pop
+   push 0x01
+   push 0x02
+   add
+   push lab
+   jump
+lab:
+   jumpdest
+   stop
+"#;
+    let states = check(asm);
+    assert_eq!(states[11].len(),1);
+    assert_eq!(states[11][0].stack().size(),1);
+}
+
+#[test]
+fn test_trace_dedup_03() {
+    // Two paths reaching lab with distinct states.
+    let asm = r#"
+.code
+   push 0x01
+   calldatasize
+   push lab
+   jumpi
+   pop
+   push 0x02
+lab:
+   jumpdest
+   stop
+"#;
+    let states = check(asm);
+    assert_eq!(states[6].len(),2);
+    // States are sorted
+    assert!(states[6][0] < states[6][1]);
+}
+
+#[test]
+fn test_trace_unknown_01() {
+    // Jump target determined by calldata
+    let asm = r#"
+.code
+   push 0x00
+   calldataload
+   jump
+lab:
+   jumpdest
+   stop
+"#;
+    let states = check(asm);
+    assert_eq!(states[2].len(),1);
+    assert!(states[3].is_empty());
+}
+
+#[test]
+fn test_trace_unknown_02() {
+    // Conditional jump target determined by calldata
+    let asm = r#"
+.code
+   calldatasize
+   push 0x00
+   calldataload
+   jumpi
+   push 0x01
+lab:
+   jumpdest
+   stop
+"#;
+    let states = check(asm);
+    assert_eq!(states[4].len(),1);
+    assert_eq!(states[5].len(),1);
+}
+
+// ============================================================================
+// Intervals
+// ============================================================================
+
+/// A loop whose counter is initialised from (masked) calldata, and
+/// which exits once it reaches `0x20`.
+const LOOP_ASM : &str = r#"
+.code
+   push 0x00
+   calldataload
+   push 0x0f
+   and
+loop:
+   jumpdest
+   push 0x01
+   add
+   dup1
+   push 0x20
+   gt
+   push loop
+   jumpi
+   stop
+"#;
+
+#[test]
+fn test_trace_interval_01() {
+    // Masked value is bounded
+    let states = check_interval(LOOP_ASM);
+    assert_eq!(*states[4][0].stack().peek(0),Interval::new(w256::from(0),w256::from(15)));
+    // Whereas it is unknown in the simpler domain
+    let states = check(LOOP_ASM);
+    assert_eq!(*states[4][0].stack().peek(0),aw256::Unknown);
+}
+
+#[test]
+fn test_trace_interval_02() {
+    // Loop counter is bounded on exit
+    let states = check_interval(LOOP_ASM);
+    assert!(!states[12].is_empty());
+    for st in &states[12] {
+        let i = st.stack().peek(0);
+        assert!(i.start >= w256::from(17) && i.end <= w256::from(47));
+    }
+    // Whereas it is unknown in the simpler domain
+    let states = check(LOOP_ASM);
+    assert_eq!(states[12].len(),1);
+    assert_eq!(*states[12][0].stack().peek(0),aw256::Unknown);
+}
+
+#[test]
+fn test_trace_interval_03() {
+    // Loop condition can sometimes be resolved
+    let states = check_interval(LOOP_ASM);
+    assert!(states[11].iter().any(|st| st.stack().peek(1).is_constant()));
+    let states = check(LOOP_ASM);
+    assert!(states[11].iter().all(|st| !st.stack().peek(1).is_constant()));
+}
+
+// ============================================================================
+// Trace API
+// ============================================================================
+
+#[test]
+fn test_trace_api_01() {
+    // States are indexed by byte offset
+    let trace : Trace<Vec<State>> = check_trace(LOOP_ASM,State::new(),usize::MAX).unwrap();
+    // push 0x00; calldataload; push 0x0f; and; loop: jumpdest
+    assert!(trace.is_reachable(0x00));
+    assert!(!trace.is_reachable(0x01));
+    assert!(trace.is_reachable(0x02));
+    assert!(trace.is_reachable(0x06));
+    assert_eq!(trace.states_at(0x03).count(),1);
+    assert_eq!(trace.states_at(0x04).count(),0);
+    assert_eq!(trace.states_at(0x100).count(),0);
+    // Operands are pushed as expected
+    let st = trace.states_at(0x05).next().unwrap();
+    assert_eq!(*st.stack().peek(0),aw256::from(w256::from(0x0f)));
+}
+
+#[test]
+fn test_trace_api_02() {
+    // Generic over the underlying domain
+    let trace : Trace<Vec<IntervalState>> = check_trace(LOOP_ASM,IntervalState::new(),usize::MAX).unwrap();
+    let st = trace.states_at(0x06).next().unwrap();
+    assert_eq!(*st.stack().peek(0),Interval::new(w256::from(0),w256::from(15)));
+    assert_eq!(trace.into_states(),check_interval(LOOP_ASM));
+}
+
+#[test]
+fn test_trace_api_03() {
+    // Iteration covers every instruction
+    let trace : Trace<Vec<State>> = check_trace(LOOP_ASM,State::new(),usize::MAX).unwrap();
+    let pcs : Vec<usize> = trace.iter().map(|(pc,_)| pc).collect();
+    assert_eq!(pcs,[0,2,3,5,6,7,9,10,11,13,14,17,18]);
+    assert!(trace.iter().all(|(pc,sts)| trace.is_reachable(pc) == (sts.size() > 0)));
+    assert_eq!(trace.iter().filter(|(_,sts)| sts.size() > 0).count(),13);
+}
+
+#[test]
+fn test_trace_api_04() {
+    // Incomplete traces are returned on hitting the limit
+    let trace : Trace<Vec<State>> = check_trace(LOOP_ASM,State::new(),3).unwrap_err();
+    assert!(trace.is_reachable(0x02));
+    assert!(!trace.is_reachable(0x05));
+}
+
+// ============================================================================
+// Widening
+// ============================================================================
+
+/// A loop whose counter increases without bound, and which exits
+/// under an unknown condition.
+const UNBOUNDED_ASM : &str = r#"
+.code
+   push 0x00
+loop:
+   jumpdest
+   push 0x01
+   add
+   calldatasize
+   push loop
+   jumpi
+   stop
+"#;
+
+#[test]
+fn test_trace_widen_01() {
+    // Without widening, the trace never converges
+    let config = TraceConfig::new().with_limit(10_000);
+    assert!(check_config::<Vec<State>>(UNBOUNDED_ASM,State::new(),&config).is_err());
+    assert!(check_config::<Vec<IntervalState>>(UNBOUNDED_ASM,IntervalState::new(),&config).is_err());
+}
+
+#[test]
+fn test_trace_widen_02() {
+    // With widening, the trace converges and the counter is unknown
+    let config = TraceConfig::new().with_limit(10_000).with_widening(3);
+    let states = check_config::<Vec<State>>(UNBOUNDED_ASM,State::new(),&config).unwrap();
+    assert!(states[7].iter().any(|st| *st.stack().peek(0) == aw256::Unknown));
+    let states = check_config::<Vec<IntervalState>>(UNBOUNDED_ASM,IntervalState::new(),&config).unwrap();
+    assert!(states[7].iter().any(|st| st.stack().peek(0).is_top()));
+}
+
+#[test]
+fn test_trace_widen_03() {
+    // States prior to widening are retained
+    let config = TraceConfig::new().with_widening(3);
+    let states = check_config::<Vec<State>>(UNBOUNDED_ASM,State::new(),&config).unwrap();
+    for i in 1..=3 {
+        let v = aw256::from(w256::from(i));
+        assert!(states[7].iter().any(|st| *st.stack().peek(0) == v));
+    }
+    assert!(states[7].iter().all(|st| *st.stack().peek(0) != aw256::from(w256::from(5))));
+}
+
+#[test]
+fn test_trace_widen_04() {
+    // Widening doesn't affect loops which converge anyway
+    let config = TraceConfig::new().with_widening(100);
+    let insns : Vec<Instruction> = Assembly::from_str(LOOP_ASM).unwrap().to_legacy_bytes().disassemble();
+    let states : Vec<Vec<IntervalState>> = trace_with_config(&insns,IntervalState::new(),&config).unwrap();
+    assert_eq!(states,check_interval(LOOP_ASM));
+    let trace : Trace<Vec<IntervalState>> = Trace::with_config(&insns,IntervalState::new(),&config).unwrap();
+    assert_eq!(trace.into_states(),states);
+}
+
+/// A loop whose counter is held in memory, and which exits under an
+/// unknown condition.
+const MEMORY_ASM : &str = r#"
+.code
+   push 0x00
+   push 0x80
+   mstore
+loop:
+   jumpdest
+   push 0x80
+   mload
+   push 0x01
+   add
+   push 0x80
+   mstore
+   calldatasize
+   push loop
+   jumpi
+   stop
+"#;
+
+#[test]
+fn test_trace_widen_05() {
+    // Without widening, a counter held in memory never converges
+    let config = TraceConfig::new().with_limit(50_000);
+    assert!(check_config::<Vec<State>>(MEMORY_ASM,State::new(),&config).is_err());
+    // With widening, memory is widened and the trace converges
+    let config = config.with_widening(3);
+    let states = check_config::<Vec<State>>(MEMORY_ASM,State::new(),&config).unwrap();
+    assert!(states[13].iter().any(|st| {
+        let mut st = st.clone();
+        st.memory_mut().read(aw256::from(w256::from(0x80))) == aw256::Unknown
+    }));
+}
+
+#[test]
+fn test_trace_widen_06() {
+    // Without widening, tracking gas prevents convergence
+    let config = TraceConfig::new().with_limit(50_000);
+    let init = State::new().with_gas(1_000_000);
+    assert!(check_config::<Vec<State>>(UNBOUNDED_ASM,init.clone(),&config).is_err());
+    // With widening, gas is no longer tracked and the trace converges
+    let config = config.with_widening(3);
+    let states = check_config::<Vec<State>>(UNBOUNDED_ASM,init,&config).unwrap();
+    assert!(states[7].iter().any(|st| st.gas().is_none()));
+    assert!(states[7].iter().any(|st| st.gas().is_some()));
+}
+
+fn check_config<T>(asm: &str, init: T::State, config: &TraceConfig) -> Result<Vec<T>,Vec<T>>
+where T:EvmStateSet+Bottom+PartialEq+std::fmt::Debug,
+      T::State: Clone, <T::State as EvmState>::Word: Top
+{
+    let bytes = Assembly::from_str(asm).unwrap().to_legacy_bytes();
+    let insns : Vec<Instruction> = bytes.disassemble();
+    trace_with_config(&insns,init,config)
+}
+
+fn check_trace<T>(asm: &str, init: T::State, limit: usize) -> Result<Trace<T>,Trace<T>>
+where T:EvmStateSet+Bottom+PartialEq+std::fmt::Debug,
+      T::State: Clone, <T::State as EvmState>::Word: Top
+{
+    let bytes = Assembly::from_str(asm).unwrap().to_legacy_bytes();
+    let insns : Vec<Instruction> = bytes.disassemble();
+    Trace::new(&insns,init,limit)
+}
+
+fn check_interval(asm: &str) -> Vec<Vec<IntervalState>> {
+    let bytes = Assembly::from_str(asm).unwrap().to_legacy_bytes();
+    let insns : Vec<Instruction> = bytes.disassemble();
+    trace(&insns,IntervalState::new(),usize::MAX).unwrap()
+}
+
+fn check(asm: &str) -> Vec<Vec<State>> {
+    let bytes = Assembly::from_str(asm).unwrap().to_legacy_bytes();
+    let insns : Vec<Instruction> = bytes.disassemble();
+    trace(&insns,State::new(),usize::MAX).unwrap()
+}
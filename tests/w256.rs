@@ -0,0 +1,316 @@
+use evmil::analysis::{aw256,cw256};
+use evmil::util::{w256,FromHexString,FromWordString,W256ParseError,W256_ONE,W256_ZERO};
+
+/// `2^64`, i.e. the smallest value which requires the second limb.
+const LIMB : w256 = w256::from_limbs([0,1,0,0]);
+
+// ============================================================================
+// Addition / Subtraction
+// ============================================================================
+
+#[test]
+fn test_w256_add_01() {
+    assert_eq!(w256::from(1) + w256::from(2),w256::from(3));
+    assert_eq!(W256_ZERO + W256_ZERO,W256_ZERO);
+}
+
+#[test]
+fn test_w256_add_02() {
+    // Carry propagates into each limb
+    for i in 0..3 {
+        let mut limbs = [0u64;4];
+        limbs[..=i].fill(u64::MAX);
+        let mut expected = [0u64;4];
+        expected[i+1] = 1;
+        assert_eq!(w256::from_limbs(limbs) + W256_ONE,w256::from_limbs(expected));
+    }
+}
+
+#[test]
+fn test_w256_add_03() {
+    // Carry propagates across all limbs
+    let lhs = w256::from_limbs([u64::MAX,u64::MAX,u64::MAX,0]);
+    assert_eq!(lhs + W256_ONE,w256::from_limbs([0,0,0,1]));
+}
+
+#[test]
+fn test_w256_add_04() {
+    // Addition wraps at 2^256
+    assert_eq!(w256::MAX + W256_ONE,W256_ZERO);
+    assert_eq!(w256::MAX + w256::MAX,w256::MAX - W256_ONE);
+}
+
+#[test]
+fn test_w256_sub_01() {
+    assert_eq!(w256::from(3) - w256::from(2),W256_ONE);
+    assert_eq!(w256::MAX - w256::MAX,W256_ZERO);
+}
+
+#[test]
+fn test_w256_sub_02() {
+    // Borrow propagates across limbs
+    assert_eq!(LIMB - W256_ONE,w256::from(u64::MAX));
+    assert_eq!(w256::from_limbs([0,0,0,1]) - W256_ONE,w256::from_limbs([u64::MAX,u64::MAX,u64::MAX,0]));
+}
+
+#[test]
+fn test_w256_sub_03() {
+    // Subtraction wraps at zero
+    assert_eq!(W256_ZERO - W256_ONE,w256::MAX);
+    assert_eq!(W256_ONE - w256::from(2),w256::MAX);
+}
+
+// ============================================================================
+// Multiplication
+// ============================================================================
+
+#[test]
+fn test_w256_mul_01() {
+    assert_eq!(w256::from(6) * w256::from(7),w256::from(42));
+    assert_eq!(w256::MAX * W256_ZERO,W256_ZERO);
+    assert_eq!(w256::MAX * W256_ONE,w256::MAX);
+}
+
+#[test]
+fn test_w256_mul_02() {
+    // Product of low limbs spills into the next limb
+    let lhs = w256::from(u64::MAX);
+    assert_eq!(lhs * lhs,w256::from_limbs([1,u64::MAX-1,0,0]));
+    assert_eq!(LIMB * LIMB,w256::from_limbs([0,0,1,0]));
+}
+
+#[test]
+fn test_w256_mul_03() {
+    // Multiplication wraps at 2^256
+    let top = w256::from_limbs([0,0,0,1<<63]);
+    assert_eq!(top * w256::from(2),W256_ZERO);
+    assert_eq!(w256::MAX * w256::MAX,W256_ONE);
+    assert_eq!(w256::MAX * w256::from(2),w256::MAX - W256_ONE);
+}
+
+// ============================================================================
+// Division / Remainder
+// ============================================================================
+
+#[test]
+fn test_w256_div_01() {
+    assert_eq!(w256::from(42) / w256::from(6),w256::from(7));
+    assert_eq!(w256::from(43) / w256::from(6),w256::from(7));
+    assert_eq!(w256::from(5) / w256::from(6),W256_ZERO);
+}
+
+#[test]
+fn test_w256_div_02() {
+    // Division across limbs
+    assert_eq!(w256::from_limbs([0,0,1,0]) / LIMB,LIMB);
+    assert_eq!(w256::MAX / w256::MAX,W256_ONE);
+    assert_eq!(w256::MAX / W256_ONE,w256::MAX);
+}
+
+#[test]
+fn test_w256_div_03() {
+    // Division by zero is left to callers
+    assert_eq!(w256::MAX.checked_div(W256_ZERO),None);
+    assert_eq!(w256::MAX.checked_rem(W256_ZERO),None);
+}
+
+#[test]
+fn test_w256_rem_01() {
+    assert_eq!(w256::from(43) % w256::from(6),W256_ONE);
+    assert_eq!(w256::from(42) % w256::from(6),W256_ZERO);
+    assert_eq!((LIMB + W256_ONE) % LIMB,W256_ONE);
+    assert_eq!(w256::MAX % w256::from(2),W256_ONE);
+}
+
+// ============================================================================
+// Bitwise
+// ============================================================================
+
+#[test]
+fn test_w256_bitwise_01() {
+    let x = w256::from_limbs([0xff00,0xff,0,u64::MAX]);
+    let y = w256::from_limbs([0x0ff0,0xf0,u64::MAX,0]);
+    assert_eq!(x & y,w256::from_limbs([0x0f00,0xf0,0,0]));
+    assert_eq!(x | y,w256::from_limbs([0xfff0,0xff,u64::MAX,u64::MAX]));
+    assert_eq!(x ^ y,w256::from_limbs([0xf0f0,0x0f,u64::MAX,u64::MAX]));
+}
+
+#[test]
+fn test_w256_bitwise_02() {
+    assert_eq!(!W256_ZERO,w256::MAX);
+    assert_eq!(!w256::MAX,W256_ZERO);
+    assert_eq!(w256::MAX ^ w256::MAX,W256_ZERO);
+}
+
+// ============================================================================
+// Shifts
+// ============================================================================
+
+#[test]
+fn test_w256_shl_01() {
+    // Bits move across limb boundaries
+    assert_eq!(W256_ONE << 64,LIMB);
+    assert_eq!(w256::from(u64::MAX) << 4,w256::from_limbs([u64::MAX << 4,0xf,0,0]));
+    assert_eq!(W256_ONE << 255,w256::from_limbs([0,0,0,1<<63]));
+}
+
+#[test]
+fn test_w256_shl_02() {
+    // Bits shifted beyond the top are lost
+    assert_eq!(W256_ONE << 256,W256_ZERO);
+    assert_eq!(w256::MAX << 1,w256::MAX - W256_ONE);
+}
+
+#[test]
+fn test_w256_shr_01() {
+    assert_eq!(LIMB >> 64,W256_ONE);
+    assert_eq!(LIMB >> 1,w256::from(1u64 << 63));
+    assert_eq!(w256::MAX >> 255,W256_ONE);
+    assert_eq!(w256::MAX >> 256,W256_ZERO);
+}
+
+// ============================================================================
+// Comparisons
+// ============================================================================
+
+#[test]
+fn test_w256_cmp_01() {
+    assert!(W256_ZERO < W256_ONE);
+    assert!(w256::MAX > W256_ZERO);
+    // High limbs take priority
+    assert!(w256::from_limbs([0,0,0,1]) > w256::from_limbs([u64::MAX,u64::MAX,u64::MAX,0]));
+    assert!(LIMB > w256::from(u64::MAX));
+}
+
+#[test]
+fn test_w256_cmp_02() {
+    assert_eq!(w256::MAX.max(W256_ZERO),w256::MAX);
+    assert_eq!(w256::MAX.min(W256_ZERO),W256_ZERO);
+    assert_eq!(w256::MAX,w256::from_limbs([u64::MAX;4]));
+}
+
+// ============================================================================
+// Bytes
+// ============================================================================
+
+#[test]
+fn test_w256_bytes_01() {
+    let bytes : [u8;32] = W256_ONE.to_be_bytes();
+    assert_eq!(bytes[31],1);
+    assert!(bytes[..31].iter().all(|b| *b == 0));
+    assert_eq!(w256::from_be_bytes(bytes),W256_ONE);
+}
+
+#[test]
+fn test_w256_bytes_02() {
+    // Round trip across limb boundaries
+    for w in [W256_ZERO,LIMB,LIMB - W256_ONE,w256::MAX,w256::from_limbs([1,2,3,4])] {
+        let bytes : [u8;32] = w.to_be_bytes();
+        assert_eq!(w256::from_be_bytes(bytes),w);
+    }
+}
+
+#[test]
+fn test_w256_bytes_03() {
+    let bytes = "0x0000000000000004000000000000000300000000000000020000000000000001".from_hex_string().unwrap();
+    let bytes : [u8;32] = bytes.try_into().unwrap();
+    assert_eq!(w256::from_be_bytes(bytes),w256::from_limbs([1,2,3,4]));
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+const MAX_DEC : &str = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+const MAX_HEX : &str = "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+
+#[test]
+fn test_w256_parse_01() {
+    assert_eq!(w256::from_dec_str("0"),Ok(W256_ZERO));
+    assert_eq!(w256::from_dec_str("1234"),Ok(w256::from(1234)));
+    assert_eq!(w256::from_hex_str("0x4d2"),Ok(w256::from(1234)));
+    assert_eq!(w256::from_hex_str("4D2"),Ok(w256::from(1234)));
+}
+
+#[test]
+fn test_w256_parse_02() {
+    // Maximum value
+    assert_eq!(w256::from_dec_str(MAX_DEC),Ok(w256::MAX));
+    assert_eq!(w256::from_hex_str(MAX_HEX),Ok(w256::MAX));
+    assert_eq!(w256::from_hex_str("0x10000000000000000"),Ok(LIMB));
+}
+
+#[test]
+fn test_w256_parse_03() {
+    // Overflow is rejected
+    let dec = "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+    assert_eq!(w256::from_dec_str(dec),Err(W256ParseError::Overflow));
+    assert_eq!(w256::from_hex_str(&format!("{MAX_HEX}0")),Err(W256ParseError::Overflow));
+    assert_eq!(w256::from_hex_str(&format!("0x1{}","0".repeat(64))),Err(W256ParseError::Overflow));
+}
+
+#[test]
+fn test_w256_parse_04() {
+    // Leading zeros are ignored, even beyond 256 bits
+    let zeros = "0".repeat(100);
+    assert_eq!(w256::from_dec_str(&format!("{zeros}42")),Ok(w256::from(42)));
+    assert_eq!(w256::from_hex_str(&format!("0x{zeros}ff")),Ok(w256::from(255)));
+    assert_eq!(w256::from_dec_str(&format!("{zeros}{MAX_DEC}")),Ok(w256::MAX));
+}
+
+#[test]
+fn test_w256_parse_05() {
+    // Invalid digits are rejected
+    assert_eq!(w256::from_dec_str("12a"),Err(W256ParseError::InvalidDigit('a')));
+    assert_eq!(w256::from_hex_str("0xfg"),Err(W256ParseError::InvalidDigit('g')));
+    assert_eq!(w256::from_dec_str("-1"),Err(W256ParseError::InvalidDigit('-')));
+    assert_eq!(w256::from_dec_str(""),Err(W256ParseError::Empty));
+    assert_eq!(w256::from_hex_str("0x"),Err(W256ParseError::Empty));
+}
+
+#[test]
+fn test_w256_parse_06() {
+    // Standard parsing
+    assert_eq!(MAX_DEC.parse::<w256>(),Ok(w256::MAX));
+    assert_eq!(MAX_HEX.parse::<w256>(),Ok(w256::MAX));
+    assert!("12a".parse::<w256>().is_err());
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+#[test]
+fn test_w256_fmt_01() {
+    assert_eq!(format!("{}",W256_ZERO),"0");
+    assert_eq!(format!("{:x}",W256_ZERO),"0");
+    assert_eq!(format!("{:#x}",W256_ZERO),"0x0");
+    assert_eq!(format!("{:X}",W256_ZERO),"0");
+}
+
+#[test]
+fn test_w256_fmt_02() {
+    let w = w256::from(0xbeef);
+    assert_eq!(format!("{}",w),"48879");
+    assert_eq!(format!("{:x}",w),"beef");
+    assert_eq!(format!("{:#x}",w),"0xbeef");
+    assert_eq!(format!("{:X}",w),"BEEF");
+    assert_eq!(format!("{:#X}",w),"0xBEEF");
+}
+
+#[test]
+fn test_w256_fmt_03() {
+    assert_eq!(format!("{}",w256::MAX),MAX_DEC);
+    assert_eq!(format!("{:#x}",w256::MAX),MAX_HEX);
+    assert_eq!(format!("{:X}",w256::MAX),"F".repeat(64));
+}
+
+#[test]
+fn test_w256_fmt_04() {
+    // Abstract words are shown in hex, with inner limbs zero padded
+    assert_eq!(aw256::from(W256_ZERO).to_string(),"0x00");
+    assert_eq!(aw256::from(W256_ONE).to_string(),"0x01");
+    assert_eq!(aw256::from(LIMB + W256_ONE).to_string(),"0x10000000000000001");
+    assert_eq!(cw256::from(w256::MAX).to_string(),MAX_HEX);
+    assert_eq!(aw256::Unknown.to_string(),"??");
+}